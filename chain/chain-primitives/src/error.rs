@@ -180,6 +180,9 @@ pub enum ErrorKind {
     /// Invalid Balance Burnt
     #[error("Invalid Balance Burnt")]
     InvalidBalanceBurnt,
+    /// Invalid Delayed Receipts Gas
+    #[error("Invalid Delayed Receipts Gas")]
+    InvalidDelayedReceiptsGas,
     /// Invalid shard id
     #[error("Shard id {0} does not exist")]
     InvalidShardId(ShardId),
@@ -310,6 +313,7 @@ impl Error {
             | ErrorKind::InvalidGasPrice
             | ErrorKind::InvalidGasUsed
             | ErrorKind::InvalidBalanceBurnt
+            | ErrorKind::InvalidDelayedReceiptsGas
             | ErrorKind::InvalidShardId(_)
             | ErrorKind::InvalidStateRequest(_)
             | ErrorKind::InvalidRandomnessBeaconOutput
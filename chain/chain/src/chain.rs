@@ -19,6 +19,7 @@ use near_primitives::challenge::{
     MaybeEncodedShardChunk, SlashedValidator,
 };
 use near_primitives::checked_feature;
+use near_primitives::congestion_control::BlockCongestionInfo;
 use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::merkle::{
     combine_hash, merklize, verify_path, Direction, MerklePath, MerklePathItem,
@@ -589,6 +590,7 @@ impl Chain {
                                 0,
                                 chain_genesis.gas_limit,
                                 0,
+                                None,
                             ),
                         );
                     }
@@ -2206,6 +2208,31 @@ impl Chain {
         Ok(())
     }
 
+    /// Returns the blocks that are not stored yet but are needed to finalize the downloaded state
+    /// of the shard. Applying the chunks from the last chunk of the shard up to the sync block
+    /// needs the congestion info of each prev block, so these are the blocks on the chain from
+    /// the one that chunk was produced on up to the prev block of `sync_hash`.
+    pub fn get_state_finalize_missing_blocks(
+        &mut self,
+        shard_id: ShardId,
+        sync_hash: CryptoHash,
+    ) -> Result<Vec<CryptoHash>, Error> {
+        let shard_state_header = self.get_state_header(shard_id, sync_hash)?;
+        let chunk_prev_block_hash = *shard_state_header.cloned_chunk().prev_block();
+        let mut missing_blocks = vec![];
+        let mut block_hash = *self.get_block_header(&sync_hash)?.prev_hash();
+        loop {
+            if !self.block_exists(&block_hash)? {
+                missing_blocks.push(block_hash);
+            }
+            if block_hash == chunk_prev_block_hash {
+                break;
+            }
+            block_hash = *self.get_block_header(&block_hash)?.prev_hash();
+        }
+        Ok(missing_blocks)
+    }
+
     pub fn set_state_finalize(
         &mut self,
         shard_id: ShardId,
@@ -3182,6 +3209,39 @@ impl Chain {
     pub fn patch_state_in_progress(&self) -> bool {
         self.pending_states_to_patch.is_some()
     }
+
+    /// Moves the heads of the chain back to the given block, so that the next block is produced on
+    /// top of it. Blocks produced after it are left on an abandoned fork, and their states remain
+    /// in the store until garbage collected, so the chain can also be moved forward again.
+    /// New blocks keep increasing heights, since no height can be produced twice.
+    pub fn sandbox_revert(&mut self, block_hash: &CryptoHash) -> Result<(), Error> {
+        let header = self.get_block_header(block_hash)?.clone();
+        // Headers are never garbage collected, unlike blocks and their states.
+        if header.height() < self.store.tail()? || !self.block_exists(block_hash)? {
+            return Err(ErrorKind::Other(format!(
+                "Block {} at height {} was garbage collected, take a more recent snapshot",
+                block_hash,
+                header.height()
+            ))
+            .into());
+        }
+        let last_final_hash = *header.last_final_block();
+        let final_head = if last_final_hash == CryptoHash::default() {
+            Tip::from_header(self.genesis())
+        } else {
+            Tip::from_header(self.get_block_header(&last_final_hash)?)
+        };
+        let tip = Tip::from_header(&header);
+        let mut chain_store_update = self.store.store_update();
+        chain_store_update.save_body_head(&tip)?;
+        // Also rewinds the block height index, so that blocks of the abandoned fork aren't found
+        // by height and header sync continues from the new head.
+        chain_store_update.save_header_head_if_not_challenged(&tip)?;
+        chain_store_update.save_final_head(&final_head)?;
+        chain_store_update.commit()?;
+        info!(target: "chain", "Sandbox reverted head to {} at {}", block_hash, header.height());
+        Ok(())
+    }
 }
 
 /// Chain update helper, contains information that is needed to process block
@@ -3511,6 +3571,11 @@ impl<'a> ChainUpdate<'a> {
             prev_block.hash(),
             chunk_shard_id,
         )?;
+        let congestion_info = {
+            let prev_prev_block =
+                self.chain_store_update.get_block(prev_chunk_inner.prev_block_hash())?;
+            get_block_congestion_info(self.runtime_adapter.as_ref(), prev_prev_block)?
+        };
         let apply_result = self
             .runtime_adapter
             .apply_transactions_with_optional_storage_proof(
@@ -3526,6 +3591,7 @@ impl<'a> ChainUpdate<'a> {
                 prev_block.header().gas_price(),
                 prev_chunk_inner.gas_limit(),
                 &challenges_result,
+                &congestion_info,
                 *block.header().random_value(),
                 true,
                 true,
@@ -3613,6 +3679,7 @@ impl<'a> ChainUpdate<'a> {
         let will_shard_layout_change =
             self.runtime_adapter.will_shard_layout_change_next_epoch(prev_hash)?;
         let prev_chunk_headers = Chain::get_prev_chunk_headers(&*self.runtime_adapter, prev_block)?;
+        let congestion_info = get_block_congestion_info(&*self.runtime_adapter, prev_block)?;
         for (shard_id, (chunk_header, prev_chunk_header)) in
             (block.chunks().iter().zip(prev_chunk_headers.iter())).enumerate()
         {
@@ -3776,6 +3843,7 @@ impl<'a> ChainUpdate<'a> {
                     let random_seed = *block.header().random_value();
                     let height = chunk_header.height_included();
                     let prev_block_hash = chunk_header.prev_block_hash();
+                    let congestion_info = congestion_info.clone();
                     #[cfg(feature = "sandbox")]
                     let states_to_patch = self.states_to_patch.take();
 
@@ -3794,6 +3862,7 @@ impl<'a> ChainUpdate<'a> {
                             gas_price,
                             gas_limit,
                             &challenges_result,
+                            &congestion_info,
                             random_seed,
                             true,
                             is_first_block_with_chunk_of_version,
@@ -3839,6 +3908,7 @@ impl<'a> ChainUpdate<'a> {
                     let random_seed = *block.header().random_value();
                     let height = block.header().height();
                     let prev_block_hash = *prev_block.hash();
+                    let congestion_info = congestion_info.clone();
                     #[cfg(feature = "sandbox")]
                     let states_to_patch = self.states_to_patch.take();
                     #[cfg(not(feature = "sandbox"))]
@@ -3858,6 +3928,7 @@ impl<'a> ChainUpdate<'a> {
                             gas_price,
                             new_extra.gas_limit(),
                             &challenges_result,
+                            &congestion_info,
                             random_seed,
                             false,
                             false,
@@ -4007,6 +4078,9 @@ impl<'a> ChainUpdate<'a> {
                 let balance_split = total_balance_burnt / (num_split_shards as u128);
                 let gas_limit = chunk_extra.gas_limit();
                 let outcome_root = *chunk_extra.outcome_root();
+                // Split shards don't track the gas of their delayed receipts yet. The runtime
+                // recomputes it when applying their first chunk.
+                let delayed_receipts_gas = chunk_extra.delayed_receipts_gas().map(|_| 0);
 
                 let mut sum_gas_used = 0;
                 let mut sum_balance_burnt = 0;
@@ -4021,6 +4095,7 @@ impl<'a> ChainUpdate<'a> {
                         gas_burnt,
                         gas_limit,
                         balance_burnt,
+                        delayed_receipts_gas,
                     );
                     sum_gas_used += gas_burnt;
                     sum_balance_burnt += balance_burnt;
@@ -4075,6 +4150,7 @@ impl<'a> ChainUpdate<'a> {
                         apply_result.total_gas_burnt,
                         gas_limit,
                         apply_result.total_balance_burnt,
+                        apply_result.delayed_receipts_gas,
                     ),
                 );
                 self.chain_store_update.save_trie_changes(apply_result.trie_changes);
@@ -4800,6 +4876,10 @@ impl<'a> ChainUpdate<'a> {
             &chunk_header.prev_block_hash(),
             shard_id,
         )?;
+        // State sync downloads the blocks from the one the chunk was produced on up to the sync
+        // block, see `Chain::get_state_finalize_missing_blocks`.
+        let prev_block = self.chain_store_update.get_block(&chunk_header.prev_block_hash())?;
+        let congestion_info = get_block_congestion_info(self.runtime_adapter.as_ref(), prev_block)?;
 
         let apply_result = self.runtime_adapter.apply_transactions(
             shard_id,
@@ -4814,6 +4894,7 @@ impl<'a> ChainUpdate<'a> {
             gas_price,
             gas_limit,
             block_header.challenges_result(),
+            &congestion_info,
             *block_header.random_value(),
             true,
             is_first_block_with_chunk_of_version,
@@ -4833,6 +4914,7 @@ impl<'a> ChainUpdate<'a> {
             apply_result.total_gas_burnt,
             gas_limit,
             apply_result.total_balance_burnt,
+            apply_result.delayed_receipts_gas,
         );
         let shard_uid = self.runtime_adapter.shard_id_to_uid(shard_id, block_header.epoch_id())?;
         self.chain_store_update.save_chunk_extra(block_header.hash(), &shard_uid, chunk_extra);
@@ -4883,6 +4965,8 @@ impl<'a> ChainUpdate<'a> {
         let shard_uid = self.runtime_adapter.shard_id_to_uid(shard_id, block_header.epoch_id())?;
         let mut chunk_extra =
             self.chain_store_update.get_chunk_extra(prev_block_header.hash(), &shard_uid)?.clone();
+        let prev_block = self.chain_store_update.get_block(prev_block_header.hash())?;
+        let congestion_info = get_block_congestion_info(self.runtime_adapter.as_ref(), prev_block)?;
 
         let apply_result = self.runtime_adapter.apply_transactions(
             shard_id,
@@ -4897,6 +4981,7 @@ impl<'a> ChainUpdate<'a> {
             prev_block_header.gas_price(),
             chunk_extra.gas_limit(),
             block_header.challenges_result(),
+            &congestion_info,
             *block_header.random_value(),
             false,
             false,
@@ -4921,7 +5006,13 @@ impl<'a> ChainUpdate<'a> {
         debug!(target: "chain", "Verifying challenges {:?}", challenges);
         let mut result = vec![];
         for challenge in challenges.iter() {
-            match validate_challenge(&*self.runtime_adapter, epoch_id, prev_block_hash, challenge) {
+            match validate_challenge(
+                &mut self.chain_store_update,
+                &*self.runtime_adapter,
+                epoch_id,
+                prev_block_hash,
+                challenge,
+            ) {
                 Ok((hash, account_ids)) => {
                     let is_double_sign = match challenge.body {
                         // If it's double signed block, we don't invalidate blocks just slash.
@@ -4969,6 +5060,16 @@ pub fn do_apply_chunks(
     work.into_par_iter().map(|task| task()).collect::<Vec<_>>()
 }
 
+/// Congestion of the shards as advertised by the chunks of the given block. Chunks applied on top
+/// of this block throttle the receipts they send according to it.
+pub fn get_block_congestion_info(
+    runtime_adapter: &dyn RuntimeAdapter,
+    block: &Block,
+) -> Result<BlockCongestionInfo, Error> {
+    let shard_layout = runtime_adapter.get_shard_layout(block.header().epoch_id())?;
+    Ok(BlockCongestionInfo::from_chunk_headers(shard_layout, block.chunks().iter()))
+}
+
 pub fn collect_receipts<'a, T>(receipt_proofs: T) -> Vec<Receipt>
 where
     T: IntoIterator<Item = &'a ReceiptProof>,
@@ -15,6 +15,7 @@ use near_pool::types::PoolIterator;
 use near_primitives::account::{AccessKey, Account};
 use near_primitives::block_header::{Approval, ApprovalInner};
 use near_primitives::challenge::ChallengesResult;
+use near_primitives::congestion_control::BlockCongestionInfo;
 use near_primitives::epoch_manager::block_info::BlockInfo;
 use near_primitives::epoch_manager::epoch_info::EpochInfo;
 use near_primitives::errors::{EpochError, InvalidTxError};
@@ -37,8 +38,9 @@ use near_primitives::types::{
 use near_primitives::validator_signer::InMemoryValidatorSigner;
 use near_primitives::version::{ProtocolVersion, PROTOCOL_VERSION};
 use near_primitives::views::{
-    AccessKeyInfoView, AccessKeyList, CallResult, ContractCodeView, EpochValidatorInfo,
-    QueryRequest, QueryResponse, QueryResponseKind, ViewStateResult,
+    AccessKeyInfoView, AccessKeyList, CallResult, ContractCodeView, DelayedReceiptsView,
+    EpochValidatorInfo, QueryRequest, QueryResponse, QueryResponseKind, StorageBreakdownView,
+    ViewStateResult,
 };
 use near_store::test_utils::create_test_store;
 use near_store::{
@@ -592,6 +594,7 @@ impl RuntimeAdapter for KeyValueRuntime {
         &self,
         _gas_price: Balance,
         _state_update: Option<StateRoot>,
+        _next_block_height: BlockHeight,
         _transaction: &SignedTransaction,
         _verify_signature: bool,
         _epoch_id: &EpochId,
@@ -655,6 +658,7 @@ impl RuntimeAdapter for KeyValueRuntime {
         gas_price: Balance,
         _gas_limit: Gas,
         _challenges: &ChallengesResult,
+        _congestion_info: &BlockCongestionInfo,
         _random_seed: CryptoHash,
         generate_storage_proof: bool,
         _is_new_chunk: bool,
@@ -830,6 +834,7 @@ impl RuntimeAdapter for KeyValueRuntime {
             total_balance_burnt: 0,
             proof: None,
             processed_delayed_receipts: vec![],
+            delayed_receipts_gas: None,
         })
     }
 
@@ -848,6 +853,7 @@ impl RuntimeAdapter for KeyValueRuntime {
         _gas_price: Balance,
         _gas_limit: Gas,
         _challenges: &ChallengesResult,
+        _congestion_info: &BlockCongestionInfo,
         _random_value: CryptoHash,
         _is_new_chunk: bool,
         _is_first_block_with_chunk_of_version: bool,
@@ -922,6 +928,19 @@ impl RuntimeAdapter for KeyValueRuntime {
                 block_height,
                 block_hash: *block_hash,
             }),
+            QueryRequest::ViewStorageBreakdown { .. } => Ok(QueryResponse {
+                kind: QueryResponseKind::StorageBreakdown(StorageBreakdownView {
+                    account: 0,
+                    contract_code: 0,
+                    num_access_keys: 0,
+                    access_keys: 0,
+                    contract_data: vec![],
+                    total: 0,
+                    stored_total: 0,
+                }),
+                block_height,
+                block_hash: *block_hash,
+            }),
         }
     }
 
@@ -940,6 +959,24 @@ impl RuntimeAdapter for KeyValueRuntime {
         Ok(data)
     }
 
+    fn get_delayed_receipts(
+        &self,
+        shard_uid: ShardUId,
+        _state_root: &StateRoot,
+        _from_index: Option<u64>,
+        _limit: u64,
+    ) -> Result<DelayedReceiptsView, Error> {
+        // KeyValueRuntime never delays receipts.
+        Ok(DelayedReceiptsView {
+            shard_id: shard_uid.shard_id as ShardId,
+            first_index: 0,
+            length: 0,
+            total_gas: 0,
+            oldest_receipt_id: None,
+            receipts: vec![],
+        })
+    }
+
     fn validate_state_part(
         &self,
         _state_root: &StateRoot,
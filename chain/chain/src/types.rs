@@ -14,6 +14,7 @@ use near_pool::types::PoolIterator;
 pub use near_primitives::block::{Block, BlockHeader, Tip};
 use near_primitives::challenge::{ChallengesResult, SlashedValidator};
 use near_primitives::checked_feature;
+use near_primitives::congestion_control::BlockCongestionInfo;
 use near_primitives::epoch_manager::block_info::BlockInfo;
 use near_primitives::epoch_manager::epoch_info::EpochInfo;
 use near_primitives::errors::{EpochError, InvalidTxError};
@@ -32,7 +33,9 @@ use near_primitives::version::{
     ProtocolVersion, MIN_GAS_PRICE_NEP_92, MIN_GAS_PRICE_NEP_92_FIX, MIN_PROTOCOL_VERSION_NEP_92,
     MIN_PROTOCOL_VERSION_NEP_92_FIX,
 };
-use near_primitives::views::{EpochValidatorInfo, QueryRequest, QueryResponse};
+use near_primitives::views::{
+    DelayedReceiptsView, EpochValidatorInfo, QueryRequest, QueryResponse,
+};
 use near_store::{PartialStorage, ShardTries, Store, StoreUpdate, Trie, WrappedTrieChanges};
 
 use crate::DoomslugThresholdMode;
@@ -106,6 +109,9 @@ pub struct ApplyTransactionResult {
     pub total_balance_burnt: Balance,
     pub proof: Option<PartialStorage>,
     pub processed_delayed_receipts: Vec<Receipt>,
+    /// Gas of the delayed receipts queue after applying the chunk, if congestion control is
+    /// enabled.
+    pub delayed_receipts_gas: Option<Gas>,
 }
 
 impl ApplyTransactionResult {
@@ -288,6 +294,8 @@ pub trait RuntimeAdapter: Send + Sync {
     /// Validates a given signed transaction.
     /// If the state root is given, then the verification will use the account. Otherwise it will
     /// only validate the transaction math, limits and signatures.
+    /// `next_block_height` is the height after the head, at which a chunk producer would check the
+    /// transaction's nonce, validity window and access key expiration.
     /// Returns an option of `InvalidTxError`, it contains `Some(InvalidTxError)` if there is
     /// a validation error, or `None` in case the transaction succeeded.
     /// Throws an `Error` with `ErrorKind::StorageError` in case the runtime throws
@@ -296,6 +304,7 @@ pub trait RuntimeAdapter: Send + Sync {
         &self,
         gas_price: Balance,
         state_root: Option<StateRoot>,
+        next_block_height: BlockHeight,
         transaction: &SignedTransaction,
         verify_signature: bool,
         epoch_id: &EpochId,
@@ -606,6 +615,7 @@ pub trait RuntimeAdapter: Send + Sync {
         gas_price: Balance,
         gas_limit: Gas,
         challenges_result: &ChallengesResult,
+        congestion_info: &BlockCongestionInfo,
         random_seed: CryptoHash,
         is_new_chunk: bool,
         is_first_block_with_chunk_of_version: bool,
@@ -624,6 +634,7 @@ pub trait RuntimeAdapter: Send + Sync {
             gas_price,
             gas_limit,
             challenges_result,
+            congestion_info,
             random_seed,
             false,
             is_new_chunk,
@@ -646,6 +657,7 @@ pub trait RuntimeAdapter: Send + Sync {
         gas_price: Balance,
         gas_limit: Gas,
         challenges_result: &ChallengesResult,
+        congestion_info: &BlockCongestionInfo,
         random_seed: CryptoHash,
         generate_storage_proof: bool,
         is_new_chunk: bool,
@@ -668,6 +680,7 @@ pub trait RuntimeAdapter: Send + Sync {
         gas_price: Balance,
         gas_limit: Gas,
         challenges_result: &ChallengesResult,
+        congestion_info: &BlockCongestionInfo,
         random_value: CryptoHash,
         is_new_chunk: bool,
         is_first_block_with_chunk_of_version: bool,
@@ -686,6 +699,16 @@ pub trait RuntimeAdapter: Send + Sync {
         request: &QueryRequest,
     ) -> Result<QueryResponse, near_chain_primitives::error::QueryError>;
 
+    /// Returns the delayed receipt queue of the shard at the given state root, with up to `limit`
+    /// queued receipts starting at `from_index`.
+    fn get_delayed_receipts(
+        &self,
+        shard_uid: ShardUId,
+        state_root: &StateRoot,
+        from_index: Option<u64>,
+        limit: u64,
+    ) -> Result<DelayedReceiptsView, Error>;
+
     fn get_validator_info(
         &self,
         epoch_id: ValidatorInfoIdentifier,
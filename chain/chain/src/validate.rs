@@ -19,9 +19,10 @@ use near_primitives::types::validator_stake::ValidatorStakeIter;
 use near_primitives::types::{AccountId, BlockHeight, EpochId, Nonce};
 use near_store::PartialStorage;
 
+use crate::chain::get_block_congestion_info;
 use crate::types::ApplyTransactionResult;
 use crate::{byzantine_assert, Chain};
-use crate::{ChainStore, Error, ErrorKind, RuntimeAdapter};
+use crate::{ChainStore, ChainStoreAccess, Error, ErrorKind, RuntimeAdapter};
 
 /// Gas limit cannot be adjusted for more than 0.1% at a time.
 const GAS_LIMIT_ADJUSTMENT_FACTOR: u64 = 1000;
@@ -158,6 +159,10 @@ pub fn validate_chunk_with_chunk_extra(
         return Err(ErrorKind::InvalidBalanceBurnt.into());
     }
 
+    if prev_chunk_extra.delayed_receipts_gas() != chunk_header.delayed_receipts_gas() {
+        return Err(ErrorKind::InvalidDelayedReceiptsGas.into());
+    }
+
     let outgoing_receipts = chain_store.get_outgoing_receipts_for_shard(
         runtime_adapter,
         *prev_block_hash,
@@ -307,6 +312,7 @@ fn validate_chunk_proofs_challenge(
 }
 
 fn validate_chunk_state_challenge(
+    chain_store: &mut dyn ChainStoreAccess,
     runtime_adapter: &dyn RuntimeAdapter,
     chunk_state: &ChunkState,
 ) -> Result<(CryptoHash, Vec<AccountId>), Error> {
@@ -336,6 +342,13 @@ fn validate_chunk_state_challenge(
         return Err(ErrorKind::MaliciousChallenge.into());
     }
 
+    // The previous chunk was produced with the congestion info of the block it was built on, the
+    // same way the challenge was created.
+    let congestion_info = {
+        let prev_prev_block = chain_store.get_block(&prev_chunk_header.prev_block_hash())?;
+        get_block_congestion_info(runtime_adapter, prev_prev_block)?
+    };
+
     // Apply state transition and check that the result state and other data doesn't match.
     let partial_storage = PartialStorage { nodes: chunk_state.partial_state.clone() };
     let result = runtime_adapter
@@ -353,6 +366,7 @@ fn validate_chunk_state_challenge(
             prev_block_header.gas_price(),
             prev_chunk_header.gas_limit(),
             &ChallengesResult::default(),
+            &congestion_info,
             *block_header.random_value(),
             // TODO: set it properly when challenges are enabled
             true,
@@ -382,6 +396,7 @@ fn validate_chunk_state_challenge(
 /// Returns `Some(block_hash, vec![account_id])` of invalid block and who to
 /// slash if challenge is correct and None if incorrect.
 pub fn validate_challenge(
+    chain_store: &mut dyn ChainStoreAccess,
     runtime_adapter: &dyn RuntimeAdapter,
     epoch_id: &EpochId,
     last_block_hash: &CryptoHash,
@@ -405,7 +420,7 @@ pub fn validate_challenge(
             validate_chunk_proofs_challenge(runtime_adapter, chunk_proofs)
         }
         ChallengeBody::ChunkState(chunk_state) => {
-            validate_chunk_state_challenge(runtime_adapter, chunk_state)
+            validate_chunk_state_challenge(chain_store, runtime_adapter, chunk_state)
         }
     }
 }
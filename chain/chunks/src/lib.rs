@@ -947,6 +947,12 @@ impl ShardsManager {
         }
     }
 
+    /// Drops the transactions of all shards, e.g. once the chain state they were validated against
+    /// is reverted.
+    pub fn clear_transactions(&mut self) {
+        self.tx_pools.clear();
+    }
+
     /// Computes a deterministic random seed for given `shard_id`.
     /// This seed is used to randomize the transaction pool.
     /// For better security we want the seed to different in each shard.
@@ -1958,6 +1964,7 @@ impl ShardsManager {
         gas_used: Gas,
         gas_limit: Gas,
         balance_burnt: Balance,
+        delayed_receipts_gas: Option<Gas>,
         validator_proposals: Vec<ValidatorStake>,
         transactions: Vec<SignedTransaction>,
         outgoing_receipts: &Vec<Receipt>,
@@ -1977,6 +1984,7 @@ impl ShardsManager {
             gas_used,
             gas_limit,
             balance_burnt,
+            delayed_receipts_gas,
             tx_root,
             validator_proposals,
             transactions,
@@ -2292,6 +2300,7 @@ mod test {
             0,
             0,
             0,
+            None,
             vec![],
             vec![],
             &vec![],
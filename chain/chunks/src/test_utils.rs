@@ -221,6 +221,7 @@ impl ChunkTestFixture {
             0,
             1000,
             0,
+            None,
             Vec::new(),
             Vec::new(),
             &receipts,
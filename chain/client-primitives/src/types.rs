@@ -19,7 +19,7 @@ use near_primitives::types::{
 use near_primitives::utils::generate_random_string;
 use near_primitives::views::validator_stake_view::ValidatorStakeView;
 use near_primitives::views::{
-    BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
+    BlockView, ChunkView, DelayedReceiptsView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
     FinalExecutionOutcomeViewEnum, GasPriceView, LightClientBlockLiteView, LightClientBlockView,
     QueryRequest, QueryResponse, ReceiptView, StateChangesKindsView, StateChangesRequestView,
     StateChangesView,
@@ -820,3 +820,43 @@ impl From<near_chain_primitives::Error> for GetProtocolConfigError {
         }
     }
 }
+
+pub struct GetDelayedReceipts {
+    pub block_reference: BlockReference,
+    /// Shard to inspect. All shards tracked by the node are inspected if not given.
+    pub shard_id: Option<ShardId>,
+    /// Index of the first queued receipt to return. Defaults to the head of the queue.
+    pub from_index: Option<u64>,
+    /// Maximum number of queued receipts to return per shard.
+    pub limit: Option<u64>,
+}
+
+impl Message for GetDelayedReceipts {
+    type Result = Result<Vec<DelayedReceiptsView>, GetDelayedReceiptsError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GetDelayedReceiptsError {
+    #[error("IO Error: {0}")]
+    IOError(String),
+    #[error("Block has never been observed: {0}")]
+    UnknownBlock(String),
+    #[error("Shard {0} is not tracked by this node")]
+    UnavailableShard(ShardId),
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error("It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {0}")]
+    Unreachable(String),
+}
+
+impl From<near_chain_primitives::Error> for GetDelayedReceiptsError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error.kind() {
+            near_chain_primitives::ErrorKind::IOErr(s) => Self::IOError(s),
+            near_chain_primitives::ErrorKind::DBNotFoundErr(s) => Self::UnknownBlock(s),
+            _ => Self::Unreachable(error.to_string()),
+        }
+    }
+}
@@ -9,8 +9,8 @@ use near_primitives::time::Clock;
 use tracing::{debug, error, info, warn};
 
 use near_chain::chain::{
-    ApplyStatePartsRequest, BlockCatchUpRequest, BlockMissingChunks, BlocksCatchUpState,
-    OrphanMissingChunks, StateSplitRequest, TX_ROUTING_HEIGHT_HORIZON,
+    get_block_congestion_info, ApplyStatePartsRequest, BlockCatchUpRequest, BlockMissingChunks,
+    BlocksCatchUpState, OrphanMissingChunks, StateSplitRequest, TX_ROUTING_HEIGHT_HORIZON,
 };
 use near_chain::test_utils::format_hash;
 use near_chain::types::{AcceptedBlock, LatestKnown};
@@ -25,6 +25,8 @@ use near_network::types::{
 };
 use near_primitives::block::{Approval, ApprovalInner, ApprovalMessage, Block, BlockHeader, Tip};
 use near_primitives::challenge::{Challenge, ChallengeBody};
+use near_primitives::checked_feature;
+use near_primitives::errors::InvalidTxError;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{merklize, MerklePath};
 use near_primitives::receipt::Receipt;
@@ -653,6 +655,7 @@ impl Client {
             chunk_extra.gas_used(),
             chunk_extra.gas_limit(),
             chunk_extra.balance_burnt(),
+            chunk_extra.delayed_receipts_gas(),
             chunk_extra.validator_proposals().collect(),
             transactions,
             &outgoing_receipts,
@@ -690,6 +693,17 @@ impl Client {
         let next_epoch_id =
             runtime_adapter.get_epoch_id_from_prev_block(prev_block_header.hash())?;
         let protocol_version = runtime_adapter.get_epoch_protocol_version(&next_epoch_id)?;
+        // Transactions to congested shards stay in the pool until the congestion clears.
+        let congestion_info = if checked_feature!(
+            "protocol_feature_congestion_control",
+            CongestionControl,
+            protocol_version
+        ) {
+            let prev_block = chain.get_block(prev_block_header.hash())?;
+            Some(get_block_congestion_info(runtime_adapter.as_ref(), prev_block)?)
+        } else {
+            None
+        };
 
         let transactions = if let Some(mut iter) = shards_mgr.get_pool_iterator(shard_id) {
             let transaction_validity_period = chain.transaction_validity_period;
@@ -705,6 +719,11 @@ impl Client {
                 prev_block_header.height() + 1,
                 &mut iter,
                 &mut |tx: &SignedTransaction| -> bool {
+                    if let Some(congestion_info) = &congestion_info {
+                        if congestion_info.congested_shard(&tx.transaction.receiver_id).is_some() {
+                            return false;
+                        }
+                    }
                     chain
                         .mut_store()
                         .check_transaction_validity_period(
@@ -1002,6 +1021,17 @@ impl Client {
         Ok(())
     }
 
+    /// Reverts the chain to a block returned by an earlier `sandbox_snapshot` request. Block
+    /// production continues on top of it from the latest known height. Pending transactions are
+    /// dropped, since they were validated against the state being reverted.
+    #[cfg(feature = "sandbox")]
+    pub fn sandbox_revert(&mut self, block_hash: &CryptoHash) -> Result<(), Error> {
+        self.chain.sandbox_revert(block_hash)?;
+        self.shards_mgr.clear_transactions();
+        let latest_known = self.chain.mut_store().get_latest_known()?;
+        self.sandbox_update_tip(latest_known.height)
+    }
+
     /// Gets the advanced timestamp delta in nanoseconds for sandbox once it has been fast-forwarded
     #[cfg(feature = "sandbox")]
     pub fn sandbox_delta_time(&self) -> chrono::Duration {
@@ -1603,6 +1633,12 @@ impl Client {
             debug!(target: "client", "Invalid tx: expired or from a different fork -- {:?}", tx);
             return Ok(NetworkClientResponses::InvalidTx(e));
         }
+        // The chunk including the transaction will be in a block with at least this height.
+        // Transactions which aren't valid yet are rejected rather than kept in the pool.
+        if let Err(e) = tx.transaction.check_validity_window(cur_block_header.height() + 1) {
+            debug!(target: "client", "Invalid tx: outside of its validity window -- {:?}", tx);
+            return Ok(NetworkClientResponses::InvalidTx(e));
+        }
         let gas_price = cur_block_header.gas_price();
         let epoch_id = self.runtime_adapter.get_epoch_id_from_prev_block(&head.last_block_hash)?;
 
@@ -1610,13 +1646,37 @@ impl Client {
 
         if let Some(err) = self
             .runtime_adapter
-            .validate_tx(gas_price, None, tx, true, &epoch_id, protocol_version)
+            .validate_tx(
+                gas_price,
+                None,
+                cur_block_header.height() + 1,
+                tx,
+                true,
+                &epoch_id,
+                protocol_version,
+            )
             .expect("no storage errors")
         {
             debug!(target: "client", "Invalid tx during basic validation: {:?}", err);
             return Ok(NetworkClientResponses::InvalidTx(err));
         }
 
+        if checked_feature!(
+            "protocol_feature_congestion_control",
+            CongestionControl,
+            protocol_version
+        ) {
+            let head_block = self.chain.get_block(&head.last_block_hash)?;
+            let congestion_info =
+                get_block_congestion_info(self.runtime_adapter.as_ref(), head_block)?;
+            if let Some(shard_id) = congestion_info.congested_shard(&tx.transaction.receiver_id) {
+                debug!(target: "client", "Invalid tx: receiver shard {} is congested -- {:?}", shard_id, tx);
+                return Ok(NetworkClientResponses::InvalidTx(InvalidTxError::ShardCongested {
+                    shard_id,
+                }));
+            }
+        }
+
         let shard_id =
             self.runtime_adapter.account_id_to_shard_id(&tx.transaction.signer_id, &epoch_id)?;
         if self.runtime_adapter.cares_about_shard(me, &head.last_block_hash, shard_id, true)
@@ -1640,7 +1700,15 @@ impl Client {
             };
             if let Some(err) = self
                 .runtime_adapter
-                .validate_tx(gas_price, Some(state_root), tx, false, &epoch_id, protocol_version)
+                .validate_tx(
+                    gas_price,
+                    Some(state_root),
+                    cur_block_header.height() + 1,
+                    tx,
+                    false,
+                    &epoch_id,
+                    protocol_version,
+                )
                 .expect("no storage errors")
             {
                 debug!(target: "client", "Invalid tx: {:?}", err);
@@ -1790,9 +1858,9 @@ impl Client {
                 state_split_scheduler,
             )? {
                 StateSyncResult::Unchanged => {}
-                StateSyncResult::Changed(fetch_block) => {
+                StateSyncResult::Changed(fetch_blocks) => {
                     debug!(target:"catchup", "state sync finished but waiting to fetch block");
-                    assert!(!fetch_block);
+                    assert!(fetch_blocks.is_empty());
                 }
                 StateSyncResult::Completed => {
                     debug!(target:"catchup", "state sync completed now catch up blocks");
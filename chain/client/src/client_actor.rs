@@ -401,6 +401,43 @@ impl ClientActor {
                             ),
                         )
                     }
+                    near_network_primitives::types::NetworkSandboxMessage::SandboxSnapshot => {
+                        match self.client.chain.head() {
+                            Ok(head) => NetworkClientResponses::SandboxResult(
+                                near_network_primitives::types::SandboxResponse::SandboxSnapshot {
+                                    block_hash: head.last_block_hash,
+                                    height: head.height,
+                                },
+                            ),
+                            Err(err) => NetworkClientResponses::SandboxResult(
+                                near_network_primitives::types::SandboxResponse::SandboxSnapshotFailed(
+                                    err.to_string(),
+                                ),
+                            ),
+                        }
+                    }
+                    near_network_primitives::types::NetworkSandboxMessage::SandboxRevert(block_hash) => {
+                        if self.fastforward_delta > 0 {
+                            return NetworkClientResponses::SandboxResult(
+                                near_network_primitives::types::SandboxResponse::SandboxRevertFailed(
+                                    "Cannot revert while a fast_forward request is going on.".to_string()));
+                        }
+                        if self.client.chain.patch_state_in_progress() {
+                            return NetworkClientResponses::SandboxResult(
+                                near_network_primitives::types::SandboxResponse::SandboxRevertFailed(
+                                    "Cannot revert while a patch_state request is going on.".to_string()));
+                        }
+                        match self.client.sandbox_revert(&block_hash) {
+                            Ok(()) => NetworkClientResponses::SandboxResult(
+                                near_network_primitives::types::SandboxResponse::SandboxRevertFinished,
+                            ),
+                            Err(err) => NetworkClientResponses::SandboxResult(
+                                near_network_primitives::types::SandboxResponse::SandboxRevertFailed(
+                                    err.to_string(),
+                                ),
+                            ),
+                        }
+                    }
                 };
             }
             NetworkClientMessages::Transaction { transaction, is_forwarded, check_only } => {
@@ -425,6 +462,20 @@ impl ClientActor {
                                 if let Err(e) = self.client.chain.save_orphan(block.into(), false) {
                                     error!(target: "client", "Received an invalid block during state sync: {}", e);
                                 }
+                            } else if block.header().height() < header.height()
+                                && self
+                                    .client
+                                    .chain
+                                    .get_header_on_chain_by_height(
+                                        &*sync_hash,
+                                        block.header().height(),
+                                    )
+                                    .map_or(false, |header| header.hash() == block.hash())
+                            {
+                                // Older blocks are needed to finalize the downloaded state.
+                                if let Err(e) = self.client.chain.save_block(block.into()) {
+                                    error!(target: "client", "Failed to save a block during state sync: {}", e);
+                                }
                             }
                             return NetworkClientResponses::NoResponse;
                         }
@@ -1659,20 +1710,18 @@ impl ClientActor {
                     &self.state_split_scheduler,
                 )) {
                     StateSyncResult::Unchanged => (),
-                    StateSyncResult::Changed(fetch_block) => {
+                    StateSyncResult::Changed(fetch_blocks) => {
                         self.client.sync_status = SyncStatus::StateSync(sync_hash, new_shard_sync);
-                        if fetch_block {
+                        if !fetch_blocks.is_empty() {
                             if let Some(peer_info) =
                                 self.network_info.highest_height_peers.choose(&mut thread_rng())
                             {
                                 let id = peer_info.peer_info.id.clone();
 
-                                if let Ok(header) = self.client.chain.get_block_header(&sync_hash) {
-                                    for hash in
-                                        vec![*header.prev_hash(), *header.hash()].into_iter()
-                                    {
-                                        self.request_block_by_hash(hash, id.clone());
-                                    }
+                                for hash in
+                                    fetch_blocks.into_iter().chain(std::iter::once(sync_hash))
+                                {
+                                    self.request_block_by_hash(hash, id.clone());
                                 }
                             }
                         }
@@ -591,8 +591,8 @@ pub enum StateSyncResult {
     /// No shard has changed its status
     Unchanged,
     /// At least one shard has changed its status
-    /// Boolean parameter specifies whether the client needs to start fetching the block
-    Changed(bool),
+    /// Parameter lists the blocks the client needs to start fetching
+    Changed(Vec<CryptoHash>),
     /// The state for all shards was downloaded.
     Completed,
 }
@@ -629,6 +629,9 @@ pub struct StateSync {
 
     /// Maps shard_id to result of splitting state for resharding
     split_state_roots: HashMap<ShardId, Result<HashMap<ShardUId, StateRoot>, Error>>,
+
+    /// Maps sync_hash to blocks needed to finalize the downloaded state of the shards
+    state_finalize_blocks: HashMap<CryptoHash, HashSet<CryptoHash>>,
 }
 
 impl StateSync {
@@ -642,35 +645,43 @@ impl StateSync {
             timeout: Duration::from_std(timeout).unwrap(),
             state_parts_apply_results: HashMap::new(),
             split_state_roots: HashMap::new(),
+            state_finalize_blocks: HashMap::new(),
         }
     }
 
+    /// Returns the blocks to request now and whether all the given blocks are stored.
     pub fn sync_block_status(
         &mut self,
-        prev_hash: &CryptoHash,
+        block_hashes: &[CryptoHash],
         chain: &mut Chain,
         now: DateTime<Utc>,
-    ) -> Result<(bool, bool), near_chain::Error> {
-        let (request_block, have_block) = if !chain.block_exists(prev_hash)? {
+    ) -> Result<(Vec<CryptoHash>, bool), near_chain::Error> {
+        let mut missing_blocks = vec![];
+        for block_hash in block_hashes {
+            if !chain.block_exists(block_hash)? {
+                missing_blocks.push(*block_hash);
+            }
+        }
+        let (request_blocks, have_blocks) = if !missing_blocks.is_empty() {
             match self.last_time_block_requested {
-                None => (true, false),
+                None => (missing_blocks, false),
                 Some(last_time) => {
                     if now - last_time >= self.timeout {
-                        error!(target: "sync", "State sync: block request for {} timed out in {} seconds", prev_hash, self.timeout.num_seconds());
-                        (true, false)
+                        error!(target: "sync", "State sync: block request for {:?} timed out in {} seconds", missing_blocks, self.timeout.num_seconds());
+                        (missing_blocks, false)
                     } else {
-                        (false, false)
+                        (vec![], false)
                     }
                 }
             }
         } else {
             self.last_time_block_requested = None;
-            (false, true)
+            (vec![], true)
         };
-        if request_block {
+        if !request_blocks.is_empty() {
             self.last_time_block_requested = Some(now);
         };
-        Ok((request_block, have_block))
+        Ok((request_blocks, have_blocks))
     }
 
     pub fn sync_shards_status(
@@ -814,7 +825,27 @@ impl StateSync {
                     }
                 }
                 ShardSyncStatus::StateDownloadApplying => {
-                    let result = self.state_parts_apply_results.remove(&shard_id);
+                    // Finalizing needs the blocks from the one the chunk was produced on, so the
+                    // result is kept until all of them are downloaded.
+                    let missing_blocks = if self.state_parts_apply_results.contains_key(&shard_id) {
+                        chain.get_state_finalize_missing_blocks(shard_id, sync_hash)?
+                    } else {
+                        vec![]
+                    };
+                    let mut new_missing_block = false;
+                    let state_finalize_blocks =
+                        self.state_finalize_blocks.entry(sync_hash).or_default();
+                    for block_hash in &missing_blocks {
+                        new_missing_block |= state_finalize_blocks.insert(*block_hash);
+                    }
+                    if new_missing_block {
+                        self.last_time_block_requested = None;
+                    }
+                    let result = if missing_blocks.is_empty() {
+                        self.state_parts_apply_results.remove(&shard_id)
+                    } else {
+                        None
+                    };
                     if let Some(result) = result {
                         match chain.set_state_finalize(shard_id, sync_hash, result) {
                             Ok(()) => {
@@ -1182,14 +1213,18 @@ impl StateSync {
         let prev_hash = *chain.get_block_header(&sync_hash)?.prev_hash();
         let now = Clock::utc();
 
-        let (request_block, have_block) = self.sync_block_status(&prev_hash, chain, now)?;
+        let mut block_hashes = vec![prev_hash];
+        if let Some(state_finalize_blocks) = self.state_finalize_blocks.get(&sync_hash) {
+            block_hashes.extend(state_finalize_blocks.iter().filter(|hash| **hash != prev_hash));
+        }
+        let (request_blocks, have_block) = self.sync_block_status(&block_hashes, chain, now)?;
 
         if tracking_shards.is_empty() {
             // This case is possible if a validator cares about the same shards in the new epoch as
             //    in the previous (or about a subset of them), return success right away
 
             return if !have_block {
-                Ok(StateSyncResult::Changed(request_block))
+                Ok(StateSyncResult::Changed(request_blocks))
             } else {
                 Ok(StateSyncResult::Completed)
             };
@@ -1210,11 +1245,12 @@ impl StateSync {
 
         if have_block && all_done {
             self.state_sync_time.clear();
+            self.state_finalize_blocks.clear();
             return Ok(StateSyncResult::Completed);
         }
 
-        Ok(if update_sync_status || request_block {
-            StateSyncResult::Changed(request_block)
+        Ok(if update_sync_status || !request_blocks.is_empty() {
+            StateSyncResult::Changed(request_blocks)
         } else {
             StateSyncResult::Unchanged
         })
@@ -994,6 +994,8 @@ pub fn setup_mock_all_validators(
                         | NetworkRequests::ReceiptOutComeRequest(_, _) => {}
                         #[cfg(feature = "protocol_feature_routing_exchange_algorithm")]
                         | NetworkRequests::IbfMessage { .. } => {}
+                        #[cfg(feature = "protocol_feature_validator_direct_connections")]
+                        | NetworkRequests::SyncAccountsAddrs { .. } => {}
                     };
                 }
                 Box::new(Some(resp))
@@ -1632,6 +1634,7 @@ pub fn create_chunk(
             header.gas_used(),
             header.gas_limit(),
             header.balance_burnt(),
+            header.delayed_receipts_gas(),
             tx_root,
             header.validator_proposals().collect(),
             transactions,
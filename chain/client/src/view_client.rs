@@ -19,12 +19,12 @@ use near_chain::{
 use near_chain_configs::{ClientConfig, ProtocolConfigView};
 use near_client_primitives::types::{
     Error, GetBlock, GetBlockError, GetBlockHash, GetBlockProof, GetBlockProofError,
-    GetBlockProofResponse, GetBlockWithMerkleTree, GetChunkError, GetExecutionOutcome,
-    GetExecutionOutcomeError, GetExecutionOutcomesForBlock, GetGasPrice, GetGasPriceError,
-    GetNextLightClientBlockError, GetProtocolConfig, GetProtocolConfigError, GetReceipt,
-    GetReceiptError, GetStateChangesError, GetStateChangesWithCauseInBlock,
-    GetStateChangesWithCauseInBlockForTrackedShards, GetValidatorInfoError, Query, QueryError,
-    TxStatus, TxStatusError,
+    GetBlockProofResponse, GetBlockWithMerkleTree, GetChunkError, GetDelayedReceipts,
+    GetDelayedReceiptsError, GetExecutionOutcome, GetExecutionOutcomeError,
+    GetExecutionOutcomesForBlock, GetGasPrice, GetGasPriceError, GetNextLightClientBlockError,
+    GetProtocolConfig, GetProtocolConfigError, GetReceipt, GetReceiptError, GetStateChangesError,
+    GetStateChangesWithCauseInBlock, GetStateChangesWithCauseInBlockForTrackedShards,
+    GetValidatorInfoError, Query, QueryError, TxStatus, TxStatusError,
 };
 use near_network::types::{NetworkRequests, PeerManagerAdapter, PeerManagerMessageRequest};
 #[cfg(feature = "test_features")]
@@ -37,7 +37,7 @@ use near_performance_metrics_macros::{perf, perf_with_debug};
 use near_primitives::block::{Block, BlockHeader, GenesisId, Tip};
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{merklize, PartialMerkleTree};
-use near_primitives::network::AnnounceAccount;
+use near_primitives::network::{AnnounceAccount, AnnounceAccountAddrs};
 use near_primitives::sharding::ShardChunk;
 use near_primitives::syncing::{
     ShardStateSyncResponse, ShardStateSyncResponseHeader, ShardStateSyncResponseV1,
@@ -49,7 +49,7 @@ use near_primitives::types::{
 };
 use near_primitives::views::validator_stake_view::ValidatorStakeView;
 use near_primitives::views::{
-    BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
+    BlockView, ChunkView, DelayedReceiptsView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
     FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum, FinalExecutionStatus, GasPriceView,
     LightClientBlockView, QueryRequest, QueryResponse, ReceiptView, StateChangesKindsView,
     StateChangesView,
@@ -64,6 +64,10 @@ use crate::{
 const QUERY_REQUEST_LIMIT: usize = 500;
 /// Waiting time between requests, in ms
 const REQUEST_WAIT_TIME: u64 = 1000;
+/// Number of delayed receipts returned per shard if the request doesn't specify a limit.
+const DEFAULT_DELAYED_RECEIPTS_LIMIT: u64 = 100;
+/// Max number of delayed receipts returned per shard.
+const MAX_DELAYED_RECEIPTS_LIMIT: u64 = 1000;
 
 const POISONED_LOCK_ERR: &str = "The lock was poisoned.";
 
@@ -255,6 +259,7 @@ impl ViewClientActor {
             QueryRequest::ViewAccessKeyList { account_id, .. } => account_id,
             QueryRequest::CallFunction { account_id, .. } => account_id,
             QueryRequest::ViewCode { account_id, .. } => account_id,
+            QueryRequest::ViewStorageBreakdown { account_id, .. } => account_id,
         };
         let shard_id =
             self.runtime_adapter
@@ -502,6 +507,23 @@ impl ViewClientActor {
             .map_err(|e| e.into())
     }
 
+    fn check_signature_account_addrs(
+        &self,
+        account_addrs: &AnnounceAccountAddrs,
+    ) -> Result<bool, Error> {
+        let head = self.chain.head()?;
+
+        self.runtime_adapter
+            .verify_validator_signature(
+                &account_addrs.epoch_id,
+                &head.last_block_hash,
+                &account_addrs.account_id,
+                account_addrs.hash().as_ref(),
+                &account_addrs.signature,
+            )
+            .map_err(|e| e.into())
+    }
+
     fn get_height(&self, head: &Tip) -> BlockHeight {
         #[cfg(feature = "test_features")]
         {
@@ -1031,6 +1053,64 @@ impl Handler<GetProtocolConfig> for ViewClientActor {
     }
 }
 
+impl Handler<GetDelayedReceipts> for ViewClientActor {
+    type Result = Result<Vec<DelayedReceiptsView>, GetDelayedReceiptsError>;
+
+    #[perf]
+    fn handle(&mut self, msg: GetDelayedReceipts, _: &mut Self::Context) -> Self::Result {
+        let block_hash = match msg.block_reference {
+            BlockReference::Finality(finality) => self.get_block_hash_by_finality(&finality)?,
+            BlockReference::BlockId(block_id) => {
+                self.maybe_block_id_to_block_hash(Some(block_id))?
+            }
+            BlockReference::SyncCheckpoint(sync_checkpoint) => {
+                match self.get_block_hash_by_sync_checkpoint(&sync_checkpoint)? {
+                    Some(block_hash) => block_hash,
+                    None => {
+                        return Err(GetDelayedReceiptsError::UnknownBlock(format!(
+                            "{:?}",
+                            sync_checkpoint
+                        )))
+                    }
+                }
+            }
+        };
+        let header = self.chain.get_block_header(&block_hash)?.clone();
+        let epoch_id = header.epoch_id();
+        let limit =
+            msg.limit.unwrap_or(DEFAULT_DELAYED_RECEIPTS_LIMIT).min(MAX_DELAYED_RECEIPTS_LIMIT);
+
+        let shard_ids = match msg.shard_id {
+            Some(shard_id) => vec![shard_id],
+            None => (0..self.runtime_adapter.num_shards(epoch_id)?).collect(),
+        };
+        let mut views = vec![];
+        for shard_id in shard_ids {
+            let shard_uid = self.runtime_adapter.shard_id_to_uid(shard_id, epoch_id)?;
+            let chunk_extra = match self.chain.get_chunk_extra(header.hash(), &shard_uid) {
+                Ok(chunk_extra) => chunk_extra,
+                Err(err) => match err.kind() {
+                    near_chain::near_chain_primitives::ErrorKind::DBNotFoundErr(_) => {
+                        if msg.shard_id.is_some() {
+                            return Err(GetDelayedReceiptsError::UnavailableShard(shard_id));
+                        }
+                        // Skip the shards this node doesn't track.
+                        continue;
+                    }
+                    _ => return Err(err.into()),
+                },
+            };
+            views.push(self.runtime_adapter.get_delayed_receipts(
+                shard_uid,
+                chunk_extra.state_root(),
+                msg.from_index,
+                limit,
+            )?);
+        }
+        Ok(views)
+    }
+}
+
 impl Handler<NetworkViewClientMessages> for ViewClientActor {
     type Result = NetworkViewClientResponses;
 
@@ -1363,6 +1443,38 @@ impl Handler<NetworkViewClientMessages> for ViewClientActor {
 
                 NetworkViewClientResponses::AnnounceAccount(filtered_announce_accounts)
             }
+            NetworkViewClientMessages::AnnounceAccountAddrs(accounts_addrs) => {
+                let mut filtered_accounts_addrs = Vec::new();
+
+                for (account_addrs, last_epoch) in accounts_addrs {
+                    // Keep the addresses if they are newer than the last ones from the same account.
+                    if let Some(last_epoch) = last_epoch {
+                        match self
+                            .runtime_adapter
+                            .compare_epoch_id(&account_addrs.epoch_id, &last_epoch)
+                        {
+                            Ok(Ordering::Greater) => {}
+                            _ => continue,
+                        }
+                    }
+
+                    match self.check_signature_account_addrs(&account_addrs) {
+                        Ok(true) => {
+                            filtered_accounts_addrs.push(account_addrs);
+                        }
+                        Ok(false) => {
+                            return NetworkViewClientResponses::Ban {
+                                ban_reason: ReasonForBan::InvalidSignature,
+                            };
+                        }
+                        Err(e) => {
+                            debug!(target: "view_client", "Failed to validate account addrs signature: {}", e);
+                        }
+                    }
+                }
+
+                NetworkViewClientResponses::AnnounceAccountAddrs(filtered_accounts_addrs)
+            }
             NetworkViewClientMessages::EpochSyncRequest { epoch_id: _epoch_id } => {
                 // TODO #3488
                 NetworkViewClientResponses::NoResponse
@@ -39,6 +39,8 @@ pub(crate) async fn convert_transactions_sir_into_local_receipts(
                                 near_primitives::transaction::Action::try_from(action).unwrap()
                             })
                             .collect(),
+                        valid_from_height: tx.transaction.valid_from_height,
+                        valid_until_height: tx.transaction.valid_until_height,
                     },
                     prev_block_gas_price,
                     true,
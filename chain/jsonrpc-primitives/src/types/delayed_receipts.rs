@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcDelayedReceiptsRequest {
+    #[serde(flatten)]
+    pub block_reference: near_primitives::types::BlockReference,
+    #[serde(default)]
+    pub shard_id: Option<near_primitives::types::ShardId>,
+    #[serde(default)]
+    pub from_index: Option<u64>,
+    #[serde(default)]
+    pub limit: Option<u64>,
+}
+
+impl RpcDelayedReceiptsRequest {
+    pub fn parse(value: Option<Value>) -> Result<Self, crate::errors::RpcParseError> {
+        Ok(crate::utils::parse_params::<Self>(value)?)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcDelayedReceiptsResponse {
+    pub shards: Vec<near_primitives::views::DelayedReceiptsView>,
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcDelayedReceiptsError {
+    #[error("Block has never been observed: {error_message}")]
+    UnknownBlock {
+        #[serde(skip_serializing)]
+        error_message: String,
+    },
+    #[error("Shard id {requested_shard_id} does not exist or is not tracked by this node")]
+    UnavailableShard { requested_shard_id: near_primitives::types::ShardId },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<RpcDelayedReceiptsRequest> for near_client_primitives::types::GetDelayedReceipts {
+    fn from(request: RpcDelayedReceiptsRequest) -> Self {
+        Self {
+            block_reference: request.block_reference,
+            shard_id: request.shard_id,
+            from_index: request.from_index,
+            limit: request.limit,
+        }
+    }
+}
+
+impl From<near_client_primitives::types::GetDelayedReceiptsError> for RpcDelayedReceiptsError {
+    fn from(error: near_client_primitives::types::GetDelayedReceiptsError) -> Self {
+        match error {
+            near_client_primitives::types::GetDelayedReceiptsError::UnknownBlock(error_message) => {
+                Self::UnknownBlock { error_message }
+            }
+            near_client_primitives::types::GetDelayedReceiptsError::UnavailableShard(shard_id) => {
+                Self::UnavailableShard { requested_shard_id: shard_id }
+            }
+            near_client_primitives::types::GetDelayedReceiptsError::IOError(error_message) => {
+                Self::InternalError { error_message }
+            }
+            near_client_primitives::types::GetDelayedReceiptsError::Unreachable(
+                ref error_message,
+            ) => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", &error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcDelayedReceiptsError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
+
+impl From<actix::MailboxError> for RpcDelayedReceiptsError {
+    fn from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl From<RpcDelayedReceiptsError> for crate::errors::RpcError {
+    fn from(error: RpcDelayedReceiptsError) -> Self {
+        let error_data = match &error {
+            RpcDelayedReceiptsError::UnknownBlock { error_message } => {
+                Some(Value::String(format!("Block Not Found: {}", error_message)))
+            }
+            RpcDelayedReceiptsError::UnavailableShard { .. }
+            | RpcDelayedReceiptsError::InternalError { .. } => {
+                Some(Value::String(error.to_string()))
+            }
+        };
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcDelayedReceiptsError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
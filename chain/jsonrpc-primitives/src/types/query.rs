@@ -87,6 +87,7 @@ pub enum QueryResponseKind {
     CallResult(near_primitives::views::CallResult),
     AccessKey(near_primitives::views::AccessKeyView),
     AccessKeyList(near_primitives::views::AccessKeyList),
+    StorageBreakdown(near_primitives::views::StorageBreakdownView),
 }
 
 impl RpcQueryRequest {
@@ -255,6 +256,9 @@ impl From<near_primitives::views::QueryResponseKind> for QueryResponseKind {
             near_primitives::views::QueryResponseKind::AccessKeyList(access_key_list) => {
                 Self::AccessKeyList(access_key_list)
             }
+            near_primitives::views::QueryResponseKind::StorageBreakdown(storage_breakdown) => {
+                Self::StorageBreakdown(storage_breakdown)
+            }
         }
     }
 }
@@ -1,5 +1,6 @@
+use near_primitives::hash::CryptoHash;
 use near_primitives::state_record::StateRecord;
-use near_primitives::types::BlockHeightDelta;
+use near_primitives::types::{BlockHeight, BlockHeightDelta};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -86,3 +87,178 @@ impl From<RpcSandboxFastForwardError> for crate::errors::RpcError {
         Self::new_internal_or_handler_error(Some(error_data.clone()), error_data)
     }
 }
+
+#[derive(Deserialize, Serialize)]
+pub struct RpcSandboxSnapshotRequest {}
+
+impl RpcSandboxSnapshotRequest {
+    pub fn parse(value: Option<Value>) -> Result<Self, crate::errors::RpcParseError> {
+        Ok(crate::utils::parse_params::<RpcSandboxSnapshotRequest>(value)?)
+    }
+}
+
+/// Identifies the snapshot by the head block at the time of the request. The snapshot can be
+/// reverted to as long as the block isn't garbage collected.
+#[derive(Deserialize, Serialize)]
+pub struct RpcSandboxSnapshotResponse {
+    pub block_hash: CryptoHash,
+    pub block_height: BlockHeight,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct RpcSandboxRevertRequest {
+    /// `block_hash` returned by `sandbox_snapshot`.
+    pub block_hash: CryptoHash,
+}
+
+impl RpcSandboxRevertRequest {
+    pub fn parse(value: Option<Value>) -> Result<Self, crate::errors::RpcParseError> {
+        Ok(crate::utils::parse_params::<RpcSandboxRevertRequest>(value)?)
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct RpcSandboxRevertResponse {}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcSandboxSnapshotError {
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<actix::MailboxError> for RpcSandboxSnapshotError {
+    fn from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl From<RpcSandboxSnapshotError> for crate::errors::RpcError {
+    fn from(error: RpcSandboxSnapshotError) -> Self {
+        let error_data = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcSandboxSnapshotError: {:?}", err),
+                )
+            }
+        };
+        Self::new_internal_or_handler_error(Some(error_data.clone()), error_data)
+    }
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcSandboxRevertError {
+    #[error("Failed to revert to the snapshot: {error_message}")]
+    RevertFailed { error_message: String },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<actix::MailboxError> for RpcSandboxRevertError {
+    fn from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl From<RpcSandboxRevertError> for crate::errors::RpcError {
+    fn from(error: RpcSandboxRevertError) -> Self {
+        let error_data = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcSandboxRevertError: {:?}", err),
+                )
+            }
+        };
+        Self::new_internal_or_handler_error(Some(error_data.clone()), error_data)
+    }
+}
+
+/// Output format of the collected coverage.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcSandboxCoverageFormat {
+    Json,
+    Lcov,
+}
+
+impl Default for RpcSandboxCoverageFormat {
+    fn default() -> Self {
+        Self::Json
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RpcSandboxCoverageRequest {
+    /// Enables or disables instrumentation of contracts executed from now on. Leaves the current
+    /// setting unchanged if not given.
+    #[serde(default)]
+    pub enable: Option<bool>,
+    /// Discards the counts collected so far, after returning them.
+    #[serde(default)]
+    pub reset: bool,
+    #[serde(default)]
+    pub format: RpcSandboxCoverageFormat,
+}
+
+impl RpcSandboxCoverageRequest {
+    pub fn parse(value: Option<Value>) -> Result<Self, crate::errors::RpcParseError> {
+        Ok(crate::utils::parse_params::<RpcSandboxCoverageRequest>(value)?)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RpcFunctionCoverageView {
+    /// Index of the function in the function index space, imported functions included.
+    pub index: u32,
+    /// Name of the function from the Wasm name section.
+    pub name: Option<String>,
+    /// Index of the first block of the function among the blocks of the contract.
+    pub first_block: u32,
+    /// Execution counts of the blocks of the function, the function body first.
+    pub blocks: Vec<u64>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RpcContractCoverageView {
+    pub code_hash: CryptoHash,
+    pub functions: Vec<RpcFunctionCoverageView>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RpcSandboxCoverageResponse {
+    /// Whether contracts are instrumented after the request.
+    pub enabled: bool,
+    /// Counts per contract code hash, with the `json` format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contracts: Option<Vec<RpcContractCoverageView>>,
+    /// LCOV tracefile, with the `lcov` format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lcov: Option<String>,
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcSandboxCoverageError {
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<RpcSandboxCoverageError> for crate::errors::RpcError {
+    fn from(error: RpcSandboxCoverageError) -> Self {
+        let error_data = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcSandboxCoverageError: {:?}", err),
+                )
+            }
+        };
+        Self::new_internal_or_handler_error(Some(error_data.clone()), error_data)
+    }
+}
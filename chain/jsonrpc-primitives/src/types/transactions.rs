@@ -11,6 +11,15 @@ pub struct RpcBroadcastTransactionRequest {
 #[derive(Debug)]
 pub struct RpcTransactionStatusCommonRequest {
     pub transaction_info: TransactionInfo,
+    pub options: RpcTransactionStatusOptions,
+}
+
+/// Optional last parameter of the transaction status requests.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct RpcTransactionStatusOptions {
+    /// Whether to return the tree of gas burnt by the transaction and all of its receipts.
+    #[serde(default)]
+    pub gas_profile_tree: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -46,6 +55,8 @@ pub enum RpcTransactionError {
 pub struct RpcTransactionResponse {
     #[serde(flatten)]
     pub final_execution_outcome: near_primitives::views::FinalExecutionOutcomeViewEnum,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_profile_tree: Option<near_primitives::views::GasProfileNodeView>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -62,18 +73,32 @@ impl RpcBroadcastTransactionRequest {
 
 impl RpcTransactionStatusCommonRequest {
     pub fn parse(value: Option<Value>) -> Result<Self, crate::errors::RpcParseError> {
+        let (value, options) = split_transaction_status_options(value)?;
         if let Ok((hash, account_id)) = crate::utils::parse_params::<(
             near_primitives::hash::CryptoHash,
             AccountId,
         )>(value.clone())
         {
             let transaction_info = TransactionInfo::TransactionId { hash, account_id };
-            Ok(Self { transaction_info })
+            Ok(Self { transaction_info, options })
         } else {
             let signed_transaction = crate::utils::parse_signed_transaction(value)?;
             let transaction_info = TransactionInfo::Transaction(signed_transaction);
-            Ok(Self { transaction_info })
+            Ok(Self { transaction_info, options })
+        }
+    }
+}
+
+/// Removes the trailing options object, if any, from the positional parameters.
+fn split_transaction_status_options(
+    value: Option<Value>,
+) -> Result<(Option<Value>, RpcTransactionStatusOptions), crate::errors::RpcParseError> {
+    match value {
+        Some(Value::Array(mut params)) if matches!(params.last(), Some(Value::Object(_))) => {
+            let options = crate::utils::parse_params(params.pop())?;
+            Ok((Some(Value::Array(params)), options))
         }
+        value => Ok((value, RpcTransactionStatusOptions::default())),
     }
 }
 
@@ -101,7 +126,7 @@ impl From<near_primitives::views::FinalExecutionOutcomeViewEnum> for RpcTransact
     fn from(
         final_execution_outcome: near_primitives::views::FinalExecutionOutcomeViewEnum,
     ) -> Self {
-        Self { final_execution_outcome }
+        Self { final_execution_outcome, gas_profile_tree: None }
     }
 }
 
@@ -141,3 +166,30 @@ impl From<actix::MailboxError> for RpcTransactionError {
         Self::InternalError { debug_info: error.to_string() }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_transaction_status_options() {
+        let hash = near_primitives::hash::CryptoHash::default();
+        let request = RpcTransactionStatusCommonRequest::parse(Some(json!([hash, "alice.near"])))
+            .ok()
+            .unwrap();
+        assert!(matches!(request.transaction_info, TransactionInfo::TransactionId { .. }));
+        assert!(!request.options.gas_profile_tree);
+
+        let request = RpcTransactionStatusCommonRequest::parse(Some(json!([
+            hash,
+            "alice.near",
+            {"gas_profile_tree": true}
+        ])))
+        .ok()
+        .unwrap();
+        assert!(matches!(request.transaction_info, TransactionInfo::TransactionId { .. }));
+        assert!(request.options.gas_profile_tree);
+    }
+}
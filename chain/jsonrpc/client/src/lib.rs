@@ -258,6 +258,15 @@ impl JsonRpcClient {
     ) -> RpcRequest<near_jsonrpc_primitives::types::config::RpcProtocolConfigResponse> {
         call_method(&self.client, &self.server_addr, "EXPERIMENTAL_protocol_config", request)
     }
+
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_delayed_receipts(
+        &self,
+        request: near_jsonrpc_primitives::types::delayed_receipts::RpcDelayedReceiptsRequest,
+    ) -> RpcRequest<near_jsonrpc_primitives::types::delayed_receipts::RpcDelayedReceiptsResponse>
+    {
+        call_method(&self.client, &self.server_addr, "EXPERIMENTAL_delayed_receipts", request)
+    }
 }
 
 fn create_client() -> Client {
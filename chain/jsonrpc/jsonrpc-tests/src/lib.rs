@@ -8,6 +8,8 @@ use near_client::test_utils::setup_no_network_with_validity_period_and_no_epoch_
 use near_client::ViewClientActor;
 use near_jsonrpc::{start_http, RpcConfig};
 use near_jsonrpc_primitives::message::{from_slice, Message};
+#[cfg(not(feature = "test_features"))]
+use near_network::test_utils::make_routing_table_addr;
 use near_network::test_utils::open_port;
 #[cfg(feature = "test_features")]
 use near_network::test_utils::test_features::make_peer_manager_routing_table_addr_pair;
@@ -47,6 +49,8 @@ pub fn start_all_with_validity_period_and_no_epoch_sync(
 
     #[cfg(feature = "test_features")]
     let (peer_manager_addr, routing_table_addr) = make_peer_manager_routing_table_addr_pair();
+    #[cfg(not(feature = "test_features"))]
+    let routing_table_addr = make_routing_table_addr();
 
     start_http(
         RpcConfig::new(&addr),
@@ -55,7 +59,6 @@ pub fn start_all_with_validity_period_and_no_epoch_sync(
         view_client_addr.clone(),
         #[cfg(feature = "test_features")]
         peer_manager_addr,
-        #[cfg(feature = "test_features")]
         routing_table_addr,
     );
     (view_client_addr, addr)
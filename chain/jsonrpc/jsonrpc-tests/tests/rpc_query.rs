@@ -7,6 +7,7 @@ use serde_json::json;
 use near_actix_test_utils::run_actix;
 use near_crypto::{KeyType, PublicKey, Signature};
 use near_jsonrpc::client::{new_client, ChunkId};
+use near_jsonrpc_primitives::types::delayed_receipts::RpcDelayedReceiptsRequest;
 use near_jsonrpc_primitives::types::query::QueryResponseKind;
 use near_jsonrpc_primitives::types::validator::RpcValidatorsOrderedRequest;
 use near_logger_utils::init_test_logger;
@@ -466,6 +467,29 @@ fn test_validators_ordered() {
     });
 }
 
+/// Retrieve the delayed receipt queues via JSON RPC.
+#[test]
+fn test_delayed_receipts() {
+    test_with_client!(test_utils::NodeType::NonValidator, client, async move {
+        let response = client
+            .EXPERIMENTAL_delayed_receipts(RpcDelayedReceiptsRequest {
+                block_reference: BlockReference::BlockId(BlockId::Height(0)),
+                shard_id: None,
+                from_index: None,
+                limit: None,
+            })
+            .await
+            .unwrap();
+        assert!(!response.shards.is_empty());
+        for shard in response.shards {
+            assert_eq!(shard.length, 0);
+            assert_eq!(shard.total_gas, 0);
+            assert_eq!(shard.oldest_receipt_id, None);
+            assert!(shard.receipts.is_empty());
+        }
+    });
+}
+
 /// Retrieve genesis config via JSON RPC.
 /// WARNING: Be mindful about changing genesis structure as it is part of the public protocol!
 #[test]
@@ -15,10 +15,10 @@ use tracing::info;
 
 use near_chain_configs::GenesisConfig;
 use near_client::{
-    ClientActor, GetBlock, GetBlockProof, GetChunk, GetExecutionOutcome, GetGasPrice,
-    GetNetworkInfo, GetNextLightClientBlock, GetProtocolConfig, GetReceipt, GetStateChanges,
-    GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered, Query, Status, TxStatus,
-    TxStatusError, ViewClientActor,
+    ClientActor, GetBlock, GetBlockProof, GetChunk, GetDelayedReceipts, GetExecutionOutcome,
+    GetGasPrice, GetNetworkInfo, GetNextLightClientBlock, GetProtocolConfig, GetReceipt,
+    GetStateChanges, GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered, Query, Status,
+    TxStatus, TxStatusError, ViewClientActor,
 };
 pub use near_jsonrpc_client as client;
 use near_jsonrpc_primitives::errors::RpcError;
@@ -211,7 +211,6 @@ struct JsonRpcHandler {
     enable_debug_rpc: bool,
     #[cfg(feature = "test_features")]
     peer_manager_addr: Addr<near_network::PeerManagerActor>,
-    #[cfg(feature = "test_features")]
     routing_table_addr: Addr<near_network::RoutingTableActor>,
 }
 
@@ -512,6 +511,15 @@ impl JsonRpcHandler {
                 serde_json::to_value(broadcast_tx_sync_response)
                     .map_err(|err| RpcError::serialization_error(err.to_string()))
             }
+            "EXPERIMENTAL_delayed_receipts" => {
+                let rpc_delayed_receipts_request =
+                    near_jsonrpc_primitives::types::delayed_receipts::RpcDelayedReceiptsRequest::parse(
+                        request.params,
+                    )?;
+                let delayed_receipts = self.delayed_receipts(rpc_delayed_receipts_request).await?;
+                serde_json::to_value(delayed_receipts)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
             "EXPERIMENTAL_genesis_config" => {
                 let genesis_config = self.genesis_config().await;
                 serde_json::to_value(genesis_config)
@@ -581,6 +589,37 @@ impl JsonRpcHandler {
                 serde_json::to_value(sandbox_fast_forward_response)
                     .map_err(|err| RpcError::serialization_error(err.to_string()))
             }
+            #[cfg(feature = "sandbox")]
+            "sandbox_snapshot" => {
+                let sandbox_snapshot_request =
+                    near_jsonrpc_primitives::types::sandbox::RpcSandboxSnapshotRequest::parse(
+                        request.params,
+                    )?;
+                let sandbox_snapshot_response =
+                    self.sandbox_snapshot(sandbox_snapshot_request).await?;
+                serde_json::to_value(sandbox_snapshot_response)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
+            #[cfg(feature = "sandbox")]
+            "sandbox_revert" => {
+                let sandbox_revert_request =
+                    near_jsonrpc_primitives::types::sandbox::RpcSandboxRevertRequest::parse(
+                        request.params,
+                    )?;
+                let sandbox_revert_response = self.sandbox_revert(sandbox_revert_request).await?;
+                serde_json::to_value(sandbox_revert_response)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
+            #[cfg(feature = "sandbox")]
+            "sandbox_coverage" => {
+                let sandbox_coverage_request =
+                    near_jsonrpc_primitives::types::sandbox::RpcSandboxCoverageRequest::parse(
+                        request.params,
+                    )?;
+                let sandbox_coverage_response = self.sandbox_coverage(sandbox_coverage_request)?;
+                serde_json::to_value(sandbox_coverage_response)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
             _ => Err(RpcError::method_not_found(request.method.clone())),
         };
 
@@ -710,13 +749,7 @@ impl JsonRpcHandler {
         timeout(self.polling_config.polling_timeout, async {
             loop {
                 match self.tx_status_fetch(tx_info.clone(), false).await {
-                    Ok(tx_status) => {
-                        break Ok(
-                            near_jsonrpc_primitives::types::transactions::RpcTransactionResponse {
-                                final_execution_outcome: tx_status,
-                            },
-                        )
-                    }
+                    Ok(tx_status) => break Ok(tx_status.into()),
                     // If transaction is missing, keep polling.
                     Err(TxStatusError::MissingTransaction(_)) => {}
                     // If we hit any other error, we return to the user.
@@ -843,9 +876,7 @@ impl JsonRpcHandler {
             .await
         {
             Ok(outcome) => {
-                return Ok(near_jsonrpc_primitives::types::transactions::RpcTransactionResponse {
-                    final_execution_outcome: outcome,
-                });
+                return Ok(outcome.into());
             }
             Err(TxStatusError::InvalidTx(invalid_tx_error)) => {
                 return Err(near_jsonrpc_primitives::types::transactions::RpcTransactionError::InvalidTransaction {
@@ -904,6 +935,24 @@ impl JsonRpcHandler {
         }
     }
 
+    pub async fn network_graph(
+        &self,
+    ) -> Result<Option<near_primitives::views::NetworkGraphHistoryView>, actix::MailboxError> {
+        if !self.enable_debug_rpc {
+            return Ok(None);
+        }
+        match self
+            .routing_table_addr
+            .send(near_network::RoutingTableMessages::RequestRoutingGraph)
+            .await?
+        {
+            near_network::RoutingTableMessagesResponse::RequestRoutingGraphResponse(graph) => {
+                Ok(Some(*graph))
+            }
+            _ => Ok(None),
+        }
+    }
+
     /// Expose Genesis Config (with internal Runtime Config) without state records to keep the
     /// output at a reasonable size.
     ///
@@ -926,6 +975,17 @@ impl JsonRpcHandler {
         Ok(RpcProtocolConfigResponse { config_view })
     }
 
+    async fn delayed_receipts(
+        &self,
+        request_data: near_jsonrpc_primitives::types::delayed_receipts::RpcDelayedReceiptsRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::delayed_receipts::RpcDelayedReceiptsResponse,
+        near_jsonrpc_primitives::types::delayed_receipts::RpcDelayedReceiptsError,
+    > {
+        let shards = self.view_client_addr.send(GetDelayedReceipts::from(request_data)).await??;
+        Ok(near_jsonrpc_primitives::types::delayed_receipts::RpcDelayedReceiptsResponse { shards })
+    }
+
     async fn query(
         &self,
         request_data: near_jsonrpc_primitives::types::query::RpcQueryRequest,
@@ -945,7 +1005,24 @@ impl JsonRpcHandler {
         near_jsonrpc_primitives::types::transactions::RpcTransactionResponse,
         near_jsonrpc_primitives::types::transactions::RpcTransactionError,
     > {
-        Ok(self.tx_status_fetch(request_data.transaction_info, fetch_receipt).await?.into())
+        let final_execution_outcome =
+            self.tx_status_fetch(request_data.transaction_info, fetch_receipt).await?;
+        let gas_profile_tree = if request_data.options.gas_profile_tree {
+            Some(match &final_execution_outcome {
+                FinalExecutionOutcomeViewEnum::FinalExecutionOutcome(outcome) => {
+                    outcome.gas_profile_tree()
+                }
+                FinalExecutionOutcomeViewEnum::FinalExecutionOutcomeWithReceipt(outcome) => {
+                    outcome.final_outcome.gas_profile_tree()
+                }
+            })
+        } else {
+            None
+        };
+        Ok(near_jsonrpc_primitives::types::transactions::RpcTransactionResponse {
+            final_execution_outcome,
+            gas_profile_tree,
+        })
     }
 
     async fn block(
@@ -1221,6 +1298,118 @@ impl JsonRpcHandler {
 
         Ok(near_jsonrpc_primitives::types::sandbox::RpcSandboxFastForwardResponse {})
     }
+
+    async fn sandbox_snapshot(
+        &self,
+        _snapshot_request: near_jsonrpc_primitives::types::sandbox::RpcSandboxSnapshotRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::sandbox::RpcSandboxSnapshotResponse,
+        near_jsonrpc_primitives::types::sandbox::RpcSandboxSnapshotError,
+    > {
+        use near_jsonrpc_primitives::types::sandbox::{
+            RpcSandboxSnapshotError, RpcSandboxSnapshotResponse,
+        };
+        use near_network_primitives::types::SandboxResponse;
+
+        match self
+            .client_addr
+            .send(NetworkClientMessages::Sandbox(
+                near_network_primitives::types::NetworkSandboxMessage::SandboxSnapshot,
+            ))
+            .await?
+        {
+            NetworkClientResponses::SandboxResult(SandboxResponse::SandboxSnapshot {
+                block_hash,
+                height,
+            }) => Ok(RpcSandboxSnapshotResponse { block_hash, block_height: height }),
+            NetworkClientResponses::SandboxResult(SandboxResponse::SandboxSnapshotFailed(err)) => {
+                Err(RpcSandboxSnapshotError::InternalError { error_message: err })
+            }
+            response => Err(RpcSandboxSnapshotError::InternalError {
+                error_message: format!("unexpected response to sandbox snapshot: {:?}", response),
+            }),
+        }
+    }
+
+    async fn sandbox_revert(
+        &self,
+        revert_request: near_jsonrpc_primitives::types::sandbox::RpcSandboxRevertRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::sandbox::RpcSandboxRevertResponse,
+        near_jsonrpc_primitives::types::sandbox::RpcSandboxRevertError,
+    > {
+        use near_jsonrpc_primitives::types::sandbox::{
+            RpcSandboxRevertError, RpcSandboxRevertResponse,
+        };
+        use near_network_primitives::types::SandboxResponse;
+
+        match self
+            .client_addr
+            .send(NetworkClientMessages::Sandbox(
+                near_network_primitives::types::NetworkSandboxMessage::SandboxRevert(
+                    revert_request.block_hash,
+                ),
+            ))
+            .await?
+        {
+            NetworkClientResponses::SandboxResult(SandboxResponse::SandboxRevertFinished) => {
+                Ok(RpcSandboxRevertResponse {})
+            }
+            NetworkClientResponses::SandboxResult(SandboxResponse::SandboxRevertFailed(err)) => {
+                Err(RpcSandboxRevertError::RevertFailed { error_message: err })
+            }
+            response => Err(RpcSandboxRevertError::InternalError {
+                error_message: format!("unexpected response to sandbox revert: {:?}", response),
+            }),
+        }
+    }
+
+    /// Returns the coverage collected by the runtime of this node. Contracts are executed in the
+    /// node process, so the counters are read directly rather than through the client actor.
+    fn sandbox_coverage(
+        &self,
+        coverage_request: near_jsonrpc_primitives::types::sandbox::RpcSandboxCoverageRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::sandbox::RpcSandboxCoverageResponse,
+        near_jsonrpc_primitives::types::sandbox::RpcSandboxCoverageError,
+    > {
+        use near_jsonrpc_primitives::types::sandbox::{
+            RpcContractCoverageView, RpcFunctionCoverageView, RpcSandboxCoverageFormat,
+            RpcSandboxCoverageResponse,
+        };
+        use near_vm_runner::coverage;
+
+        let report = coverage::report();
+        let (contracts, lcov) = match coverage_request.format {
+            RpcSandboxCoverageFormat::Json => {
+                let contracts = report
+                    .into_iter()
+                    .map(|contract| RpcContractCoverageView {
+                        code_hash: contract.code_hash,
+                        functions: contract
+                            .functions
+                            .into_iter()
+                            .map(|function| RpcFunctionCoverageView {
+                                index: function.index,
+                                name: function.name,
+                                first_block: function.first_block,
+                                blocks: function.blocks,
+                            })
+                            .collect(),
+                    })
+                    .collect();
+                (Some(contracts), None)
+            }
+            RpcSandboxCoverageFormat::Lcov => (None, Some(coverage::to_lcov(&report))),
+        };
+        if coverage_request.reset {
+            coverage::reset();
+        }
+        if let Some(enable) = coverage_request.enable {
+            coverage::set_enabled(enable);
+        }
+        Ok(RpcSandboxCoverageResponse { enabled: coverage::is_enabled(), contracts, lcov })
+    }
 }
 
 #[cfg(feature = "test_features")]
@@ -1378,6 +1567,28 @@ async fn debug_handler(handler: web::Data<JsonRpcHandler>) -> Result<HttpRespons
     }
 }
 
+async fn network_graph_handler(
+    handler: web::Data<JsonRpcHandler>,
+) -> Result<HttpResponse, HttpError> {
+    match handler.network_graph().await {
+        Ok(Some(value)) => Ok(HttpResponse::Ok().json(&value)),
+        Ok(None) => Ok(HttpResponse::MethodNotAllowed().finish()),
+        Err(_) => Ok(HttpResponse::ServiceUnavailable().finish()),
+    }
+}
+
+async fn network_graph_dot_handler(
+    handler: web::Data<JsonRpcHandler>,
+) -> Result<HttpResponse, HttpError> {
+    match handler.network_graph().await {
+        Ok(Some(value)) => {
+            Ok(HttpResponse::Ok().content_type("text/vnd.graphviz").body(value.current.to_dot()))
+        }
+        Ok(None) => Ok(HttpResponse::MethodNotAllowed().finish()),
+        Err(_) => Ok(HttpResponse::ServiceUnavailable().finish()),
+    }
+}
+
 fn health_handler(
     handler: web::Data<JsonRpcHandler>,
 ) -> impl Future<Output = Result<HttpResponse, HttpError>> {
@@ -1478,7 +1689,7 @@ pub fn start_http(
     client_addr: Addr<ClientActor>,
     view_client_addr: Addr<ViewClientActor>,
     #[cfg(feature = "test_features")] peer_manager_addr: Addr<near_network::PeerManagerActor>,
-    #[cfg(feature = "test_features")] routing_table_addr: Addr<near_network::RoutingTableActor>,
+    routing_table_addr: Addr<near_network::RoutingTableActor>,
 ) -> Vec<(&'static str, actix_web::dev::Server)> {
     let RpcConfig {
         addr,
@@ -1503,7 +1714,6 @@ pub fn start_http(
                 enable_debug_rpc,
                 #[cfg(feature = "test_features")]
                 peer_manager_addr: peer_manager_addr.clone(),
-                #[cfg(feature = "test_features")]
                 routing_table_addr: routing_table_addr.clone(),
             })
             .app_data(web::JsonConfig::default().limit(limits_config.json_payload_max_size))
@@ -1522,6 +1732,14 @@ pub fn start_http(
             .service(web::resource("/network_info").route(web::get().to(network_info_handler)))
             .service(web::resource("/metrics").route(web::get().to(prometheus_handler)))
             .service(web::resource("/debug/api/status").route(web::get().to(debug_handler)))
+            .service(
+                web::resource("/debug/api/network_graph")
+                    .route(web::get().to(network_graph_handler)),
+            )
+            .service(
+                web::resource("/debug/api/network_graph.dot")
+                    .route(web::get().to(network_graph_dot_handler)),
+            )
             .service(debug_html)
             .service(last_blocks_html)
             .service(sync_info_html)
@@ -2,7 +2,10 @@ use crate::network_protocol::PeerInfo;
 use crate::types::ROUTED_MESSAGE_TTL;
 use near_crypto::{KeyType, PublicKey, SecretKey};
 use near_primitives::types::AccountId;
+use near_primitives::validator_signer::ValidatorSigner;
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Configuration for the peer-to-peer manager.
@@ -64,6 +67,52 @@ pub struct NetworkConfig {
     pub outbound_disabled: bool,
     /// Not clear old data, set `true` for archive nodes.
     pub archive: bool,
+    /// Limits on the rate of messages of each type received from a single peer.
+    pub message_rate_limits: MessageRateLimits,
+    /// Whether to keep connections with peers whose protocol version doesn't support the
+    /// encrypted transport. If false, such peers are disconnected on the handshake. Peers which
+    /// support it are always required to complete the key exchange within the handshake timeout.
+    pub allow_unencrypted_peers: bool,
+    /// Public addresses of this node. Validators announce them to other validators, which
+    /// connect to them directly.
+    pub public_addrs: Vec<SocketAddr>,
+    /// Signer of the validator running this node, used to sign announced `public_addrs`.
+    pub validator_signer: Option<Arc<dyn ValidatorSigner>>,
+}
+
+/// Token bucket limit for a single message type received from a peer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MessageRateLimit {
+    /// Maximum number of messages accepted in a burst.
+    pub burst: u32,
+    /// Number of messages per second accepted in the long run.
+    pub qps: f64,
+}
+
+/// Per peer limits on the rate of received messages. Messages over the limits are dropped and
+/// peers dropping too many of them are banned. Peers aren't deprioritized otherwise.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MessageRateLimits {
+    /// Limits keyed by `PeerMessage` variant name (e.g. `BlockRequest`, `Transaction`).
+    /// Routed messages are keyed by `RoutedMessageBody` variant name (e.g. `StateRequestPart`).
+    /// Message types without a limit are never throttled.
+    pub limits: HashMap<String, MessageRateLimit>,
+    /// Number of messages dropped for exceeding the limits within `dropped_messages_window`
+    /// after which the peer is banned.
+    /// If `None`, such messages are dropped, but the peer is never banned.
+    pub max_dropped_messages_before_ban: Option<u64>,
+    /// Period in which the dropped messages are counted towards the ban.
+    pub dropped_messages_window: Duration,
+}
+
+impl Default for MessageRateLimits {
+    fn default() -> Self {
+        Self {
+            limits: HashMap::default(),
+            max_dropped_messages_before_ban: None,
+            dropped_messages_window: Duration::from_secs(60),
+        }
+    }
 }
 
 impl NetworkConfig {
@@ -99,6 +148,10 @@ impl NetworkConfig {
             blacklist: vec![],
             outbound_disabled: false,
             archive: false,
+            message_rate_limits: Default::default(),
+            allow_unencrypted_peers: true,
+            public_addrs: vec![],
+            validator_signer: None,
         }
     }
 
@@ -136,6 +189,16 @@ impl NetworkConfig {
                 self.peer_recent_time_window.as_secs(), UPDATE_INTERVAL_LAST_TIME_RECEIVED_MESSAGE.as_secs()
             );
         }
+        for (msg_type, limit) in self.message_rate_limits.limits.iter() {
+            if !(limit.qps > 0.0 && limit.burst > 0) {
+                anyhow::bail!(
+                    "Invalid rate limit for {}: burst({}) and qps({}) must be positive.",
+                    msg_type,
+                    limit.burst,
+                    limit.qps
+                );
+            }
+        }
         Ok(())
     }
 }
@@ -147,7 +210,9 @@ pub const UPDATE_INTERVAL_LAST_TIME_RECEIVED_MESSAGE: Duration = Duration::from_
 
 #[cfg(test)]
 mod test {
-    use crate::types::{NetworkConfig, UPDATE_INTERVAL_LAST_TIME_RECEIVED_MESSAGE};
+    use crate::types::{
+        MessageRateLimit, NetworkConfig, UPDATE_INTERVAL_LAST_TIME_RECEIVED_MESSAGE,
+    };
 
     #[test]
     fn test_network_config() {
@@ -173,5 +238,12 @@ mod test {
         nc.peer_recent_time_window = UPDATE_INTERVAL_LAST_TIME_RECEIVED_MESSAGE;
         let res = nc.verify();
         assert!(res.is_err(), "{:?}", res);
+
+        let mut nc = NetworkConfig::from_seed("123", 213);
+        nc.message_rate_limits
+            .limits
+            .insert("BlockRequest".to_string(), MessageRateLimit { burst: 10, qps: 0.0 });
+        let res = nc.verify();
+        assert!(res.is_err(), "{:?}", res);
     }
 }
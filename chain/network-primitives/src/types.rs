@@ -14,7 +14,7 @@ use chrono::DateTime;
 use near_crypto::SecretKey;
 use near_primitives::block::{Block, BlockHeader, GenesisId};
 use near_primitives::hash::CryptoHash;
-use near_primitives::network::{AnnounceAccount, PeerId};
+use near_primitives::network::{AnnounceAccount, AnnounceAccountAddrs, PeerId};
 use near_primitives::syncing::{EpochSyncFinalizationResponse, EpochSyncResponse};
 use near_primitives::time::Utc;
 use near_primitives::transaction::ExecutionOutcomeWithIdAndProof;
@@ -36,7 +36,7 @@ pub use crate::network_protocol::{
 };
 
 pub use crate::blacklist::Blacklist;
-pub use crate::config::NetworkConfig;
+pub use crate::config::{MessageRateLimit, MessageRateLimits, NetworkConfig};
 
 pub use crate::network_protocol::edge::{Edge, EdgeState, PartialEdgeInfo, SimpleEdge};
 
@@ -226,6 +226,7 @@ pub enum ReasonForBan {
     EpochSyncInvalidResponse = 12,
     EpochSyncInvalidFinalizationResponse = 13,
     Blacklisted = 14,
+    RateLimitExceeded = 15,
 }
 
 /// Banning signal sent from Peer instance to PeerManager
@@ -279,6 +280,8 @@ pub enum NetworkSandboxMessage {
     SandboxPatchStateStatus,
     SandboxFastForward(near_primitives::types::BlockHeightDelta),
     SandboxFastForwardStatus,
+    SandboxSnapshot,
+    SandboxRevert(CryptoHash),
 }
 
 #[cfg(feature = "sandbox")]
@@ -287,6 +290,14 @@ pub enum SandboxResponse {
     SandboxPatchStateFinished(bool),
     SandboxFastForwardFinished(bool),
     SandboxFastForwardFailed(String),
+    /// Head of the chain at the time of the snapshot.
+    SandboxSnapshot {
+        block_hash: CryptoHash,
+        height: near_primitives::types::BlockHeight,
+    },
+    SandboxSnapshotFailed(String),
+    SandboxRevertFinished,
+    SandboxRevertFailed(String),
 }
 
 #[derive(actix::Message, AsStaticStr)]
@@ -321,6 +332,9 @@ pub enum NetworkViewClientMessages {
     /// They are paired with last epoch id known to this announcement, in order to accept only
     /// newer announcements.
     AnnounceAccount(Vec<(AnnounceAccount, Option<EpochId>)>),
+    /// Validator addresses that need to be validated before being processed.
+    /// Paired with the last epoch id known for the same account, like `AnnounceAccount`.
+    AnnounceAccountAddrs(Vec<(AnnounceAccountAddrs, Option<EpochId>)>),
 }
 
 #[derive(Debug, actix::MessageResponse)]
@@ -346,6 +360,8 @@ pub enum NetworkViewClientResponses {
     StateResponse(Box<StateResponseInfo>),
     /// Valid announce accounts.
     AnnounceAccount(Vec<AnnounceAccount>),
+    /// Valid validator addresses.
+    AnnounceAccountAddrs(Vec<AnnounceAccountAddrs>),
     /// A response to a request for a light client block during Epoch Sync
     EpochSyncResponse(Box<EpochSyncResponse>),
     /// A response to a request for headers and proofs during Epoch Sync
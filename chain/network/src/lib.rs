@@ -2,7 +2,6 @@ pub use crate::peer_manager::peer_manager_actor::PeerManagerActor;
 pub use crate::peer_manager::peer_store::iter_peers_from_store;
 /// For benchmarks only
 pub use crate::routing::routing_table_actor::RoutingTableActor;
-#[cfg(feature = "test_features")]
 pub use crate::routing::routing_table_actor::{RoutingTableMessages, RoutingTableMessagesResponse};
 #[cfg(feature = "test_features")]
 pub use crate::stats::metrics::RECEIVED_INFO_ABOUT_ITSELF;
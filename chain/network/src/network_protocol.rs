@@ -4,12 +4,16 @@
 /// WARNING WARNING WARNING
 /// We need to maintain backwards compatibility, all changes to this file needs to be reviews.
 use borsh::{BorshDeserialize, BorshSerialize};
+#[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+use near_crypto::secure_channel::EphemeralPublicKey;
 use near_network_primitives::types::{
     Edge, PartialEdgeInfo, PeerChainInfoV2, PeerInfo, RoutedMessage, RoutedMessageBody,
 };
 use near_primitives::block::{Block, BlockHeader, GenesisId};
 use near_primitives::challenge::Challenge;
 use near_primitives::hash::CryptoHash;
+#[cfg(feature = "protocol_feature_validator_direct_connections")]
+use near_primitives::network::AnnounceAccountAddrs;
 use near_primitives::network::{AnnounceAccount, PeerId};
 use near_primitives::syncing::{EpochSyncFinalizationResponse, EpochSyncResponse};
 use near_primitives::transaction::SignedTransaction;
@@ -147,7 +151,9 @@ impl RoutingTableUpdate {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
-#[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug)]
+#[derive(
+    BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug, strum::EnumVariantNames,
+)]
 pub enum HandshakeFailureReason {
     ProtocolVersionMismatch { version: u32, oldest_supported_version: u32 },
     GenesisMismatch(GenesisId),
@@ -169,6 +175,8 @@ impl std::error::Error for HandshakeFailureReason {}
 /// Warning, position of each message type in this enum defines the protocol due to serialization.
 /// DO NOT MOVE, REORDER, DELETE items from the list. Only add new items to the end.
 /// If need to remove old items - replace with `None`.
+/// The layout of each protocol version is recorded in `res/wire_format/schema.json` and checked
+/// against golden vectors by `tests::wire_format`.
 #[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
 #[derive(
     BorshSerialize,
@@ -215,6 +223,13 @@ pub enum PeerMessage {
 
     #[cfg(feature = "protocol_feature_routing_exchange_algorithm")]
     RoutingTableSyncV2(RoutingSyncV2),
+    /// Ephemeral key used to establish the encrypted transport, sent once by each side
+    /// after the handshake. All frames sent after it are encrypted.
+    #[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+    SecureChannelInit(EphemeralPublicKey),
+    /// Signed addresses of validators, used to establish direct connections between them.
+    #[cfg(feature = "protocol_feature_validator_direct_connections")]
+    SyncAccountsAddrs(Vec<AnnounceAccountAddrs>),
 }
 #[cfg(target_arch = "x86_64")] // Non-x86_64 doesn't match this requirement yet but it's not bad as it's not production-ready
 const _: () = assert!(std::mem::size_of::<PeerMessage>() <= 1144, "PeerMessage > 1144 bytes");
@@ -264,7 +279,7 @@ impl fmt::Display for PeerMessage {
 }
 
 impl PeerMessage {
-    pub(crate) fn msg_variant(&self) -> &str {
+    pub(crate) fn msg_variant(&self) -> &'static str {
         match self {
             PeerMessage::Routed(routed_message) => {
                 strum::AsStaticRef::as_static(&routed_message.body)
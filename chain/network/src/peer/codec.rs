@@ -3,11 +3,16 @@
 ///     - 4 bytes - length of the message as u32
 ///     - the message itself, which is encoded with `borsh`
 ///
+/// Once the encrypted transport is established, the message is encrypted and followed by
+/// an authentication tag, see `TransportEncryption`.
+///
 /// NOTES:
 ///     - Code has an extra logic to ban peers if they sent messages that are too large.
 use crate::stats::metrics;
 use bytes::{Buf, BufMut, BytesMut};
 use bytesize::{GIB, MIB};
+#[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+use near_crypto::secure_channel::{EphemeralSecretKey, SecureChannelError, SessionKeys};
 use near_network_primitives::types::ReasonForBan;
 use near_performance_metrics::framed_write::EncoderCallBack;
 use std::io::{Error, ErrorKind};
@@ -104,6 +109,83 @@ impl Decoder for Codec {
     }
 }
 
+/// Maximum number of outgoing frames queued until the encrypted transport is established.
+#[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+const MAX_QUEUED_FRAMES: usize = 1024;
+
+/// Encryption state of the frames exchanged with a single peer.
+///
+/// Once the handshake is completed at a protocol version supporting the encrypted transport,
+/// each side sends a single `SecureChannelInit` in cleartext, and encrypts every frame it sends
+/// after it. Since frames are delivered in order, the receiving side switches to decryption
+/// right after processing the peer's `SecureChannelInit`. Until then, the peer may only send
+/// its `SecureChannelInit` in cleartext.
+#[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+pub(crate) enum TransportEncryption {
+    /// Frames are sent in cleartext: the handshake isn't completed yet,
+    /// or the peer's protocol version doesn't support the encrypted transport.
+    Disabled,
+    /// We wait for the peer's ephemeral key to reply with ours.
+    /// Outgoing frames are queued until the session keys are known.
+    AwaitingPeerKey { queued_frames: Vec<Vec<u8>> },
+    /// We've sent our ephemeral key and wait for the peer's one.
+    /// Outgoing frames are queued until the session keys are known.
+    Negotiating { ephemeral_key: EphemeralSecretKey, queued_frames: Vec<Vec<u8>> },
+    /// All frames in both directions are encrypted.
+    Established(SessionKeys),
+}
+
+#[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+impl TransportEncryption {
+    pub(crate) fn is_established(&self) -> bool {
+        matches!(self, TransportEncryption::Established(_))
+    }
+
+    /// Whether the key exchange is in progress, so only the peer's `SecureChannelInit` may be
+    /// received in cleartext.
+    pub(crate) fn is_negotiating(&self) -> bool {
+        matches!(
+            self,
+            TransportEncryption::AwaitingPeerKey { .. } | TransportEncryption::Negotiating { .. }
+        )
+    }
+
+    /// Whether no more outgoing frames can be queued until the key exchange is completed.
+    pub(crate) fn is_queue_full(&self) -> bool {
+        match self {
+            TransportEncryption::AwaitingPeerKey { queued_frames }
+            | TransportEncryption::Negotiating { queued_frames, .. } => {
+                queued_frames.len() >= MAX_QUEUED_FRAMES
+            }
+            TransportEncryption::Disabled | TransportEncryption::Established(_) => false,
+        }
+    }
+
+    /// Prepares an outgoing frame. Returns `None` if the frame was queued.
+    pub(crate) fn seal(&mut self, frame: Vec<u8>) -> Result<Option<Vec<u8>>, SecureChannelError> {
+        match self {
+            TransportEncryption::Disabled => Ok(Some(frame)),
+            TransportEncryption::AwaitingPeerKey { queued_frames }
+            | TransportEncryption::Negotiating { queued_frames, .. } => {
+                queued_frames.push(frame);
+                Ok(None)
+            }
+            TransportEncryption::Established(keys) => keys.send.encrypt(frame).map(Some),
+        }
+    }
+
+    /// Verifies and decrypts an incoming frame. Frames received during the key exchange are
+    /// returned as they are, to be checked for being the peer's `SecureChannelInit`.
+    pub(crate) fn open(&mut self, frame: Vec<u8>) -> Result<Vec<u8>, SecureChannelError> {
+        match self {
+            TransportEncryption::Disabled
+            | TransportEncryption::AwaitingPeerKey { .. }
+            | TransportEncryption::Negotiating { .. } => Ok(frame),
+            TransportEncryption::Established(keys) => keys.recv.decrypt(frame),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::peer::codec::{Codec, NETWORK_MESSAGE_MAX_SIZE_BYTES};
@@ -253,4 +335,86 @@ mod test {
         buffer.put_u32_le(NETWORK_MESSAGE_MAX_SIZE_BYTES as u32);
         assert_ne!(codec.decode(&mut buffer).unwrap(), Some(Err(ReasonForBan::Abusive)));
     }
+
+    #[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+    #[test]
+    fn test_encrypted_transport() {
+        use crate::peer::codec::TransportEncryption;
+        use near_crypto::secure_channel::{
+            derive_session_keys, EphemeralSecretKey, SecureChannelRole,
+        };
+
+        let initiator_key = SecretKey::from_random(KeyType::ED25519);
+        let responder_key = SecretKey::from_random(KeyType::ED25519);
+        let initiator_ephemeral = EphemeralSecretKey::from_random();
+        let initiator_ephemeral_public = initiator_ephemeral.public_key();
+        let mut initiator = TransportEncryption::Negotiating {
+            ephemeral_key: initiator_ephemeral,
+            queued_frames: vec![],
+        };
+        let msg = PeerMessage::PeersRequest.try_to_vec().unwrap();
+        // Frames sent by the initiator are queued until it receives responder's key.
+        assert_eq!(initiator.seal(msg.clone()).unwrap(), None);
+        assert_eq!(initiator.open(msg.clone()).unwrap(), msg);
+
+        let responder_ephemeral = EphemeralSecretKey::from_random();
+        let responder_ephemeral_public = responder_ephemeral.public_key();
+        let mut responder = TransportEncryption::Established(
+            derive_session_keys(
+                SecureChannelRole::Responder,
+                &responder_key,
+                &responder_ephemeral,
+                &initiator_key.public_key(),
+                &initiator_ephemeral_public,
+            )
+            .unwrap(),
+        );
+
+        let queued_frames = match std::mem::replace(&mut initiator, TransportEncryption::Disabled) {
+            TransportEncryption::Negotiating { ephemeral_key, queued_frames } => {
+                initiator = TransportEncryption::Established(
+                    derive_session_keys(
+                        SecureChannelRole::Initiator,
+                        &initiator_key,
+                        &ephemeral_key,
+                        &responder_key.public_key(),
+                        &responder_ephemeral_public,
+                    )
+                    .unwrap(),
+                );
+                queued_frames
+            }
+            _ => unreachable!(),
+        };
+        assert_eq!(queued_frames, vec![msg.clone()]);
+
+        let mut codec = Codec::default();
+        let mut buffer = BytesMut::new();
+        let sealed = initiator.seal(msg.clone()).unwrap().unwrap();
+        assert_ne!(sealed, msg);
+        codec.encode(sealed, &mut buffer).unwrap();
+        let decoded = codec.decode(&mut buffer).unwrap().unwrap().unwrap();
+        assert_eq!(responder.open(decoded).unwrap(), msg);
+
+        let sealed = responder.seal(msg.clone()).unwrap().unwrap();
+        assert_eq!(initiator.open(sealed.clone()).unwrap(), msg);
+        // Replayed frame doesn't pass authentication.
+        assert!(initiator.open(sealed).is_err());
+    }
+
+    #[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+    #[test]
+    fn test_encrypted_transport_queue_limit() {
+        use crate::peer::codec::{TransportEncryption, MAX_QUEUED_FRAMES};
+
+        let mut transport = TransportEncryption::AwaitingPeerKey { queued_frames: vec![] };
+        assert!(transport.is_negotiating());
+        let msg = PeerMessage::PeersRequest.try_to_vec().unwrap();
+        for _ in 0..MAX_QUEUED_FRAMES {
+            assert!(!transport.is_queue_full());
+            assert_eq!(transport.seal(msg.clone()).unwrap(), None);
+        }
+        assert!(transport.is_queue_full());
+        assert!(!TransportEncryption::Disabled.is_negotiating());
+    }
 }
@@ -0,0 +1,126 @@
+use near_network_primitives::types::MessageRateLimits;
+use near_rate_limiter::TokenBucket;
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+/// Decision made by `MessageRateLimiter` about a received message.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum RateLimitDecision {
+    /// Message is within limits, process it.
+    Accept,
+    /// Message exceeds the limit for its type, drop it.
+    Drop,
+    /// Peer exceeded the limits too many times, ban it.
+    Ban,
+}
+
+/// Limits the rate of messages of each type received from a single peer.
+/// Each limited message type has its own token bucket.
+pub(crate) struct MessageRateLimiter {
+    config: MessageRateLimits,
+    /// Buckets keyed by message variant name, created lazily on the first message of given type.
+    buckets: HashMap<&'static str, TokenBucket>,
+    /// Times of the messages dropped because of the limits within the last
+    /// `dropped_messages_window`, kept only if the peer can be banned.
+    dropped_messages: VecDeque<Instant>,
+}
+
+impl MessageRateLimiter {
+    pub(crate) fn new(config: MessageRateLimits) -> Self {
+        Self { config, buckets: Default::default(), dropped_messages: Default::default() }
+    }
+
+    /// Checks whether message of type `msg_variant` received at `now` is within limits.
+    pub(crate) fn check(&mut self, msg_variant: &'static str, now: Instant) -> RateLimitDecision {
+        let limit = match self.config.limits.get(msg_variant) {
+            Some(limit) => limit,
+            None => return RateLimitDecision::Accept,
+        };
+        let bucket = self
+            .buckets
+            .entry(msg_variant)
+            .or_insert_with(|| TokenBucket::new(limit.burst, limit.qps, now));
+        if bucket.try_acquire(now) {
+            return RateLimitDecision::Accept;
+        }
+
+        let max_dropped = match self.config.max_dropped_messages_before_ban {
+            Some(max_dropped) => max_dropped,
+            None => return RateLimitDecision::Drop,
+        };
+        while let Some(time) = self.dropped_messages.front() {
+            if now.saturating_duration_since(*time) < self.config.dropped_messages_window {
+                break;
+            }
+            self.dropped_messages.pop_front();
+        }
+        self.dropped_messages.push_back(now);
+        if self.dropped_messages.len() as u64 > max_dropped {
+            RateLimitDecision::Ban
+        } else {
+            RateLimitDecision::Drop
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::peer::message_rate_limiter::{MessageRateLimiter, RateLimitDecision};
+    use near_network_primitives::types::{MessageRateLimit, MessageRateLimits};
+    use std::time::{Duration, Instant};
+
+    fn make_limiter(max_dropped_messages_before_ban: Option<u64>) -> MessageRateLimiter {
+        let mut config =
+            MessageRateLimits { max_dropped_messages_before_ban, ..Default::default() };
+        config.limits.insert("BlockRequest".to_string(), MessageRateLimit { burst: 2, qps: 1.0 });
+        MessageRateLimiter::new(config)
+    }
+
+    #[test]
+    fn test_unlimited_message_type() {
+        let now = Instant::now();
+        let mut limiter = make_limiter(Some(0));
+        for _ in 0..100 {
+            assert_eq!(limiter.check("Block", now), RateLimitDecision::Accept);
+        }
+    }
+
+    #[test]
+    fn test_drop_over_limit() {
+        let now = Instant::now();
+        let mut limiter = make_limiter(None);
+        assert_eq!(limiter.check("BlockRequest", now), RateLimitDecision::Accept);
+        assert_eq!(limiter.check("BlockRequest", now), RateLimitDecision::Accept);
+        for _ in 0..100 {
+            assert_eq!(limiter.check("BlockRequest", now), RateLimitDecision::Drop);
+        }
+        let now = now + Duration::from_secs(1);
+        assert_eq!(limiter.check("BlockRequest", now), RateLimitDecision::Accept);
+    }
+
+    #[test]
+    fn test_ban_after_too_many_drops() {
+        let now = Instant::now();
+        let mut limiter = make_limiter(Some(2));
+        assert_eq!(limiter.check("BlockRequest", now), RateLimitDecision::Accept);
+        assert_eq!(limiter.check("BlockRequest", now), RateLimitDecision::Accept);
+        assert_eq!(limiter.check("BlockRequest", now), RateLimitDecision::Drop);
+        assert_eq!(limiter.check("BlockRequest", now), RateLimitDecision::Drop);
+        assert_eq!(limiter.check("BlockRequest", now), RateLimitDecision::Ban);
+    }
+
+    #[test]
+    fn test_drops_expire_after_window() {
+        let mut now = Instant::now();
+        let mut limiter = make_limiter(Some(2));
+        assert_eq!(limiter.check("BlockRequest", now), RateLimitDecision::Accept);
+        assert_eq!(limiter.check("BlockRequest", now), RateLimitDecision::Accept);
+        // An occasional drop over a long connection never adds up to a ban.
+        for _ in 0..10 {
+            assert_eq!(limiter.check("BlockRequest", now), RateLimitDecision::Drop);
+            now += limiter.config.dropped_messages_window;
+            assert_eq!(limiter.check("BlockRequest", now), RateLimitDecision::Accept);
+            assert_eq!(limiter.check("BlockRequest", now), RateLimitDecision::Accept);
+        }
+    }
+}
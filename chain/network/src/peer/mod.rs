@@ -1,4 +1,5 @@
 pub(crate) mod codec;
+mod message_rate_limiter;
 pub(crate) mod peer_actor;
 mod tracker;
 mod transfer_stats;
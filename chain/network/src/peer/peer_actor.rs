@@ -1,4 +1,7 @@
 use crate::peer::codec::Codec;
+#[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+use crate::peer::codec::TransportEncryption;
+use crate::peer::message_rate_limiter::{MessageRateLimiter, RateLimitDecision};
 use crate::peer::tracker::Tracker;
 use crate::peer::utils;
 use crate::private_actix::{
@@ -17,11 +20,15 @@ use actix::{
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 use lru::LruCache;
-use near_crypto::Signature;
+#[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+use near_crypto::secure_channel::{
+    derive_session_keys, EphemeralPublicKey, EphemeralSecretKey, SecureChannelRole,
+};
+use near_crypto::{SecretKey, Signature};
 use near_network_primitives::types::{
-    Ban, NetworkViewClientMessages, NetworkViewClientResponses, PeerChainInfoV2, PeerIdOrHash,
-    PeerInfo, PeerManagerRequest, PeerStatsResult, PeerType, QueryPeerStats, ReasonForBan,
-    RoutedMessage, RoutedMessageBody, RoutedMessageFrom, StateResponseInfo,
+    Ban, MessageRateLimits, NetworkViewClientMessages, NetworkViewClientResponses, PeerChainInfoV2,
+    PeerIdOrHash, PeerInfo, PeerManagerRequest, PeerStatsResult, PeerType, QueryPeerStats,
+    ReasonForBan, RoutedMessage, RoutedMessageBody, RoutedMessageFrom, StateResponseInfo,
     UPDATE_INTERVAL_LAST_TIME_RECEIVED_MESSAGE,
 };
 
@@ -30,6 +37,7 @@ use near_performance_metrics::framed_write::{FramedWrite, WriteHandler};
 use near_performance_metrics_macros::perf;
 use near_primitives::block::GenesisId;
 use near_primitives::borsh::maybestd::io::Error;
+use near_primitives::checked_feature;
 use near_primitives::logging;
 use near_primitives::network::PeerId;
 use near_primitives::sharding::PartialEncodedChunk;
@@ -107,6 +115,17 @@ pub(crate) struct PeerActor {
     routed_message_cache: LruCache<(PeerId, PeerIdOrHash, Signature), Instant>,
     /// A helper data structure for limiting reading
     throttle_controller: ThrottleController,
+    /// Limits the rate of received messages by message type.
+    message_rate_limiter: MessageRateLimiter,
+    /// This node's secret key, used for the encrypted transport key exchange.
+    #[cfg_attr(not(feature = "protocol_feature_encrypted_peer_transport"), allow(dead_code))]
+    secret_key: SecretKey,
+    /// Whether to keep connections with peers whose protocol version doesn't support the
+    /// encrypted transport.
+    allow_unencrypted_peers: bool,
+    /// Encryption state of the frames exchanged with the peer.
+    #[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+    transport: TransportEncryption,
 }
 
 impl Debug for PeerActor {
@@ -132,6 +151,9 @@ impl PeerActor {
         txns_since_last_block: Arc<AtomicUsize>,
         peer_counter: Arc<AtomicUsize>,
         throttle_controller: ThrottleController,
+        message_rate_limits: MessageRateLimits,
+        secret_key: SecretKey,
+        allow_unencrypted_peers: bool,
     ) -> Self {
         PeerActor {
             my_node_info,
@@ -155,6 +177,11 @@ impl PeerActor {
             peer_counter,
             routed_message_cache: LruCache::new(ROUTED_MESSAGE_CACHE_SIZE),
             throttle_controller,
+            message_rate_limiter: MessageRateLimiter::new(message_rate_limits),
+            secret_key,
+            allow_unencrypted_peers,
+            #[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+            transport: TransportEncryption::Disabled,
         }
     }
 
@@ -171,6 +198,26 @@ impl PeerActor {
             Ok(bytes) => {
                 self.tracker.increment_sent(bytes.len() as u64);
                 let bytes_len = bytes.len();
+                #[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+                let bytes = if is_connection_setup_message(msg) && !self.transport.is_established()
+                {
+                    bytes
+                } else {
+                    if self.transport.is_queue_full() {
+                        warn!(target: "network", "Too many messages queued for {} during the key exchange. Disconnecting.", self.peer_info);
+                        self.framed.close();
+                        return;
+                    }
+                    match self.transport.seal(bytes) {
+                        Ok(Some(bytes)) => bytes,
+                        // Will be sent once the encrypted transport is established.
+                        Ok(None) => return,
+                        Err(err) => {
+                            error!(target: "network", "Failed to encrypt message {}: {}", msg, err);
+                            return;
+                        }
+                    }
+                };
                 if !self.framed.write(bytes) {
                     #[cfg(feature = "performance_stats")]
                     let tid = near_rust_allocator_proxy::get_tid();
@@ -250,6 +297,90 @@ impl PeerActor {
             .spawn(ctx);
     }
 
+    /// Starts the encrypted transport key exchange once the peer's handshake is accepted at a
+    /// protocol version supporting it: the outbound side sends its ephemeral key, the inbound side
+    /// waits for it. Messages other than `is_connection_setup_message` are queued until the
+    /// exchange is completed, and the peer is disconnected if it isn't completed within the
+    /// handshake timeout.
+    #[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+    fn start_secure_channel(&mut self, ctx: &mut Context<PeerActor>) {
+        if self.peer_type == PeerType::Outbound {
+            let ephemeral_key = EphemeralSecretKey::from_random();
+            self.send_message(&PeerMessage::SecureChannelInit(ephemeral_key.public_key()));
+            self.transport =
+                TransportEncryption::Negotiating { ephemeral_key, queued_frames: vec![] };
+        } else {
+            self.transport = TransportEncryption::AwaitingPeerKey { queued_frames: vec![] };
+        }
+        near_performance_metrics::actix::run_later(ctx, self.handshake_timeout, move |act, ctx| {
+            if !act.transport.is_established() {
+                info!(target: "network", "Encrypted transport wasn't established with {}. Disconnecting.", act.peer_info);
+                ctx.stop();
+            }
+        });
+    }
+
+    /// Derives the session keys from the peer's ephemeral key. The inbound side replies with
+    /// its own ephemeral key, the outbound side flushes frames queued during the key exchange.
+    #[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+    fn on_secure_channel_init(
+        &mut self,
+        ctx: &mut Context<PeerActor>,
+        peer_ephemeral_key: EphemeralPublicKey,
+    ) {
+        let (role, ephemeral_key, queued_frames) = match (
+            self.peer_type,
+            std::mem::replace(&mut self.transport, TransportEncryption::Disabled),
+        ) {
+            (PeerType::Inbound, TransportEncryption::AwaitingPeerKey { queued_frames }) => {
+                (SecureChannelRole::Responder, EphemeralSecretKey::from_random(), queued_frames)
+            }
+            (
+                PeerType::Outbound,
+                TransportEncryption::Negotiating { ephemeral_key, queued_frames },
+            ) => (SecureChannelRole::Initiator, ephemeral_key, queued_frames),
+            _ => {
+                warn!(target: "network", "Unexpected SecureChannelInit from {}. Disconnecting.", self.peer_info);
+                ctx.stop();
+                return;
+            }
+        };
+        let keys = match derive_session_keys(
+            role,
+            &self.secret_key,
+            &ephemeral_key,
+            self.other_peer_id().unwrap().public_key(),
+            &peer_ephemeral_key,
+        ) {
+            Ok(keys) => keys,
+            Err(err) => {
+                warn!(target: "network", "Failed to establish encrypted transport with {}: {}. Disconnecting.", self.peer_info, err);
+                ctx.stop();
+                return;
+            }
+        };
+        if role == SecureChannelRole::Responder {
+            // Last frame sent in cleartext.
+            self.send_message(&PeerMessage::SecureChannelInit(ephemeral_key.public_key()));
+        }
+        self.transport = TransportEncryption::Established(keys);
+        debug!(target: "network", "Established encrypted transport with {}", self.peer_info);
+
+        for frame in queued_frames {
+            match self.transport.seal(frame) {
+                Ok(Some(bytes)) => {
+                    if !self.framed.write(bytes) {
+                        error!(target: "network", "Failed to send queued message to {}", self.peer_info);
+                    }
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    error!(target: "network", "Failed to encrypt queued message: {}", err)
+                }
+            }
+        }
+    }
+
     fn ban_peer(&mut self, ctx: &mut Context<PeerActor>, ban_reason: ReasonForBan) {
         warn!(target: "network", "Banning peer {} for {:?}", self.peer_info, ban_reason);
         self.peer_status = PeerStatus::Banned(ban_reason);
@@ -494,6 +625,16 @@ impl PeerActor {
                 error!(target: "network", "Peer receive_client_message received unexpected type: {:?}", msg);
                 return;
             }
+            #[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+            PeerMessage::SecureChannelInit(_) => {
+                error!(target: "network", "Peer receive_client_message received unexpected type: {:?}", msg);
+                return;
+            }
+            #[cfg(feature = "protocol_feature_validator_direct_connections")]
+            PeerMessage::SyncAccountsAddrs(_) => {
+                error!(target: "network", "Peer receive_client_message received unexpected type: {:?}", msg);
+                return;
+            }
         };
 
         self.client_addr
@@ -585,6 +726,20 @@ impl PeerActor {
     }
 }
 
+/// Messages which set up the connection and are exchanged in cleartext until the encrypted
+/// transport is established. Any other message received in cleartext during the key exchange
+/// disconnects the peer.
+#[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+fn is_connection_setup_message(msg: &PeerMessage) -> bool {
+    matches!(
+        msg,
+        PeerMessage::Handshake(_)
+            | PeerMessage::HandshakeFailure(..)
+            | PeerMessage::LastEdge(_)
+            | PeerMessage::SecureChannelInit(_)
+    )
+}
+
 impl Actor for PeerActor {
     type Context = Context<PeerActor>;
 
@@ -653,6 +808,15 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for PeerActor {
                 return;
             }
         };
+        #[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+        let msg = match self.transport.open(msg) {
+            Ok(msg) => msg,
+            Err(err) => {
+                warn!(target: "network", "Failed to decrypt message from {}: {}. Disconnecting.", self.peer_info, err);
+                ctx.stop();
+                return;
+            }
+        };
         // TODO(#5155) We should change our code to track size of messages received from Peer
         // as long as it travels to PeerManager, etc.
 
@@ -669,6 +833,12 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for PeerActor {
                 return;
             }
         };
+        #[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+        if self.transport.is_negotiating() && !is_connection_setup_message(&peer_msg) {
+            warn!(target: "network", "Received {} from {} in cleartext during the key exchange. Disconnecting.", peer_msg.msg_variant(), self.peer_info);
+            ctx.stop();
+            return;
+        }
 
         // Drop duplicated messages routed within DROP_DUPLICATED_MESSAGES_PERIOD ms
         if let PeerMessage::Routed(msg) = &peer_msg {
@@ -682,6 +852,28 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for PeerActor {
             }
             self.routed_message_cache.put(key, now);
         }
+        trace!(target: "network", "Received message: {}", peer_msg);
+
+        match self.message_rate_limiter.check(peer_msg.msg_variant(), Clock::instant()) {
+            RateLimitDecision::Accept => {}
+            RateLimitDecision::Drop => {
+                debug!(
+                    target: "network",
+                    "Dropping {} from {}: rate limit exceeded",
+                    peer_msg.msg_variant(),
+                    self.peer_info
+                );
+                metrics::PEER_MESSAGE_RATE_LIMITED_BY_TYPE_TOTAL
+                    .with_label_values(&[peer_msg.msg_variant()])
+                    .inc();
+                return;
+            }
+            RateLimitDecision::Ban => {
+                self.ban_peer(ctx, ReasonForBan::RateLimitExceeded);
+                return;
+            }
+        }
+
         if let PeerMessage::Routed(routed) = &peer_msg {
             if let RoutedMessage { body: RoutedMessageBody::ForwardTx(_), .. } = routed.as_ref() {
                 self.txns_since_last_block.fetch_add(1, Ordering::AcqRel);
@@ -690,8 +882,6 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for PeerActor {
             self.txns_since_last_block.store(0, Ordering::Release);
         }
 
-        trace!(target: "network", "Received message: {}", peer_msg);
-
         self.on_receive_message();
 
         self.network_metrics
@@ -755,6 +945,18 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for PeerActor {
                 let target_version = std::cmp::min(handshake.protocol_version, PROTOCOL_VERSION);
                 self.protocol_version = target_version;
 
+                if !self.allow_unencrypted_peers
+                    && !checked_feature!(
+                        "protocol_feature_encrypted_peer_transport",
+                        EncryptedPeerTransport,
+                        target_version
+                    )
+                {
+                    info!(target: "network", "Peer {} doesn't support encrypted transport at protocol version {}. Disconnecting.", handshake.sender_peer_id, target_version);
+                    ctx.stop();
+                    return;
+                }
+
                 if handshake.sender_chain_info.genesis_id != self.genesis_id {
                     debug!(target: "network", "Received connection from node with different genesis.");
                     ctx.address().do_send(SendMessage {
@@ -813,6 +1015,15 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for PeerActor {
                     account_id: None,
                 };
                 self.chain_info = handshake.sender_chain_info.clone();
+                // Started before registering the peer, which may send messages to it right away.
+                checked_feature!(
+                    "protocol_feature_encrypted_peer_transport",
+                    EncryptedPeerTransport,
+                    self.protocol_version,
+                    {
+                        self.start_secure_channel(ctx);
+                    }
+                );
                 self.peer_manager_addr
                     .send(ActixMessageWrapper::new_without_size(PeerManagerMessageRequest::RegisterPeer(RegisterPeer {
                         actor: ctx.address(),
@@ -976,6 +1187,22 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for PeerActor {
                     Some(self.throttle_controller.clone()),
                 ));
             }
+            #[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+            (PeerStatus::Ready, PeerMessage::SecureChannelInit(ephemeral_key)) => {
+                self.on_secure_channel_init(ctx, ephemeral_key);
+            }
+            #[cfg(feature = "protocol_feature_validator_direct_connections")]
+            (PeerStatus::Ready, PeerMessage::SyncAccountsAddrs(accounts_addrs)) => {
+                self.peer_manager_addr.do_send(ActixMessageWrapper::new_without_size(
+                    PeerManagerMessageRequest::NetworkRequests(
+                        NetworkRequests::SyncAccountsAddrs {
+                            peer_id: self.other_peer_id().unwrap().clone(),
+                            accounts_addrs,
+                        },
+                    ),
+                    Some(self.throttle_controller.clone()),
+                ));
+            }
             (PeerStatus::Ready, PeerMessage::Routed(routed_message)) => {
                 trace!(target: "network", "Received routed message from {} to {:?}.", self.peer_info, routed_message.target);
 
@@ -1,2 +1,4 @@
 pub(crate) mod peer_manager_actor;
 pub(crate) mod peer_store;
+#[cfg(feature = "protocol_feature_validator_direct_connections")]
+pub(crate) mod validator_connections;
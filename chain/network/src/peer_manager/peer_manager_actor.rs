@@ -1,6 +1,8 @@
 use crate::peer::codec::Codec;
 use crate::peer::peer_actor::PeerActor;
 use crate::peer_manager::peer_store::{PeerStore, TrustLevel};
+#[cfg(feature = "protocol_feature_validator_direct_connections")]
+use crate::peer_manager::validator_connections::ValidatorConnections;
 use crate::private_actix::{
     PeerRequestResult, PeersRequest, RegisterPeer, RegisterPeerResponse, SendMessage, StopMsg,
     Unregister, ValidateEdgeList,
@@ -35,10 +37,14 @@ use near_performance_metrics::framed_write::FramedWrite;
 use near_performance_metrics_macros::perf;
 use near_primitives::checked_feature;
 use near_primitives::hash::CryptoHash;
+#[cfg(feature = "protocol_feature_validator_direct_connections")]
+use near_primitives::network::AnnounceAccountAddrs;
 use near_primitives::network::{AnnounceAccount, PeerId};
 use near_primitives::time::Clock;
 use near_primitives::types::{AccountId, ProtocolVersion};
 use near_primitives::utils::from_timestamp;
+#[cfg(feature = "protocol_feature_validator_direct_connections")]
+use near_primitives::version::ProtocolFeature;
 use near_rate_limiter::{
     ActixMessageResponse, ActixMessageWrapper, ThrottleController, ThrottleFramedRead,
     ThrottleToken,
@@ -84,6 +90,9 @@ const WAIT_FOR_SYNC_DELAY: Duration = Duration::from_millis(1_000);
 const UPDATE_ROUTING_TABLE_INTERVAL: Duration = Duration::from_millis(1_000);
 /// How often to report bandwidth stats.
 const REPORT_BANDWIDTH_STATS_TRIGGER_INTERVAL: Duration = Duration::from_millis(60_000);
+/// How often to check connections with validators of the current epoch.
+#[cfg(feature = "protocol_feature_validator_direct_connections")]
+const VALIDATOR_CONNECTIONS_INTERVAL: Duration = Duration::from_millis(10_000);
 
 /// Max number of messages we received from peer, and they are in progress, before we start throttling.
 /// Disabled for now (TODO PUT UNDER FEATURE FLAG)
@@ -114,6 +123,9 @@ struct ConnectedPeer {
     peer_type: PeerType,
     /// A helper data structure for limiting reading, reporting stats.
     throttle_controller: ThrottleController,
+    /// Protocol version negotiated with the peer during the handshake.
+    #[cfg(feature = "protocol_feature_validator_direct_connections")]
+    protocol_version: ProtocolVersion,
 }
 
 #[derive(Default)]
@@ -187,6 +199,9 @@ pub struct PeerManagerActor {
     peer_counter: Arc<AtomicUsize>,
     /// Used for testing, for disabling features.
     adv_helper: AdvHelper,
+    /// Addresses of validators, used to keep direct connections among validators.
+    #[cfg(feature = "protocol_feature_validator_direct_connections")]
+    validator_connections: ValidatorConnections,
 }
 
 impl Actor for PeerManagerActor {
@@ -244,6 +259,10 @@ impl Actor for PeerManagerActor {
 
         // Periodically prints bandwidth stats for each peer.
         self.report_bandwidth_stats_trigger(ctx, REPORT_BANDWIDTH_STATS_TRIGGER_INTERVAL);
+
+        // Periodically connects to validators of the current epoch.
+        #[cfg(feature = "protocol_feature_validator_direct_connections")]
+        self.validator_connections_trigger(ctx, VALIDATOR_CONNECTIONS_INTERVAL);
     }
 
     /// Try to gracefully disconnect from connected peers.
@@ -298,6 +317,8 @@ impl PeerManagerActor {
             txns_since_last_block,
             peer_counter: Arc::new(AtomicUsize::new(0)),
             adv_helper: AdvHelper::default(),
+            #[cfg(feature = "protocol_feature_validator_direct_connections")]
+            validator_connections: Default::default(),
         })
     }
 
@@ -368,6 +389,87 @@ impl PeerManagerActor {
         )
     }
 
+    /// Sends validator addresses to all peers which support direct validator connections.
+    #[cfg(feature = "protocol_feature_validator_direct_connections")]
+    fn broadcast_accounts_addrs(&self, accounts_addrs: Vec<AnnounceAccountAddrs>) {
+        if accounts_addrs.is_empty() {
+            return;
+        }
+        debug!(target: "network", ?accounts_addrs, "Broadcast validator addresses");
+        let msg = SendMessage { message: PeerMessage::SyncAccountsAddrs(accounts_addrs) };
+        for connected_peer in self.connected_peers.values() {
+            if connected_peer.protocol_version
+                >= ProtocolFeature::ValidatorDirectConnections.protocol_version()
+            {
+                connected_peer.addr.do_send(msg.clone());
+            }
+        }
+    }
+
+    /// Periodically starts outbound connections with validators of the current epoch we are not
+    /// connected to yet.
+    #[cfg(feature = "protocol_feature_validator_direct_connections")]
+    fn validator_connections_trigger(&mut self, ctx: &mut Context<Self>, interval: Duration) {
+        let mut rng = thread_rng();
+        let mut connected = 0;
+        let mut to_connect = vec![];
+        for account_addrs in self.validator_connections.validators(&self.my_peer_id) {
+            if self.connected_peers.contains_key(&account_addrs.peer_id) {
+                connected += 1;
+            } else if !self.outgoing_peers.contains(&account_addrs.peer_id)
+                && !self.peer_store.is_banned(&account_addrs.peer_id)
+            {
+                if let Some(addr) = account_addrs.addrs.iter().choose(&mut rng) {
+                    to_connect.push(PeerInfo {
+                        id: account_addrs.peer_id.clone(),
+                        addr: Some(*addr),
+                        account_id: Some(account_addrs.account_id.clone()),
+                    });
+                }
+            }
+        }
+        metrics::VALIDATOR_DIRECT_CONNECTIONS.set(connected);
+
+        if !self.config.outbound_disabled {
+            for peer_info in to_connect {
+                debug!(target: "network", ?peer_info, "Connecting to validator");
+                self.outgoing_peers.insert(peer_info.id.clone());
+                ctx.notify(PeerManagerMessageRequest::OutboundTcpConnect(OutboundTcpConnect {
+                    peer_info,
+                }));
+            }
+        }
+
+        near_performance_metrics::actix::run_later(ctx, interval, move |act, ctx| {
+            act.validator_connections_trigger(ctx, interval);
+        });
+    }
+
+    /// Whether `peer_id` is run by a validator we should keep a direct connection with.
+    #[cfg(feature = "protocol_feature_validator_direct_connections")]
+    fn is_validator_peer(&self, peer_id: &PeerId) -> bool {
+        self.validator_connections.is_validator_peer(peer_id)
+    }
+
+    #[cfg(not(feature = "protocol_feature_validator_direct_connections"))]
+    fn is_validator_peer(&self, _peer_id: &PeerId) -> bool {
+        false
+    }
+
+    /// Whether some validators we should be directly connected to are not connected yet, so
+    /// inbound connections above `max_num_peers` may still be accepted from them.
+    #[cfg(feature = "protocol_feature_validator_direct_connections")]
+    fn has_free_validator_slots(&self) -> bool {
+        self.validator_connections
+            .validators(&self.my_peer_id)
+            .any(|account_addrs| !self.connected_peers.contains_key(&account_addrs.peer_id))
+    }
+
+    #[cfg(not(feature = "protocol_feature_validator_direct_connections"))]
+    fn has_free_validator_slots(&self) -> bool {
+        false
+    }
+
     /// `update_routing_table_trigger` schedule updating routing table to `RoutingTableActor`
     /// Usually we do edge pruning one an hour. However it may be disabled in following cases:
     /// - there are edges, that were supposed to be added, but are still in EdgeValidatorActor,
@@ -595,9 +697,21 @@ impl PeerManagerActor {
                 connection_established_time: Clock::instant(),
                 peer_type,
                 throttle_controller: throttle_controller.clone(),
+                #[cfg(feature = "protocol_feature_validator_direct_connections")]
+                protocol_version: peer_protocol_version,
             },
         );
 
+        #[cfg(feature = "protocol_feature_validator_direct_connections")]
+        if peer_protocol_version >= ProtocolFeature::ValidatorDirectConnections.protocol_version() {
+            let accounts_addrs: Vec<_> = self.validator_connections.all().cloned().collect();
+            if !accounts_addrs.is_empty() {
+                addr.do_send(SendMessage {
+                    message: PeerMessage::SyncAccountsAddrs(accounts_addrs),
+                });
+            }
+        }
+
         self.add_verified_edges_to_routing_table(vec![new_edge.clone()]);
 
         checked_feature!(
@@ -789,6 +903,9 @@ impl PeerManagerActor {
         let account_id = self.config.account_id.clone();
         let server_addr = self.config.addr;
         let handshake_timeout = self.config.handshake_timeout;
+        let message_rate_limits = self.config.message_rate_limits.clone();
+        let secret_key = self.config.secret_key.clone();
+        let allow_unencrypted_peers = self.config.allow_unencrypted_peers;
         let client_addr = self.client_addr.clone();
         let view_client_addr = self.view_client_addr.clone();
 
@@ -852,6 +969,9 @@ impl PeerManagerActor {
                 txns_since_last_block,
                 peer_counter,
                 rate_limiter,
+                message_rate_limits,
+                secret_key,
+                allow_unencrypted_peers,
             )
         });
     }
@@ -1095,6 +1215,13 @@ impl PeerManagerActor {
             }
         }
 
+        // Keep direct connections with validators.
+        for peer_id in self.connected_peers.keys() {
+            if self.is_validator_peer(peer_id) {
+                safe_set.insert(peer_id);
+            }
+        }
+
         // Find all recent connections
         let mut recent_connections = (self.connected_peers.iter())
             .filter_map(|(peer_id, active)| {
@@ -1275,6 +1402,39 @@ impl PeerManagerActor {
                 },
             );
         }
+        #[cfg(feature = "protocol_feature_validator_direct_connections")]
+        self.announce_account_addrs(&announce_account);
+    }
+
+    /// If this node is the validator announcing the account, remember the epoch for which to keep
+    /// direct connections with other validators and announce our public addresses to them.
+    #[cfg(feature = "protocol_feature_validator_direct_connections")]
+    fn announce_account_addrs(&mut self, announce_account: &AnnounceAccount) {
+        let validator_signer = match &self.config.validator_signer {
+            Some(signer) if signer.validator_id() == &announce_account.account_id => signer,
+            _ => return,
+        };
+        self.validator_connections.set_epoch_id(announce_account.epoch_id.clone());
+        if self.config.public_addrs.is_empty() {
+            return;
+        }
+
+        let signature = validator_signer.sign_account_addrs(
+            &announce_account.account_id,
+            &self.my_peer_id,
+            &announce_account.epoch_id,
+            &self.config.public_addrs,
+        );
+        let account_addrs = AnnounceAccountAddrs {
+            account_id: announce_account.account_id.clone(),
+            peer_id: self.my_peer_id.clone(),
+            epoch_id: announce_account.epoch_id.clone(),
+            addrs: self.config.public_addrs.clone(),
+            signature,
+        };
+        if self.validator_connections.insert(account_addrs.clone()) {
+            self.broadcast_accounts_addrs(vec![account_addrs]);
+        }
     }
 
     /// Send message to peer that belong to our active set
@@ -1367,6 +1527,26 @@ impl PeerManagerActor {
     /// Send message to specific account.
     /// Return whether the message is sent or not.
     fn send_message_to_account(&mut self, account_id: &AccountId, msg: RoutedMessageBody) -> bool {
+        // Consensus critical messages go over the direct connection with the validator if we have one.
+        #[cfg(feature = "protocol_feature_validator_direct_connections")]
+        if let Some(peer_id) = self.validator_connections.direct_peer(account_id, &msg) {
+            if self.connected_peers.contains_key(peer_id) {
+                let peer_id = peer_id.clone();
+                let msg = self.sign_routed_message(
+                    RawRoutedMessage {
+                        target: AccountOrPeerIdOrHash::PeerId(peer_id.clone()),
+                        body: msg,
+                    },
+                    self.my_peer_id.clone(),
+                );
+                return Self::send_message(
+                    &self.connected_peers,
+                    peer_id,
+                    PeerMessage::Routed(msg),
+                );
+            }
+        }
+
         let target = match self.routing_table_view.account_owner(account_id) {
             Ok(peer_id) => peer_id,
             Err(find_route_error) => {
@@ -1770,6 +1950,54 @@ impl PeerManagerActor {
                     NetworkResponses::NoResponse
                 }
             },
+            #[cfg(feature = "protocol_feature_validator_direct_connections")]
+            NetworkRequests::SyncAccountsAddrs { peer_id, accounts_addrs } => {
+                // Filter addresses we already know for the same epoch before validating them.
+                let accounts_addrs = accounts_addrs
+                    .into_iter()
+                    .filter_map(|account_addrs| {
+                        match self.validator_connections.get(&account_addrs.account_id) {
+                            Some(current) if current.epoch_id == account_addrs.epoch_id => None,
+                            Some(current) => {
+                                let last_epoch = Some(current.epoch_id.clone());
+                                Some((account_addrs, last_epoch))
+                            }
+                            None => Some((account_addrs, None)),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                if accounts_addrs.is_empty() {
+                    return NetworkResponses::NoResponse;
+                }
+
+                // Ask client to validate signatures before accepting the addresses.
+                self.view_client_addr
+                    .send(NetworkViewClientMessages::AnnounceAccountAddrs(accounts_addrs))
+                    .into_actor(self)
+                    .then(move |response, act, _ctx| {
+                        match response {
+                            Ok(NetworkViewClientResponses::Ban { ban_reason }) => {
+                                act.try_ban_peer(&peer_id, ban_reason);
+                            }
+                            Ok(NetworkViewClientResponses::AnnounceAccountAddrs(accounts_addrs)) => {
+                                let mut new_accounts_addrs = vec![];
+                                for account_addrs in accounts_addrs {
+                                    if act.validator_connections.insert(account_addrs.clone()) {
+                                        new_accounts_addrs.push(account_addrs);
+                                    }
+                                }
+                                act.broadcast_accounts_addrs(new_accounts_addrs);
+                            }
+                            _ => {
+                                debug!(target: "network", "Received invalid account addresses confirmation from client.");
+                            }
+                        }
+                        actix::fut::ready(())
+                    })
+                    .spawn(ctx);
+
+                NetworkResponses::NoResponse
+            }
             NetworkRequests::Challenge(challenge) => {
                 // TODO(illia): smarter routing?
                 Self::broadcast_message(
@@ -1908,7 +2136,7 @@ impl PeerManagerActor {
     fn handle_msg_inbound_tcp_connect(&self, msg: InboundTcpConnect, ctx: &mut Context<Self>) {
         let _d = delay_detector::DelayDetector::new(|| "inbound tcp connect".into());
 
-        if self.is_inbound_allowed() {
+        if self.is_inbound_allowed() || self.has_free_validator_slots() {
             self.try_connect_peer(ctx.address(), msg.stream, PeerType::Inbound, None, None);
         } else {
             // TODO(1896): Gracefully drop inbound connection for other peer.
@@ -2008,7 +2236,10 @@ impl PeerManagerActor {
             }
         }
 
-        if msg.peer_type == PeerType::Inbound && !self.is_inbound_allowed() {
+        if msg.peer_type == PeerType::Inbound
+            && !self.is_inbound_allowed()
+            && !self.is_validator_peer(&msg.peer_info.id)
+        {
             // TODO(1896): Gracefully drop inbound connection for other peer.
             debug!(target: "network",
                 connected_peers = self.connected_peers.len(), outgoing_peers = self.outgoing_peers.len(),
@@ -0,0 +1,189 @@
+use lru::LruCache;
+use near_network_primitives::types::RoutedMessageBody;
+use near_primitives::network::{AnnounceAccountAddrs, PeerId};
+use near_primitives::types::{AccountId, EpochId};
+
+/// Maximum number of validators whose announced addresses are kept. Validators of the current
+/// epoch re-announce their addresses, so the least recently announced ones are dropped first.
+pub(crate) const MAX_ACCOUNTS_ADDRS: usize = 1024;
+
+/// Returns whether the message is on the critical path of block and chunk production and should
+/// be sent over a direct connection with the target validator when one exists.
+pub(crate) fn is_consensus_critical(body: &RoutedMessageBody) -> bool {
+    matches!(
+        body,
+        RoutedMessageBody::BlockApproval(_)
+            | RoutedMessageBody::PartialEncodedChunk(_)
+            | RoutedMessageBody::VersionedPartialEncodedChunk(_)
+            | RoutedMessageBody::PartialEncodedChunkForward(_)
+    )
+}
+
+/// Keeps track of addresses announced by validators, and which of them this node should keep
+/// direct connections with.
+/// Only validators of the epoch this node itself announced addresses for are connected, so nodes
+/// which are not validators don't build the tier, but still relay announcements.
+pub(crate) struct ValidatorConnections {
+    /// Latest verified addresses announced by each validator.
+    accounts_addrs: LruCache<AccountId, AnnounceAccountAddrs>,
+    /// Epoch for which this node announced its addresses.
+    epoch_id: Option<EpochId>,
+}
+
+impl Default for ValidatorConnections {
+    fn default() -> Self {
+        Self { accounts_addrs: LruCache::new(MAX_ACCOUNTS_ADDRS), epoch_id: None }
+    }
+}
+
+impl ValidatorConnections {
+    pub(crate) fn get(&self, account_id: &AccountId) -> Option<&AnnounceAccountAddrs> {
+        self.accounts_addrs.peek(account_id)
+    }
+
+    /// Stores verified addresses of a validator, replacing older ones.
+    /// Returns `false` if exactly this announcement is already known.
+    pub(crate) fn insert(&mut self, account_addrs: AnnounceAccountAddrs) -> bool {
+        if self.accounts_addrs.peek(&account_addrs.account_id) == Some(&account_addrs) {
+            return false;
+        }
+        self.accounts_addrs.put(account_addrs.account_id.clone(), account_addrs);
+        true
+    }
+
+    pub(crate) fn set_epoch_id(&mut self, epoch_id: EpochId) {
+        self.epoch_id = Some(epoch_id);
+    }
+
+    /// All known announcements, sent to new peers.
+    pub(crate) fn all(&self) -> impl Iterator<Item = &AnnounceAccountAddrs> {
+        self.accounts_addrs.iter().map(|(_, account_addrs)| account_addrs)
+    }
+
+    /// Validators this node should be directly connected to.
+    pub(crate) fn validators<'a>(
+        &'a self,
+        my_peer_id: &'a PeerId,
+    ) -> impl Iterator<Item = &'a AnnounceAccountAddrs> + 'a {
+        self.all().filter(move |account_addrs| {
+            Some(&account_addrs.epoch_id) == self.epoch_id.as_ref()
+                && &account_addrs.peer_id != my_peer_id
+        })
+    }
+
+    /// Whether `peer_id` runs a validator this node should be directly connected to.
+    pub(crate) fn is_validator_peer(&self, peer_id: &PeerId) -> bool {
+        self.epoch_id.is_some()
+            && self.all().any(|account_addrs| {
+                Some(&account_addrs.epoch_id) == self.epoch_id.as_ref()
+                    && &account_addrs.peer_id == peer_id
+            })
+    }
+
+    /// Returns the peer to which `body` addressed to `account_id` should be sent directly.
+    pub(crate) fn direct_peer(
+        &self,
+        account_id: &AccountId,
+        body: &RoutedMessageBody,
+    ) -> Option<&PeerId> {
+        if !is_consensus_critical(body) {
+            return None;
+        }
+        self.get(account_id)
+            .filter(|account_addrs| Some(&account_addrs.epoch_id) == self.epoch_id.as_ref())
+            .map(|account_addrs| &account_addrs.peer_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::peer_manager::validator_connections::{ValidatorConnections, MAX_ACCOUNTS_ADDRS};
+    use near_crypto::{KeyType, SecretKey, Signature};
+    use near_network_primitives::types::{Ping, RoutedMessageBody};
+    use near_primitives::block::Approval;
+    use near_primitives::hash::{hash, CryptoHash};
+    use near_primitives::network::{AnnounceAccountAddrs, PeerId};
+    use near_primitives::types::{AccountId, EpochId};
+    use near_primitives::validator_signer::InMemoryValidatorSigner;
+
+    fn peer_id(seed: &str) -> PeerId {
+        PeerId::new(SecretKey::from_seed(KeyType::ED25519, seed).public_key())
+    }
+
+    fn account_addrs(account_id: &str, epoch: &[u8]) -> AnnounceAccountAddrs {
+        AnnounceAccountAddrs {
+            account_id: account_id.parse().unwrap(),
+            peer_id: peer_id(account_id),
+            epoch_id: EpochId(hash(epoch)),
+            addrs: vec!["127.0.0.1:24567".parse().unwrap()],
+            signature: Signature::default(),
+        }
+    }
+
+    fn approval() -> RoutedMessageBody {
+        let signer =
+            InMemoryValidatorSigner::from_seed("test1".parse().unwrap(), KeyType::ED25519, "test1");
+        RoutedMessageBody::BlockApproval(Approval::new(CryptoHash::default(), 0, 1, &signer))
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut connections = ValidatorConnections::default();
+        assert!(connections.insert(account_addrs("test1", b"epoch")));
+        assert!(!connections.insert(account_addrs("test1", b"epoch")));
+        assert!(connections.insert(account_addrs("test1", b"next_epoch")));
+        let account_id: AccountId = "test1".parse().unwrap();
+        assert_eq!(connections.get(&account_id).unwrap().epoch_id, EpochId(hash(b"next_epoch")));
+        assert_eq!(connections.all().count(), 1);
+    }
+
+    #[test]
+    fn test_insert_evicts_least_recently_announced() {
+        let mut connections = ValidatorConnections::default();
+        for i in 0..MAX_ACCOUNTS_ADDRS {
+            assert!(connections.insert(account_addrs(&format!("test{}", i), b"epoch")));
+        }
+        // Re-announcement with a new epoch makes `test0` the most recent one.
+        assert!(connections.insert(account_addrs("test0", b"next_epoch")));
+        assert!(connections.insert(account_addrs("new", b"epoch")));
+        assert_eq!(connections.all().count(), MAX_ACCOUNTS_ADDRS);
+        assert!(connections.get(&"test0".parse().unwrap()).is_some());
+        assert!(connections.get(&"test1".parse().unwrap()).is_none());
+        assert!(connections.get(&"new".parse().unwrap()).is_some());
+    }
+
+    #[test]
+    fn test_validators_of_current_epoch() {
+        let mut connections = ValidatorConnections::default();
+        connections.insert(account_addrs("test0", b"epoch"));
+        connections.insert(account_addrs("test1", b"epoch"));
+        connections.insert(account_addrs("test2", b"old_epoch"));
+        let me = peer_id("test0");
+
+        // Nodes which didn't announce their own addresses don't connect to anyone.
+        assert_eq!(connections.validators(&me).count(), 0);
+        assert!(!connections.is_validator_peer(&peer_id("test1")));
+
+        connections.set_epoch_id(EpochId(hash(b"epoch")));
+        let validators: Vec<_> = connections
+            .validators(&me)
+            .map(|account_addrs| account_addrs.peer_id.clone())
+            .collect();
+        assert_eq!(validators, vec![peer_id("test1")]);
+        assert!(connections.is_validator_peer(&peer_id("test1")));
+        assert!(!connections.is_validator_peer(&peer_id("test2")));
+    }
+
+    #[test]
+    fn test_direct_peer() {
+        let mut connections = ValidatorConnections::default();
+        connections.insert(account_addrs("test1", b"epoch"));
+        connections.set_epoch_id(EpochId(hash(b"epoch")));
+        let account_id: AccountId = "test1".parse().unwrap();
+
+        assert_eq!(connections.direct_peer(&account_id, &approval()), Some(&peer_id("test1")));
+        let ping = RoutedMessageBody::Ping(Ping { nonce: 0, source: peer_id("test0") });
+        assert_eq!(connections.direct_peer(&account_id, &ping), None);
+        assert_eq!(connections.direct_peer(&"test2".parse().unwrap(), &approval()), None);
+    }
+}
@@ -180,6 +180,29 @@ impl Graph {
         self.compute_result(&routes, &distance)
     }
 
+    /// Compute number of hops from `source` to every node reachable from it.
+    /// Used for debugging only, `calculate_distance` is used for routing.
+    pub fn calculate_hop_distances(&self) -> HashMap<PeerId, u32> {
+        let mut res = HashMap::new();
+        let mut distance: Vec<Option<u32>> = vec![None; self.id2p.len()];
+        let mut queue = VecDeque::new();
+
+        distance[self.source_id as usize] = Some(0);
+        queue.push_back(self.source_id);
+
+        while let Some(cur_peer) = queue.pop_front() {
+            let cur_distance = distance[cur_peer as usize].unwrap_or_default();
+            for &neighbor in &self.adjacency[cur_peer as usize] {
+                if distance[neighbor as usize].is_none() {
+                    distance[neighbor as usize] = Some(cur_distance + 1);
+                    res.insert(self.id2p[neighbor as usize].clone(), cur_distance + 1);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        res
+    }
+
     /// Converts representation of the result, from an array representation, to
     /// a hashmap of PeerId -> Vec<PeerIds>
     /// Arguments:
@@ -389,4 +412,26 @@ mod test {
         assert_eq!(22, graph.total_active_edges() as usize);
         assert_eq!(22, graph.compute_total_active_edges() as usize);
     }
+
+    #[test]
+    fn graph_hop_distances() {
+        let source = random_peer_id();
+        let nodes: Vec<_> = (0..4).map(|_| random_peer_id()).collect();
+
+        let mut graph = Graph::new(source.clone());
+
+        graph.add_edge(&source, &nodes[0]);
+        graph.add_edge(&nodes[0], &nodes[1]);
+        graph.add_edge(&source, &nodes[1]);
+        graph.add_edge(&nodes[1], &nodes[2]);
+        // Disconnected from `source`.
+        graph.add_edge(&nodes[3], &random_peer_id());
+
+        let distances = graph.calculate_hop_distances();
+        assert_eq!(3, distances.len());
+        assert_eq!(Some(&1), distances.get(&nodes[0]));
+        assert_eq!(Some(&1), distances.get(&nodes[1]));
+        assert_eq!(Some(&2), distances.get(&nodes[2]));
+        assert_eq!(None, distances.get(&nodes[3]));
+    }
 }
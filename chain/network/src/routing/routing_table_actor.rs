@@ -11,15 +11,24 @@ use near_network_primitives::types::{Edge, EdgeState};
 use near_performance_metrics_macros::perf;
 use near_primitives::borsh::BorshSerialize;
 use near_primitives::network::PeerId;
+use near_primitives::time::Clock;
 use near_primitives::utils::index_to_bytes;
+use near_primitives::views::{
+    NetworkGraphEdgeView, NetworkGraphHistoryView, NetworkGraphPeerView, NetworkGraphView,
+};
 use near_rate_limiter::{ActixMessageResponse, ActixMessageWrapper, ThrottleToken};
 use near_store::db::DBCol::{ColComponentEdges, ColLastComponentNonce, ColPeerComponent};
 use near_store::{Store, StoreUpdate};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{debug, trace, warn};
 
+/// Maximum number of routing graph snapshots kept for debugging.
+const ROUTING_GRAPH_HISTORY_SIZE: usize = 16;
+/// Minimum time between two consecutive routing graph snapshots.
+const ROUTING_GRAPH_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(10);
+
 /// `Prune` enum is to specify how often should we prune edges.
 #[derive(Debug, Eq, PartialEq)]
 pub enum Prune {
@@ -78,6 +87,11 @@ pub struct RoutingTableActor {
     edge_validator_requests_in_progress: u64,
     /// List of Peers to ban
     peers_to_ban: Vec<PeerId>,
+    /// Snapshots of the routing graph taken after routing table recalculations, oldest first.
+    /// Used for debugging how the topology changed over time.
+    routing_graph_history: VecDeque<NetworkGraphView>,
+    /// Time when the last snapshot was added to `routing_graph_history`.
+    last_routing_graph_snapshot: Option<Instant>,
 }
 
 impl RoutingTableActor {
@@ -100,6 +114,8 @@ impl RoutingTableActor {
             edge_validator_pool,
             edge_validator_requests_in_progress: Default::default(),
             peers_to_ban: Default::default(),
+            routing_graph_history: Default::default(),
+            last_routing_graph_snapshot: None,
         }
     }
 
@@ -272,6 +288,57 @@ impl RoutingTableActor {
 
         metrics::ROUTING_TABLE_RECALCULATIONS.inc();
         metrics::PEER_REACHABLE.set(self.peer_forwarding.len() as i64);
+
+        self.maybe_save_routing_graph_snapshot(now);
+    }
+
+    /// Saves current view of the routing graph to `routing_graph_history`, unless the previous
+    /// snapshot was taken less than `ROUTING_GRAPH_SNAPSHOT_INTERVAL` ago.
+    fn maybe_save_routing_graph_snapshot(&mut self, now: Instant) {
+        if let Some(last) = self.last_routing_graph_snapshot {
+            if now.saturating_duration_since(last) < ROUTING_GRAPH_SNAPSHOT_INTERVAL {
+                return;
+            }
+        }
+        self.last_routing_graph_snapshot = Some(now);
+        if self.routing_graph_history.len() >= ROUTING_GRAPH_HISTORY_SIZE {
+            self.routing_graph_history.pop_front();
+        }
+        let view = self.routing_graph_view();
+        self.routing_graph_history.push_back(view);
+    }
+
+    /// Returns view of all known edges, together with distance and next hops for each peer.
+    pub fn routing_graph_view(&self) -> NetworkGraphView {
+        let distances = self.raw_graph.calculate_hop_distances();
+        let mut edges: Vec<_> = (self.edges_info.values())
+            .map(|edge| NetworkGraphEdgeView {
+                peer0: edge.key().0.clone(),
+                peer1: edge.key().1.clone(),
+                nonce: edge.nonce(),
+                active: edge.edge_type() == EdgeState::Active,
+            })
+            .collect();
+        edges.sort_by(|a, b| (&a.peer0, &a.peer1).cmp(&(&b.peer0, &b.peer1)));
+
+        let peers = (self.edges_info.keys())
+            .flat_map(|(peer0, peer1)| [peer0, peer1])
+            .filter(|peer_id| *peer_id != self.my_peer_id())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|peer_id| NetworkGraphPeerView {
+                peer_id: peer_id.clone(),
+                distance: distances.get(peer_id).cloned(),
+                next_hops: self.peer_forwarding.get(peer_id).cloned().unwrap_or_default(),
+            })
+            .collect();
+
+        NetworkGraphView {
+            my_peer_id: self.my_peer_id().clone(),
+            timestamp: Clock::utc().timestamp_nanos() as u64,
+            edges,
+            peers,
+        }
     }
 
     /// If pruning is enabled we will remove unused edges and store them to disk.
@@ -458,6 +525,8 @@ pub enum RoutingTableMessages {
     AdvRemoveEdges(Vec<Edge>),
     /// Get `RoutingTable` for debugging purposes.
     RequestRoutingTable,
+    /// Get current routing graph and its recent snapshots for debugging purposes.
+    RequestRoutingGraph,
     /// Add `PeerId` and generate `IbfSet`.
     #[cfg(feature = "protocol_feature_routing_exchange_algorithm")]
     AddPeerIfMissing(PeerId, Option<u64>),
@@ -492,6 +561,7 @@ pub enum RoutingTableMessagesResponse {
     RequestRoutingTableResponse {
         edges_info: Vec<Edge>,
     },
+    RequestRoutingGraphResponse(Box<NetworkGraphHistoryView>),
     AddVerifiedEdgesResponse(Vec<Edge>),
     #[cfg(feature = "protocol_feature_routing_exchange_algorithm")]
     StartRoutingTableSyncResponse(crate::types::RoutingSyncV2),
@@ -595,6 +665,14 @@ impl Handler<RoutingTableMessages> for RoutingTableActor {
                     edges_info: self.edges_info.iter().map(|(_k, v)| v.clone()).collect(),
                 }
             }
+            RoutingTableMessages::RequestRoutingGraph => {
+                RoutingTableMessagesResponse::RequestRoutingGraphResponse(Box::new(
+                    NetworkGraphHistoryView {
+                        current: self.routing_graph_view(),
+                        history: self.routing_graph_history.iter().cloned().collect(),
+                    },
+                ))
+            }
             #[cfg(feature = "protocol_feature_routing_exchange_algorithm")]
             RoutingTableMessages::AddPeerIfMissing(peer_id, ibf_set) => {
                 let seed = self.peer_ibf_set.add_peer(peer_id, ibf_set, &mut self.edges_info);
@@ -31,6 +31,14 @@ pub static PEER_MESSAGE_RECEIVED_BY_TYPE_TOTAL: Lazy<IntCounterVec> = Lazy::new(
     )
     .unwrap()
 });
+pub static PEER_MESSAGE_RATE_LIMITED_BY_TYPE_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_peer_message_rate_limited_by_type_total",
+        "Number of messages received from peers dropped due to rate limits, by message types",
+        &["type"],
+    )
+    .unwrap()
+});
 pub static PEER_CLIENT_MESSAGE_RECEIVED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
     try_create_int_counter(
         "near_peer_client_message_received_total",
@@ -82,6 +90,14 @@ pub static PEER_REACHABLE: Lazy<IntGauge> = Lazy::new(|| {
     )
     .unwrap()
 });
+#[cfg(feature = "protocol_feature_validator_direct_connections")]
+pub static VALIDATOR_DIRECT_CONNECTIONS: Lazy<IntGauge> = Lazy::new(|| {
+    try_create_int_gauge(
+        "near_validator_direct_connections",
+        "Number of connected peers run by validators of the current epoch",
+    )
+    .unwrap()
+});
 pub static DROP_MESSAGE_UNKNOWN_ACCOUNT: Lazy<IntCounter> = Lazy::new(|| {
     try_create_int_counter(
         "near_drop_message_unknown_account",
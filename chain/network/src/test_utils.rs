@@ -1,9 +1,10 @@
+use crate::routing::routing_table_actor::{start_routing_table_actor, RoutingTableActor};
 use crate::types::{
     NetworkInfo, NetworkResponses, PeerManagerAdapter, PeerManagerMessageRequest,
     PeerManagerMessageResponse,
 };
 use crate::PeerManagerActor;
-use actix::{Actor, ActorContext, Context, Handler, MailboxError, Message, Recipient};
+use actix::{Actor, ActorContext, Addr, Context, Handler, MailboxError, Message, Recipient};
 use futures::future::BoxFuture;
 use futures::{future, FutureExt};
 use near_crypto::{KeyType, SecretKey};
@@ -12,6 +13,7 @@ use near_primitives::hash::hash;
 use near_primitives::network::PeerId;
 use near_primitives::types::EpochId;
 use near_primitives::utils::index_to_bytes;
+use near_store::test_utils::create_test_store;
 use once_cell::sync::Lazy;
 use rand::{thread_rng, RngCore};
 use std::collections::{HashMap, HashSet, VecDeque};
@@ -141,6 +143,11 @@ pub fn random_peer_id() -> PeerId {
     PeerId::new(sk.public_key())
 }
 
+// Starts `RoutingTableActor` with random PeerId, backed by a test store.
+pub fn make_routing_table_addr() -> Addr<RoutingTableActor> {
+    start_routing_table_actor(random_peer_id(), create_test_store())
+}
+
 // Gets random EpochId
 pub fn random_epoch_id() -> EpochId {
     EpochId(hash(index_to_bytes(thread_rng().next_u64()).as_ref()))
@@ -345,6 +352,11 @@ pub mod test_features {
                         accounts.clone().into_iter().map(|obj| obj.0).collect(),
                     )))
                 }
+                NetworkViewClientMessages::AnnounceAccountAddrs(accounts_addrs) => {
+                    Box::new(Some(NetworkViewClientResponses::AnnounceAccountAddrs(
+                        accounts_addrs.clone().into_iter().map(|obj| obj.0).collect(),
+                    )))
+                }
                 NetworkViewClientMessages::GetChainInfo => {
                     Box::new(Some(NetworkViewClientResponses::ChainInfo {
                         genesis_id: GenesisId::default(),
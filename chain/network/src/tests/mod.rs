@@ -1,2 +1,3 @@
 mod cache;
 mod cache_edges;
+mod wire_format;
@@ -0,0 +1,562 @@
+//! Compatibility checks of the network wire format.
+//!
+//! `res/wire_format/schema.json` describes the variants of every borsh-serialized enum sent over
+//! the network. Each entry lists the variants, in serialization order, used starting from its
+//! `protocol_version` until the next entry. `res/wire_format/v{N}.json` contains golden encodings
+//! of one sample of every message which a peer running protocol version `N` can send. Vectors are
+//! only recorded for `PEER_MIN_ALLOWED_PROTOCOL_VERSION` and the versions which change the wire
+//! format: protocol versions which only change the runtime don't need them.
+//!
+//! To add a new message, append the variant at the end of the enum, add a schema entry for the
+//! protocol version which introduces it, extend `sample_messages` and record the vectors of that
+//! version by running the tests with `NEAR_RECORD_WIRE_FORMAT=1`.
+//! Existing vector files must never be edited: if they fail to decode, the change breaks
+//! decoding of messages sent by peers running older versions.
+use crate::network_protocol::{Handshake, HandshakeFailureReason, PeerMessage, RoutingTableUpdate};
+#[cfg(feature = "protocol_feature_routing_exchange_algorithm")]
+use crate::network_protocol::{RoutingState, RoutingSyncV2, RoutingVersion2};
+use borsh::{BorshDeserialize, BorshSerialize};
+#[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+use near_crypto::secure_channel::EphemeralPublicKey;
+use near_crypto::{InMemorySigner, KeyType, SecretKey};
+use near_network_primitives::types::{
+    Edge, PartialEdgeInfo, PartialEncodedChunkForwardMsg, PartialEncodedChunkRequestMsg,
+    PartialEncodedChunkResponseMsg, PeerChainInfoV2, PeerIdOrHash, PeerInfo, Ping, Pong,
+    RoutedMessage, RoutedMessageBody, StateResponseInfo, StateResponseInfoV1, StateResponseInfoV2,
+};
+use near_primitives::block::{Approval, Block, GenesisId};
+use near_primitives::challenge::{BlockDoubleSign, Challenge, ChallengeBody};
+use near_primitives::epoch_manager::block_info::BlockInfo;
+use near_primitives::epoch_manager::epoch_info::{EpochInfo, EpochInfoV2};
+use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::merkle::PartialMerkleTree;
+#[cfg(feature = "protocol_feature_validator_direct_connections")]
+use near_primitives::network::AnnounceAccountAddrs;
+use near_primitives::network::{AnnounceAccount, PeerId};
+use near_primitives::sharding::{
+    ChunkHash, PartialEncodedChunk, PartialEncodedChunkPart, PartialEncodedChunkV1,
+    PartialEncodedChunkV2, ShardChunkHeader, ShardChunkHeaderV1, ShardChunkHeaderV3,
+};
+use near_primitives::syncing::{
+    EpochSyncFinalizationResponse, EpochSyncResponse, ShardStateSyncResponse,
+    ShardStateSyncResponseV1, ShardStateSyncResponseV2,
+};
+use near_primitives::transaction::{
+    ExecutionOutcome, ExecutionOutcomeWithId, ExecutionOutcomeWithIdAndProof, SignedTransaction,
+};
+use near_primitives::types::{BlockReference, EpochId, Finality, ProtocolVersion};
+use near_primitives::utils::from_timestamp;
+use near_primitives::validator_signer::{InMemoryValidatorSigner, ValidatorSigner};
+#[cfg(any(
+    feature = "protocol_feature_routing_exchange_algorithm",
+    feature = "protocol_feature_encrypted_peer_transport",
+    feature = "protocol_feature_validator_direct_connections"
+))]
+use near_primitives::version::ProtocolFeature;
+use near_primitives::version::{PEER_MIN_ALLOWED_PROTOCOL_VERSION, PROTOCOL_VERSION};
+use near_primitives::views::{
+    AccountView, FinalExecutionOutcomeView, FinalExecutionStatus, QueryRequest, QueryResponse,
+    QueryResponseKind,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+use strum::VariantNames;
+
+fn secret_key(seed: &str) -> SecretKey {
+    SecretKey::from_seed(KeyType::ED25519, seed)
+}
+
+fn peer_id(seed: &str) -> PeerId {
+    PeerId::new(secret_key(seed).public_key())
+}
+
+fn validator_signer() -> InMemoryValidatorSigner {
+    InMemoryValidatorSigner::from_seed("test0".parse().unwrap(), KeyType::ED25519, "test0")
+}
+
+fn edge(nonce: u64) -> Edge {
+    let (peer0, peer1) = (peer_id("node0"), peer_id("node1"));
+    let signature0 = PartialEdgeInfo::new(&peer0, &peer1, nonce, &secret_key("node0")).signature;
+    let signature1 = PartialEdgeInfo::new(&peer1, &peer0, nonce, &secret_key("node1")).signature;
+    Edge::new(peer0, peer1, nonce, signature0, signature1)
+}
+
+#[cfg(feature = "protocol_feature_validator_direct_connections")]
+fn account_addrs() -> AnnounceAccountAddrs {
+    let signer = validator_signer();
+    let peer_id = peer_id("node0");
+    let epoch_id = EpochId(hash(b"epoch"));
+    let addrs = vec!["127.0.0.1:24567".parse().unwrap()];
+    let signature = signer.sign_account_addrs(signer.validator_id(), &peer_id, &epoch_id, &addrs);
+    AnnounceAccountAddrs {
+        account_id: signer.validator_id().clone(),
+        peer_id,
+        epoch_id,
+        addrs,
+        signature,
+    }
+}
+
+fn peer_info() -> PeerInfo {
+    PeerInfo {
+        id: peer_id("node0"),
+        addr: Some("127.0.0.1:24567".parse().unwrap()),
+        account_id: Some("test0".parse().unwrap()),
+    }
+}
+
+fn genesis_id() -> GenesisId {
+    GenesisId { chain_id: "wire-format".to_string(), hash: hash(b"genesis") }
+}
+
+fn block(version: ProtocolVersion) -> Block {
+    Block::genesis(
+        version,
+        vec![],
+        from_timestamp(1_600_000_000_000_000_000),
+        0,
+        1_000_000_000,
+        1_000_000_000_000_000_000_000_000_000,
+        CryptoHash::default(),
+    )
+}
+
+fn transaction() -> SignedTransaction {
+    let signer = InMemorySigner::from_seed("test0".parse().unwrap(), KeyType::ED25519, "test0");
+    SignedTransaction::send_money(
+        1,
+        "test0".parse().unwrap(),
+        "test1".parse().unwrap(),
+        &signer,
+        100,
+        hash(b"block"),
+    )
+}
+
+fn announce_account() -> AnnounceAccount {
+    let signer = validator_signer();
+    let (peer_id, epoch_id) = (peer_id("node0"), EpochId(hash(b"epoch")));
+    let signature = signer.sign_account_announce(signer.validator_id(), &peer_id, &epoch_id);
+    AnnounceAccount { account_id: signer.validator_id().clone(), peer_id, epoch_id, signature }
+}
+
+fn chunk_part() -> PartialEncodedChunkPart {
+    PartialEncodedChunkPart {
+        part_ord: 0,
+        part: vec![1, 2, 3].into_boxed_slice(),
+        merkle_proof: vec![],
+    }
+}
+
+fn chunk_header_v1() -> ShardChunkHeaderV1 {
+    ShardChunkHeaderV1::new(
+        hash(b"prev"),
+        hash(b"state"),
+        hash(b"outcome"),
+        hash(b"merkle"),
+        3,
+        10,
+        0,
+        100,
+        1000,
+        5,
+        hash(b"receipts"),
+        hash(b"tx"),
+        vec![],
+        &validator_signer(),
+    )
+}
+
+fn chunk_header_v3() -> ShardChunkHeader {
+    ShardChunkHeader::V3(ShardChunkHeaderV3::new(
+        hash(b"prev"),
+        hash(b"state"),
+        hash(b"outcome"),
+        hash(b"merkle"),
+        3,
+        10,
+        0,
+        100,
+        1000,
+        5,
+        hash(b"receipts"),
+        hash(b"tx"),
+        vec![],
+        None,
+        &validator_signer(),
+    ))
+}
+
+fn final_execution_outcome() -> FinalExecutionOutcomeView {
+    let transaction = transaction();
+    let outcome = ExecutionOutcomeWithIdAndProof {
+        proof: vec![],
+        block_hash: hash(b"block"),
+        outcome_with_id: ExecutionOutcomeWithId {
+            id: transaction.get_hash(),
+            outcome: ExecutionOutcome {
+                executor_id: "test0".parse().unwrap(),
+                gas_burnt: 100,
+                ..Default::default()
+            },
+        },
+    };
+    FinalExecutionOutcomeView {
+        status: FinalExecutionStatus::SuccessValue(String::new()),
+        transaction: transaction.into(),
+        transaction_outcome: outcome.into(),
+        receipts_outcome: vec![],
+    }
+}
+
+fn routed_bodies() -> Vec<RoutedMessageBody> {
+    let sync_hash = hash(b"sync");
+    let chunk_hash = ChunkHash(hash(b"chunk"));
+    vec![
+        RoutedMessageBody::BlockApproval(Approval::new(
+            hash(b"parent"),
+            10,
+            11,
+            &validator_signer(),
+        )),
+        RoutedMessageBody::ForwardTx(transaction()),
+        RoutedMessageBody::TxStatusRequest("test0".parse().unwrap(), hash(b"tx")),
+        RoutedMessageBody::TxStatusResponse(final_execution_outcome()),
+        RoutedMessageBody::QueryRequest {
+            query_id: "query".to_string(),
+            block_reference: BlockReference::Finality(Finality::Final),
+            request: QueryRequest::ViewAccount { account_id: "test0".parse().unwrap() },
+        },
+        RoutedMessageBody::QueryResponse {
+            query_id: "query".to_string(),
+            response: Ok(QueryResponse {
+                kind: QueryResponseKind::ViewAccount(AccountView {
+                    amount: 100,
+                    locked: 0,
+                    code_hash: CryptoHash::default(),
+                    storage_usage: 182,
+                    storage_paid_at: 0,
+                }),
+                block_height: 10,
+                block_hash: hash(b"block"),
+            }),
+        },
+        RoutedMessageBody::ReceiptOutcomeRequest(hash(b"receipt")),
+        RoutedMessageBody::Unused,
+        RoutedMessageBody::StateRequestHeader(0, sync_hash),
+        RoutedMessageBody::StateRequestPart(0, sync_hash, 2),
+        RoutedMessageBody::StateResponse(StateResponseInfoV1 {
+            shard_id: 0,
+            sync_hash,
+            state_response: ShardStateSyncResponseV1 {
+                header: None,
+                part: Some((2, vec![1, 2, 3])),
+            },
+        }),
+        RoutedMessageBody::PartialEncodedChunkRequest(PartialEncodedChunkRequestMsg {
+            chunk_hash: chunk_hash.clone(),
+            part_ords: vec![0, 1],
+            tracking_shards: [0].into_iter().collect(),
+        }),
+        RoutedMessageBody::PartialEncodedChunkResponse(PartialEncodedChunkResponseMsg {
+            chunk_hash,
+            parts: vec![chunk_part()],
+            receipts: vec![],
+        }),
+        RoutedMessageBody::PartialEncodedChunk(PartialEncodedChunkV1 {
+            header: chunk_header_v1(),
+            parts: vec![chunk_part()],
+            receipts: vec![],
+        }),
+        RoutedMessageBody::Ping(Ping { nonce: 5, source: peer_id("node0") }),
+        RoutedMessageBody::Pong(Pong { nonce: 5, source: peer_id("node1") }),
+        RoutedMessageBody::VersionedPartialEncodedChunk(PartialEncodedChunk::V2(
+            PartialEncodedChunkV2 {
+                header: chunk_header_v3(),
+                parts: vec![chunk_part()],
+                receipts: vec![],
+            },
+        )),
+        RoutedMessageBody::VersionedStateResponse(StateResponseInfo::V2(StateResponseInfoV2 {
+            shard_id: 0,
+            sync_hash,
+            state_response: ShardStateSyncResponse::V2(ShardStateSyncResponseV2 {
+                header: None,
+                part: Some((2, vec![1, 2, 3])),
+            }),
+        })),
+        RoutedMessageBody::PartialEncodedChunkForward(
+            PartialEncodedChunkForwardMsg::from_header_and_parts(
+                &chunk_header_v3(),
+                vec![chunk_part()],
+            ),
+        ),
+    ]
+}
+
+fn routed(body: RoutedMessageBody) -> RoutedMessage {
+    let target = PeerIdOrHash::PeerId(peer_id("node1"));
+    let author = peer_id("node0");
+    let signature =
+        secret_key("node0").sign(RoutedMessage::build_hash(&target, &author, &body).as_ref());
+    RoutedMessage { target, author, signature, ttl: 100, body }
+}
+
+/// Returns one sample of every message which can be sent by a peer running `version`.
+fn sample_messages(version: ProtocolVersion) -> Vec<PeerMessage> {
+    let header = block(version).header().clone();
+    let epoch_info = EpochInfo::V2(EpochInfoV2 { protocol_version: version, ..Default::default() });
+    let mut messages = vec![
+        PeerMessage::Handshake(Handshake {
+            protocol_version: version,
+            oldest_supported_version: version,
+            sender_peer_id: peer_id("node0"),
+            target_peer_id: peer_id("node1"),
+            sender_listen_port: Some(24567),
+            sender_chain_info: PeerChainInfoV2 {
+                genesis_id: genesis_id(),
+                height: 10,
+                tracked_shards: vec![0, 1],
+                archival: false,
+            },
+            partial_edge_info: PartialEdgeInfo::new(
+                &peer_id("node0"),
+                &peer_id("node1"),
+                1,
+                &secret_key("node0"),
+            ),
+        }),
+        PeerMessage::HandshakeFailure(
+            peer_info(),
+            HandshakeFailureReason::ProtocolVersionMismatch {
+                version,
+                oldest_supported_version: version,
+            },
+        ),
+        PeerMessage::LastEdge(edge(2)),
+        PeerMessage::SyncRoutingTable(RoutingTableUpdate {
+            edges: vec![edge(1)],
+            accounts: vec![announce_account()],
+        }),
+        PeerMessage::RequestUpdateNonce(PartialEdgeInfo::new(
+            &peer_id("node0"),
+            &peer_id("node1"),
+            3,
+            &secret_key("node0"),
+        )),
+        PeerMessage::ResponseUpdateNonce(edge(3)),
+        PeerMessage::PeersRequest,
+        PeerMessage::PeersResponse(vec![peer_info()]),
+        PeerMessage::BlockHeadersRequest(vec![hash(b"block"), header.hash().clone()]),
+        PeerMessage::BlockHeaders(vec![header.clone()]),
+        PeerMessage::BlockRequest(hash(b"block")),
+        PeerMessage::Block(block(version)),
+        PeerMessage::Transaction(transaction()),
+    ];
+    messages.extend(
+        routed_bodies().into_iter().map(|body| PeerMessage::Routed(Box::new(routed(body)))),
+    );
+    messages.extend(vec![
+        PeerMessage::Disconnect,
+        PeerMessage::Challenge(Challenge::produce(
+            ChallengeBody::BlockDoubleSign(BlockDoubleSign {
+                left_block_header: vec![1, 2, 3],
+                right_block_header: vec![4, 5, 6],
+            }),
+            &validator_signer(),
+        )),
+        PeerMessage::_HandshakeV2,
+        PeerMessage::EpochSyncRequest(EpochId(hash(b"epoch"))),
+        PeerMessage::EpochSyncResponse(Box::new(EpochSyncResponse::UpToDate)),
+        PeerMessage::EpochSyncFinalizationRequest(EpochId(hash(b"epoch"))),
+        PeerMessage::EpochSyncFinalizationResponse(Box::new(EpochSyncFinalizationResponse {
+            cur_epoch_header: header.clone(),
+            prev_epoch_headers: vec![],
+            header_sync_init_header: header,
+            header_sync_init_header_tree: PartialMerkleTree::default(),
+            prev_epoch_first_block_info: BlockInfo::default(),
+            prev_epoch_prev_last_block_info: BlockInfo::default(),
+            prev_epoch_last_block_info: BlockInfo::default(),
+            prev_epoch_info: epoch_info.clone(),
+            cur_epoch_info: epoch_info.clone(),
+            next_epoch_info: epoch_info,
+        })),
+    ]);
+    #[cfg(feature = "protocol_feature_routing_exchange_algorithm")]
+    if version >= ProtocolFeature::RoutingExchangeAlgorithm.protocol_version() {
+        messages.push(PeerMessage::RoutingTableSyncV2(RoutingSyncV2::Version2(RoutingVersion2 {
+            known_edges: 1,
+            seed: 2,
+            edges: vec![edge(1)],
+            routing_state: RoutingState::RequestMissingEdges(vec![3]),
+        })));
+    }
+    #[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+    if version >= ProtocolFeature::EncryptedPeerTransport.protocol_version() {
+        messages.push(PeerMessage::SecureChannelInit(EphemeralPublicKey([7; 32])));
+    }
+    #[cfg(feature = "protocol_feature_validator_direct_connections")]
+    if version >= ProtocolFeature::ValidatorDirectConnections.protocol_version() {
+        messages.push(PeerMessage::SyncAccountsAddrs(vec![account_addrs()]));
+    }
+    messages
+}
+
+fn message_name(message: &PeerMessage) -> String {
+    match message {
+        PeerMessage::Routed(routed) => format!("Routed/{}", routed.body.as_ref()),
+        _ => strum::AsStaticRef::as_static(message).to_string(),
+    }
+}
+
+const RECORD_ENV_VAR: &str = "NEAR_RECORD_WIRE_FORMAT";
+
+fn wire_format_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("res").join("wire_format")
+}
+
+fn load_json(path: &Path) -> serde_json::Value {
+    let data = fs::read_to_string(path)
+        .unwrap_or_else(|err| panic!("failed to read {}: {}", path.display(), err));
+    serde_json::from_str(&data)
+        .unwrap_or_else(|err| panic!("failed to parse {}: {}", path.display(), err))
+}
+
+/// Returns variants of `enum_name` recorded in the schema entries which are in effect for any
+/// protocol version between `PEER_MIN_ALLOWED_PROTOCOL_VERSION` and `PROTOCOL_VERSION`.
+fn supported_schema_entries(enum_name: &str) -> Vec<(ProtocolVersion, Vec<String>)> {
+    let schema = load_json(&wire_format_dir().join("schema.json"));
+    let entries: Vec<(ProtocolVersion, Vec<String>)> = schema
+        .as_array()
+        .expect("schema must be a list of entries")
+        .iter()
+        .map(|entry| {
+            let version = entry["protocol_version"].as_u64().unwrap() as ProtocolVersion;
+            let variants = entry["enums"][enum_name]
+                .as_array()
+                .unwrap_or_else(|| panic!("{} is missing in entry {}", enum_name, version))
+                .iter()
+                .map(|variant| variant.as_str().unwrap().to_string())
+                .collect();
+            (version, variants)
+        })
+        .collect();
+    assert!(
+        entries.windows(2).all(|w| w[0].0 < w[1].0),
+        "schema entries must be sorted by protocol version"
+    );
+    let first = entries
+        .iter()
+        .rposition(|(version, _)| *version <= PEER_MIN_ALLOWED_PROTOCOL_VERSION)
+        .expect("schema doesn't cover PEER_MIN_ALLOWED_PROTOCOL_VERSION");
+    entries
+        .into_iter()
+        .skip(first)
+        .take_while(|(version, _)| *version <= PROTOCOL_VERSION)
+        .collect()
+}
+
+fn check_enum_schema(enum_name: &str, variants: &[&str]) {
+    let entries = supported_schema_entries(enum_name);
+    for (version, recorded) in &entries {
+        assert!(
+            recorded.len() <= variants.len()
+                && recorded.iter().zip(variants).all(|(recorded, variant)| recorded == variant),
+            "variants of {} used by protocol version {} were removed or reordered: \
+             recorded {:?}, current {:?}",
+            enum_name,
+            version,
+            recorded,
+            variants
+        );
+    }
+    let (version, latest) = entries.last().unwrap();
+    assert_eq!(
+        latest.len(),
+        variants.len(),
+        "{} has variants not recorded in schema.json (latest entry is {}), \
+         add an entry for the protocol version which introduces them",
+        enum_name,
+        version
+    );
+}
+
+/// Returns the protocol version of the latest schema entry in effect for `PROTOCOL_VERSION`.
+fn latest_schema_version() -> ProtocolVersion {
+    supported_schema_entries("PeerMessage").last().unwrap().0
+}
+
+#[test]
+fn test_wire_format_schema() {
+    check_enum_schema("PeerMessage", PeerMessage::VARIANTS);
+    check_enum_schema("RoutedMessageBody", RoutedMessageBody::VARIANTS);
+    check_enum_schema("HandshakeFailureReason", HandshakeFailureReason::VARIANTS);
+}
+
+fn record_vectors(path: &Path, version: ProtocolVersion) {
+    let vectors: serde_json::Map<String, serde_json::Value> = sample_messages(version)
+        .iter()
+        .map(|message| (message_name(message), hex::encode(message.try_to_vec().unwrap()).into()))
+        .collect();
+    fs::write(path, serde_json::to_string_pretty(&vectors).unwrap() + "\n").unwrap();
+}
+
+#[test]
+fn test_wire_format_golden_vectors() {
+    let latest_version = latest_schema_version();
+    for version in PEER_MIN_ALLOWED_PROTOCOL_VERSION..=PROTOCOL_VERSION {
+        let path = wire_format_dir().join(format!("v{}.json", version));
+        if !path.exists() {
+            if version == latest_version && env::var_os(RECORD_ENV_VAR).is_some() {
+                record_vectors(&path, version);
+            } else {
+                assert!(
+                    version != PEER_MIN_ALLOWED_PROTOCOL_VERSION && version != latest_version,
+                    "{} is missing, run tests with {}=1 to record it",
+                    path.display(),
+                    RECORD_ENV_VAR
+                );
+                continue;
+            }
+        }
+
+        let samples: HashMap<String, PeerMessage> = sample_messages(version)
+            .into_iter()
+            .map(|message| (message_name(&message), message))
+            .collect();
+        let vectors = load_json(&path);
+        let vectors = vectors.as_object().unwrap();
+        for (name, encoded) in vectors {
+            let bytes = hex::decode(encoded.as_str().unwrap()).unwrap();
+            let message = PeerMessage::try_from_slice(&bytes).unwrap_or_else(|err| {
+                panic!("failed to decode {} recorded for version {}: {}", name, version, err)
+            });
+            assert_eq!(
+                Some(&message),
+                samples.get(name),
+                "{} recorded for version {} decodes to a different message",
+                name,
+                version
+            );
+            assert_eq!(
+                message.try_to_vec().unwrap(),
+                bytes,
+                "{} recorded for version {} is encoded differently",
+                name,
+                version
+            );
+        }
+        if version == latest_version {
+            for name in samples.keys() {
+                assert!(
+                    vectors.contains_key(name),
+                    "{} has no vector for version {}, add a schema entry for the version which \
+                     introduces it",
+                    name,
+                    version
+                );
+            }
+        }
+    }
+}
@@ -20,6 +20,8 @@ use near_primitives::block::{Approval, ApprovalMessage, Block, BlockHeader};
 use near_primitives::challenge::Challenge;
 use near_primitives::errors::InvalidTxError;
 use near_primitives::hash::CryptoHash;
+#[cfg(feature = "protocol_feature_validator_direct_connections")]
+use near_primitives::network::AnnounceAccountAddrs;
 use near_primitives::network::{AnnounceAccount, PeerId};
 use near_primitives::sharding::{PartialEncodedChunk, PartialEncodedChunkWithArcReceipts};
 use near_primitives::syncing::{EpochSyncFinalizationResponse, EpochSyncResponse};
@@ -329,6 +331,12 @@ pub enum NetworkRequests {
         peer_id: PeerId,
         ibf_msg: RoutingSyncV2,
     },
+    /// Signed validator addresses received from a peer.
+    #[cfg(feature = "protocol_feature_validator_direct_connections")]
+    SyncAccountsAddrs {
+        peer_id: PeerId,
+        accounts_addrs: Vec<AnnounceAccountAddrs>,
+    },
 }
 
 /// Combines peer address info, chain and edge information.
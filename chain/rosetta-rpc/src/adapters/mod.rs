@@ -419,6 +419,18 @@ impl From<NearActions> for Vec<crate::models::Operation> {
                     );
                     operations.push(deploy_contract_operation);
                 }
+
+                // The inner actions of a delegate action are executed in a separate receipt,
+                // which is converted to operations on its own.
+                #[cfg(feature = "protocol_feature_delegate_action")]
+                near_primitives::transaction::Action::Delegate(_) => {}
+
+                // Global contract code is not owned by any account, so there is no operation to
+                // describe it. The storage cost burnt from the deployer is reflected in the
+                // balance changes.
+                #[cfg(feature = "protocol_feature_global_contracts")]
+                near_primitives::transaction::Action::DeployGlobalContract(_)
+                | near_primitives::transaction::Action::UseGlobalContract(_) => {}
             }
         }
         operations
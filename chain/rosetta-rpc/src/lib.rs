@@ -599,6 +599,8 @@ async fn construction_payloads(
         nonce: signer_public_access_key_nonce,
         receiver_id: receiver_account_id,
         actions,
+        valid_from_height: None,
+        valid_until_height: None,
     };
 
     let (transaction_hash, _) = unsigned_transaction.get_hash_and_size().clone();
@@ -17,6 +17,7 @@ mod errors;
 pub mod key_conversion;
 mod key_file;
 pub mod randomness;
+pub mod secure_channel;
 mod signature;
 mod signer;
 mod test_utils;
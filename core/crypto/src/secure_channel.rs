@@ -0,0 +1,354 @@
+//! Authenticated key exchange and frame encryption for peer-to-peer connections.
+//!
+//! The key exchange follows the Noise `KK` pattern: both sides already know each other's static
+//! ed25519 keys (the `PeerId`s exchanged during the handshake), and each side contributes a fresh
+//! X25519 ephemeral key. Session keys are derived from the `ee`, `es` and `se` Diffie-Hellman
+//! results, so only the owners of the static keys can compute them. There is no separate
+//! authentication message: a peer that doesn't own its static key fails on the first frame.
+//!
+//! Each frame is encrypted and authenticated with ChaCha20-Poly1305. Nonces are implicit
+//! per-direction frame counters, which relies on the transport delivering frames in order.
+use crate::signature::{ED25519PublicKey, ED25519SecretKey, PublicKey, SecretKey};
+use arrayref::array_ref;
+use blake2::digest::{Update, VariableOutput};
+use blake2::VarBlake2b;
+use borsh::{BorshDeserialize, BorshSerialize};
+use chacha20poly1305::aead::{AeadInPlace, NewAead};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use curve25519_dalek::constants::X25519_BASEPOINT;
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand_core::{OsRng, RngCore};
+use subtle::ConstantTimeEq;
+
+/// Size of the authentication tag appended to every encrypted frame.
+pub const TAG_LENGTH: usize = 16;
+
+const PROTOCOL_NAME: &[u8] = b"near-secure-channel-kk-x25519-chacha20poly1305-blake2b-v1";
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SecureChannelError {
+    #[error("only ED25519 keys can be used for the secure channel")]
+    UnsupportedKeyType,
+    #[error("invalid static public key")]
+    InvalidPublicKey,
+    #[error("Diffie-Hellman exchange with a low order point")]
+    LowOrderPoint,
+    #[error("frame is shorter than the authentication tag")]
+    FrameTooShort,
+    #[error("frame authentication failed")]
+    InvalidTag,
+    #[error("frame is too long to be encrypted")]
+    FrameTooLong,
+    #[error("frame counter exhausted")]
+    NonceExhausted,
+}
+
+/// Side of the connection; determines which derived keys are used for sending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecureChannelRole {
+    /// Side which opened the connection and sends the first ephemeral key.
+    Initiator,
+    /// Side which accepted the connection and replies with its ephemeral key.
+    Responder,
+}
+
+/// X25519 public key sent to the other peer during the key exchange.
+#[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EphemeralPublicKey(pub [u8; 32]);
+
+/// X25519 secret key used for a single key exchange.
+pub struct EphemeralSecretKey(Scalar);
+
+impl EphemeralSecretKey {
+    pub fn from_random() -> Self {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        Self(clamped_scalar(bytes))
+    }
+
+    pub fn public_key(&self) -> EphemeralPublicKey {
+        EphemeralPublicKey((&self.0 * &X25519_BASEPOINT).to_bytes())
+    }
+}
+
+/// Keys and frame counter for a single direction of the channel.
+pub struct CipherState {
+    cipher: ChaCha20Poly1305,
+    nonce: u64,
+}
+
+impl CipherState {
+    fn new(key: [u8; 32]) -> Self {
+        Self { cipher: ChaCha20Poly1305::new(Key::from_slice(&key)), nonce: 0 }
+    }
+
+    /// Encrypts `frame` in place and appends the authentication tag.
+    pub fn encrypt(&mut self, mut frame: Vec<u8>) -> Result<Vec<u8>, SecureChannelError> {
+        let nonce = self.next_nonce()?;
+        // Only fails for frames above the 256 GiB limit of ChaCha20-Poly1305.
+        self.cipher
+            .encrypt_in_place(&nonce, &[], &mut frame)
+            .map_err(|_| SecureChannelError::FrameTooLong)?;
+        Ok(frame)
+    }
+
+    /// Verifies the authentication tag and decrypts the frame.
+    pub fn decrypt(&mut self, mut frame: Vec<u8>) -> Result<Vec<u8>, SecureChannelError> {
+        if frame.len() < TAG_LENGTH {
+            return Err(SecureChannelError::FrameTooShort);
+        }
+        let nonce = self.next_nonce()?;
+        self.cipher
+            .decrypt_in_place(&nonce, &[], &mut frame)
+            .map_err(|_| SecureChannelError::InvalidTag)?;
+        Ok(frame)
+    }
+
+    /// Returns the nonce for the next frame: 32 zero bits followed by the little-endian frame
+    /// counter, as in Noise.
+    fn next_nonce(&mut self) -> Result<Nonce, SecureChannelError> {
+        let counter = self.nonce;
+        self.nonce = counter.checked_add(1).ok_or(SecureChannelError::NonceExhausted)?;
+        let mut nonce = Nonce::default();
+        nonce[4..].copy_from_slice(&counter.to_le_bytes());
+        Ok(nonce)
+    }
+}
+
+/// Keys of an established channel.
+pub struct SessionKeys {
+    pub send: CipherState,
+    pub recv: CipherState,
+}
+
+/// Derives session keys from our static and ephemeral keys and the peer's ones.
+/// Both sides arrive at the same keys, with `send` of one side matching `recv` of the other.
+pub fn derive_session_keys(
+    role: SecureChannelRole,
+    my_static_key: &SecretKey,
+    my_ephemeral_key: &EphemeralSecretKey,
+    peer_static_key: &PublicKey,
+    peer_ephemeral_key: &EphemeralPublicKey,
+) -> Result<SessionKeys, SecureChannelError> {
+    let (my_static_secret, my_static_public) = match my_static_key {
+        SecretKey::ED25519(key) => (static_secret_scalar(key), my_static_key.public_key()),
+        SecretKey::SECP256K1(_) => return Err(SecureChannelError::UnsupportedKeyType),
+    };
+    let peer_static = match peer_static_key {
+        PublicKey::ED25519(key) => static_public_point(key)?,
+        PublicKey::SECP256K1(_) => return Err(SecureChannelError::UnsupportedKeyType),
+    };
+    let peer_ephemeral = MontgomeryPoint(peer_ephemeral_key.0);
+    let my_ephemeral_public = my_ephemeral_key.public_key();
+
+    let ee = diffie_hellman(&my_ephemeral_key.0, &peer_ephemeral)?;
+    // `es` is always initiator's ephemeral with responder's static key, `se` the other way round.
+    let (es, se) = match role {
+        SecureChannelRole::Initiator => (
+            diffie_hellman(&my_ephemeral_key.0, &peer_static)?,
+            diffie_hellman(&my_static_secret, &peer_ephemeral)?,
+        ),
+        SecureChannelRole::Responder => (
+            diffie_hellman(&my_static_secret, &peer_ephemeral)?,
+            diffie_hellman(&my_ephemeral_key.0, &peer_static)?,
+        ),
+    };
+
+    let (initiator_static, responder_static, initiator_ephemeral, responder_ephemeral) = match role
+    {
+        SecureChannelRole::Initiator => {
+            (&my_static_public, peer_static_key, &my_ephemeral_public, peer_ephemeral_key)
+        }
+        SecureChannelRole::Responder => {
+            (peer_static_key, &my_static_public, peer_ephemeral_key, &my_ephemeral_public)
+        }
+    };
+    let mut transcript = [0u8; 32];
+    keyed_hash(
+        &[],
+        &[
+            PROTOCOL_NAME,
+            initiator_static.key_data(),
+            responder_static.key_data(),
+            &initiator_ephemeral.0[..],
+            &responder_ephemeral.0[..],
+        ],
+        &mut transcript,
+    );
+    let mut chaining_key = transcript;
+    for dh in [ee, es, se] {
+        let mut next = [0u8; 32];
+        keyed_hash(&chaining_key, &[&dh[..]], &mut next);
+        chaining_key = next;
+    }
+
+    let derive = |label: &[u8]| {
+        let mut key = [0u8; 32];
+        keyed_hash(&chaining_key, &[label], &mut key);
+        key
+    };
+    let initiator_to_responder = CipherState::new(derive(b"i2r"));
+    let responder_to_initiator = CipherState::new(derive(b"r2i"));
+    Ok(match role {
+        SecureChannelRole::Initiator => {
+            SessionKeys { send: initiator_to_responder, recv: responder_to_initiator }
+        }
+        SecureChannelRole::Responder => {
+            SessionKeys { send: responder_to_initiator, recv: initiator_to_responder }
+        }
+    })
+}
+
+fn clamped_scalar(mut bytes: [u8; 32]) -> Scalar {
+    bytes[0] &= 248;
+    bytes[31] &= 127;
+    bytes[31] |= 64;
+    Scalar::from_bits(bytes)
+}
+
+/// X25519 secret scalar corresponding to ed25519 secret key.
+fn static_secret_scalar(key: &ED25519SecretKey) -> Scalar {
+    let expanded = ed25519_dalek::ExpandedSecretKey::from(
+        &ed25519_dalek::SecretKey::from_bytes(&key.0[..32]).unwrap(),
+    )
+    .to_bytes();
+    clamped_scalar(*array_ref!(&expanded, 0, 32))
+}
+
+/// X25519 public key corresponding to ed25519 public key.
+fn static_public_point(key: &ED25519PublicKey) -> Result<MontgomeryPoint, SecureChannelError> {
+    CompressedEdwardsY(key.0)
+        .decompress()
+        .map(|point| point.to_montgomery())
+        .ok_or(SecureChannelError::InvalidPublicKey)
+}
+
+fn diffie_hellman(
+    secret: &Scalar,
+    public: &MontgomeryPoint,
+) -> Result<[u8; 32], SecureChannelError> {
+    let shared = (secret * public).to_bytes();
+    if bool::from(shared.ct_eq(&[0u8; 32])) {
+        return Err(SecureChannelError::LowOrderPoint);
+    }
+    Ok(shared)
+}
+
+fn keyed_hash(key: &[u8], data: &[&[u8]], out: &mut [u8]) {
+    let mut hasher = VarBlake2b::new_keyed(key, out.len());
+    for chunk in data {
+        hasher.update(chunk);
+    }
+    hasher.finalize_variable(|res| out.copy_from_slice(res));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::secure_channel::{
+        derive_session_keys, EphemeralSecretKey, SecureChannelError, SecureChannelRole,
+        SessionKeys, TAG_LENGTH,
+    };
+    use crate::{KeyType, SecretKey};
+
+    fn make_channel() -> (SessionKeys, SessionKeys) {
+        let initiator_key = SecretKey::from_random(KeyType::ED25519);
+        let responder_key = SecretKey::from_random(KeyType::ED25519);
+        let initiator_ephemeral = EphemeralSecretKey::from_random();
+        let responder_ephemeral = EphemeralSecretKey::from_random();
+        let initiator = derive_session_keys(
+            SecureChannelRole::Initiator,
+            &initiator_key,
+            &initiator_ephemeral,
+            &responder_key.public_key(),
+            &responder_ephemeral.public_key(),
+        )
+        .unwrap();
+        let responder = derive_session_keys(
+            SecureChannelRole::Responder,
+            &responder_key,
+            &responder_ephemeral,
+            &initiator_key.public_key(),
+            &initiator_ephemeral.public_key(),
+        )
+        .unwrap();
+        (initiator, responder)
+    }
+
+    #[test]
+    fn test_secure_channel_roundtrip() {
+        let (mut initiator, mut responder) = make_channel();
+        for len in [0, 1, 63, 64, 65, 1000] {
+            let frame = vec![7u8; len];
+            let encrypted = initiator.send.encrypt(frame.clone()).unwrap();
+            assert_eq!(encrypted.len(), len + TAG_LENGTH);
+            assert_eq!(responder.recv.decrypt(encrypted).unwrap(), frame);
+
+            let encrypted = responder.send.encrypt(frame.clone()).unwrap();
+            assert_eq!(initiator.recv.decrypt(encrypted).unwrap(), frame);
+        }
+    }
+
+    #[test]
+    fn test_secure_channel_rejects_tampered_frame() {
+        let (mut initiator, mut responder) = make_channel();
+        let mut encrypted = initiator.send.encrypt(b"hello".to_vec()).unwrap();
+        encrypted[0] ^= 1;
+        assert_eq!(responder.recv.decrypt(encrypted), Err(SecureChannelError::InvalidTag));
+        assert_eq!(responder.recv.decrypt(vec![0u8; 3]), Err(SecureChannelError::FrameTooShort));
+    }
+
+    #[test]
+    fn test_secure_channel_rejects_reordered_frames() {
+        let (mut initiator, mut responder) = make_channel();
+        let first = initiator.send.encrypt(b"first".to_vec()).unwrap();
+        let second = initiator.send.encrypt(b"second".to_vec()).unwrap();
+        assert_eq!(responder.recv.decrypt(second), Err(SecureChannelError::InvalidTag));
+        assert!(responder.recv.decrypt(first).is_err());
+    }
+
+    #[test]
+    fn test_secure_channel_wrong_static_key() {
+        let initiator_key = SecretKey::from_random(KeyType::ED25519);
+        let responder_key = SecretKey::from_random(KeyType::ED25519);
+        let impostor_key = SecretKey::from_random(KeyType::ED25519);
+        let initiator_ephemeral = EphemeralSecretKey::from_random();
+        let responder_ephemeral = EphemeralSecretKey::from_random();
+        let mut initiator = derive_session_keys(
+            SecureChannelRole::Initiator,
+            &initiator_key,
+            &initiator_ephemeral,
+            &responder_key.public_key(),
+            &responder_ephemeral.public_key(),
+        )
+        .unwrap();
+        // Impostor claims to be `responder_key` but doesn't own it.
+        let mut impostor = derive_session_keys(
+            SecureChannelRole::Responder,
+            &impostor_key,
+            &responder_ephemeral,
+            &initiator_key.public_key(),
+            &initiator_ephemeral.public_key(),
+        )
+        .unwrap();
+        let encrypted = initiator.send.encrypt(b"secret".to_vec()).unwrap();
+        assert_eq!(impostor.recv.decrypt(encrypted), Err(SecureChannelError::InvalidTag));
+    }
+
+    #[test]
+    fn test_secure_channel_unsupported_key() {
+        let secp_key = SecretKey::from_random(KeyType::SECP256K1);
+        let ed_key = SecretKey::from_random(KeyType::ED25519);
+        let ephemeral = EphemeralSecretKey::from_random();
+        let res = derive_session_keys(
+            SecureChannelRole::Initiator,
+            &secp_key,
+            &ephemeral,
+            &ed_key.public_key(),
+            &EphemeralSecretKey::from_random().public_key(),
+        );
+        assert_eq!(res.err(), Some(SecureChannelError::UnsupportedKeyType));
+    }
+}
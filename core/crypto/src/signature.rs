@@ -109,6 +109,32 @@ impl TryFrom<&[u8]> for Secp256K1PublicKey {
     }
 }
 
+impl Secp256K1PublicKey {
+    /// Verifies a compact `r | s` ECDSA `signature` of the 32-byte message hash `msg`.
+    ///
+    /// Unlike `Signature::verify`, never panics: signatures that can't be parsed, signatures with
+    /// `s` in the upper half of the curve order and keys that are not valid curve points don't
+    /// verify.
+    pub fn verify_compact(&self, signature: &[u8; 64], msg: [u8; 32]) -> bool {
+        // The recovery id doesn't matter when parsing `r` and `s`.
+        let signature = match secp256k1::RecoverableSignature::from_compact(
+            &SECP256K1,
+            signature,
+            secp256k1::RecoveryId::from_i32(0).unwrap(),
+        ) {
+            Ok(signature) => signature.to_standard(&SECP256K1),
+            Err(_) => return false,
+        };
+        let mut pdata = [4u8; 65];
+        pdata[1..65].copy_from_slice(&self.0);
+        let public_key = match secp256k1::key::PublicKey::from_slice(&SECP256K1, &pdata) {
+            Ok(public_key) => public_key,
+            Err(_) => return false,
+        };
+        SECP256K1.verify(&Message::from(msg), &signature, &public_key).is_ok()
+    }
+}
+
 impl AsRef<[u8]> for Secp256K1PublicKey {
     fn as_ref(&self) -> &[u8] {
         &self.0
@@ -906,6 +932,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_secp256k1_verify_compact() {
+        let secret_key = SecretKey::from_seed(KeyType::SECP256K1, "test");
+        let public_key = match secret_key.public_key() {
+            PublicKey::SECP256K1(public_key) => public_key,
+            _ => unreachable!(),
+        };
+        use sha2::Digest;
+        let msg: [u8; 32] = sha2::Sha256::digest(b"123").into();
+        let mut signature = match secret_key.sign(&msg) {
+            Signature::SECP256K1(signature) => signature.0[..64].try_into().unwrap(),
+            _ => unreachable!(),
+        };
+        assert!(public_key.verify_compact(&signature, msg));
+        assert!(!public_key.verify_compact(&signature, [0; 32]));
+        assert!(!Secp256K1PublicKey([0; 64]).verify_compact(&signature, msg));
+        signature[0] ^= 1;
+        assert!(!public_key.verify_compact(&signature, msg));
+    }
+
     #[test]
     fn test_json_serialize_ed25519() {
         let sk = SecretKey::from_seed(KeyType::ED25519, "test");
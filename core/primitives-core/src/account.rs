@@ -7,6 +7,8 @@ pub use near_account_id as id;
 use crate::hash::CryptoHash;
 use crate::serialize::{option_u128_dec_format, u128_dec_format_compatible};
 use crate::types::{Balance, Nonce, StorageUsage};
+#[cfg(feature = "protocol_feature_access_key_expiration")]
+use crate::types::{BlockHeight, EpochHeight};
 #[derive(
     BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy,
 )]
@@ -180,6 +182,37 @@ pub enum AccessKeyPermission {
     /// Grants full access to the account.
     /// NOTE: It's used to replace account-level public keys.
     FullAccess,
+
+    /// Same as `FunctionCall`, but the key expires at a given block height and its allowance is
+    /// refilled every epoch.
+    #[cfg(feature = "protocol_feature_access_key_expiration")]
+    ExpiringFunctionCall(ExpiringFunctionCallPermission),
+}
+
+impl AccessKeyPermission {
+    /// Returns the function call restrictions of the key, or `None` for a full access key.
+    pub fn function_call_permission(&self) -> Option<&FunctionCallPermission> {
+        match self {
+            AccessKeyPermission::FunctionCall(permission) => Some(permission),
+            AccessKeyPermission::FullAccess => None,
+            #[cfg(feature = "protocol_feature_access_key_expiration")]
+            AccessKeyPermission::ExpiringFunctionCall(permission) => {
+                Some(&permission.function_call)
+            }
+        }
+    }
+
+    /// Mutable counterpart of `function_call_permission`.
+    pub fn function_call_permission_mut(&mut self) -> Option<&mut FunctionCallPermission> {
+        match self {
+            AccessKeyPermission::FunctionCall(permission) => Some(permission),
+            AccessKeyPermission::FullAccess => None,
+            #[cfg(feature = "protocol_feature_access_key_expiration")]
+            AccessKeyPermission::ExpiringFunctionCall(permission) => {
+                Some(&mut permission.function_call)
+            }
+        }
+    }
 }
 
 /// Grants limited permission to make transactions with FunctionCallActions
@@ -212,6 +245,49 @@ pub struct FunctionCallPermission {
     pub method_names: Vec<String>,
 }
 
+/// Function call permission of an access key which lapses after `expiration_block_height` and
+/// whose allowance is reset to `allowance_per_epoch` once per epoch.
+#[cfg(feature = "protocol_feature_access_key_expiration")]
+#[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug,
+)]
+pub struct ExpiringFunctionCallPermission {
+    /// Restrictions of the function calls. The allowance is what is left to spend in the epoch
+    /// `allowance_epoch_height`.
+    pub function_call: FunctionCallPermission,
+
+    /// The access key can't be used in blocks with a greater height.
+    /// `None` means the access key doesn't expire.
+    pub expiration_block_height: Option<BlockHeight>,
+
+    /// The allowance is reset to this value in every epoch in which the access key is used.
+    /// `None` means the allowance is never refilled.
+    #[serde(with = "option_u128_dec_format")]
+    pub allowance_per_epoch: Option<Balance>,
+
+    /// Height of the epoch in which the allowance was last reset.
+    pub allowance_epoch_height: EpochHeight,
+}
+
+#[cfg(feature = "protocol_feature_access_key_expiration")]
+impl ExpiringFunctionCallPermission {
+    /// Whether the access key can no longer be used in a block at the given height.
+    pub fn is_expired(&self, block_height: BlockHeight) -> bool {
+        self.expiration_block_height.map_or(false, |expiration| block_height > expiration)
+    }
+
+    /// Resets the allowance if the given epoch is later than the one of the last refill.
+    pub fn refill_allowance(&mut self, epoch_height: EpochHeight) {
+        if let Some(allowance_per_epoch) = self.allowance_per_epoch {
+            if epoch_height > self.allowance_epoch_height {
+                self.function_call.allowance = Some(allowance_per_epoch);
+                self.allowance_epoch_height = epoch_height;
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use borsh::BorshSerialize;
@@ -248,4 +324,27 @@ mod tests {
             <Account as BorshDeserialize>::deserialize(&mut new_bytes).unwrap();
         assert_eq!(deserialized_account, new_account);
     }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_access_key_expiration")]
+    fn test_expiring_function_call_permission() {
+        let mut permission = ExpiringFunctionCallPermission {
+            function_call: FunctionCallPermission {
+                allowance: Some(10),
+                receiver_id: "test".to_string(),
+                method_names: vec![],
+            },
+            expiration_block_height: Some(100),
+            allowance_per_epoch: Some(50),
+            allowance_epoch_height: 3,
+        };
+        assert!(!permission.is_expired(100));
+        assert!(permission.is_expired(101));
+
+        permission.refill_allowance(3);
+        assert_eq!(permission.function_call.allowance, Some(10));
+        permission.refill_allowance(4);
+        assert_eq!(permission.function_call.allowance, Some(50));
+        assert_eq!(permission.allowance_epoch_height, 4);
+    }
 }
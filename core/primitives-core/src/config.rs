@@ -14,6 +14,15 @@ pub struct VMConfig {
     pub grow_mem_cost: u32,
     /// Gas cost of a regular operation.
     pub regular_op_cost: u32,
+    /// Cost of copying or filling a single page of memory with bulk memory instructions, in
+    /// regular operations. Charged proportionally to the number of bytes processed.
+    #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+    #[serde(default)]
+    pub bulk_memory_page_cost: u32,
+    /// Number of `regular_op_cost` units charged for each type of Wasm instruction.
+    #[cfg(feature = "protocol_feature_wasm_op_cost_table")]
+    #[serde(default)]
+    pub op_cost_table: WasmOpCostTable,
 
     /// Describes limits for VM and Runtime.
     pub limit_config: VMLimitConfig,
@@ -35,6 +44,10 @@ pub struct VMLimitConfig {
     /// [`StackLimiterVersion`].
     #[serde(default = "StackLimiterVersion::v0")]
     pub stack_limiter_version: StackLimiterVersion,
+    /// Whether contracts may use instructions of the Wasm bulk memory proposal.
+    #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+    #[serde(default)]
+    pub wasm_bulk_memory: bool,
 
     /// The initial number of memory pages.
     /// NOTE: It's not a limiter itself, but it's a value we use for initial_memory_pages.
@@ -86,6 +99,93 @@ pub struct VMLimitConfig {
     pub max_functions_number_per_contract: Option<u64>,
 }
 
+/// Cost of Wasm instructions by instruction type, in units of `regular_op_cost`.
+///
+/// The types follow the classification used by the gas injection pass. With the default table
+/// every instruction costs a single unit, which is the same as charging a flat
+/// `regular_op_cost` per instruction.
+#[cfg(feature = "protocol_feature_wasm_op_cost_table")]
+#[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq, Eq)]
+pub struct WasmOpCostTable {
+    /// Bitwise operations, shifts and rotations, e.g. `i64.and`, `i32.shl`.
+    pub bit: u32,
+    /// Integer addition and subtraction.
+    pub add: u32,
+    /// Integer multiplication.
+    pub mul: u32,
+    /// Integer division and remainder.
+    pub div: u32,
+    /// Memory loads.
+    pub load: u32,
+    /// Memory stores.
+    pub store: u32,
+    /// Integer constants.
+    pub constant: u32,
+    /// Float constants.
+    pub float_const: u32,
+    /// `local.get`, `local.set`, `local.tee`.
+    pub local: u32,
+    /// `global.get`, `global.set`.
+    pub global: u32,
+    /// Blocks, branches, returns and calls.
+    pub control_flow: u32,
+    /// Integer comparisons, e.g. `i32.eqz`, `i64.lt_u`.
+    pub integer_comparison: u32,
+    /// Float comparisons.
+    pub float_comparison: u32,
+    /// Float arithmetic.
+    pub float: u32,
+    /// Integer wrapping and extension.
+    pub conversion: u32,
+    /// Conversions between integers and floats.
+    pub float_conversion: u32,
+    /// Reinterpretation of bits, e.g. `f64.reinterpret_i64`.
+    pub reinterpretation: u32,
+    pub unreachable: u32,
+    pub nop: u32,
+    /// `memory.size`.
+    pub current_memory: u32,
+    /// `memory.grow`, charged on top of `grow_mem_cost` per page.
+    pub grow_memory: u32,
+}
+
+#[cfg(feature = "protocol_feature_wasm_op_cost_table")]
+impl Default for WasmOpCostTable {
+    fn default() -> Self {
+        Self {
+            bit: 1,
+            add: 1,
+            mul: 1,
+            div: 1,
+            load: 1,
+            store: 1,
+            constant: 1,
+            float_const: 1,
+            local: 1,
+            global: 1,
+            control_flow: 1,
+            integer_comparison: 1,
+            float_comparison: 1,
+            float: 1,
+            conversion: 1,
+            float_conversion: 1,
+            reinterpretation: 1,
+            unreachable: 1,
+            nop: 1,
+            current_memory: 1,
+            grow_memory: 1,
+        }
+    }
+}
+
+#[cfg(feature = "protocol_feature_wasm_op_cost_table")]
+impl WasmOpCostTable {
+    /// Whether every instruction costs a single unit, i.e. the table doesn't change metering.
+    pub fn is_flat(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 /// Our original code for limiting WASM stack was buggy. We fixed that, but we
 /// still have to use old (`V0`) limiter for old protocol versions.
 ///
@@ -148,6 +248,10 @@ impl VMConfig {
             ext_costs: ExtCostsConfig::test(),
             grow_mem_cost: 1,
             regular_op_cost: (SAFETY_MULTIPLIER as u32) * 1285457,
+            #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+            bulk_memory_page_cost: 8 * 1024,
+            #[cfg(feature = "protocol_feature_wasm_op_cost_table")]
+            op_cost_table: WasmOpCostTable::default(),
             limit_config: VMLimitConfig::test(),
         }
     }
@@ -165,6 +269,10 @@ impl VMConfig {
             ext_costs: ExtCostsConfig::free(),
             grow_mem_cost: 0,
             regular_op_cost: 0,
+            #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+            bulk_memory_page_cost: 0,
+            #[cfg(feature = "protocol_feature_wasm_op_cost_table")]
+            op_cost_table: WasmOpCostTable::default(),
             // We shouldn't have any costs in the limit config.
             limit_config: VMLimitConfig { max_gas_burnt: u64::MAX, ..VMLimitConfig::test() },
         }
@@ -180,6 +288,8 @@ impl VMLimitConfig {
             // For experimentation try `test_stack_overflow`.
             max_stack_height: 16 * 1024, // 16Kib of stack.
             stack_limiter_version: StackLimiterVersion::V1,
+            #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+            wasm_bulk_memory: false,
             initial_memory_pages: 2u32.pow(10), // 64Mib of memory.
             max_memory_pages: 2u32.pow(11),     // 128Mib of memory.
 
@@ -390,6 +500,82 @@ pub struct ExtCostsConfig {
     /// Cost for pairing check per byte
     #[cfg(feature = "protocol_feature_alt_bn128")]
     pub alt_bn128_pairing_check_byte: Gas,
+
+    // ###########
+    // # Ed25519 #
+    // ###########
+    /// Cost of verifying a single ed25519 signature
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    #[serde(default)]
+    pub ed25519_verify_base: Gas,
+    /// Cost of verifying an ed25519 signature per byte of the message
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    #[serde(default)]
+    pub ed25519_verify_byte: Gas,
+    /// Cost of verifying a single secp256k1 signature
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    #[serde(default)]
+    pub secp256k1_verify_base: Gas,
+
+    // #############
+    // # BLS12-381 #
+    // #############
+    /// Base cost of sum of signed G1 points
+    #[cfg(feature = "protocol_feature_bls12381")]
+    #[serde(default)]
+    pub bls12381_p1_sum_base: Gas,
+    /// Cost of sum of signed G1 points per point
+    #[cfg(feature = "protocol_feature_bls12381")]
+    #[serde(default)]
+    pub bls12381_p1_sum_element: Gas,
+    /// Base cost of sum of signed G2 points
+    #[cfg(feature = "protocol_feature_bls12381")]
+    #[serde(default)]
+    pub bls12381_p2_sum_base: Gas,
+    /// Cost of sum of signed G2 points per point
+    #[cfg(feature = "protocol_feature_bls12381")]
+    #[serde(default)]
+    pub bls12381_p2_sum_element: Gas,
+    /// Base cost of G1 multiexponentiation
+    #[cfg(feature = "protocol_feature_bls12381")]
+    #[serde(default)]
+    pub bls12381_g1_multiexp_base: Gas,
+    /// Cost of G1 multiexponentiation per (point, scalar) pair
+    #[cfg(feature = "protocol_feature_bls12381")]
+    #[serde(default)]
+    pub bls12381_g1_multiexp_element: Gas,
+    /// Base cost of G2 multiexponentiation
+    #[cfg(feature = "protocol_feature_bls12381")]
+    #[serde(default)]
+    pub bls12381_g2_multiexp_base: Gas,
+    /// Cost of G2 multiexponentiation per (point, scalar) pair
+    #[cfg(feature = "protocol_feature_bls12381")]
+    #[serde(default)]
+    pub bls12381_g2_multiexp_element: Gas,
+    /// Base cost of mapping field elements to G1
+    #[cfg(feature = "protocol_feature_bls12381")]
+    #[serde(default)]
+    pub bls12381_map_fp_to_g1_base: Gas,
+    /// Cost of mapping field elements to G1 per element
+    #[cfg(feature = "protocol_feature_bls12381")]
+    #[serde(default)]
+    pub bls12381_map_fp_to_g1_element: Gas,
+    /// Base cost of mapping field elements to G2
+    #[cfg(feature = "protocol_feature_bls12381")]
+    #[serde(default)]
+    pub bls12381_map_fp2_to_g2_base: Gas,
+    /// Cost of mapping field elements to G2 per element
+    #[cfg(feature = "protocol_feature_bls12381")]
+    #[serde(default)]
+    pub bls12381_map_fp2_to_g2_element: Gas,
+    /// Base cost of pairing check
+    #[cfg(feature = "protocol_feature_bls12381")]
+    #[serde(default)]
+    pub bls12381_pairing_base: Gas,
+    /// Cost of pairing check per (G1, G2) pair
+    #[cfg(feature = "protocol_feature_bls12381")]
+    #[serde(default)]
+    pub bls12381_pairing_element: Gas,
 }
 
 // We multiply the actual computed costs by the fixed factor to ensure we
@@ -466,6 +652,40 @@ impl ExtCostsConfig {
             alt_bn128_g1_sum_base: SAFETY_MULTIPLIER * 1058438125,
             #[cfg(feature = "protocol_feature_alt_bn128")]
             alt_bn128_g1_sum_byte: SAFETY_MULTIPLIER * 25406181,
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            ed25519_verify_base: SAFETY_MULTIPLIER * 70000000000,
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            ed25519_verify_byte: SAFETY_MULTIPLIER * 3000000,
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            secp256k1_verify_base: SAFETY_MULTIPLIER * 90000000000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_sum_base: SAFETY_MULTIPLIER * 5500000000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_sum_element: SAFETY_MULTIPLIER * 2000000000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_sum_base: SAFETY_MULTIPLIER * 6200000000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_sum_element: SAFETY_MULTIPLIER * 5000000000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g1_multiexp_base: SAFETY_MULTIPLIER * 5500000000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g1_multiexp_element: SAFETY_MULTIPLIER * 310000000000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g2_multiexp_base: SAFETY_MULTIPLIER * 6200000000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g2_multiexp_element: SAFETY_MULTIPLIER * 665000000000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp_to_g1_base: SAFETY_MULTIPLIER * 500000000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp_to_g1_element: SAFETY_MULTIPLIER * 84000000000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp2_to_g2_base: SAFETY_MULTIPLIER * 500000000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp2_to_g2_element: SAFETY_MULTIPLIER * 300000000000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_pairing_base: SAFETY_MULTIPLIER * 710000000000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_pairing_element: SAFETY_MULTIPLIER * 710000000000,
         }
     }
 
@@ -537,6 +757,40 @@ impl ExtCostsConfig {
             alt_bn128_g1_sum_base: 0,
             #[cfg(feature = "protocol_feature_alt_bn128")]
             alt_bn128_g1_sum_byte: 0,
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            ed25519_verify_base: 0,
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            ed25519_verify_byte: 0,
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            secp256k1_verify_base: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_sum_base: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_sum_element: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_sum_base: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_sum_element: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g1_multiexp_base: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g1_multiexp_element: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g2_multiexp_base: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g2_multiexp_element: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp_to_g1_base: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp_to_g1_element: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp2_to_g2_base: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp2_to_g2_element: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_pairing_base: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_pairing_element: 0,
         }
     }
 }
@@ -611,6 +865,40 @@ pub enum ExtCosts {
     alt_bn128_g1_sum_base,
     #[cfg(feature = "protocol_feature_alt_bn128")]
     alt_bn128_g1_sum_byte,
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    ed25519_verify_base,
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    ed25519_verify_byte,
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    secp256k1_verify_base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_p1_sum_base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_p1_sum_element,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_p2_sum_base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_p2_sum_element,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_g1_multiexp_base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_g1_multiexp_element,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_g2_multiexp_base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_g2_multiexp_element,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_map_fp_to_g1_base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_map_fp_to_g1_element,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_map_fp2_to_g2_base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_map_fp2_to_g2_element,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_pairing_base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_pairing_element,
 }
 
 // Type of an action, used in fees logic.
@@ -699,6 +987,132 @@ impl ExtCosts {
             alt_bn128_g1_sum_base => config.alt_bn128_g1_sum_base,
             #[cfg(feature = "protocol_feature_alt_bn128")]
             alt_bn128_g1_sum_byte => config.alt_bn128_g1_sum_byte,
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            ed25519_verify_base => config.ed25519_verify_base,
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            ed25519_verify_byte => config.ed25519_verify_byte,
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            secp256k1_verify_base => config.secp256k1_verify_base,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_sum_base => config.bls12381_p1_sum_base,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_sum_element => config.bls12381_p1_sum_element,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_sum_base => config.bls12381_p2_sum_base,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_sum_element => config.bls12381_p2_sum_element,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g1_multiexp_base => config.bls12381_g1_multiexp_base,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g1_multiexp_element => config.bls12381_g1_multiexp_element,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g2_multiexp_base => config.bls12381_g2_multiexp_base,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g2_multiexp_element => config.bls12381_g2_multiexp_element,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp_to_g1_base => config.bls12381_map_fp_to_g1_base,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp_to_g1_element => config.bls12381_map_fp_to_g1_element,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp2_to_g2_base => config.bls12381_map_fp2_to_g2_base,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp2_to_g2_element => config.bls12381_map_fp2_to_g2_element,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_pairing_base => config.bls12381_pairing_base,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_pairing_element => config.bls12381_pairing_element,
+        }
+    }
+
+    /// Name of the group of host functions the cost is charged by, used to aggregate gas
+    /// profiles, e.g. `STORAGE` for all `storage_*` costs.
+    pub fn category(self) -> &'static str {
+        use ExtCosts::*;
+        match self {
+            base => "BASE",
+            contract_compile_base | contract_compile_bytes => "CONTRACT_LOADING",
+            read_memory_base | read_memory_byte | write_memory_base | write_memory_byte => "MEMORY",
+            read_register_base | read_register_byte | write_register_base | write_register_byte => {
+                "REGISTERS"
+            }
+            utf8_decoding_base | utf8_decoding_byte | utf16_decoding_base | utf16_decoding_byte => {
+                "UTF_DECODING"
+            }
+            sha256_base | sha256_byte | keccak256_base | keccak256_byte | keccak512_base
+            | keccak512_byte | ripemd160_base | ripemd160_block => "HASHING",
+            ecrecover_base => "CRYPTOGRAPHY",
+            log_base | log_byte => "LOGS",
+            storage_write_base
+            | storage_write_key_byte
+            | storage_write_value_byte
+            | storage_write_evicted_byte
+            | storage_read_base
+            | storage_read_key_byte
+            | storage_read_value_byte
+            | storage_remove_base
+            | storage_remove_key_byte
+            | storage_remove_ret_value_byte
+            | storage_has_key_base
+            | storage_has_key_byte
+            | storage_iter_create_prefix_base
+            | storage_iter_create_prefix_byte
+            | storage_iter_create_range_base
+            | storage_iter_create_from_byte
+            | storage_iter_create_to_byte
+            | storage_iter_next_base
+            | storage_iter_next_key_byte
+            | storage_iter_next_value_byte
+            | touching_trie_node => "STORAGE",
+            promise_and_base | promise_and_per_promise | promise_return => "PROMISES",
+            validator_stake_base | validator_total_stake_base => "VALIDATORS",
+            #[cfg(feature = "protocol_feature_alt_bn128")]
+            alt_bn128_g1_multiexp_base => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_alt_bn128")]
+            alt_bn128_g1_multiexp_byte => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_alt_bn128")]
+            alt_bn128_g1_multiexp_sublinear => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_alt_bn128")]
+            alt_bn128_pairing_check_base => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_alt_bn128")]
+            alt_bn128_pairing_check_byte => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_alt_bn128")]
+            alt_bn128_g1_sum_base => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_alt_bn128")]
+            alt_bn128_g1_sum_byte => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            ed25519_verify_base => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            ed25519_verify_byte => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            secp256k1_verify_base => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_sum_base => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_sum_element => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_sum_base => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_sum_element => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g1_multiexp_base => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g1_multiexp_element => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g2_multiexp_base => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g2_multiexp_element => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp_to_g1_base => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp_to_g1_element => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp2_to_g2_base => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp2_to_g2_element => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_pairing_base => "CRYPTOGRAPHY",
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_pairing_element => "CRYPTOGRAPHY",
         }
     }
 }
@@ -9,4 +9,5 @@ pub mod logging;
 pub mod profile;
 pub mod runtime;
 pub mod serialize;
+pub mod trace;
 pub mod types;
@@ -259,8 +259,53 @@ impl Cost {
         Cost::ExtCost { ext_cost_kind: ExtCosts::alt_bn128_g1_sum_base },
         #[cfg(feature = "protocol_feature_alt_bn128")]
         Cost::ExtCost { ext_cost_kind: ExtCosts::alt_bn128_g1_sum_byte },
+        #[cfg(feature = "protocol_feature_ed25519_verify")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::ed25519_verify_base },
+        #[cfg(feature = "protocol_feature_ed25519_verify")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::ed25519_verify_byte },
+        #[cfg(feature = "protocol_feature_ed25519_verify")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::secp256k1_verify_base },
+        #[cfg(feature = "protocol_feature_bls12381")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_p1_sum_base },
+        #[cfg(feature = "protocol_feature_bls12381")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_p1_sum_element },
+        #[cfg(feature = "protocol_feature_bls12381")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_p2_sum_base },
+        #[cfg(feature = "protocol_feature_bls12381")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_p2_sum_element },
+        #[cfg(feature = "protocol_feature_bls12381")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_g1_multiexp_base },
+        #[cfg(feature = "protocol_feature_bls12381")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_g1_multiexp_element },
+        #[cfg(feature = "protocol_feature_bls12381")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_g2_multiexp_base },
+        #[cfg(feature = "protocol_feature_bls12381")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_g2_multiexp_element },
+        #[cfg(feature = "protocol_feature_bls12381")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_map_fp_to_g1_base },
+        #[cfg(feature = "protocol_feature_bls12381")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_map_fp_to_g1_element },
+        #[cfg(feature = "protocol_feature_bls12381")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_map_fp2_to_g2_base },
+        #[cfg(feature = "protocol_feature_bls12381")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_map_fp2_to_g2_element },
+        #[cfg(feature = "protocol_feature_bls12381")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_pairing_base },
+        #[cfg(feature = "protocol_feature_bls12381")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_pairing_element },
     ];
 
+    /// Index of the first signature verification cost. Indices must stay below `DataArray::LEN`, so costs behind
+    /// protocol features are placed right after the ones compiled in before them.
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    const ED25519_START: usize = if cfg!(feature = "protocol_feature_alt_bn128") { 70 } else { 63 };
+
+    /// Index of the first BLS12-381 cost.
+    #[cfg(feature = "protocol_feature_bls12381")]
+    const BLS12381_START: usize = 63
+        + if cfg!(feature = "protocol_feature_alt_bn128") { 7 } else { 0 }
+        + if cfg!(feature = "protocol_feature_ed25519_verify") { 3 } else { 0 };
+
     pub fn index(self) -> usize {
         match self {
             Cost::ActionCost { action_cost_kind: ActionCosts::create_account } => 0,
@@ -340,6 +385,70 @@ impl Cost {
             Cost::ExtCost { ext_cost_kind: ExtCosts::alt_bn128_g1_sum_base } => 68,
             #[cfg(feature = "protocol_feature_alt_bn128")]
             Cost::ExtCost { ext_cost_kind: ExtCosts::alt_bn128_g1_sum_byte } => 69,
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::ed25519_verify_base } => Self::ED25519_START,
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::ed25519_verify_byte } => {
+                Self::ED25519_START + 1
+            }
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::secp256k1_verify_base } => {
+                Self::ED25519_START + 2
+            }
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_p1_sum_base } => Self::BLS12381_START,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_p1_sum_element } => {
+                Self::BLS12381_START + 1
+            }
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_p2_sum_base } => {
+                Self::BLS12381_START + 2
+            }
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_p2_sum_element } => {
+                Self::BLS12381_START + 3
+            }
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_g1_multiexp_base } => {
+                Self::BLS12381_START + 4
+            }
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_g1_multiexp_element } => {
+                Self::BLS12381_START + 5
+            }
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_g2_multiexp_base } => {
+                Self::BLS12381_START + 6
+            }
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_g2_multiexp_element } => {
+                Self::BLS12381_START + 7
+            }
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_map_fp_to_g1_base } => {
+                Self::BLS12381_START + 8
+            }
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_map_fp_to_g1_element } => {
+                Self::BLS12381_START + 9
+            }
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_map_fp2_to_g2_base } => {
+                Self::BLS12381_START + 10
+            }
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_map_fp2_to_g2_element } => {
+                Self::BLS12381_START + 11
+            }
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_pairing_base } => {
+                Self::BLS12381_START + 12
+            }
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_pairing_element } => {
+                Self::BLS12381_START + 13
+            }
         }
     }
 }
@@ -384,6 +493,15 @@ mod test {
         assert_eq!(res, u64::MAX);
     }
 
+    #[test]
+    fn test_cost_indices_are_unique() {
+        let mut seen = vec![false; DataArray::LEN];
+        for cost in Cost::ALL {
+            assert!(!seen[cost.index()], "{:?} shares its index with another cost", cost);
+            seen[cost.index()] = true;
+        }
+    }
+
     #[test]
     fn test_merge() {
         let mut profile_data = ProfileData::new();
@@ -12,7 +12,7 @@ use crate::types::Gas;
 /// by the receiver).
 /// NOTE: `send_sir` or `send_not_sir` fees are usually burned when the item is being created.
 /// And `execution` fee is burned when the item is being executed.
-#[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq, Eq, Default)]
 pub struct Fee {
     /// Fee for sending an object from the sender to itself, guaranteeing that it does not leave
     /// the shard.
@@ -113,6 +113,11 @@ pub struct ActionCreationConfig {
 
     /// Base cost of deleting an account.
     pub delete_account_cost: Fee,
+
+    /// Base cost of a `Delegate` action, on top of the cost of sending the delegated actions.
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    #[serde(default)]
+    pub delegate_cost: Fee,
 }
 
 /// Describes the cost of creating an access key.
@@ -219,6 +224,12 @@ impl RuntimeFeesConfig {
                     send_not_sir: 147489000000,
                     execution: 147489000000,
                 },
+                #[cfg(feature = "protocol_feature_delegate_action")]
+                delegate_cost: Fee {
+                    send_sir: 200000000000,
+                    send_not_sir: 200000000000,
+                    execution: 200000000000,
+                },
             },
             storage_usage_config: StorageUsageConfig {
                 // See Account in core/primitives/src/account.rs for the data structure.
@@ -253,6 +264,8 @@ impl RuntimeFeesConfig {
                     function_call_cost_per_byte: free.clone(),
                 },
                 delete_key_cost: free.clone(),
+                #[cfg(feature = "protocol_feature_delegate_action")]
+                delegate_cost: free.clone(),
                 delete_account_cost: free,
             },
             storage_usage_config: StorageUsageConfig {
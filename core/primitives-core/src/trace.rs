@@ -0,0 +1,34 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::types::Gas;
+
+/// Maximum number of host function calls recorded for a single function call. Calls made after
+/// the limit is reached are not recorded.
+pub const MAX_TRACED_HOST_FUNCTION_CALLS: usize = 10_000;
+
+/// A single host function invocation made by a contract, recorded when host function tracing is
+/// enabled for the execution.
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct HostFunctionCall {
+    /// Name of the host function, e.g. `storage_write`.
+    pub name: String,
+    /// Arguments passed by the contract as `(name, value)` pairs, in the order of the signature.
+    pub args: Vec<(String, u64)>,
+    /// Gas burnt by the execution right before the call.
+    pub burnt_gas_before: Gas,
+    /// Gas burnt by the execution right after the call.
+    pub burnt_gas_after: Gas,
+    /// Storage keys read, written, removed or checked by the call.
+    pub storage_keys: Vec<Vec<u8>>,
+    /// Registers written by the call as `(register_id, length)` pairs.
+    pub registers_written: Vec<(u64, u64)>,
+    /// Error returned by the host function, if any.
+    pub error: Option<String>,
+}
+
+impl HostFunctionCall {
+    /// Gas burnt by the call itself.
+    pub fn burnt_gas(&self) -> Gas {
+        self.burnt_gas_after.saturating_sub(self.burnt_gas_before)
+    }
+}
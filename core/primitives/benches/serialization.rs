@@ -32,6 +32,8 @@ fn create_transaction() -> SignedTransaction {
             receiver_id: "1231231232131".parse().unwrap(),
             block_hash: Default::default(),
             actions,
+            valid_from_height: None,
+            valid_until_height: None,
         },
     )
 }
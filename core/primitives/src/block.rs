@@ -101,6 +101,7 @@ pub fn genesis_chunks(
                 0,
                 initial_gas_limit,
                 0,
+                None,
                 CryptoHash::default(),
                 vec![],
                 vec![],
@@ -0,0 +1,146 @@
+//! Shard congestion control.
+//!
+//! Every chunk header advertises the total gas of the receipts waiting in the delayed receipts
+//! queue of its shard. A shard whose queue exceeds [`CONGESTED_DELAYED_RECEIPTS_GAS`] is
+//! considered congested: other shards stop sending it more than
+//! [`MAX_OUTGOING_GAS_TO_CONGESTED_SHARD`] of receipts per chunk and chunk producers stop
+//! accepting transactions targeting it, until its queue drains.
+use std::collections::HashMap;
+
+use crate::shard_layout::{account_id_to_shard_id, ShardLayout};
+use crate::sharding::ShardChunkHeader;
+use crate::types::{AccountId, Gas, ShardId};
+
+/// Gas of the delayed receipts queue above which a shard is considered congested.
+/// Roughly ten chunks worth of work at the default gas limit.
+pub const CONGESTED_DELAYED_RECEIPTS_GAS: Gas = 10_000_000_000_000_000;
+
+/// Maximum gas of receipts a chunk may send to a single congested shard. Once a chunk reaches it,
+/// the remaining receipts to that shard wait in the delayed receipts queue of the sending shard,
+/// while receipts to other shards are still sent.
+pub const MAX_OUTGOING_GAS_TO_CONGESTED_SHARD: Gas = 300_000_000_000_000;
+
+/// Congestion of all shards as advertised by the chunk headers of a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockCongestionInfo {
+    shard_layout: ShardLayout,
+    delayed_receipts_gas: HashMap<ShardId, Gas>,
+}
+
+impl Default for BlockCongestionInfo {
+    fn default() -> Self {
+        Self { shard_layout: ShardLayout::v0_single_shard(), delayed_receipts_gas: HashMap::new() }
+    }
+}
+
+impl BlockCongestionInfo {
+    pub fn new(shard_layout: ShardLayout, delayed_receipts_gas: HashMap<ShardId, Gas>) -> Self {
+        Self { shard_layout, delayed_receipts_gas }
+    }
+
+    /// Collects the delayed receipts gas advertised by the given chunk headers. Headers of older
+    /// versions don't advertise it and are treated as not congested.
+    pub fn from_chunk_headers<'a>(
+        shard_layout: ShardLayout,
+        chunk_headers: impl IntoIterator<Item = &'a ShardChunkHeader>,
+    ) -> Self {
+        let delayed_receipts_gas = chunk_headers
+            .into_iter()
+            .filter_map(|header| Some((header.shard_id(), header.delayed_receipts_gas()?)))
+            .collect();
+        Self::new(shard_layout, delayed_receipts_gas)
+    }
+
+    pub fn shard_layout(&self) -> &ShardLayout {
+        &self.shard_layout
+    }
+
+    /// Gas of the delayed receipts queue advertised by the given shard.
+    pub fn delayed_receipts_gas(&self, shard_id: ShardId) -> Gas {
+        self.delayed_receipts_gas.get(&shard_id).copied().unwrap_or_default()
+    }
+
+    pub fn is_shard_congested(&self, shard_id: ShardId) -> bool {
+        self.delayed_receipts_gas(shard_id) > CONGESTED_DELAYED_RECEIPTS_GAS
+    }
+
+    /// Returns the shard of the given account if it is congested.
+    pub fn congested_shard(&self, account_id: &AccountId) -> Option<ShardId> {
+        let shard_id = account_id_to_shard_id(account_id, &self.shard_layout);
+        if self.is_shard_congested(shard_id) {
+            Some(shard_id)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_congested_shard() {
+        let shard_layout = ShardLayout::v0(4, 0);
+        let account_id: AccountId = "alice.near".parse().unwrap();
+        let shard_id = account_id_to_shard_id(&account_id, &shard_layout);
+
+        let congestion_info = BlockCongestionInfo::new(
+            shard_layout.clone(),
+            [(shard_id, CONGESTED_DELAYED_RECEIPTS_GAS)].into_iter().collect(),
+        );
+        assert_eq!(congestion_info.congested_shard(&account_id), None);
+
+        let congestion_info = BlockCongestionInfo::new(
+            shard_layout,
+            [(shard_id, CONGESTED_DELAYED_RECEIPTS_GAS + 1)].into_iter().collect(),
+        );
+        assert_eq!(congestion_info.congested_shard(&account_id), Some(shard_id));
+        assert!(!BlockCongestionInfo::default().is_shard_congested(shard_id));
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_congestion_control")]
+    fn test_from_chunk_headers() {
+        use crate::hash::CryptoHash;
+        use crate::sharding::ShardChunkHeaderV3;
+        use crate::validator_signer::EmptyValidatorSigner;
+        use crate::views::ChunkHeaderView;
+        use borsh::{BorshDeserialize, BorshSerialize};
+
+        let chunk_header = |shard_id: ShardId, delayed_receipts_gas: Option<Gas>| {
+            ShardChunkHeader::V3(ShardChunkHeaderV3::new(
+                CryptoHash::default(),
+                CryptoHash::default(),
+                CryptoHash::default(),
+                CryptoHash::default(),
+                0,
+                1,
+                shard_id,
+                0,
+                1000,
+                0,
+                CryptoHash::default(),
+                CryptoHash::default(),
+                vec![],
+                delayed_receipts_gas,
+                &EmptyValidatorSigner::default(),
+            ))
+        };
+        let congested = chunk_header(0, Some(CONGESTED_DELAYED_RECEIPTS_GAS + 1));
+        let legacy = chunk_header(1, None);
+
+        let congestion_info =
+            BlockCongestionInfo::from_chunk_headers(ShardLayout::v0(2, 0), [&congested, &legacy]);
+        assert!(congestion_info.is_shard_congested(0));
+        assert!(!congestion_info.is_shard_congested(1));
+
+        for header in [congested, legacy] {
+            let decoded = ShardChunkHeader::try_from_slice(&header.try_to_vec().unwrap()).unwrap();
+            assert_eq!(decoded, header);
+            let view = ChunkHeaderView::from(header.clone());
+            assert_eq!(view.delayed_receipts_gas, header.delayed_receipts_gas());
+            assert_eq!(ShardChunkHeader::from(view), header);
+        }
+    }
+}
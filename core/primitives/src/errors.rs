@@ -1,5 +1,6 @@
 use crate::serialize::u128_dec_format;
-use crate::types::{AccountId, Balance, EpochId, Gas, Nonce};
+use crate::types::{AccountId, Balance, BlockHeight, EpochId, Gas, Nonce, ShardId};
+use crate::version::ProtocolVersion;
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_crypto::PublicKey;
 use serde::{Deserialize, Serialize};
@@ -140,6 +141,15 @@ pub enum InvalidTxError {
     ActionsValidation(ActionsValidationError),
     /// The size of serialized transaction exceeded the limit.
     TransactionSizeExceeded { size: u64, limit: u64 },
+    /// The receiver of the transaction belongs to a shard whose delayed receipts queue is too
+    /// long to accept new transactions.
+    ShardCongested { shard_id: ShardId },
+    /// The transaction's validity window starts after it ends.
+    InvalidValidityWindow { valid_from_height: BlockHeight, valid_until_height: BlockHeight },
+    /// The transaction can't be included at this height yet.
+    NotValidYet { valid_from_height: BlockHeight, height: BlockHeight },
+    /// The transaction can't be included at this height anymore.
+    ValidityWindowExpired { valid_until_height: BlockHeight, height: BlockHeight },
 }
 
 impl std::error::Error for InvalidTxError {}
@@ -168,6 +178,12 @@ pub enum InvalidAccessKeyError {
     },
     /// Having a deposit with a function call action is not allowed with a function call access key.
     DepositWithFunctionCall,
+    /// The access key has expired at the given block height.
+    AccessKeyExpired {
+        account_id: AccountId,
+        public_key: PublicKey,
+        expiration_block_height: BlockHeight,
+    },
 }
 
 /// Describes the error for validating a list of actions.
@@ -200,6 +216,15 @@ pub enum ActionsValidationError {
     UnsuitableStakingKey { public_key: PublicKey },
     /// The attached amount of gas in a FunctionCall action has to be a positive number.
     FunctionCallZeroAttachedGas,
+    /// There should be the only one DelegateAction
+    DelegateActionMustBeOnlyOne,
+    /// The transaction includes a feature that the current protocol version
+    /// does not support.
+    ///
+    /// Note: we stringify the protocol feature name instead of using
+    /// `ProtocolFeature` here because we don't want to leak the internals of
+    /// that type into observable borsh serialization.
+    UnsupportedProtocolFeature { protocol_feature: String, version: ProtocolVersion },
 }
 
 /// Describes the error for validating a receipt.
@@ -317,6 +342,16 @@ impl Display for ActionsValidationError {
                 f,
                 "The attached amount of gas in a FunctionCall action has to be a positive number",
             ),
+            ActionsValidationError::DelegateActionMustBeOnlyOne => {
+                write!(f, "The actions can contain the only one DelegateAction")
+            }
+            ActionsValidationError::UnsupportedProtocolFeature { protocol_feature, version } => {
+                write!(
+                    f,
+                    "Transaction requires protocol feature {} / version {} which is not supported by the current protocol version",
+                    protocol_feature, version,
+                )
+            }
         }
     }
 }
@@ -441,6 +476,32 @@ pub enum ActionErrorKind {
     OnlyImplicitAccountCreationAllowed { account_id: AccountId },
     /// Delete account whose state is large is temporarily banned.
     DeleteAccountWithLargeState { account_id: AccountId },
+    /// Signature does not match the provided actions and given signer public key.
+    DelegateActionInvalidSignature,
+    /// Receiver of the transaction doesn't match Sender of the delegate action
+    DelegateActionSenderDoesNotMatchTxReceiver { sender_id: AccountId, receiver_id: AccountId },
+    /// Delegate action has expired. `max_block_height` is less than actual block height.
+    DelegateActionExpired,
+    /// The given public key doesn't exist for Sender account
+    DelegateActionAccessKeyError(InvalidAccessKeyError),
+    /// DelegateAction nonce must be greater sender[public_key].nonce
+    DelegateActionInvalidNonce { delegate_nonce: Nonce, ak_nonce: Nonce },
+    /// DelegateAction nonce is larger than the upper bound given by the block height
+    DelegateActionNonceTooLarge { delegate_nonce: Nonce, upper_bound: Nonce },
+    /// There is no global contract code with the given hash in the shard of the account
+    GlobalContractDoesNotExist { code_hash: CryptoHash },
+    /// The account doesn't have enough balance to pay for storing global contract code
+    LackBalanceForGlobalContract {
+        account_id: AccountId,
+        #[serde(with = "u128_dec_format")]
+        amount: Balance,
+    },
+    /// Sender of the delegate action doesn't have enough balance to pay the deposits of its actions
+    DelegateActionLackBalance {
+        sender_id: AccountId,
+        #[serde(with = "u128_dec_format")]
+        amount: Balance,
+    },
 }
 
 impl From<ActionErrorKind> for ActionError {
@@ -502,6 +563,30 @@ impl Display for InvalidTxError {
             InvalidTxError::TransactionSizeExceeded { size, limit } => {
                 write!(f, "Size of serialized transaction {} exceeded the limit {}", size, limit)
             }
+            InvalidTxError::ShardCongested { shard_id } => {
+                write!(f, "Shard {} is congested and doesn't accept new transactions", shard_id)
+            }
+            InvalidTxError::InvalidValidityWindow { valid_from_height, valid_until_height } => {
+                write!(
+                    f,
+                    "Transaction is valid from height {} which is after its last valid height {}",
+                    valid_from_height, valid_until_height
+                )
+            }
+            InvalidTxError::NotValidYet { valid_from_height, height } => {
+                write!(
+                    f,
+                    "Transaction is valid from height {} and can't be included at height {}",
+                    valid_from_height, height
+                )
+            }
+            InvalidTxError::ValidityWindowExpired { valid_until_height, height } => {
+                write!(
+                    f,
+                    "Transaction is valid until height {} and can't be included at height {}",
+                    valid_until_height, height
+                )
+            }
         }
     }
 }
@@ -546,6 +631,15 @@ impl Display for InvalidAccessKeyError {
             InvalidAccessKeyError::DepositWithFunctionCall => {
                 write!(f, "Having a deposit with a function call action is not allowed with a function call access key.")
             }
+            InvalidAccessKeyError::AccessKeyExpired {
+                account_id,
+                public_key,
+                expiration_block_height,
+            } => write!(
+                f,
+                "Access Key {:?}:{} has expired at block height {}",
+                account_id, public_key, expiration_block_height
+            ),
         }
     }
 }
@@ -751,6 +845,15 @@ impl Display for ActionErrorKind {
             ActionErrorKind::InsufficientStake { account_id, stake, minimum_stake } => write!(f, "Account {} tries to stake {} but minimum required stake is {}", account_id, stake, minimum_stake),
             ActionErrorKind::OnlyImplicitAccountCreationAllowed { account_id } => write!(f, "CreateAccount action is called on hex-characters account of length 64 {}", account_id),
             ActionErrorKind::DeleteAccountWithLargeState { account_id } => write!(f, "The state of account {} is too large and therefore cannot be deleted", account_id),
+            ActionErrorKind::DelegateActionInvalidSignature => write!(f, "DelegateAction is not signed with the given public key"),
+            ActionErrorKind::DelegateActionSenderDoesNotMatchTxReceiver { sender_id, receiver_id } => write!(f, "Transaction receiver {} doesn't match DelegateAction sender {}", receiver_id, sender_id),
+            ActionErrorKind::DelegateActionExpired => write!(f, "DelegateAction has expired"),
+            ActionErrorKind::DelegateActionAccessKeyError(access_key_error) => Display::fmt(&access_key_error, f),
+            ActionErrorKind::DelegateActionInvalidNonce { delegate_nonce, ak_nonce } => write!(f, "DelegateAction nonce {} must be larger than nonce of the used access key {}", delegate_nonce, ak_nonce),
+            ActionErrorKind::DelegateActionNonceTooLarge { delegate_nonce, upper_bound } => write!(f, "DelegateAction nonce {} must be smaller than the access key nonce upper bound {}", delegate_nonce, upper_bound),
+            ActionErrorKind::GlobalContractDoesNotExist { code_hash } => write!(f, "Global contract code with hash {} does not exist in the shard", code_hash),
+            ActionErrorKind::LackBalanceForGlobalContract { account_id, amount } => write!(f, "Account {} needs {} to pay for storing the global contract code", account_id, amount),
+            ActionErrorKind::DelegateActionLackBalance { sender_id, amount } => write!(f, "DelegateAction sender {} needs {} to pay the deposits of its actions", sender_id, amount),
         }
     }
 }
@@ -5,6 +5,7 @@ pub use near_primitives_core::account;
 pub mod block;
 pub mod block_header;
 pub mod challenge;
+pub mod congestion_control;
 pub use near_primitives_core::config;
 pub use near_primitives_core::contract;
 pub mod epoch_manager;
@@ -26,6 +27,7 @@ pub mod syncing;
 pub mod telemetry;
 pub mod test_utils;
 pub mod time;
+pub use near_primitives_core::trace;
 pub mod transaction;
 pub mod trie_key;
 pub mod types;
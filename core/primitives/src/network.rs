@@ -1,5 +1,6 @@
 use std::fmt;
 use std::hash::Hash;
+use std::net::SocketAddr;
 use std::sync::Arc;
 
 use borsh::{BorshDeserialize, BorshSerialize};
@@ -109,3 +110,50 @@ impl AnnounceAccount {
         AnnounceAccount::build_header_hash(&self.account_id, &self.peer_id, &self.epoch_id)
     }
 }
+
+/// Addresses on which a validator accepts direct connections from other validators.
+/// Signed with the validator key, so it can be relayed by other peers.
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug)]
+pub struct AnnounceAccountAddrs {
+    /// AccountId of the validator.
+    pub account_id: AccountId,
+    /// PeerId of the node run by the validator.
+    pub peer_id: PeerId,
+    /// This announcement is only valid for this `epoch`.
+    pub epoch_id: EpochId,
+    /// Public addresses of the node.
+    pub addrs: Vec<SocketAddr>,
+    /// Signature using AccountId associated secret key.
+    pub signature: Signature,
+}
+
+#[cfg(feature = "deepsize_feature")]
+impl deepsize::DeepSizeOf for AnnounceAccountAddrs {
+    fn deep_size_of_children(&self, context: &mut deepsize::Context) -> usize {
+        self.account_id.deep_size_of_children(context)
+            + self.peer_id.deep_size_of_children(context)
+            + self.epoch_id.deep_size_of_children(context)
+            + self.addrs.capacity() * std::mem::size_of::<SocketAddr>()
+            + self.signature.deep_size_of_children(context)
+    }
+}
+
+impl AnnounceAccountAddrs {
+    pub fn build_header_hash(
+        account_id: &AccountId,
+        peer_id: &PeerId,
+        epoch_id: &EpochId,
+        addrs: &[SocketAddr],
+    ) -> CryptoHash {
+        CryptoHash::hash_borsh(&(account_id, peer_id, epoch_id, addrs))
+    }
+
+    pub fn hash(&self) -> CryptoHash {
+        AnnounceAccountAddrs::build_header_hash(
+            &self.account_id,
+            &self.peer_id,
+            &self.epoch_id,
+            &self.addrs,
+        )
+    }
+}
@@ -1,8 +1,9 @@
+use crate::congestion_control::BlockCongestionInfo;
 use crate::runtime::migration_data::{MigrationData, MigrationFlags};
 use crate::{
     hash::CryptoHash,
     runtime::config::RuntimeConfig,
-    types::{Balance, BlockHeight, CompiledContractCache, EpochHeight, EpochId, Gas},
+    types::{Balance, BlockHeight, CompiledContractCache, EpochHeight, EpochId, Gas, ShardId},
     version::ProtocolVersion,
 };
 use std::sync::Arc;
@@ -12,6 +13,10 @@ pub struct ApplyState {
     /// Currently building block height.
     // TODO #1903 pub block_height: BlockHeight,
     pub block_index: BlockHeight,
+    /// Height at which the transactions of the chunk are validated: the height after the previous
+    /// block, which the chunk producer checked them at. Unlike `block_index`, it doesn't grow when
+    /// the chunk ends up in a block after skipped heights.
+    pub transaction_validity_height: BlockHeight,
     /// Prev block hash
     pub prev_block_hash: CryptoHash,
     /// Current block hash
@@ -20,6 +25,8 @@ pub struct ApplyState {
     pub epoch_id: EpochId,
     /// Current epoch height
     pub epoch_height: EpochHeight,
+    /// Shard of the chunk being applied
+    pub shard_id: ShardId,
     /// Price for the gas.
     pub gas_price: Balance,
     /// The current block timestamp (number of non-leap-nanoseconds since January 1, 1970 0:00:00 UTC).
@@ -42,4 +49,6 @@ pub struct ApplyState {
     pub migration_data: Arc<MigrationData>,
     /// Flags for migrations indicating whether they can be applied at this block
     pub migration_flags: MigrationFlags,
+    /// Congestion of the shards as advertised by the chunks of the previous block.
+    pub congestion_info: BlockCongestionInfo,
 }
@@ -1,5 +1,13 @@
 use crate::runtime::config::RuntimeConfig;
 use crate::types::ProtocolVersion;
+#[cfg(any(
+    feature = "protocol_feature_wasm_bulk_memory",
+    feature = "protocol_feature_ed25519_verify",
+    feature = "protocol_feature_bls12381",
+    feature = "protocol_feature_wasm_op_cost_table",
+    feature = "protocol_feature_delegate_action"
+))]
+use crate::version::ProtocolFeature;
 use std::collections::BTreeMap;
 use std::ops::Bound;
 use std::sync::Arc;
@@ -56,6 +64,129 @@ impl RuntimeConfigStore {
             store.insert(42, Arc::new(config));
         }
 
+        #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+        {
+            let protocol_version = ProtocolFeature::WasmBulkMemory.protocol_version();
+            let mut config = store
+                .range((Bound::Unbounded, Bound::Included(protocol_version)))
+                .next_back()
+                .unwrap()
+                .1
+                .as_ref()
+                .clone();
+            config.wasm_config.limit_config.wasm_bulk_memory = true;
+            // Copying 8 bytes costs about as much as a regular operation.
+            config.wasm_config.bulk_memory_page_cost = 8 * 1024;
+            store.insert(protocol_version, Arc::new(config));
+        }
+
+        #[cfg(feature = "protocol_feature_ed25519_verify")]
+        {
+            let protocol_version = ProtocolFeature::Ed25519Verify.protocol_version();
+            let mut config = store
+                .range((Bound::Unbounded, Bound::Included(protocol_version)))
+                .next_back()
+                .unwrap()
+                .1
+                .as_ref()
+                .clone();
+            config.wasm_config.ext_costs.ed25519_verify_base = 210_000_000_000;
+            config.wasm_config.ext_costs.ed25519_verify_byte = 9_000_000;
+            config.wasm_config.ext_costs.secp256k1_verify_base = 270_000_000_000;
+            store.insert(protocol_version, Arc::new(config));
+        }
+
+        #[cfg(feature = "protocol_feature_bls12381")]
+        {
+            let protocol_version = ProtocolFeature::Bls12381.protocol_version();
+            let mut config = store
+                .range((Bound::Unbounded, Bound::Included(protocol_version)))
+                .next_back()
+                .unwrap()
+                .1
+                .as_ref()
+                .clone();
+            let ext_costs = &mut config.wasm_config.ext_costs;
+            ext_costs.bls12381_p1_sum_base = 16_500_000_000;
+            ext_costs.bls12381_p1_sum_element = 6_000_000_000;
+            ext_costs.bls12381_p2_sum_base = 18_600_000_000;
+            ext_costs.bls12381_p2_sum_element = 15_000_000_000;
+            ext_costs.bls12381_g1_multiexp_base = 16_500_000_000;
+            ext_costs.bls12381_g1_multiexp_element = 930_000_000_000;
+            ext_costs.bls12381_g2_multiexp_base = 18_600_000_000;
+            ext_costs.bls12381_g2_multiexp_element = 1_995_000_000_000;
+            ext_costs.bls12381_map_fp_to_g1_base = 1_500_000_000;
+            ext_costs.bls12381_map_fp_to_g1_element = 252_000_000_000;
+            ext_costs.bls12381_map_fp2_to_g2_base = 1_500_000_000;
+            ext_costs.bls12381_map_fp2_to_g2_element = 900_000_000_000;
+            ext_costs.bls12381_pairing_base = 2_130_000_000_000;
+            ext_costs.bls12381_pairing_element = 2_130_000_000_000;
+            store.insert(protocol_version, Arc::new(config));
+        }
+
+        #[cfg(feature = "protocol_feature_wasm_op_cost_table")]
+        {
+            let protocol_version = ProtocolFeature::WasmOpCostTable.protocol_version();
+            let mut config = store
+                .range((Bound::Unbounded, Bound::Included(protocol_version)))
+                .next_back()
+                .unwrap()
+                .1
+                .as_ref()
+                .clone();
+            // `regular_op_cost` becomes the cost of a quarter of a memory access, so that loads
+            // and stores cost as much as before while cheaper instructions get cheaper.
+            let wasm_config = &mut config.wasm_config;
+            wasm_config.regular_op_cost /= 4;
+            wasm_config.grow_mem_cost *= 4;
+            #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+            {
+                wasm_config.bulk_memory_page_cost *= 4;
+            }
+            let table = &mut wasm_config.op_cost_table;
+            table.constant = 1;
+            table.float_const = 1;
+            table.local = 1;
+            table.nop = 1;
+            table.bit = 2;
+            table.add = 2;
+            table.integer_comparison = 2;
+            table.conversion = 2;
+            table.reinterpretation = 2;
+            table.global = 2;
+            table.load = 4;
+            table.store = 4;
+            table.control_flow = 4;
+            table.unreachable = 4;
+            table.current_memory = 4;
+            table.grow_memory = 4;
+            table.mul = 4;
+            table.float = 4;
+            table.float_comparison = 4;
+            table.float_conversion = 4;
+            table.div = 12;
+            store.insert(protocol_version, Arc::new(config));
+        }
+
+        #[cfg(feature = "protocol_feature_delegate_action")]
+        {
+            let protocol_version = ProtocolFeature::DelegateAction.protocol_version();
+            let mut config = store
+                .range((Bound::Unbounded, Bound::Included(protocol_version)))
+                .next_back()
+                .unwrap()
+                .1
+                .as_ref()
+                .clone();
+            config.transaction_costs.action_creation_config.delegate_cost =
+                crate::runtime::fees::Fee {
+                    send_sir: 200_000_000_000,
+                    send_not_sir: 200_000_000_000,
+                    execution: 200_000_000_000,
+                };
+            store.insert(protocol_version, Arc::new(config));
+        }
+
         Self { store }
     }
 
@@ -104,6 +235,53 @@ mod tests {
         );
     }
 
+    /// The config of the latest protocol version: the last config file with the changes made by
+    /// the enabled nightly protocol features.
+    fn latest_config() -> RuntimeConfig {
+        #[allow(unused_mut)]
+        let mut config =
+            serde_json::from_slice::<RuntimeConfig>(CONFIGS.last().unwrap().1).unwrap();
+        #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+        {
+            config.wasm_config.limit_config.wasm_bulk_memory = true;
+            config.wasm_config.bulk_memory_page_cost = 8 * 1024;
+        }
+        #[cfg(feature = "protocol_feature_ed25519_verify")]
+        {
+            config.wasm_config.ext_costs.ed25519_verify_base = 210_000_000_000;
+            config.wasm_config.ext_costs.ed25519_verify_byte = 9_000_000;
+            config.wasm_config.ext_costs.secp256k1_verify_base = 270_000_000_000;
+        }
+        #[cfg(feature = "protocol_feature_bls12381")]
+        {
+            let ext_costs = &mut config.wasm_config.ext_costs;
+            ext_costs.bls12381_p1_sum_base = 16_500_000_000;
+            ext_costs.bls12381_p1_sum_element = 6_000_000_000;
+            ext_costs.bls12381_p2_sum_base = 18_600_000_000;
+            ext_costs.bls12381_p2_sum_element = 15_000_000_000;
+            ext_costs.bls12381_g1_multiexp_base = 16_500_000_000;
+            ext_costs.bls12381_g1_multiexp_element = 930_000_000_000;
+            ext_costs.bls12381_g2_multiexp_base = 18_600_000_000;
+            ext_costs.bls12381_g2_multiexp_element = 1_995_000_000_000;
+            ext_costs.bls12381_map_fp_to_g1_base = 1_500_000_000;
+            ext_costs.bls12381_map_fp_to_g1_element = 252_000_000_000;
+            ext_costs.bls12381_map_fp2_to_g2_base = 1_500_000_000;
+            ext_costs.bls12381_map_fp2_to_g2_element = 900_000_000_000;
+            ext_costs.bls12381_pairing_base = 2_130_000_000_000;
+            ext_costs.bls12381_pairing_element = 2_130_000_000_000;
+        }
+        #[cfg(feature = "protocol_feature_delegate_action")]
+        {
+            config.transaction_costs.action_creation_config.delegate_cost =
+                crate::runtime::fees::Fee {
+                    send_sir: 200_000_000_000,
+                    send_not_sir: 200_000_000_000,
+                    execution: 200_000_000_000,
+                };
+        }
+        config
+    }
+
     #[test]
     fn test_get_config() {
         check_config(0, CONFIGS[0].1);
@@ -111,7 +289,11 @@ mod tests {
         check_config(GENESIS_PROTOCOL_VERSION, CONFIGS[0].1);
         // First non-trivial version for which runtime config was updated.
         check_config(LowerStorageCost.protocol_version(), CONFIGS[1].1);
-        check_config(ProtocolVersion::MAX, CONFIGS.last().unwrap().1);
+        #[cfg(not(feature = "protocol_feature_wasm_op_cost_table"))]
+        assert_eq!(
+            RuntimeConfigStore::new(None).get_config(ProtocolVersion::MAX).as_ref(),
+            &latest_config()
+        );
     }
 
     #[test]
@@ -226,4 +408,71 @@ If you add new config version, add a missing hash to the end of `expected_hashes
                 > new_cfg.wasm_config.ext_costs.ecrecover_base
         );
     }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+    fn test_wasm_bulk_memory() {
+        let store = RuntimeConfigStore::new(None);
+        let protocol_version = ProtocolFeature::WasmBulkMemory.protocol_version();
+        let base_cfg = store.get_config(protocol_version - 1);
+        let new_cfg = store.get_config(protocol_version);
+        assert!(!base_cfg.wasm_config.limit_config.wasm_bulk_memory);
+        assert!(new_cfg.wasm_config.limit_config.wasm_bulk_memory);
+        assert!(new_cfg.wasm_config.bulk_memory_page_cost > 0);
+        assert_eq!(base_cfg.transaction_costs, new_cfg.transaction_costs);
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    fn test_ed25519_verify_costs() {
+        let store = RuntimeConfigStore::new(None);
+        let protocol_version = ProtocolFeature::Ed25519Verify.protocol_version();
+        let base_cfg = store.get_config(protocol_version - 1);
+        let new_cfg = store.get_config(protocol_version);
+        assert_eq!(base_cfg.wasm_config.ext_costs.ed25519_verify_base, 0);
+        assert!(new_cfg.wasm_config.ext_costs.ed25519_verify_base > 0);
+        assert!(new_cfg.wasm_config.ext_costs.ed25519_verify_byte > 0);
+        assert!(new_cfg.wasm_config.ext_costs.secp256k1_verify_base > 0);
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn test_bls12381_costs() {
+        let store = RuntimeConfigStore::new(None);
+        let protocol_version = ProtocolFeature::Bls12381.protocol_version();
+        let base_cfg = store.get_config(protocol_version - 1);
+        let new_cfg = store.get_config(protocol_version);
+        assert_eq!(base_cfg.wasm_config.ext_costs.bls12381_pairing_base, 0);
+        assert!(new_cfg.wasm_config.ext_costs.bls12381_pairing_base > 0);
+        assert!(new_cfg.wasm_config.ext_costs.bls12381_g1_multiexp_element > 0);
+        assert_eq!(base_cfg.transaction_costs, new_cfg.transaction_costs);
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_wasm_op_cost_table")]
+    fn test_wasm_op_cost_table() {
+        let store = RuntimeConfigStore::new(None);
+        let protocol_version = ProtocolFeature::WasmOpCostTable.protocol_version();
+        let base_cfg = &store.get_config(protocol_version - 1).wasm_config;
+        let new_cfg = &store.get_config(protocol_version).wasm_config;
+        assert!(base_cfg.op_cost_table.is_flat());
+        assert!(!new_cfg.op_cost_table.is_flat());
+        // Memory accesses cost as much as before.
+        assert_eq!(new_cfg.regular_op_cost * new_cfg.op_cost_table.load, base_cfg.regular_op_cost);
+        assert!(new_cfg.op_cost_table.local < new_cfg.op_cost_table.load);
+        assert!(new_cfg.op_cost_table.div > new_cfg.op_cost_table.add);
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    fn test_delegate_action_cost() {
+        let store = RuntimeConfigStore::new(None);
+        let protocol_version = ProtocolFeature::DelegateAction.protocol_version();
+        let base_cfg =
+            &store.get_config(protocol_version - 1).transaction_costs.action_creation_config;
+        let new_cfg = &store.get_config(protocol_version).transaction_costs.action_creation_config;
+        assert_eq!(base_cfg.delegate_cost, Default::default());
+        assert!(new_cfg.delegate_cost.send_fee(false) > 0);
+        assert!(new_cfg.delegate_cost.exec_fee() > 0);
+    }
 }
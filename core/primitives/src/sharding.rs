@@ -65,6 +65,8 @@ pub struct StateSyncInfo {
 }
 
 pub mod shard_chunk_header_inner;
+#[cfg(feature = "protocol_feature_congestion_control")]
+pub use shard_chunk_header_inner::ShardChunkHeaderInnerV3;
 pub use shard_chunk_header_inner::{
     ShardChunkHeaderInner, ShardChunkHeaderInnerV1, ShardChunkHeaderInnerV2,
 };
@@ -190,8 +192,31 @@ impl ShardChunkHeaderV3 {
         outgoing_receipts_root: CryptoHash,
         tx_root: CryptoHash,
         validator_proposals: Vec<ValidatorStake>,
+        delayed_receipts_gas: Option<Gas>,
         signer: &dyn ValidatorSigner,
     ) -> Self {
+        #[cfg(feature = "protocol_feature_congestion_control")]
+        if let Some(delayed_receipts_gas) = delayed_receipts_gas {
+            let inner = ShardChunkHeaderInner::V3(ShardChunkHeaderInnerV3 {
+                prev_block_hash,
+                prev_state_root,
+                outcome_root,
+                encoded_merkle_root,
+                encoded_length,
+                height_created: height,
+                shard_id,
+                gas_used,
+                gas_limit,
+                balance_burnt,
+                outgoing_receipts_root,
+                tx_root,
+                validator_proposals,
+                delayed_receipts_gas,
+            });
+            return Self::from_inner(inner, signer);
+        }
+        #[cfg(not(feature = "protocol_feature_congestion_control"))]
+        let _ = delayed_receipts_gas;
         let inner = ShardChunkHeaderInner::V2(ShardChunkHeaderInnerV2 {
             prev_block_hash,
             prev_state_root,
@@ -207,6 +232,10 @@ impl ShardChunkHeaderV3 {
             tx_root,
             validator_proposals,
         });
+        Self::from_inner(inner, signer)
+    }
+
+    fn from_inner(inner: ShardChunkHeaderInner, signer: &dyn ValidatorSigner) -> Self {
         let hash = Self::compute_hash(&inner);
         let signature = signer.sign_chunk_hash(&hash);
         Self { inner, height_included: 0, signature, hash }
@@ -395,6 +424,16 @@ impl ShardChunkHeader {
         }
     }
 
+    /// Gas of the shard's delayed receipts queue after applying the previous chunk. Only
+    /// advertised by headers created with congestion control enabled.
+    #[inline]
+    pub fn delayed_receipts_gas(&self) -> Option<Gas> {
+        match &self {
+            ShardChunkHeader::V1(_) | ShardChunkHeader::V2(_) => None,
+            ShardChunkHeader::V3(header) => header.inner.delayed_receipts_gas(),
+        }
+    }
+
     #[inline]
     pub fn chunk_hash(&self) -> ChunkHash {
         match &self {
@@ -993,6 +1032,7 @@ impl EncodedShardChunk {
         gas_used: Gas,
         gas_limit: Gas,
         balance_burnt: Balance,
+        delayed_receipts_gas: Option<Gas>,
         tx_root: CryptoHash,
         validator_proposals: Vec<ValidatorStake>,
         transactions: Vec<SignedTransaction>,
@@ -1069,6 +1109,7 @@ impl EncodedShardChunk {
                 outgoing_receipts_root,
                 tx_root,
                 validator_proposals,
+                delayed_receipts_gas,
                 signer,
             );
             let chunk = EncodedShardChunkV2 { header: ShardChunkHeader::V3(header), content };
@@ -9,6 +9,8 @@ use near_primitives_core::types::{Balance, BlockHeight, Gas, ShardId};
 pub enum ShardChunkHeaderInner {
     V1(ShardChunkHeaderInnerV1),
     V2(ShardChunkHeaderInnerV2),
+    #[cfg(feature = "protocol_feature_congestion_control")]
+    V3(ShardChunkHeaderInnerV3),
 }
 
 impl ShardChunkHeaderInner {
@@ -17,6 +19,8 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => &inner.prev_state_root,
             Self::V2(inner) => &inner.prev_state_root,
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            Self::V3(inner) => &inner.prev_state_root,
         }
     }
 
@@ -25,6 +29,8 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => &inner.prev_block_hash,
             Self::V2(inner) => &inner.prev_block_hash,
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            Self::V3(inner) => &inner.prev_block_hash,
         }
     }
 
@@ -33,6 +39,8 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => inner.gas_limit,
             Self::V2(inner) => inner.gas_limit,
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            Self::V3(inner) => inner.gas_limit,
         }
     }
 
@@ -41,6 +49,8 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => inner.gas_used,
             Self::V2(inner) => inner.gas_used,
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            Self::V3(inner) => inner.gas_used,
         }
     }
 
@@ -49,6 +59,8 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => ValidatorStakeIter::v1(&inner.validator_proposals),
             Self::V2(inner) => ValidatorStakeIter::new(&inner.validator_proposals),
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            Self::V3(inner) => ValidatorStakeIter::new(&inner.validator_proposals),
         }
     }
 
@@ -57,6 +69,8 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => inner.height_created,
             Self::V2(inner) => inner.height_created,
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            Self::V3(inner) => inner.height_created,
         }
     }
 
@@ -65,6 +79,8 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => inner.shard_id,
             Self::V2(inner) => inner.shard_id,
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            Self::V3(inner) => inner.shard_id,
         }
     }
 
@@ -73,6 +89,8 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => &inner.outcome_root,
             Self::V2(inner) => &inner.outcome_root,
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            Self::V3(inner) => &inner.outcome_root,
         }
     }
 
@@ -81,6 +99,8 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => &inner.encoded_merkle_root,
             Self::V2(inner) => &inner.encoded_merkle_root,
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            Self::V3(inner) => &inner.encoded_merkle_root,
         }
     }
 
@@ -89,6 +109,8 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => inner.encoded_length,
             Self::V2(inner) => inner.encoded_length,
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            Self::V3(inner) => inner.encoded_length,
         }
     }
 
@@ -97,6 +119,8 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => inner.balance_burnt,
             Self::V2(inner) => inner.balance_burnt,
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            Self::V3(inner) => inner.balance_burnt,
         }
     }
 
@@ -105,6 +129,8 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => &inner.outgoing_receipts_root,
             Self::V2(inner) => &inner.outgoing_receipts_root,
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            Self::V3(inner) => &inner.outgoing_receipts_root,
         }
     }
 
@@ -113,6 +139,17 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => &inner.tx_root,
             Self::V2(inner) => &inner.tx_root,
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            Self::V3(inner) => &inner.tx_root,
+        }
+    }
+
+    #[inline]
+    pub fn delayed_receipts_gas(&self) -> Option<Gas> {
+        match self {
+            Self::V1(_) | Self::V2(_) => None,
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            Self::V3(inner) => Some(inner.delayed_receipts_gas),
         }
     }
 }
@@ -171,3 +208,34 @@ pub struct ShardChunkHeaderInnerV2 {
     /// Validator proposals.
     pub validator_proposals: Vec<ValidatorStake>,
 }
+
+// V2 -> V3: Advertise the gas of the shard's delayed receipts queue for congestion control
+#[cfg(feature = "protocol_feature_congestion_control")]
+#[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct ShardChunkHeaderInnerV3 {
+    /// Previous block hash.
+    pub prev_block_hash: CryptoHash,
+    pub prev_state_root: StateRoot,
+    /// Root of the outcomes from execution transactions and results.
+    pub outcome_root: CryptoHash,
+    pub encoded_merkle_root: CryptoHash,
+    pub encoded_length: u64,
+    pub height_created: BlockHeight,
+    /// Shard index.
+    pub shard_id: ShardId,
+    /// Gas used in this chunk.
+    pub gas_used: Gas,
+    /// Gas limit voted by validators.
+    pub gas_limit: Gas,
+    /// Total balance burnt in previous chunk
+    pub balance_burnt: Balance,
+    /// Outgoing receipts merkle root.
+    pub outgoing_receipts_root: CryptoHash,
+    /// Tx merkle root.
+    pub tx_root: CryptoHash,
+    /// Validator proposals.
+    pub validator_proposals: Vec<ValidatorStake>,
+    /// Gas of the delayed receipts queue after applying the previous chunk.
+    pub delayed_receipts_gas: Gas,
+}
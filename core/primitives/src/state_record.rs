@@ -96,6 +96,9 @@ impl StateRecord {
                 Some(StateRecord::DelayedReceipt(Box::new(receipt)))
             }
             col::DELAYED_RECEIPT_INDICES => None,
+            col::DELAYED_RECEIPTS_GAS => None,
+            // Global contract code is not owned by any account, so it can't be a genesis record.
+            col::GLOBAL_CONTRACT_CODE => None,
             _ => unreachable!(),
         }
     }
@@ -161,6 +164,11 @@ pub fn state_record_to_account_id(state_record: &StateRecord) -> &AccountId {
     }
 }
 
+/// Returns whether the key stores contract code, either of an account or a global one.
 pub fn is_contract_code_key(key: &[u8]) -> bool {
-    &key[0..1] == col::CONTRACT_CODE
+    &key[0..1] == col::CONTRACT_CODE || &key[0..1] == col::GLOBAL_CONTRACT_CODE
+}
+
+pub fn is_global_contract_code_key(key: &[u8]) -> bool {
+    key.starts_with(col::GLOBAL_CONTRACT_CODE)
 }
@@ -35,7 +35,16 @@ impl Transaction {
         nonce: Nonce,
         block_hash: CryptoHash,
     ) -> Self {
-        Self { signer_id, public_key, nonce, receiver_id, block_hash, actions: vec![] }
+        Self {
+            signer_id,
+            public_key,
+            nonce,
+            receiver_id,
+            block_hash,
+            actions: vec![],
+            valid_from_height: None,
+            valid_until_height: None,
+        }
     }
 
     pub fn sign(self, signer: &dyn Signer) -> SignedTransaction {
@@ -110,6 +119,8 @@ impl SignedTransaction {
             receiver_id,
             block_hash,
             actions,
+            valid_from_height: None,
+            valid_until_height: None,
         }
         .sign(signer)
     }
@@ -8,18 +8,19 @@ use serde::{Deserialize, Serialize};
 use near_crypto::{PublicKey, Signature};
 
 use crate::account::AccessKey;
-use crate::errors::TxExecutionError;
+use crate::errors::{InvalidTxError, TxExecutionError};
 use crate::hash::{hash, CryptoHash};
 use crate::logging;
 use crate::merkle::MerklePath;
 use crate::serialize::{base64_format, u128_dec_format_compatible};
-use crate::types::{AccountId, Balance, Gas, Nonce};
+use crate::types::{AccountId, Balance, BlockHeight, Gas, Nonce};
 use near_primitives_core::profile::ProfileData;
+use near_primitives_core::trace::HostFunctionCall;
 
 pub type LogEntry = String;
 
 #[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
-#[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Transaction {
     /// An account on which behalf transaction is signed
     pub signer_id: AccountId,
@@ -35,9 +36,58 @@ pub struct Transaction {
     pub block_hash: CryptoHash,
     /// A list of actions to be applied
     pub actions: Vec<Action>,
+    /// The transaction can't be included in a chunk of a block with a smaller height.
+    /// Nodes reject transactions which can't be included in the next block instead of keeping
+    /// them in the pool, so it should be submitted once the height is reached.
+    pub valid_from_height: Option<BlockHeight>,
+    /// The transaction can't be included in a chunk of a block with a larger height.
+    pub valid_until_height: Option<BlockHeight>,
+}
+
+/// Version of the encoding of a [`Transaction`].
+///
+/// `V0` is the original encoding, which has no version prefix: it starts with the length of
+/// `signer_id`, whose lowest byte is at least [`AccountId::MIN_LEN`]. Later versions are prefixed
+/// with the Borsh encoding of their version, which is below it. Transactions are encoded with the
+/// lowest version able to represent them, so that each transaction has a single encoding and
+/// transactions without a validity window keep their hash.
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum TransactionVersion {
+    V0,
+    /// Followed by the fields of `V0`, then `valid_from_height` and `valid_until_height`.
+    V1,
 }
 
 impl Transaction {
+    /// Whether the transaction has a validity window by block height.
+    pub fn has_validity_window(&self) -> bool {
+        self.valid_from_height.is_some() || self.valid_until_height.is_some()
+    }
+
+    /// The version the transaction is encoded with.
+    pub fn version(&self) -> TransactionVersion {
+        if self.has_validity_window() {
+            TransactionVersion::V1
+        } else {
+            TransactionVersion::V0
+        }
+    }
+
+    /// Checks that the transaction can be included in a chunk of the block at `height`.
+    pub fn check_validity_window(&self, height: BlockHeight) -> Result<(), InvalidTxError> {
+        if let Some(valid_from_height) = self.valid_from_height {
+            if height < valid_from_height {
+                return Err(InvalidTxError::NotValidYet { valid_from_height, height });
+            }
+        }
+        if let Some(valid_until_height) = self.valid_until_height {
+            if height > valid_until_height {
+                return Err(InvalidTxError::ValidityWindowExpired { valid_until_height, height });
+            }
+        }
+        Ok(())
+    }
+
     /// Computes a hash of the transaction for signing and size of serialized transaction
     pub fn get_hash_and_size(&self) -> (CryptoHash, u64) {
         let bytes = self.try_to_vec().expect("Failed to deserialize");
@@ -45,6 +95,66 @@ impl Transaction {
     }
 }
 
+impl BorshSerialize for Transaction {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let version = self.version();
+        if version > TransactionVersion::V0 {
+            BorshSerialize::serialize(&version, writer)?;
+        }
+        BorshSerialize::serialize(&self.signer_id, writer)?;
+        BorshSerialize::serialize(&self.public_key, writer)?;
+        BorshSerialize::serialize(&self.nonce, writer)?;
+        BorshSerialize::serialize(&self.receiver_id, writer)?;
+        BorshSerialize::serialize(&self.block_hash, writer)?;
+        BorshSerialize::serialize(&self.actions, writer)?;
+        if version >= TransactionVersion::V1 {
+            BorshSerialize::serialize(&self.valid_from_height, writer)?;
+            BorshSerialize::serialize(&self.valid_until_height, writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for Transaction {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let version = match buf.first() {
+            Some(&byte) if (byte as usize) < AccountId::MIN_LEN => {
+                match TransactionVersion::deserialize(buf)? {
+                    TransactionVersion::V0 => {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            "transaction of V0 has no version prefix",
+                        ))
+                    }
+                    version => version,
+                }
+            }
+            _ => TransactionVersion::V0,
+        };
+        let mut transaction = Transaction {
+            signer_id: BorshDeserialize::deserialize(buf)?,
+            public_key: BorshDeserialize::deserialize(buf)?,
+            nonce: BorshDeserialize::deserialize(buf)?,
+            receiver_id: BorshDeserialize::deserialize(buf)?,
+            block_hash: BorshDeserialize::deserialize(buf)?,
+            actions: BorshDeserialize::deserialize(buf)?,
+            valid_from_height: None,
+            valid_until_height: None,
+        };
+        if version >= TransactionVersion::V1 {
+            transaction.valid_from_height = BorshDeserialize::deserialize(buf)?;
+            transaction.valid_until_height = BorshDeserialize::deserialize(buf)?;
+        }
+        if transaction.version() != version {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("transaction of {:?} is encoded as {:?}", transaction.version(), version),
+            ));
+        }
+        Ok(transaction)
+    }
+}
+
 #[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
 pub enum Action {
@@ -62,15 +172,36 @@ pub enum Action {
     DeleteAccount(DeleteAccountAction),
     #[cfg(feature = "protocol_feature_chunk_only_producers")]
     StakeChunkOnly(StakeAction),
+    /// A list of actions signed by `sender_id` and relayed by the transaction signer,
+    /// see [`DelegateAction`].
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    Delegate(Box<SignedDelegateAction>),
+    /// Stores a Wasm code in the shard of the receiver once, so that any account of the shard
+    /// can use it with [`Action::UseGlobalContract`].
+    #[cfg(feature = "protocol_feature_global_contracts")]
+    DeployGlobalContract(DeployGlobalContractAction),
+    /// Sets a Wasm code previously stored with [`Action::DeployGlobalContract`] to a
+    /// receiver_id by its hash.
+    #[cfg(feature = "protocol_feature_global_contracts")]
+    UseGlobalContract(UseGlobalContractAction),
 }
 
 impl Action {
     pub fn get_prepaid_gas(&self) -> Gas {
         match self {
             Action::FunctionCall(a) => a.gas,
+            #[cfg(feature = "protocol_feature_delegate_action")]
+            Action::Delegate(a) => a
+                .delegate_action
+                .actions
+                .iter()
+                .map(|action| action.action().get_prepaid_gas())
+                .sum(),
             _ => 0,
         }
     }
+    /// Deposit paid by the signer of the transaction. The deposits of the actions of a delegate
+    /// action are paid by its sender, not by the relayer signing the transaction.
     pub fn get_deposit_balance(&self) -> Balance {
         match self {
             Action::FunctionCall(a) => a.deposit,
@@ -114,6 +245,48 @@ impl fmt::Debug for DeployContractAction {
     }
 }
 
+/// Deploy global contract action
+#[cfg(feature = "protocol_feature_global_contracts")]
+#[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct DeployGlobalContractAction {
+    /// WebAssembly binary
+    #[serde(with = "base64_format")]
+    pub code: Vec<u8>,
+}
+
+#[cfg(feature = "protocol_feature_global_contracts")]
+impl From<DeployGlobalContractAction> for Action {
+    fn from(deploy_global_contract_action: DeployGlobalContractAction) -> Self {
+        Self::DeployGlobalContract(deploy_global_contract_action)
+    }
+}
+
+#[cfg(feature = "protocol_feature_global_contracts")]
+impl fmt::Debug for DeployGlobalContractAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeployGlobalContractAction")
+            .field("code", &format_args!("{}", logging::pretty_utf8(&self.code)))
+            .finish()
+    }
+}
+
+/// Use global contract action
+#[cfg(feature = "protocol_feature_global_contracts")]
+#[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct UseGlobalContractAction {
+    /// Hash of the global contract code
+    pub code_hash: CryptoHash,
+}
+
+#[cfg(feature = "protocol_feature_global_contracts")]
+impl From<UseGlobalContractAction> for Action {
+    fn from(use_global_contract_action: UseGlobalContractAction) -> Self {
+        Self::UseGlobalContract(use_global_contract_action)
+    }
+}
+
 #[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct FunctionCallAction {
@@ -212,6 +385,134 @@ impl From<DeleteAccountAction> for Action {
     }
 }
 
+/// A set of actions that `sender_id` wants to execute on `receiver_id` without paying for gas.
+/// It is signed with one of the sender's access keys and then wrapped into a transaction by a
+/// relayer, who pays for the execution.
+#[cfg(feature = "protocol_feature_delegate_action")]
+#[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct DelegateAction {
+    /// Account which signed the delegate action and on whose behalf the actions are executed.
+    pub sender_id: AccountId,
+    /// Receiver of the inner actions.
+    pub receiver_id: AccountId,
+    /// Actions to be executed.
+    pub actions: Vec<NonDelegateAction>,
+    /// Nonce of the sender's access key, must be greater than the current one.
+    pub nonce: Nonce,
+    /// The delegate action is invalid after this block height.
+    pub max_block_height: BlockHeight,
+    /// Public key of the access key used to sign the delegate action.
+    pub public_key: PublicKey,
+}
+
+#[cfg(feature = "protocol_feature_delegate_action")]
+impl DelegateAction {
+    /// Prefix of the signed message which makes it impossible to confuse a delegate action with
+    /// a transaction. It is `2^30 + 366`, where 366 is the number of the NEP.
+    pub const SIGNATURE_PREFIX: u32 = (1 << 30) + 366;
+
+    /// Computes the hash which the sender has to sign.
+    pub fn get_hash(&self) -> CryptoHash {
+        let mut bytes = Self::SIGNATURE_PREFIX.to_le_bytes().to_vec();
+        BorshSerialize::serialize(self, &mut bytes).expect("Failed to serialize");
+        hash(&bytes)
+    }
+}
+
+#[cfg(feature = "protocol_feature_delegate_action")]
+#[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct SignedDelegateAction {
+    pub delegate_action: DelegateAction,
+    pub signature: Signature,
+}
+
+#[cfg(feature = "protocol_feature_delegate_action")]
+impl SignedDelegateAction {
+    pub fn verify(&self) -> bool {
+        let hash = self.delegate_action.get_hash();
+        self.signature.verify(hash.as_ref(), &self.delegate_action.public_key)
+    }
+}
+
+#[cfg(feature = "protocol_feature_delegate_action")]
+impl From<SignedDelegateAction> for Action {
+    fn from(signed_delegate_action: SignedDelegateAction) -> Self {
+        Self::Delegate(Box::new(signed_delegate_action))
+    }
+}
+
+/// An action which is not a [`Action::Delegate`]. Delegate actions can't be nested, and the
+/// borsh representation rejects such nesting on deserialization.
+///
+/// Borsh derive can't be used here because it generates trait bounds on the field types, which
+/// recurse infinitely through `Action -> SignedDelegateAction -> DelegateAction -> Action`.
+#[cfg(feature = "protocol_feature_delegate_action")]
+#[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(try_from = "Action", into = "Action")]
+pub struct NonDelegateAction(Action);
+
+#[cfg(feature = "protocol_feature_delegate_action")]
+impl NonDelegateAction {
+    pub fn action(&self) -> &Action {
+        &self.0
+    }
+}
+
+#[cfg(feature = "protocol_feature_delegate_action")]
+impl From<NonDelegateAction> for Action {
+    fn from(action: NonDelegateAction) -> Self {
+        action.0
+    }
+}
+
+#[cfg(feature = "protocol_feature_delegate_action")]
+#[derive(Debug)]
+pub struct IsDelegateAction;
+
+#[cfg(feature = "protocol_feature_delegate_action")]
+impl fmt::Display for IsDelegateAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("attempted to construct NonDelegateAction from Action::Delegate")
+    }
+}
+
+#[cfg(feature = "protocol_feature_delegate_action")]
+impl std::error::Error for IsDelegateAction {}
+
+#[cfg(feature = "protocol_feature_delegate_action")]
+impl TryFrom<Action> for NonDelegateAction {
+    type Error = IsDelegateAction;
+
+    fn try_from(action: Action) -> Result<Self, IsDelegateAction> {
+        if matches!(action, Action::Delegate(_)) {
+            Err(IsDelegateAction)
+        } else {
+            Ok(Self(action))
+        }
+    }
+}
+
+#[cfg(feature = "protocol_feature_delegate_action")]
+impl BorshSerialize for NonDelegateAction {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        BorshSerialize::serialize(&self.0, writer)
+    }
+}
+
+#[cfg(feature = "protocol_feature_delegate_action")]
+impl BorshDeserialize for NonDelegateAction {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        <Action as BorshDeserialize>::deserialize(buf)?.try_into().map_err(
+            |err: IsDelegateAction| {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, err.to_string())
+            },
+        )
+    }
+}
+
 #[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
 #[derive(BorshSerialize, BorshDeserialize, Eq, Debug, Clone)]
 #[borsh_init(init)]
@@ -374,6 +675,9 @@ pub enum ExecutionMetadata {
 
     // V2: With ProfileData
     V2(ProfileData),
+
+    // V3: With ProfileData and host function calls, recorded only if host function tracing is enabled
+    V3(ProfileData, Vec<HostFunctionCall>),
 }
 
 impl Default for ExecutionMetadata {
@@ -471,6 +775,8 @@ mod tests {
             receiver_id: "test".parse().unwrap(),
             block_hash: Default::default(),
             actions: vec![],
+            valid_from_height: None,
+            valid_until_height: None,
         }
         .sign(&signer);
         let wrong_public_key = PublicKey::from_seed(KeyType::ED25519, "wrong");
@@ -523,6 +829,8 @@ mod tests {
                     beneficiary_id: "123".parse().unwrap(),
                 }),
             ],
+            valid_from_height: None,
+            valid_until_height: None,
         };
         let signed_tx = SignedTransaction::new(Signature::empty(KeyType::ED25519), transaction);
         let new_signed_tx =
@@ -534,6 +842,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_serialize_transaction_with_validity_window() {
+        let public_key: PublicKey = "22skMptHjFWNyuEWY22ftn2AbLPSYpmYwGJRGwpNHbTV".parse().unwrap();
+        let transaction = Transaction {
+            signer_id: "test.near".parse().unwrap(),
+            public_key,
+            nonce: 1,
+            receiver_id: "123".parse().unwrap(),
+            block_hash: Default::default(),
+            actions: vec![Action::Transfer(TransferAction { deposit: 123 })],
+            valid_from_height: None,
+            valid_until_height: Some(100),
+        };
+        let bytes = transaction.try_to_vec().unwrap();
+        assert_eq!(transaction.version(), TransactionVersion::V1);
+        assert_eq!(bytes[0], TransactionVersion::V1.try_to_vec().unwrap()[0]);
+        assert_eq!(Transaction::try_from_slice(&bytes).unwrap(), transaction);
+
+        let without_window = Transaction { valid_until_height: None, ..transaction };
+        let v0_bytes = without_window.try_to_vec().unwrap();
+        assert_eq!(without_window.version(), TransactionVersion::V0);
+        assert_eq!(bytes[1..v0_bytes.len() + 1], v0_bytes[..]);
+        assert_eq!(Transaction::try_from_slice(&v0_bytes).unwrap(), without_window);
+
+        // Transactions encoded with a version other than their own are rejected.
+        let mut bytes = TransactionVersion::V1.try_to_vec().unwrap();
+        bytes.extend_from_slice(&v0_bytes);
+        bytes.extend_from_slice(&[0, 0]);
+        assert!(Transaction::try_from_slice(&bytes).is_err());
+        let mut bytes = TransactionVersion::V0.try_to_vec().unwrap();
+        bytes.extend_from_slice(&v0_bytes);
+        assert!(Transaction::try_from_slice(&bytes).is_err());
+    }
+
     #[test]
     fn test_outcome_to_hashes() {
         let outcome = ExecutionOutcome {
@@ -548,4 +890,31 @@ mod tests {
         let hashes = outcome.to_hashes();
         assert_eq!(hashes.len(), 3);
     }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    fn test_delegate_action_serialization() {
+        let signer = InMemorySigner::from_seed("alice".parse().unwrap(), KeyType::ED25519, "alice");
+        let delegate_action = DelegateAction {
+            sender_id: "alice".parse().unwrap(),
+            receiver_id: "bob".parse().unwrap(),
+            actions: vec![Action::Transfer(TransferAction { deposit: 1 }).try_into().unwrap()],
+            nonce: 1,
+            max_block_height: 2,
+            public_key: signer.public_key(),
+        };
+        let signature = signer.sign(delegate_action.get_hash().as_ref());
+        let action =
+            Action::Delegate(Box::new(SignedDelegateAction { delegate_action, signature }));
+        let decoded_action = Action::try_from_slice(&action.try_to_vec().unwrap()).unwrap();
+        assert_eq!(decoded_action, action);
+        match decoded_action {
+            Action::Delegate(signed_delegate_action) => assert!(signed_delegate_action.verify()),
+            _ => panic!("expected a delegate action"),
+        }
+
+        // Delegate actions can't be nested.
+        assert!(NonDelegateAction::try_from(action.clone()).is_err());
+        assert!(NonDelegateAction::try_from_slice(&action.try_to_vec().unwrap()).is_err());
+    }
 }
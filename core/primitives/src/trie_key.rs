@@ -35,6 +35,12 @@ pub(crate) mod col {
     pub const DELAYED_RECEIPT: &[u8] = &[8];
     /// This column id is used when storing Key-Value data from a contract on an `account_id`.
     pub const CONTRACT_DATA: &[u8] = &[9];
+    /// This column id is used when storing the total gas of the delayed receipts queue.
+    /// NOTE: It is a singleton per shard.
+    pub const DELAYED_RECEIPTS_GAS: &[u8] = &[10];
+    /// This column id is used when storing contract blob shared by the accounts of a shard for
+    /// a given `code_hash`.
+    pub const GLOBAL_CONTRACT_CODE: &[u8] = &[11];
     /// All columns
     pub const NON_DELAYED_RECEIPT_COLUMNS: &[(&[u8], &str)] = &[
         (ACCOUNT, "Account"),
@@ -83,6 +89,12 @@ pub enum TrieKey {
     /// Used to store a key-value record `Vec<u8>` within a contract deployed on a given `AccountId`
     /// and a given key.
     ContractData { account_id: AccountId, key: Vec<u8> },
+    /// Used to store the total gas `u64` of the receipts in the delayed receipts queue.
+    /// NOTE: It is a singleton per shard.
+    DelayedReceiptsGas,
+    /// Used to store `Vec<u8>` contract code for a given `code_hash`, which can be used by any
+    /// account of the shard.
+    GlobalContractCode { code_hash: CryptoHash },
 }
 
 impl TrieKey {
@@ -125,6 +137,10 @@ impl TrieKey {
                     + ACCOUNT_DATA_SEPARATOR.len()
                     + key.len()
             }
+            TrieKey::DelayedReceiptsGas => col::DELAYED_RECEIPTS_GAS.len(),
+            TrieKey::GlobalContractCode { code_hash } => {
+                col::GLOBAL_CONTRACT_CODE.len() + code_hash.as_ref().len()
+            }
         }
     }
 
@@ -184,6 +200,13 @@ impl TrieKey {
                 buf.extend(ACCOUNT_DATA_SEPARATOR);
                 buf.extend(key);
             }
+            TrieKey::DelayedReceiptsGas => {
+                buf.extend(col::DELAYED_RECEIPTS_GAS);
+            }
+            TrieKey::GlobalContractCode { code_hash } => {
+                buf.extend(col::GLOBAL_CONTRACT_CODE);
+                buf.extend(code_hash.as_ref());
+            }
         };
         debug_assert_eq!(expected_len, buf.len() - start_len);
     }
@@ -623,5 +646,13 @@ mod tests {
         let key = TrieKey::DelayedReceipt { index: 0 };
         let raw_key = key.to_vec();
         assert!(trie_key_parsers::parse_account_id_from_raw_key(&raw_key).unwrap().is_none());
+        let key = TrieKey::DelayedReceiptsGas;
+        let raw_key = key.to_vec();
+        assert_eq!(raw_key.len(), key.len());
+        assert!(trie_key_parsers::parse_account_id_from_raw_key(&raw_key).unwrap().is_none());
+        let key = TrieKey::GlobalContractCode { code_hash: CryptoHash::hash_bytes(b"code") };
+        let raw_key = key.to_vec();
+        assert_eq!(raw_key.len(), key.len());
+        assert!(trie_key_parsers::parse_account_id_from_raw_key(&raw_key).unwrap().is_none());
     }
 }
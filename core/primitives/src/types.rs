@@ -344,6 +344,8 @@ impl StateChanges {
                 TrieKey::PostponedReceipt { .. } => {}
                 TrieKey::DelayedReceiptIndices => {}
                 TrieKey::DelayedReceipt { .. } => {}
+                TrieKey::DelayedReceiptsGas => {}
+                TrieKey::GlobalContractCode { .. } => {}
             }
         }
 
@@ -720,6 +722,8 @@ pub mod chunk_extra {
     pub enum ChunkExtra {
         V1(ChunkExtraV1),
         V2(ChunkExtraV2),
+        #[cfg(feature = "protocol_feature_congestion_control")]
+        V3(ChunkExtraV3),
     }
 
     #[derive(Debug, PartialEq, BorshSerialize, BorshDeserialize, Clone, Eq)]
@@ -738,11 +742,33 @@ pub mod chunk_extra {
         pub balance_burnt: Balance,
     }
 
+    // V2 -> V3: Add the gas of the delayed receipts queue for congestion control
+    #[cfg(feature = "protocol_feature_congestion_control")]
+    #[derive(Debug, PartialEq, BorshSerialize, BorshDeserialize, Clone, Eq)]
+    pub struct ChunkExtraV3 {
+        /// Post state root after applying give chunk.
+        pub state_root: StateRoot,
+        /// Root of merklizing results of receipts (transactions) execution.
+        pub outcome_root: CryptoHash,
+        /// Validator proposals produced by given chunk.
+        pub validator_proposals: Vec<ValidatorStake>,
+        /// Actually how much gas were used.
+        pub gas_used: Gas,
+        /// Gas limit, allows to increase or decrease limit based on expected time vs real time for computing the chunk.
+        pub gas_limit: Gas,
+        /// Total balance burnt after processing the current chunk.
+        pub balance_burnt: Balance,
+        /// Total gas of the receipts left in the delayed receipts queue after processing the chunk.
+        pub delayed_receipts_gas: Gas,
+    }
+
     impl ChunkExtra {
         pub fn new_with_only_state_root(state_root: &StateRoot) -> Self {
-            Self::new(state_root, CryptoHash::default(), vec![], 0, 0, 0)
+            Self::new(state_root, CryptoHash::default(), vec![], 0, 0, 0, None)
         }
 
+        /// Creates `ChunkExtraV3` if `delayed_receipts_gas` is known, i.e. the runtime tracks it
+        /// at the protocol version of the chunk, and `ChunkExtraV2` otherwise.
         pub fn new(
             state_root: &StateRoot,
             outcome_root: CryptoHash,
@@ -750,7 +776,22 @@ pub mod chunk_extra {
             gas_used: Gas,
             gas_limit: Gas,
             balance_burnt: Balance,
+            delayed_receipts_gas: Option<Gas>,
         ) -> Self {
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            if let Some(delayed_receipts_gas) = delayed_receipts_gas {
+                return Self::V3(ChunkExtraV3 {
+                    state_root: *state_root,
+                    outcome_root,
+                    validator_proposals,
+                    gas_used,
+                    gas_limit,
+                    balance_burnt,
+                    delayed_receipts_gas,
+                });
+            }
+            #[cfg(not(feature = "protocol_feature_congestion_control"))]
+            let _ = delayed_receipts_gas;
             Self::V2(ChunkExtraV2 {
                 state_root: *state_root,
                 outcome_root,
@@ -766,6 +807,8 @@ pub mod chunk_extra {
             match self {
                 Self::V1(v1) => &v1.outcome_root,
                 Self::V2(v2) => &v2.outcome_root,
+                #[cfg(feature = "protocol_feature_congestion_control")]
+                Self::V3(v3) => &v3.outcome_root,
             }
         }
 
@@ -774,6 +817,8 @@ pub mod chunk_extra {
             match self {
                 Self::V1(v1) => &v1.state_root,
                 Self::V2(v2) => &v2.state_root,
+                #[cfg(feature = "protocol_feature_congestion_control")]
+                Self::V3(v3) => &v3.state_root,
             }
         }
 
@@ -782,6 +827,8 @@ pub mod chunk_extra {
             match self {
                 Self::V1(v1) => &mut v1.state_root,
                 Self::V2(v2) => &mut v2.state_root,
+                #[cfg(feature = "protocol_feature_congestion_control")]
+                Self::V3(v3) => &mut v3.state_root,
             }
         }
 
@@ -790,6 +837,8 @@ pub mod chunk_extra {
             match self {
                 Self::V1(v1) => ValidatorStakeIter::v1(&v1.validator_proposals),
                 Self::V2(v2) => ValidatorStakeIter::new(&v2.validator_proposals),
+                #[cfg(feature = "protocol_feature_congestion_control")]
+                Self::V3(v3) => ValidatorStakeIter::new(&v3.validator_proposals),
             }
         }
 
@@ -798,6 +847,8 @@ pub mod chunk_extra {
             match self {
                 Self::V1(v1) => v1.gas_limit,
                 Self::V2(v2) => v2.gas_limit,
+                #[cfg(feature = "protocol_feature_congestion_control")]
+                Self::V3(v3) => v3.gas_limit,
             }
         }
 
@@ -806,6 +857,8 @@ pub mod chunk_extra {
             match self {
                 Self::V1(v1) => v1.gas_used,
                 Self::V2(v2) => v2.gas_used,
+                #[cfg(feature = "protocol_feature_congestion_control")]
+                Self::V3(v3) => v3.gas_used,
             }
         }
 
@@ -814,6 +867,19 @@ pub mod chunk_extra {
             match self {
                 Self::V1(v1) => v1.balance_burnt,
                 Self::V2(v2) => v2.balance_burnt,
+                #[cfg(feature = "protocol_feature_congestion_control")]
+                Self::V3(v3) => v3.balance_burnt,
+            }
+        }
+
+        /// Gas of the delayed receipts queue after processing the chunk, if tracked by the
+        /// protocol version of the chunk.
+        #[inline]
+        pub fn delayed_receipts_gas(&self) -> Option<Gas> {
+            match self {
+                Self::V1(_) | Self::V2(_) => None,
+                #[cfg(feature = "protocol_feature_congestion_control")]
+                Self::V3(v3) => Some(v3.delayed_receipts_gas),
             }
         }
     }
@@ -958,7 +1024,7 @@ pub trait CompiledContractCache: Send + Sync {
 
 /// Provides information about current epoch validators.
 /// Used to break dependency between epoch manager and runtime.
-pub trait EpochInfoProvider {
+pub trait EpochInfoProvider: Send + Sync {
     /// Get current stake of a validator in the given epoch.
     /// If the account is not a validator, returns `None`.
     fn validator_stake(
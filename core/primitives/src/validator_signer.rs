@@ -1,3 +1,4 @@
+use std::net::SocketAddr;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -8,7 +9,7 @@ use near_crypto::{InMemorySigner, KeyType, PublicKey, Signature, Signer};
 use crate::block::{Approval, ApprovalInner, BlockHeader};
 use crate::challenge::ChallengeBody;
 use crate::hash::{hash, CryptoHash};
-use crate::network::{AnnounceAccount, PeerId};
+use crate::network::{AnnounceAccount, AnnounceAccountAddrs, PeerId};
 use crate::sharding::ChunkHash;
 use crate::telemetry::TelemetryInfo;
 use crate::types::{AccountId, BlockHeight, EpochId};
@@ -49,6 +50,15 @@ pub trait ValidatorSigner: Sync + Send {
         epoch_id: &EpochId,
     ) -> Signature;
 
+    /// Signs addresses announced to other validators.
+    fn sign_account_addrs(
+        &self,
+        account_id: &AccountId,
+        peer_id: &PeerId,
+        epoch_id: &EpochId,
+        addrs: &[SocketAddr],
+    ) -> Signature;
+
     fn compute_vrf_with_proof(
         &self,
         data: &[u8],
@@ -111,6 +121,16 @@ impl ValidatorSigner for EmptyValidatorSigner {
         Signature::default()
     }
 
+    fn sign_account_addrs(
+        &self,
+        _account_id: &AccountId,
+        _peer_id: &PeerId,
+        _epoch_id: &EpochId,
+        _addrs: &[SocketAddr],
+    ) -> Signature {
+        Signature::default()
+    }
+
     fn compute_vrf_with_proof(
         &self,
         _data: &[u8],
@@ -201,6 +221,17 @@ impl ValidatorSigner for InMemoryValidatorSigner {
         self.signer.sign(hash.as_ref())
     }
 
+    fn sign_account_addrs(
+        &self,
+        account_id: &AccountId,
+        peer_id: &PeerId,
+        epoch_id: &EpochId,
+        addrs: &[SocketAddr],
+    ) -> Signature {
+        let hash = AnnounceAccountAddrs::build_header_hash(account_id, peer_id, epoch_id, addrs);
+        self.signer.sign(hash.as_ref())
+    }
+
     fn compute_vrf_with_proof(
         &self,
         data: &[u8],
@@ -155,6 +155,47 @@ pub enum ProtocolFeature {
     FixStakingThreshold,
     #[cfg(feature = "protocol_feature_function_call_weight")]
     FunctionCallWeight,
+    /// Encrypts and authenticates all frames exchanged with a peer after the handshake.
+    #[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+    EncryptedPeerTransport,
+    /// Validators announce their addresses to each other and keep direct connections among
+    /// validators of the current epoch.
+    #[cfg(feature = "protocol_feature_validator_direct_connections")]
+    ValidatorDirectConnections,
+    /// Allows contracts to use the Wasm bulk memory proposal (`memory.copy`, `memory.fill`,
+    /// passive data segments), with gas charged per byte processed.
+    #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+    WasmBulkMemory,
+    /// Adds `ed25519_verify`, `ed25519_verify_batch` and `secp256k1_verify` host functions.
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    Ed25519Verify,
+    /// Adds host functions for operations on the BLS12-381 curve.
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381,
+    /// Charges Wasm instructions according to a per-instruction-type cost table instead of
+    /// charging every instruction `regular_op_cost`.
+    #[cfg(feature = "protocol_feature_wasm_op_cost_table")]
+    WasmOpCostTable,
+    /// Allows a relayer to pay for actions signed by another account with the `Delegate` action.
+    /// See [NEP 366](https://github.com/near/NEPs/pull/366).
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    DelegateAction,
+    /// Function call access keys which expire at a given block height and whose allowance is
+    /// refilled every epoch.
+    #[cfg(feature = "protocol_feature_access_key_expiration")]
+    AccessKeyExpiration,
+    /// Chunk headers advertise the gas of their shard's delayed receipts queue; receipts and
+    /// transactions sent to congested shards are throttled.
+    #[cfg(feature = "protocol_feature_congestion_control")]
+    CongestionControl,
+    /// Contract code can be deployed once per shard with `DeployGlobalContract` and referenced
+    /// by hash from accounts with `UseGlobalContract`, instead of every account storing a copy.
+    #[cfg(feature = "protocol_feature_global_contracts")]
+    GlobalContracts,
+    /// Transactions can set `valid_from_height` and `valid_until_height` to bound the heights of
+    /// the blocks in which they can be included.
+    #[cfg(feature = "protocol_feature_tx_validity_window")]
+    TransactionValidityWindow,
 }
 
 /// Both, outgoing and incoming tcp connections to peers, will be rejected if `peer's`
@@ -171,7 +212,7 @@ const STABLE_PROTOCOL_VERSION: ProtocolVersion = 53;
 pub const PROTOCOL_VERSION: ProtocolVersion = STABLE_PROTOCOL_VERSION;
 /// Current latest nightly version of the protocol.
 #[cfg(feature = "nightly_protocol")]
-pub const PROTOCOL_VERSION: ProtocolVersion = 127;
+pub const PROTOCOL_VERSION: ProtocolVersion = 138;
 
 /// The points in time after which the voting for the protocol version should start.
 #[allow(dead_code)]
@@ -234,6 +275,28 @@ impl ProtocolFeature {
             ProtocolFeature::FixStakingThreshold => 126,
             #[cfg(feature = "protocol_feature_function_call_weight")]
             ProtocolFeature::FunctionCallWeight => 127,
+            #[cfg(feature = "protocol_feature_encrypted_peer_transport")]
+            ProtocolFeature::EncryptedPeerTransport => 128,
+            #[cfg(feature = "protocol_feature_validator_direct_connections")]
+            ProtocolFeature::ValidatorDirectConnections => 129,
+            #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+            ProtocolFeature::WasmBulkMemory => 130,
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            ProtocolFeature::Ed25519Verify => 131,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            ProtocolFeature::Bls12381 => 132,
+            #[cfg(feature = "protocol_feature_wasm_op_cost_table")]
+            ProtocolFeature::WasmOpCostTable => 133,
+            #[cfg(feature = "protocol_feature_delegate_action")]
+            ProtocolFeature::DelegateAction => 134,
+            #[cfg(feature = "protocol_feature_access_key_expiration")]
+            ProtocolFeature::AccessKeyExpiration => 135,
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            ProtocolFeature::CongestionControl => 136,
+            #[cfg(feature = "protocol_feature_global_contracts")]
+            ProtocolFeature::GlobalContracts => 137,
+            #[cfg(feature = "protocol_feature_tx_validity_window")]
+            ProtocolFeature::TransactionValidityWindow => 138,
         }
     }
 }
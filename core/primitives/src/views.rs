@@ -3,6 +3,7 @@
 //! These types should only change when we cannot avoid this. Thus, when the counterpart internal
 //! type gets changed, the view should preserve the old shape and only re-map the necessary bits
 //! from the source structure in the relevant `From<SourceStruct>` impl.
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 use std::sync::Arc;
 
@@ -12,6 +13,8 @@ use serde::{Deserialize, Serialize};
 
 use near_crypto::{PublicKey, Signature};
 
+#[cfg(feature = "protocol_feature_access_key_expiration")]
+use crate::account::ExpiringFunctionCallPermission;
 use crate::account::{AccessKey, AccessKeyPermission, Account, FunctionCallPermission};
 use crate::block::{Block, BlockHeader, Tip};
 use crate::block_header::{
@@ -24,21 +27,29 @@ use crate::errors::TxExecutionError;
 use crate::hash::{hash, CryptoHash};
 use crate::logging;
 use crate::merkle::MerklePath;
-use crate::profile::Cost;
+use crate::network::PeerId;
+use crate::profile::{Cost, ProfileData};
 use crate::receipt::{ActionReceipt, DataReceipt, DataReceiver, Receipt, ReceiptEnum};
 use crate::serialize::{
     base64_format, from_base64, option_base64_format, option_u128_dec_format, to_base64,
     u128_dec_format, u64_dec_format,
 };
+#[cfg(feature = "protocol_feature_congestion_control")]
+use crate::sharding::ShardChunkHeaderInnerV3;
 use crate::sharding::{
     ChunkHash, ShardChunk, ShardChunkHeader, ShardChunkHeaderInner, ShardChunkHeaderInnerV2,
     ShardChunkHeaderV3,
 };
+use crate::trace::HostFunctionCall;
 use crate::transaction::{
     Action, AddKeyAction, CreateAccountAction, DeleteAccountAction, DeleteKeyAction,
     DeployContractAction, ExecutionMetadata, ExecutionOutcome, ExecutionOutcomeWithIdAndProof,
     ExecutionStatus, FunctionCallAction, SignedTransaction, StakeAction, TransferAction,
 };
+#[cfg(feature = "protocol_feature_delegate_action")]
+use crate::transaction::{DelegateAction, SignedDelegateAction};
+#[cfg(feature = "protocol_feature_global_contracts")]
+use crate::transaction::{DeployGlobalContractAction, UseGlobalContractAction};
 use crate::types::{
     AccountId, AccountWithPublicKey, Balance, BlockHeight, CompiledContractCache, EpochHeight,
     EpochId, FunctionArgs, Gas, Nonce, NumBlocks, ShardId, StateChangeCause, StateChangeKind,
@@ -147,6 +158,17 @@ pub enum AccessKeyPermissionView {
         method_names: Vec<String>,
     },
     FullAccess,
+    #[cfg(feature = "protocol_feature_access_key_expiration")]
+    ExpiringFunctionCall {
+        #[serde(with = "option_u128_dec_format")]
+        allowance: Option<Balance>,
+        receiver_id: String,
+        method_names: Vec<String>,
+        expiration_block_height: Option<BlockHeight>,
+        #[serde(with = "option_u128_dec_format")]
+        allowance_per_epoch: Option<Balance>,
+        allowance_epoch_height: EpochHeight,
+    },
 }
 
 impl From<AccessKeyPermission> for AccessKeyPermissionView {
@@ -158,6 +180,17 @@ impl From<AccessKeyPermission> for AccessKeyPermissionView {
                 method_names: func_call.method_names,
             },
             AccessKeyPermission::FullAccess => AccessKeyPermissionView::FullAccess,
+            #[cfg(feature = "protocol_feature_access_key_expiration")]
+            AccessKeyPermission::ExpiringFunctionCall(permission) => {
+                AccessKeyPermissionView::ExpiringFunctionCall {
+                    allowance: permission.function_call.allowance,
+                    receiver_id: permission.function_call.receiver_id,
+                    method_names: permission.function_call.method_names,
+                    expiration_block_height: permission.expiration_block_height,
+                    allowance_per_epoch: permission.allowance_per_epoch,
+                    allowance_epoch_height: permission.allowance_epoch_height,
+                }
+            }
         }
     }
 }
@@ -173,6 +206,20 @@ impl From<AccessKeyPermissionView> for AccessKeyPermission {
                 })
             }
             AccessKeyPermissionView::FullAccess => AccessKeyPermission::FullAccess,
+            #[cfg(feature = "protocol_feature_access_key_expiration")]
+            AccessKeyPermissionView::ExpiringFunctionCall {
+                allowance,
+                receiver_id,
+                method_names,
+                expiration_block_height,
+                allowance_per_epoch,
+                allowance_epoch_height,
+            } => AccessKeyPermission::ExpiringFunctionCall(ExpiringFunctionCallPermission {
+                function_call: FunctionCallPermission { allowance, receiver_id, method_names },
+                expiration_block_height,
+                allowance_per_epoch,
+                allowance_epoch_height,
+            }),
         }
     }
 }
@@ -215,6 +262,35 @@ pub struct ViewStateResult {
     pub proof: TrieProofPath,
 }
 
+/// Storage used by contract data records whose keys start with `prefix`.
+#[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct StoragePrefixView {
+    #[serde(rename = "prefix_base64", with = "base64_format")]
+    pub prefix: StoreKey,
+    pub num_records: u64,
+    pub storage_usage: StorageUsage,
+}
+
+/// Storage used by an account, split by category and computed with the same accounting as the
+/// runtime uses to update `Account::storage_usage`.
+#[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct StorageBreakdownView {
+    /// Fixed number of bytes charged for the account record.
+    pub account: StorageUsage,
+    pub contract_code: StorageUsage,
+    pub num_access_keys: u64,
+    pub access_keys: StorageUsage,
+    /// Contract data grouped by key prefix, ordered by prefix.
+    pub contract_data: Vec<StoragePrefixView>,
+    /// Sum of all the categories above.
+    pub total: StorageUsage,
+    /// `storage_usage` stored in the account. Differs from `total` if the runtime charged the
+    /// account for storage differently than it is computed here.
+    pub stored_total: StorageUsage,
+}
+
 #[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
 #[derive(
     BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default,
@@ -259,6 +335,7 @@ pub enum QueryResponseKind {
     CallResult(CallResult),
     AccessKey(AccessKeyView),
     AccessKeyList(AccessKeyList),
+    StorageBreakdown(StorageBreakdownView),
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
@@ -289,6 +366,13 @@ pub enum QueryRequest {
         #[serde(rename = "args_base64", with = "base64_format")]
         args: FunctionArgs,
     },
+    ViewStorageBreakdown {
+        account_id: AccountId,
+        /// Number of leading bytes of the contract data keys to group the data by. Defaults
+        /// to 1.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        data_prefix_len: Option<u32>,
+    },
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
@@ -365,6 +449,76 @@ pub struct NetworkInfoView {
     pub connected_peers: Vec<PeerInfoView>,
 }
 
+#[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NetworkGraphEdgeView {
+    pub peer0: PeerId,
+    pub peer1: PeerId,
+    pub nonce: u64,
+    /// Whether the latest known state of the edge is active (i.e. the connection is up).
+    pub active: bool,
+}
+
+#[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NetworkGraphPeerView {
+    pub peer_id: PeerId,
+    /// Number of hops from this node, `None` if the peer is not reachable.
+    pub distance: Option<u32>,
+    /// Directly connected peers which are on a shortest path towards `peer_id`.
+    pub next_hops: Vec<PeerId>,
+}
+
+/// View of the routing graph as seen by the node at a given moment.
+#[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NetworkGraphView {
+    pub my_peer_id: PeerId,
+    /// Unix timestamp in nanoseconds of the moment the view was taken.
+    #[serde(with = "u64_dec_format")]
+    pub timestamp: u64,
+    pub edges: Vec<NetworkGraphEdgeView>,
+    pub peers: Vec<NetworkGraphPeerView>,
+}
+
+impl NetworkGraphView {
+    /// Renders the graph in Graphviz DOT format.
+    ///
+    /// Active edges are drawn solid, removed edges dashed. Nodes are labelled with their distance
+    /// from this node.
+    pub fn to_dot(&self) -> String {
+        let mut res = String::from("graph routing {\n");
+        res.push_str(&format!(
+            "  \"{}\" [shape=doublecircle, label=\"{}\\n0\"];\n",
+            self.my_peer_id, self.my_peer_id
+        ));
+        for peer in &self.peers {
+            let distance = peer.distance.map_or_else(|| "-".to_string(), |d| d.to_string());
+            res.push_str(&format!(
+                "  \"{}\" [label=\"{}\\n{}\"];\n",
+                peer.peer_id, peer.peer_id, distance
+            ));
+        }
+        for edge in &self.edges {
+            let style = if edge.active { "solid" } else { "dashed" };
+            res.push_str(&format!(
+                "  \"{}\" -- \"{}\" [label=\"{}\", style={}];\n",
+                edge.peer0, edge.peer1, edge.nonce, style
+            ));
+        }
+        res.push_str("}\n");
+        res
+    }
+}
+
+/// Current routing graph together with previously recorded snapshots, oldest first.
+#[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NetworkGraphHistoryView {
+    pub current: NetworkGraphView,
+    pub history: Vec<NetworkGraphView>,
+}
+
 #[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct BlockStatusView {
@@ -738,6 +892,10 @@ pub struct ChunkHeaderView {
     pub outgoing_receipts_root: CryptoHash,
     pub tx_root: CryptoHash,
     pub validator_proposals: Vec<ValidatorStakeView>,
+    /// Gas of the shard's delayed receipts queue, advertised for congestion control.
+    #[cfg(feature = "protocol_feature_congestion_control")]
+    #[serde(default)]
+    pub delayed_receipts_gas: Option<Gas>,
     pub signature: Signature,
 }
 
@@ -765,6 +923,8 @@ impl From<ShardChunkHeader> for ChunkHeaderView {
             outgoing_receipts_root: *inner.outgoing_receipts_root(),
             tx_root: *inner.tx_root(),
             validator_proposals: inner.validator_proposals().map(Into::into).collect(),
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            delayed_receipts_gas: inner.delayed_receipts_gas(),
             signature,
         }
     }
@@ -772,22 +932,45 @@ impl From<ShardChunkHeader> for ChunkHeaderView {
 
 impl From<ChunkHeaderView> for ShardChunkHeader {
     fn from(view: ChunkHeaderView) -> Self {
-        let mut header = ShardChunkHeaderV3 {
-            inner: ShardChunkHeaderInner::V2(ShardChunkHeaderInnerV2 {
-                prev_block_hash: view.prev_block_hash,
-                prev_state_root: view.prev_state_root,
-                outcome_root: view.outcome_root,
-                encoded_merkle_root: view.encoded_merkle_root,
-                encoded_length: view.encoded_length,
-                height_created: view.height_created,
-                shard_id: view.shard_id,
-                gas_used: view.gas_used,
-                gas_limit: view.gas_limit,
-                balance_burnt: view.balance_burnt,
-                outgoing_receipts_root: view.outgoing_receipts_root,
-                tx_root: view.tx_root,
-                validator_proposals: view.validator_proposals.into_iter().map(Into::into).collect(),
+        let inner = ShardChunkHeaderInnerV2 {
+            prev_block_hash: view.prev_block_hash,
+            prev_state_root: view.prev_state_root,
+            outcome_root: view.outcome_root,
+            encoded_merkle_root: view.encoded_merkle_root,
+            encoded_length: view.encoded_length,
+            height_created: view.height_created,
+            shard_id: view.shard_id,
+            gas_used: view.gas_used,
+            gas_limit: view.gas_limit,
+            balance_burnt: view.balance_burnt,
+            outgoing_receipts_root: view.outgoing_receipts_root,
+            tx_root: view.tx_root,
+            validator_proposals: view.validator_proposals.into_iter().map(Into::into).collect(),
+        };
+        #[cfg(feature = "protocol_feature_congestion_control")]
+        let inner = match view.delayed_receipts_gas {
+            Some(delayed_receipts_gas) => ShardChunkHeaderInner::V3(ShardChunkHeaderInnerV3 {
+                prev_block_hash: inner.prev_block_hash,
+                prev_state_root: inner.prev_state_root,
+                outcome_root: inner.outcome_root,
+                encoded_merkle_root: inner.encoded_merkle_root,
+                encoded_length: inner.encoded_length,
+                height_created: inner.height_created,
+                shard_id: inner.shard_id,
+                gas_used: inner.gas_used,
+                gas_limit: inner.gas_limit,
+                balance_burnt: inner.balance_burnt,
+                outgoing_receipts_root: inner.outgoing_receipts_root,
+                tx_root: inner.tx_root,
+                validator_proposals: inner.validator_proposals,
+                delayed_receipts_gas,
             }),
+            None => ShardChunkHeaderInner::V2(inner),
+        };
+        #[cfg(not(feature = "protocol_feature_congestion_control"))]
+        let inner = ShardChunkHeaderInner::V2(inner);
+        let mut header = ShardChunkHeaderV3 {
+            inner,
             height_included: view.height_included,
             signature: view.signature,
             hash: ChunkHash::default(),
@@ -880,6 +1063,19 @@ pub enum ActionView {
         stake: Balance,
         public_key: PublicKey,
     },
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    Delegate {
+        delegate_action: DelegateAction,
+        signature: Signature,
+    },
+    #[cfg(feature = "protocol_feature_global_contracts")]
+    DeployGlobalContract {
+        code: String,
+    },
+    #[cfg(feature = "protocol_feature_global_contracts")]
+    UseGlobalContract {
+        code_hash: CryptoHash,
+    },
 }
 
 impl From<Action> for ActionView {
@@ -911,6 +1107,19 @@ impl From<Action> for ActionView {
             Action::StakeChunkOnly(action) => {
                 ActionView::StakeChunkOnly { stake: action.stake, public_key: action.public_key }
             }
+            #[cfg(feature = "protocol_feature_delegate_action")]
+            Action::Delegate(action) => ActionView::Delegate {
+                delegate_action: action.delegate_action,
+                signature: action.signature,
+            },
+            #[cfg(feature = "protocol_feature_global_contracts")]
+            Action::DeployGlobalContract(action) => {
+                ActionView::DeployGlobalContract { code: to_base64(&hash(&action.code)) }
+            }
+            #[cfg(feature = "protocol_feature_global_contracts")]
+            Action::UseGlobalContract(action) => {
+                ActionView::UseGlobalContract { code_hash: action.code_hash }
+            }
         }
     }
 }
@@ -949,6 +1158,20 @@ impl TryFrom<ActionView> for Action {
             ActionView::StakeChunkOnly { stake, public_key } => {
                 Action::StakeChunkOnly(StakeAction { stake, public_key })
             }
+            #[cfg(feature = "protocol_feature_delegate_action")]
+            ActionView::Delegate { delegate_action, signature } => {
+                Action::Delegate(Box::new(SignedDelegateAction { delegate_action, signature }))
+            }
+            #[cfg(feature = "protocol_feature_global_contracts")]
+            ActionView::DeployGlobalContract { code } => {
+                Action::DeployGlobalContract(DeployGlobalContractAction {
+                    code: from_base64(&code)?,
+                })
+            }
+            #[cfg(feature = "protocol_feature_global_contracts")]
+            ActionView::UseGlobalContract { code_hash } => {
+                Action::UseGlobalContract(UseGlobalContractAction { code_hash })
+            }
         })
     }
 }
@@ -963,6 +1186,13 @@ pub struct SignedTransactionView {
     pub actions: Vec<ActionView>,
     pub signature: Signature,
     pub hash: CryptoHash,
+    /// Not sent over the network, to keep the format of `TxStatusResponse` unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[borsh_skip]
+    pub valid_from_height: Option<BlockHeight>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[borsh_skip]
+    pub valid_until_height: Option<BlockHeight>,
 }
 
 impl From<SignedTransaction> for SignedTransactionView {
@@ -981,6 +1211,8 @@ impl From<SignedTransaction> for SignedTransactionView {
                 .collect(),
             signature: signed_tx.signature,
             hash,
+            valid_from_height: signed_tx.transaction.valid_from_height,
+            valid_until_height: signed_tx.transaction.valid_until_height,
         }
     }
 }
@@ -1078,11 +1310,62 @@ pub struct CostGasUsed {
     pub gas_used: Gas,
 }
 
+#[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Clone, Eq, Debug)]
+pub struct RegisterWriteView {
+    pub register_id: u64,
+    pub len: u64,
+}
+
+#[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Clone, Eq, Debug)]
+pub struct HostFunctionCallView {
+    pub name: String,
+    /// Arguments passed by the contract, e.g. `key_len=3, key_ptr=1024`.
+    pub args: String,
+    pub burnt_gas_before: Gas,
+    pub burnt_gas_after: Gas,
+    /// Base64 encoded storage keys accessed by the call.
+    pub storage_keys: Vec<String>,
+    pub registers_written: Vec<RegisterWriteView>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<HostFunctionCall> for HostFunctionCallView {
+    fn from(call: HostFunctionCall) -> Self {
+        let args = call
+            .args
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join(", ");
+        HostFunctionCallView {
+            name: call.name,
+            args,
+            burnt_gas_before: call.burnt_gas_before,
+            burnt_gas_after: call.burnt_gas_after,
+            storage_keys: call.storage_keys.iter().map(|key| to_base64(key)).collect(),
+            registers_written: call
+                .registers_written
+                .into_iter()
+                .map(|(register_id, len)| RegisterWriteView { register_id, len })
+                .collect(),
+            error: call.error,
+        }
+    }
+}
+
 #[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Clone, Eq, Debug)]
 pub struct ExecutionMetadataView {
     pub version: u32,
     pub gas_profile: Option<Vec<CostGasUsed>>,
+    /// Host function calls made by the contract. Only recorded by nodes with `trace_host_functions` enabled.
+    /// Not sent over the network, to keep the format of `TxStatusResponse` unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[borsh_skip]
+    pub host_function_calls: Option<Vec<HostFunctionCallView>>,
 }
 
 impl Default for ExecutionMetadataView {
@@ -1093,49 +1376,55 @@ impl Default for ExecutionMetadataView {
 
 impl From<ExecutionMetadata> for ExecutionMetadataView {
     fn from(metadata: ExecutionMetadata) -> Self {
-        let gas_profile = match metadata {
-            ExecutionMetadata::V1 => None,
-            ExecutionMetadata::V2(profile_data) => {
-                let mut costs: Vec<_> = Cost::ALL
-                    .iter()
-                    .filter(|&cost| profile_data[*cost] > 0)
-                    .map(|&cost| CostGasUsed {
-                        cost_category: match cost {
-                            Cost::ActionCost { .. } => "ACTION_COST",
-                            Cost::ExtCost { .. } => "WASM_HOST_COST",
-                            Cost::WasmInstruction => "WASM_HOST_COST",
-                        }
-                        .to_string(),
-                        cost: match cost {
-                            Cost::ActionCost { action_cost_kind: action_cost } => {
-                                format!("{:?}", action_cost).to_ascii_uppercase()
-                            }
-                            Cost::ExtCost { ext_cost_kind: ext_cost } => {
-                                format!("{:?}", ext_cost).to_ascii_uppercase()
-                            }
-                            Cost::WasmInstruction => "WASM_INSTRUCTION".to_string(),
-                        },
-                        gas_used: profile_data[cost],
-                    })
-                    .collect();
-
-                // The order doesn't really matter, but the default one is just
-                // historical, which is especially unintuitive, so let's sort
-                // lexicographically.
-                //
-                // Can't `sort_by_key` here because lifetime inference in
-                // closures is limited.
-                costs.sort_by(|lhs, rhs| {
-                    lhs.cost_category.cmp(&rhs.cost_category).then(lhs.cost.cmp(&rhs.cost))
-                });
-
-                Some(costs)
-            }
+        let (gas_profile, host_function_calls) = match metadata {
+            ExecutionMetadata::V1 => (None, None),
+            ExecutionMetadata::V2(profile_data) => (Some(gas_profile_view(&profile_data)), None),
+            ExecutionMetadata::V3(profile_data, calls) => (
+                Some(gas_profile_view(&profile_data)),
+                Some(calls.into_iter().map(Into::into).collect()),
+            ),
         };
-        ExecutionMetadataView { version: 1, gas_profile }
+        ExecutionMetadataView { version: 1, gas_profile, host_function_calls }
     }
 }
 
+fn gas_profile_view(profile_data: &ProfileData) -> Vec<CostGasUsed> {
+    let mut costs: Vec<_> = Cost::ALL
+        .iter()
+        .filter(|&cost| profile_data[*cost] > 0)
+        .map(|&cost| CostGasUsed {
+            cost_category: match cost {
+                Cost::ActionCost { .. } => "ACTION_COST",
+                Cost::ExtCost { .. } => "WASM_HOST_COST",
+                Cost::WasmInstruction => "WASM_HOST_COST",
+            }
+            .to_string(),
+            cost: match cost {
+                Cost::ActionCost { action_cost_kind: action_cost } => {
+                    format!("{:?}", action_cost).to_ascii_uppercase()
+                }
+                Cost::ExtCost { ext_cost_kind: ext_cost } => {
+                    format!("{:?}", ext_cost).to_ascii_uppercase()
+                }
+                Cost::WasmInstruction => "WASM_INSTRUCTION".to_string(),
+            },
+            gas_used: profile_data[cost],
+        })
+        .collect();
+
+    // The order doesn't really matter, but the default one is just
+    // historical, which is especially unintuitive, so let's sort
+    // lexicographically.
+    //
+    // Can't `sort_by_key` here because lifetime inference in
+    // closures is limited.
+    costs.sort_by(|lhs, rhs| {
+        lhs.cost_category.cmp(&rhs.cost_category).then(lhs.cost.cmp(&rhs.cost))
+    });
+
+    costs
+}
+
 #[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct ExecutionOutcomeView {
@@ -1247,6 +1536,150 @@ impl From<FinalExecutionOutcomeWithReceiptView> for FinalExecutionOutcomeView {
     }
 }
 
+impl FinalExecutionOutcomeView {
+    /// Builds the tree of gas burnt by the transaction and every receipt it spawned, directly or
+    /// through other receipts. Receipts without an outcome yet are left out.
+    pub fn gas_profile_tree(&self) -> GasProfileNodeView {
+        let outcomes: HashMap<_, _> =
+            self.receipts_outcome.iter().map(|outcome| (outcome.id, outcome)).collect();
+        let mut visited = HashSet::from([self.transaction_outcome.id]);
+        GasProfileNodeView::new(&self.transaction_outcome, &outcomes, &mut visited)
+    }
+}
+
+/// Gas burnt by a transaction or receipt, broken down by action and host function category,
+/// together with the gas burnt by the receipts it spawned.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Eq, Debug)]
+pub struct GasProfileNodeView {
+    /// Id of the transaction or receipt.
+    pub id: CryptoHash,
+    pub executor_id: AccountId,
+    /// Gas burnt by this transaction or receipt alone.
+    #[serde(with = "u64_dec_format")]
+    pub gas_burnt: Gas,
+    /// Gas burnt by this transaction or receipt and all of its descendants.
+    #[serde(with = "u64_dec_format")]
+    pub total_gas_burnt: Gas,
+    /// Gas burnt per action cost, e.g. `FUNCTION_CALL`.
+    pub actions: Vec<CostGasUsed>,
+    /// Gas burnt per host function cost, with `cost_category` set to the host function
+    /// category, e.g. `STORAGE`.
+    pub host_functions: Vec<CostGasUsed>,
+    /// Gas burnt executing Wasm instructions.
+    #[serde(with = "u64_dec_format")]
+    pub wasm_instructions: Gas,
+    /// Receipts spawned by this transaction or receipt.
+    pub children: Vec<GasProfileNodeView>,
+}
+
+impl GasProfileNodeView {
+    fn new(
+        outcome: &ExecutionOutcomeWithIdView,
+        outcomes: &HashMap<CryptoHash, &ExecutionOutcomeWithIdView>,
+        visited: &mut HashSet<CryptoHash>,
+    ) -> Self {
+        let mut actions = vec![];
+        let mut host_functions = vec![];
+        let mut wasm_instructions = 0;
+        for cost in outcome.outcome.metadata.gas_profile.iter().flatten() {
+            if cost.cost_category == "ACTION_COST" {
+                actions.push(cost.clone());
+            } else if cost.cost == "WASM_INSTRUCTION" {
+                wasm_instructions += cost.gas_used;
+            } else {
+                host_functions.push(CostGasUsed {
+                    cost_category: host_function_category(&cost.cost).to_string(),
+                    cost: cost.cost.clone(),
+                    gas_used: cost.gas_used,
+                });
+            }
+        }
+        let children: Vec<_> = outcome
+            .outcome
+            .receipt_ids
+            .iter()
+            .filter_map(|receipt_id| {
+                let child = outcomes.get(receipt_id)?;
+                visited.insert(child.id).then(|| GasProfileNodeView::new(child, outcomes, visited))
+            })
+            .collect();
+        let total_gas_burnt = children.iter().fold(outcome.outcome.gas_burnt, |total, child| {
+            total.saturating_add(child.total_gas_burnt)
+        });
+        GasProfileNodeView {
+            id: outcome.id,
+            executor_id: outcome.outcome.executor_id.clone(),
+            gas_burnt: outcome.outcome.gas_burnt,
+            total_gas_burnt,
+            actions,
+            host_functions,
+            wasm_instructions,
+            children,
+        }
+    }
+
+    /// All nodes of the tree in depth-first order, starting with this one.
+    pub fn nodes(&self) -> Vec<&GasProfileNodeView> {
+        let mut nodes = vec![];
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            nodes.push(node);
+            stack.extend(node.children.iter().rev());
+        }
+        nodes
+    }
+
+    /// The node which burnt the most gas by itself, e.g. the most expensive callback.
+    pub fn most_expensive(&self) -> &GasProfileNodeView {
+        self.nodes().into_iter().max_by_key(|node| node.gas_burnt).unwrap_or(self)
+    }
+
+    /// Gas burnt in the whole tree per executor account.
+    pub fn gas_by_executor(&self) -> BTreeMap<AccountId, Gas> {
+        let mut gas = BTreeMap::new();
+        for node in self.nodes() {
+            let entry = gas.entry(node.executor_id.clone()).or_insert(0u64);
+            *entry = entry.saturating_add(node.gas_burnt);
+        }
+        gas
+    }
+
+    /// Gas burnt in the whole tree per action cost.
+    pub fn gas_by_action(&self) -> BTreeMap<String, Gas> {
+        let mut gas = BTreeMap::new();
+        for cost in self.nodes().into_iter().flat_map(|node| node.actions.iter()) {
+            let entry = gas.entry(cost.cost.clone()).or_insert(0u64);
+            *entry = entry.saturating_add(cost.gas_used);
+        }
+        gas
+    }
+
+    /// Gas burnt in the whole tree per host function category.
+    pub fn gas_by_host_function_category(&self) -> BTreeMap<String, Gas> {
+        let mut gas = BTreeMap::new();
+        for cost in self.nodes().into_iter().flat_map(|node| node.host_functions.iter()) {
+            let entry = gas.entry(cost.cost_category.clone()).or_insert(0u64);
+            *entry = entry.saturating_add(cost.gas_used);
+        }
+        gas
+    }
+}
+
+/// Maps a host function cost name from `gas_profile_view` back to its category.
+fn host_function_category(cost_name: &str) -> &'static str {
+    Cost::ALL
+        .iter()
+        .find_map(|cost| match cost {
+            Cost::ExtCost { ext_cost_kind }
+                if format!("{:?}", ext_cost_kind).eq_ignore_ascii_case(cost_name) =>
+            {
+                Some(ext_cost_kind.category())
+            }
+            _ => None,
+        })
+        .unwrap_or("OTHER")
+}
+
 pub mod validator_stake_view {
     use crate::types::validator_stake::ValidatorStake;
     use borsh::{BorshDeserialize, BorshSerialize};
@@ -1462,6 +1895,22 @@ impl TryFrom<ReceiptView> for Receipt {
     }
 }
 
+/// State of the delayed receipt queue of a shard, together with a page of the queued receipts.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct DelayedReceiptsView {
+    pub shard_id: ShardId,
+    /// Index of the oldest receipt in the queue.
+    pub first_index: u64,
+    /// Number of receipts in the queue.
+    pub length: u64,
+    /// Prepaid and execution gas of the queued receipts, as tracked by congestion control. Zero
+    /// on protocol versions without congestion control.
+    pub total_gas: Gas,
+    pub oldest_receipt_id: Option<CryptoHash>,
+    /// Queued receipts, oldest first, starting at the requested index.
+    pub receipts: Vec<ReceiptView>,
+}
+
 /// Information about this epoch validators and next epoch validators
 #[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -1763,3 +2212,93 @@ impl From<StateChangeWithCause> for StateChangeWithCauseView {
 }
 
 pub type StateChangesView = Vec<StateChangeWithCauseView>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome_view(
+        id: CryptoHash,
+        executor_id: &str,
+        receipt_ids: Vec<CryptoHash>,
+        gas_profile: Vec<(&str, &str, Gas)>,
+    ) -> ExecutionOutcomeWithIdView {
+        let gas_profile: Vec<_> = gas_profile
+            .into_iter()
+            .map(|(cost_category, cost, gas_used)| CostGasUsed {
+                cost_category: cost_category.to_string(),
+                cost: cost.to_string(),
+                gas_used,
+            })
+            .collect();
+        ExecutionOutcomeWithIdView {
+            proof: vec![],
+            block_hash: CryptoHash::default(),
+            id,
+            outcome: ExecutionOutcomeView {
+                logs: vec![],
+                receipt_ids,
+                gas_burnt: gas_profile.iter().map(|cost| cost.gas_used).sum(),
+                tokens_burnt: 0,
+                executor_id: executor_id.parse().unwrap(),
+                status: ExecutionStatusView::SuccessValue(String::new()),
+                metadata: ExecutionMetadataView {
+                    version: 1,
+                    gas_profile: Some(gas_profile),
+                    host_function_calls: None,
+                },
+            },
+        }
+    }
+
+    #[test]
+    fn test_gas_profile_tree() {
+        let (tx, call, callback, refund) =
+            (hash(b"tx"), hash(b"call"), hash(b"callback"), hash(b"refund"));
+        let final_outcome = FinalExecutionOutcomeView {
+            status: FinalExecutionStatus::SuccessValue(String::new()),
+            transaction: SignedTransaction::empty(CryptoHash::default()).into(),
+            transaction_outcome: outcome_view(tx, "alice.near", vec![call], vec![]),
+            receipts_outcome: vec![
+                outcome_view(
+                    call,
+                    "dex.near",
+                    vec![callback],
+                    vec![
+                        ("ACTION_COST", "FUNCTION_CALL", 10),
+                        ("WASM_HOST_COST", "STORAGE_READ_BASE", 20),
+                        ("WASM_HOST_COST", "WASM_INSTRUCTION", 5),
+                    ],
+                ),
+                outcome_view(
+                    callback,
+                    "token.near",
+                    vec![refund],
+                    vec![
+                        ("WASM_HOST_COST", "STORAGE_WRITE_BASE", 100),
+                        ("WASM_HOST_COST", "SHA256_BASE", 7),
+                    ],
+                ),
+                outcome_view(refund, "alice.near", vec![], vec![("ACTION_COST", "TRANSFER", 3)]),
+            ],
+        };
+
+        let tree = final_outcome.gas_profile_tree();
+        assert_eq!(tree.id, tx);
+        assert_eq!(tree.total_gas_burnt, 145);
+        assert_eq!(
+            tree.nodes().iter().map(|node| node.id).collect::<Vec<_>>(),
+            vec![tx, call, callback, refund]
+        );
+        let call_node = &tree.children[0];
+        assert_eq!(call_node.gas_burnt, 35);
+        assert_eq!(call_node.wasm_instructions, 5);
+        assert_eq!(call_node.host_functions[0].cost_category, "STORAGE");
+        assert_eq!(tree.most_expensive().id, callback);
+        assert_eq!(tree.gas_by_executor()[&"alice.near".parse::<AccountId>().unwrap()], 3);
+        assert_eq!(tree.gas_by_action()["FUNCTION_CALL"], 10);
+        let by_category = tree.gas_by_host_function_category();
+        assert_eq!(by_category["STORAGE"], 120);
+        assert_eq!(by_category["HASHING"], 7);
+    }
+}
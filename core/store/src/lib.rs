@@ -31,10 +31,12 @@ use crate::db::{
     GENESIS_STATE_ROOTS_KEY,
 };
 pub use crate::trie::iterator::TrieIterator;
-pub use crate::trie::update::{TrieUpdate, TrieUpdateIterator, TrieUpdateValuePtr};
+pub use crate::trie::update::{
+    TrieUpdate, TrieUpdateIterator, TrieUpdateSnapshot, TrieUpdateValuePtr,
+};
 pub use crate::trie::{
     split_state, ApplyStatePartResult, KeyForStateChanges, PartialStorage, ShardTries, Trie,
-    TrieChanges, WrappedTrieChanges,
+    TrieChanges, TrieHandle, WrappedTrieChanges,
 };
 
 pub mod db;
@@ -438,14 +440,55 @@ pub fn set_code(state_update: &mut TrieUpdate, account_id: AccountId, code: &Con
     state_update.set(TrieKey::ContractCode { account_id }, code.code().to_vec());
 }
 
+/// Returns the contract code of the account. Accounts using global contract code don't store
+/// their own copy, so the code is looked up by `code_hash` among the global ones.
 pub fn get_code(
     state_update: &TrieUpdate,
     account_id: &AccountId,
     code_hash: Option<CryptoHash>,
+) -> Result<Option<ContractCode>, StorageError> {
+    match state_update.get(&TrieKey::ContractCode { account_id: account_id.clone() })? {
+        Some(code) => Ok(Some(ContractCode::new(code, code_hash))),
+        None => match code_hash {
+            Some(code_hash) if code_hash != CryptoHash::default() => {
+                get_global_code(state_update, code_hash)
+            }
+            _ => Ok(None),
+        },
+    }
+}
+
+/// Returns the length of the contract code stored by the account itself, which is included in
+/// its storage usage. Global contract code used by the account is not included.
+pub fn get_code_storage_usage(
+    state_update: &TrieUpdate,
+    account_id: &AccountId,
+) -> Result<u64, StorageError> {
+    Ok(state_update
+        .get(&TrieKey::ContractCode { account_id: account_id.clone() })?
+        .map(|code| code.len() as u64)
+        .unwrap_or_default())
+}
+
+pub fn set_global_code(state_update: &mut TrieUpdate, code: &ContractCode) {
+    state_update.set(TrieKey::GlobalContractCode { code_hash: *code.hash() }, code.code().to_vec());
+}
+
+pub fn get_global_code(
+    state_update: &TrieUpdate,
+    code_hash: CryptoHash,
 ) -> Result<Option<ContractCode>, StorageError> {
     state_update
-        .get(&TrieKey::ContractCode { account_id: account_id.clone() })
-        .map(|opt| opt.map(|code| ContractCode::new(code, code_hash)))
+        .get(&TrieKey::GlobalContractCode { code_hash })
+        .map(|opt| opt.map(|code| ContractCode::new(code, Some(code_hash))))
+}
+
+/// Checks whether global contract code with the given hash exists without reading the code.
+pub fn has_global_code(
+    state_update: &TrieUpdate,
+    code_hash: CryptoHash,
+) -> Result<bool, StorageError> {
+    Ok(state_update.get_ref(&TrieKey::GlobalContractCode { code_hash })?.is_some())
 }
 
 /// Removes account, code and all access keys associated to it.
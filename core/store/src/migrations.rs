@@ -207,6 +207,7 @@ pub fn migrate_9_to_10(path: &Path, is_archival: bool) {
                 header.inner.gas_used,
                 header.inner.gas_limit,
                 header.inner.balance_burnt,
+                None,
                 header.inner.tx_root,
                 proposals,
                 transactions,
@@ -1,4 +1,4 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt;
@@ -18,8 +18,10 @@ use crate::trie::iterator::TrieIterator;
 use crate::trie::nibble_slice::NibbleSlice;
 pub use crate::trie::shard_tries::{KeyForStateChanges, ShardTries, WrappedTrieChanges};
 pub(crate) use crate::trie::trie_storage::{TrieCache, TrieCachingStorage};
-use crate::trie::trie_storage::{TrieMemoryPartialStorage, TrieRecordingStorage, TrieStorage};
-use crate::StorageError;
+use crate::trie::trie_storage::{
+    TrieMemoryPartialStorage, TrieOverlayStorage, TrieRecordingStorage, TrieStorage,
+};
+use crate::{StorageError, Store};
 
 mod insert_delete;
 pub mod iterator;
@@ -407,6 +409,25 @@ pub struct Trie {
     pub(crate) storage: Box<dyn TrieStorage>,
 }
 
+/// Handle to the storage of a caching `Trie`. Unlike `Trie` it is `Send`, so it can be used to
+/// open tries over the same shard state on other threads.
+#[derive(Clone)]
+pub struct TrieHandle {
+    store: Store,
+    shard_uid: ShardUId,
+    shard_cache: TrieCache,
+}
+
+impl TrieHandle {
+    /// Opens a new trie sharing the shard cache with the trie this handle was taken from.
+    /// Touched nodes are counted separately.
+    pub fn open(&self) -> Trie {
+        let storage =
+            TrieCachingStorage::new(self.store.clone(), self.shard_cache.clone(), self.shard_uid);
+        Trie { storage: Box::new(storage) }
+    }
+}
+
 /// Stores reference count change for some key-value pair in DB.
 #[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub struct TrieRefcountChange {
@@ -482,6 +503,41 @@ impl Trie {
         Trie { storage: Box::new(storage) }
     }
 
+    /// Returns a trie which reads the nodes inserted by `changes` from memory, so that the new
+    /// root of `changes` can be used without committing them to the store, or `None` if the
+    /// storage is not a `TrieCachingStorage`.
+    pub fn with_changes(&self, changes: &TrieChanges) -> Option<Self> {
+        let storage = self.storage.as_caching_storage()?;
+        let overlay = changes
+            .insertions
+            .iter()
+            .map(|change| {
+                (change.trie_node_or_value_hash, change.trie_node_or_value.as_slice().into())
+            })
+            .collect();
+        let storage = TrieOverlayStorage {
+            overlay,
+            storage: TrieCachingStorage::new(
+                storage.store.clone(),
+                storage.shard_cache.clone(),
+                storage.shard_uid,
+            ),
+            counter: Cell::new(0),
+        };
+        Some(Trie { storage: Box::new(storage) })
+    }
+
+    /// Returns a handle to open more tries over the same storage, or `None` if the storage is not
+    /// a `TrieCachingStorage`, e.g. when reads are recorded.
+    pub fn handle(&self) -> Option<TrieHandle> {
+        let storage = self.storage.as_caching_storage()?;
+        Some(TrieHandle {
+            store: storage.store.clone(),
+            shard_uid: storage.shard_uid,
+            shard_cache: storage.shard_cache.clone(),
+        })
+    }
+
     pub fn empty_root() -> StateRoot {
         StateRoot::default()
     }
@@ -1149,6 +1205,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_trie_with_changes() {
+        let store = create_test_store();
+        let tries = ShardTries::new(store, 0, 1);
+        let empty_root = Trie::empty_root();
+        let changes = vec![
+            (b"doge".to_vec(), Some(b"coin".to_vec())),
+            (b"docu".to_vec(), Some(b"value".to_vec())),
+        ];
+        let root = test_populate_trie(&tries, &empty_root, ShardUId::single_shard(), changes);
+
+        let trie = tries.get_trie_for_shard(ShardUId::single_shard());
+        let updates = vec![(b"doge".to_vec(), None), (b"dodo".to_vec(), Some(b"asdf".to_vec()))];
+        let trie_changes = trie.update(&root, updates.into_iter()).unwrap();
+        let new_root = trie_changes.new_root;
+        assert!(trie.get(&new_root, b"dodo").is_err());
+
+        let trie2 = trie.with_changes(&trie_changes).unwrap();
+        assert_eq!(trie2.get(&new_root, b"dodo"), Ok(Some(b"asdf".to_vec())));
+        assert_eq!(trie2.get(&new_root, b"docu"), Ok(Some(b"value".to_vec())));
+        assert_eq!(trie2.get(&new_root, b"doge"), Ok(None));
+        assert_eq!(trie2.get(&root, b"doge"), Ok(Some(b"coin".to_vec())));
+        assert!(trie2.get_touched_nodes_count() > 0);
+        assert!(trie.recording_reads().with_changes(&trie_changes).is_none());
+    }
+
     #[test]
     fn test_dump_load_trie() {
         let store = create_test_store();
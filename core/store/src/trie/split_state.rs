@@ -9,6 +9,7 @@ use near_primitives::errors::StorageError;
 use near_primitives::receipt::Receipt;
 use near_primitives::shard_layout::ShardUId;
 use near_primitives::state_part::PartId;
+use near_primitives::state_record::is_global_contract_code_key;
 use near_primitives::trie_key::trie_key_parsers::parse_account_id_from_raw_key;
 use near_primitives::trie_key::TrieKey;
 use near_primitives::types::{
@@ -57,7 +58,18 @@ impl ShardTries {
         let mut insert_receipts = Vec::new();
         for ConsolidatedStateChange { trie_key, value } in changes.changes {
             match &trie_key {
-                TrieKey::DelayedReceiptIndices => {}
+                // The delayed receipts gas is recomputed by the runtime for the new shards.
+                TrieKey::DelayedReceiptIndices | TrieKey::DelayedReceiptsGas => {}
+                // Global contract code can be used by any account of the parent shard, so every
+                // split shard keeps a copy.
+                TrieKey::GlobalContractCode { .. } => {
+                    for trie_update in trie_updates.values_mut() {
+                        match &value {
+                            Some(value) => trie_update.set(trie_key.clone(), value.clone()),
+                            None => trie_update.remove(trie_key.clone()),
+                        }
+                    }
+                }
                 TrieKey::DelayedReceipt { index } => match value {
                     Some(value) => {
                         let receipt = Receipt::try_from_slice(&value).map_err(|err| {
@@ -150,7 +162,16 @@ impl ShardTries {
     ) -> Result<(StoreUpdate, HashMap<ShardUId, StateRoot>), StorageError> {
         let mut changes_by_shard: HashMap<_, Vec<_>> = HashMap::new();
         for (raw_key, value) in values.into_iter() {
-            if let Some(new_shard_uid) = key_to_shard_id(&raw_key)? {
+            if is_global_contract_code_key(&raw_key) {
+                // Global contract code is copied to every split shard, see
+                // `apply_state_changes_to_split_states`.
+                for shard_uid in state_roots.keys() {
+                    changes_by_shard
+                        .entry(*shard_uid)
+                        .or_default()
+                        .push((raw_key.clone(), value.clone()));
+                }
+            } else if let Some(new_shard_uid) = key_to_shard_id(&raw_key)? {
                 changes_by_shard.entry(new_shard_uid).or_default().push((raw_key, value));
             }
         }
@@ -211,6 +232,26 @@ impl ShardTries {
     }
 }
 
+/// Returns the split shard whose delayed receipts queue keeps the receipt. Receipts to other shards
+/// were stalled by congestion control and are waiting to be forwarded, which any of the split
+/// shards can do, so they stay in the first one.
+fn delayed_receipt_shard_uid<'a>(
+    trie_updates: &HashMap<ShardUId, TrieUpdate>,
+    receipt: &Receipt,
+    account_id_to_shard_id: &(dyn Fn(&AccountId) -> ShardUId + 'a),
+) -> Result<ShardUId, StorageError> {
+    let new_shard_uid: ShardUId = account_id_to_shard_id(&receipt.receiver_id);
+    if trie_updates.contains_key(&new_shard_uid) {
+        return Ok(new_shard_uid);
+    }
+    trie_updates.keys().min().copied().ok_or_else(|| {
+        StorageError::StorageInconsistentState(format!(
+            "Account {} is in new shard {:?} but there are no split states",
+            receipt.receiver_id, new_shard_uid,
+        ))
+    })
+}
+
 fn apply_delayed_receipts_to_split_states_impl<'a>(
     trie_updates: &mut HashMap<ShardUId, TrieUpdate>,
     insert_receipts: &[Receipt],
@@ -223,16 +264,8 @@ fn apply_delayed_receipts_to_split_states_impl<'a>(
     }
 
     for receipt in insert_receipts {
-        let new_shard_uid: ShardUId = account_id_to_shard_id(&receipt.receiver_id);
-        if !trie_updates.contains_key(&new_shard_uid) {
-            let err = format!(
-                "Account {} is in new shard {:?} but state_roots only contains {:?}",
-                receipt.receiver_id,
-                new_shard_uid,
-                trie_updates.keys(),
-            );
-            return Err(StorageError::StorageInconsistentState(err));
-        }
+        let new_shard_uid =
+            delayed_receipt_shard_uid(trie_updates, receipt, account_id_to_shard_id)?;
         // we already checked that new_shard_uid is in trie_updates and delayed_receipts_indices
         // so we can safely unwrap here
         let mut delayed_receipts_indices =
@@ -252,16 +285,8 @@ fn apply_delayed_receipts_to_split_states_impl<'a>(
     }
 
     for receipt in delete_receipts {
-        let new_shard_uid: ShardUId = account_id_to_shard_id(&receipt.receiver_id);
-        if !trie_updates.contains_key(&new_shard_uid) {
-            let err = format!(
-                "Account {} is in new shard {:?} but state_roots only contains {:?}",
-                receipt.receiver_id,
-                new_shard_uid,
-                trie_updates.keys(),
-            );
-            return Err(StorageError::StorageInconsistentState(err));
-        }
+        let new_shard_uid =
+            delayed_receipt_shard_uid(trie_updates, receipt, account_id_to_shard_id)?;
         let mut delayed_receipts_indices =
             delayed_receipts_indices_by_shard.get_mut(&new_shard_uid).unwrap();
 
@@ -143,6 +143,31 @@ impl TrieStorage for TrieMemoryPartialStorage {
     }
 }
 
+/// Serves the nodes inserted by some `TrieChanges` from memory and reads the rest from the
+/// underlying storage. Retrieved nodes are counted the same way as by `TrieCachingStorage`.
+/// Used to apply chunks on top of a modified state in read-only tools, without writing the new
+/// nodes to the store.
+pub struct TrieOverlayStorage {
+    pub(crate) overlay: HashMap<CryptoHash, Arc<[u8]>>,
+    pub(crate) storage: TrieCachingStorage,
+    /// Counts nodes retrieved from the overlay.
+    pub(crate) counter: Cell<u64>,
+}
+
+impl TrieStorage for TrieOverlayStorage {
+    fn retrieve_raw_bytes(&self, hash: &CryptoHash) -> Result<Arc<[u8]>, StorageError> {
+        if let Some(val) = self.overlay.get(hash) {
+            self.counter.set(self.counter.get() + 1);
+            return Ok(val.clone());
+        }
+        self.storage.retrieve_raw_bytes(hash)
+    }
+
+    fn get_touched_nodes_count(&self) -> u64 {
+        self.counter.get() + self.storage.get_touched_nodes_count()
+    }
+}
+
 /// Maximum number of cache entries.
 /// It was chosen to fit into RAM well. RAM spend on trie cache should not exceed 50_000 * 4 (number of shards) *
 /// TRIE_LIMIT_CACHED_VALUE_SIZE * 2 (number of caches - for regular and view client) = 1.6 GB.
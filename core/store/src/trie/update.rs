@@ -1,5 +1,6 @@
 use std::collections::BTreeMap;
 use std::iter::Peekable;
+use std::sync::Arc;
 
 use near_primitives::hash::CryptoHash;
 use near_primitives::types::{
@@ -22,10 +23,15 @@ pub struct TrieKeyValueUpdate {
 /// key that was updated -> the update.
 pub type TrieUpdates = BTreeMap<Vec<u8>, TrieKeyValueUpdate>;
 
+/// Latest committed values of a `TrieUpdate`, see `TrieUpdate::snapshot`.
+pub type TrieUpdateSnapshot = Arc<BTreeMap<Vec<u8>, Option<Vec<u8>>>>;
+
 /// Provides a way to access Storage and record changes with future commit.
 pub struct TrieUpdate {
     pub trie: Rc<Trie>,
     root: CryptoHash,
+    /// Committed values of the update this one was forked from, read below `committed`.
+    base: Option<TrieUpdateSnapshot>,
     committed: RawStateChanges,
     prospective: TrieUpdates,
 }
@@ -55,7 +61,58 @@ impl<'a> TrieUpdateValuePtr<'a> {
 
 impl TrieUpdate {
     pub fn new(trie: Rc<Trie>, root: CryptoHash) -> Self {
-        TrieUpdate { trie, root, committed: Default::default(), prospective: Default::default() }
+        TrieUpdate {
+            trie,
+            root,
+            base: None,
+            committed: Default::default(),
+            prospective: Default::default(),
+        }
+    }
+
+    /// Creates an update on top of the `snapshot` of another update with the same `root`.
+    /// Changes committed to the fork are moved back with `TrieUpdate::merge`.
+    pub fn fork(trie: Rc<Trie>, root: CryptoHash, snapshot: TrieUpdateSnapshot) -> Self {
+        TrieUpdate {
+            trie,
+            root,
+            base: Some(snapshot),
+            committed: Default::default(),
+            prospective: Default::default(),
+        }
+    }
+
+    /// Returns the latest committed value of every key changed by this update, to fork it.
+    pub fn snapshot(&self) -> TrieUpdateSnapshot {
+        assert!(self.prospective.is_empty(), "Snapshot cannot be taken with uncommitted changes.");
+        let mut values = self.base.as_ref().map_or_else(BTreeMap::new, |base| (**base).clone());
+        for (raw_key, changes_with_trie_key) in self.committed.iter() {
+            let RawStateChange { data, .. } = changes_with_trie_key
+                .changes
+                .last()
+                .expect("Committed entry should have at least one change");
+            values.insert(raw_key.clone(), data.clone());
+        }
+        Arc::new(values)
+    }
+
+    /// Consumes a fork and returns the changes committed to it.
+    pub fn into_committed(self) -> RawStateChanges {
+        assert!(self.prospective.is_empty(), "Fork cannot be merged with uncommitted changes.");
+        self.committed
+    }
+
+    /// Appends changes committed to a fork of this update. Forks merged into the same update
+    /// must not change the same keys, otherwise the order of the changes is lost.
+    pub fn merge(&mut self, committed: RawStateChanges) {
+        assert!(self.prospective.is_empty(), "Merge cannot be done with uncommitted changes.");
+        for (raw_key, RawStateChangesWithTrieKey { trie_key, mut changes }) in committed {
+            self.committed
+                .entry(raw_key)
+                .or_insert_with(|| RawStateChangesWithTrieKey { trie_key, changes: Vec::new() })
+                .changes
+                .append(&mut changes);
+        }
     }
 
     pub fn trie(&self) -> &Trie {
@@ -70,6 +127,8 @@ impl TrieUpdate {
             if let Some(RawStateChange { data, .. }) = changes_with_trie_key.changes.last() {
                 return Ok(data.as_ref().map(<Vec<u8>>::clone));
             }
+        } else if let Some(data) = self.base.as_ref().and_then(|base| base.get(&key)) {
+            return Ok(data.as_ref().map(<Vec<u8>>::clone));
         }
 
         self.trie.get(&self.root, &key)
@@ -83,6 +142,8 @@ impl TrieUpdate {
             if let Some(RawStateChange { data, .. }) = changes_with_trie_key.changes.last() {
                 return Ok(data.as_ref().map(TrieUpdateValuePtr::MemoryRef));
             }
+        } else if let Some(data) = self.base.as_ref().and_then(|base| base.get(&key)) {
+            return Ok(data.as_ref().map(TrieUpdateValuePtr::MemoryRef));
         }
         self.trie.get_ref(&self.root, &key).map(|option| {
             option.map(|(length, hash)| TrieUpdateValuePtr::HashAndSize(&self.trie, length, hash))
@@ -118,6 +179,7 @@ impl TrieUpdate {
 
     pub fn finalize(self) -> Result<(TrieChanges, Vec<RawStateChangesWithTrieKey>), StorageError> {
         assert!(self.prospective.is_empty(), "Finalize cannot be called with uncommitted changes.");
+        assert!(self.base.is_none(), "Finalize cannot be called on a fork.");
         let TrieUpdate { trie, root, committed, .. } = self;
         let mut state_changes = Vec::with_capacity(committed.len());
         let trie_changes = trie.update(
@@ -235,6 +297,10 @@ impl<'a> TrieUpdateIterator<'a> {
             None => None,
         };
         trie_iter.seek(&start_offset)?;
+        let base_iter: Box<dyn Iterator<Item = _> + 'a> = match state_update.base.as_ref() {
+            Some(base) => Box::new(base.range(start_offset.clone()..)),
+            None => Box::new(std::iter::empty()),
+        };
         let committed_iter = state_update.committed.range(start_offset.clone()..).map(
             |(raw_key, changes_with_trie_key)| {
                 (
@@ -252,6 +318,10 @@ impl<'a> TrieUpdateIterator<'a> {
             .prospective
             .range(start_offset..)
             .map(|(raw_key, key_value)| (raw_key, &key_value.value));
+        let committed_iter = MergeIter {
+            left: base_iter.peekable(),
+            right: (Box::new(committed_iter) as Box<dyn Iterator<Item = _>>).peekable(),
+        };
         let overlay_iter = MergeIter {
             left: (Box::new(committed_iter) as Box<dyn Iterator<Item = _>>).peekable(),
             right: (Box::new(prospective_iter) as Box<dyn Iterator<Item = _>>).peekable(),
@@ -351,6 +421,8 @@ impl<'a> Iterator for TrieUpdateIterator<'a> {
 
 #[cfg(test)]
 mod tests {
+    use borsh::BorshSerialize;
+
     use crate::test_utils::{create_tries, create_tries_complex};
 
     use super::*;
@@ -516,4 +588,66 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn trie_fork_merge() {
+        let tries = create_tries();
+        let cause = |n: u8| StateChangeCause::ReceiptProcessing {
+            receipt_hash: CryptoHash::hash_bytes(&[n]),
+        };
+        let mut trie_update =
+            tries.new_trie_update(ShardUId::single_shard(), CryptoHash::default());
+        trie_update.set(test_key(b"dog".to_vec()), b"puppy".to_vec());
+        trie_update.set(test_key(b"cat".to_vec()), b"kitten".to_vec());
+        trie_update.commit(cause(0));
+        let trie_changes = trie_update.finalize().unwrap().0;
+        let (store_update, root) =
+            tries.apply_all(&trie_changes, ShardUId::single_shard()).unwrap();
+        store_update.commit().unwrap();
+
+        let apply_changes = |trie_update: &mut TrieUpdate, first: bool| {
+            if first {
+                trie_update.set(test_key(b"dog".to_vec()), b"dog".to_vec());
+                trie_update.commit(cause(2));
+            } else {
+                trie_update.remove(test_key(b"cat".to_vec()));
+                trie_update.set(test_key(b"cow".to_vec()), b"calf".to_vec());
+                trie_update.commit(cause(3));
+            }
+        };
+        let new_update = || {
+            let mut trie_update = tries.new_trie_update(ShardUId::single_shard(), root);
+            trie_update.set(test_key(b"dog".to_vec()), b"doggy".to_vec());
+            trie_update.commit(cause(1));
+            trie_update
+        };
+
+        let mut sequential = new_update();
+        apply_changes(&mut sequential, true);
+        apply_changes(&mut sequential, false);
+
+        let mut merged = new_update();
+        let snapshot = merged.snapshot();
+        let handle = merged.trie().handle().unwrap();
+        let mut forks: Vec<_> = (0..2)
+            .map(|_| TrieUpdate::fork(Rc::new(handle.open()), root, snapshot.clone()))
+            .collect();
+        assert_eq!(forks[0].get(&test_key(b"dog".to_vec())), Ok(Some(b"doggy".to_vec())));
+        apply_changes(&mut forks[1], false);
+        apply_changes(&mut forks[0], true);
+        let values: Vec<Vec<u8>> =
+            forks[1].iter(&test_key(b"".to_vec()).to_vec()).unwrap().map(Result::unwrap).collect();
+        assert_eq!(
+            values,
+            vec![test_key(b"cow".to_vec()).to_vec(), test_key(b"dog".to_vec()).to_vec()]
+        );
+        for fork in forks {
+            merged.merge(fork.into_committed());
+        }
+
+        let (merged_trie_changes, merged_state_changes) = merged.finalize().unwrap();
+        let (trie_changes, state_changes) = sequential.finalize().unwrap();
+        assert_eq!(merged_trie_changes, trie_changes);
+        assert_eq!(merged_state_changes.try_to_vec().unwrap(), state_changes.try_to_vec().unwrap());
+    }
 }
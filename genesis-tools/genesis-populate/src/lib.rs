@@ -235,6 +235,7 @@ impl GenesisBuilder {
                     0,
                     self.genesis.config.gas_limit.clone(),
                     0,
+                    None,
                 ),
             );
         }
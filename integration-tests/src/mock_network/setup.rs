@@ -11,6 +11,7 @@ use near_client::AdversarialControls;
 use near_client::{start_client, start_view_client, ClientActor, ViewClientActor};
 #[cfg(feature = "mock_network")]
 use near_epoch_manager::EpochManager;
+use near_network::routing::start_routing_table_actor;
 use near_network::test_utils::NetworkRecipient;
 use near_network::types::NetworkClientMessages;
 use near_primitives::network::PeerId;
@@ -338,6 +339,10 @@ pub fn setup_mock_network(
             config.genesis.config,
             client_actor.clone(),
             view_client.clone(),
+            start_routing_table_actor(
+                PeerId::new(config.network_config.public_key.clone()),
+                client_runtime.get_store(),
+            ),
         );
     }
     network_adapter.set_recipient(mock_network_actor.clone().recipient());
@@ -81,9 +81,15 @@ fn test_verify_block_double_sign_challenge() {
     );
     let runtime_adapter = env.clients[1].chain.runtime_adapter.clone();
     assert_eq!(
-        &validate_challenge(&*runtime_adapter, &epoch_id, genesis.hash(), &valid_challenge)
-            .unwrap()
-            .0,
+        &validate_challenge(
+            env.clients[1].chain.mut_store(),
+            &*runtime_adapter,
+            &epoch_id,
+            genesis.hash(),
+            &valid_challenge,
+        )
+        .unwrap()
+        .0,
         if b1.hash() > b2.hash() { b1.hash() } else { b2.hash() }
     );
     let invalid_challenge = Challenge::produce(
@@ -94,8 +100,14 @@ fn test_verify_block_double_sign_challenge() {
         &signer,
     );
     let runtime_adapter = env.clients[1].chain.runtime_adapter.clone();
-    assert!(validate_challenge(&*runtime_adapter, &epoch_id, genesis.hash(), &invalid_challenge,)
-        .is_err());
+    assert!(validate_challenge(
+        env.clients[1].chain.mut_store(),
+        &*runtime_adapter,
+        &epoch_id,
+        genesis.hash(),
+        &invalid_challenge,
+    )
+    .is_err());
     let b3 = env.clients[0].produce_block(3).unwrap().unwrap();
     let invalid_challenge = Challenge::produce(
         ChallengeBody::BlockDoubleSign(BlockDoubleSign {
@@ -105,8 +117,14 @@ fn test_verify_block_double_sign_challenge() {
         &signer,
     );
     let runtime_adapter = env.clients[1].chain.runtime_adapter.clone();
-    assert!(validate_challenge(&*runtime_adapter, &epoch_id, genesis.hash(), &invalid_challenge,)
-        .is_err());
+    assert!(validate_challenge(
+        env.clients[1].chain.mut_store(),
+        &*runtime_adapter,
+        &epoch_id,
+        genesis.hash(),
+        &invalid_challenge,
+    )
+    .is_err());
 
     let (_, result) = env.clients[0].process_block(b2.into(), Provenance::SYNC);
     assert!(result.is_ok());
@@ -244,7 +262,7 @@ fn test_verify_chunk_proofs_challenge_transaction_order() {
 }
 
 fn challenge(
-    env: TestEnv,
+    mut env: TestEnv,
     shard_id: usize,
     chunk: MaybeEncodedShardChunk,
     block: &Block,
@@ -260,6 +278,7 @@ fn challenge(
     );
     let runtime_adapter = env.clients[0].chain.runtime_adapter.clone();
     validate_challenge(
+        env.clients[0].chain.mut_store(),
         &*runtime_adapter,
         block.header().epoch_id(),
         block.header().prev_hash(),
@@ -315,6 +334,7 @@ fn test_verify_chunk_invalid_state_challenge() {
         0,
         1_000,
         0,
+        None,
         vec![],
         vec![],
         &vec![],
@@ -426,6 +446,7 @@ fn test_verify_chunk_invalid_state_challenge() {
     let runtime_adapter = client.chain.runtime_adapter.clone();
     assert_eq!(
         validate_challenge(
+            client.chain.mut_store(),
             &*runtime_adapter,
             block.header().epoch_id(),
             block.header().prev_hash(),
@@ -1028,6 +1028,8 @@ fn test_process_invalid_tx() {
             receiver_id: "test".parse().unwrap(),
             block_hash: *client.chain.genesis().hash(),
             actions: vec![],
+            valid_from_height: None,
+            valid_until_height: None,
         },
     );
     produce_blocks(&mut client, 12);
@@ -1044,6 +1046,8 @@ fn test_process_invalid_tx() {
             receiver_id: "test".parse().unwrap(),
             block_hash: hash(&[1]),
             actions: vec![],
+            valid_from_height: None,
+            valid_until_height: None,
         },
     );
     assert_eq!(
@@ -1052,6 +1056,45 @@ fn test_process_invalid_tx() {
     );
 }
 
+/// A chunk that ends up in a block after skipped heights still accepts the transactions that the
+/// chunk producer checked against their validity window.
+#[test]
+#[cfg(feature = "protocol_feature_tx_validity_window")]
+fn test_tx_validity_window_with_skipped_heights() {
+    let genesis = Genesis::test(vec!["test0".parse().unwrap(), "test1".parse().unwrap()], 1);
+    let mut env = TestEnv::builder(ChainGenesis::test())
+        .runtime_adapters(create_nightshade_runtimes(&genesis, 1))
+        .build();
+    let genesis_hash = *env.clients[0].chain.genesis().hash();
+    let signer = InMemorySigner::from_seed("test0".parse().unwrap(), KeyType::ED25519, "test0");
+    env.produce_block(0, 1);
+
+    // The chunk including the transaction is produced on top of height 2 for height 3.
+    let tx = Transaction {
+        valid_until_height: Some(3),
+        ..Transaction::new(
+            "test0".parse().unwrap(),
+            signer.public_key(),
+            "test1".parse().unwrap(),
+            1,
+            genesis_hash,
+        )
+    }
+    .sign(&signer);
+    let tx_hash = tx.get_hash();
+    assert_eq!(env.clients[0].process_tx(tx, false, false), NetworkClientResponses::ValidTx);
+    env.produce_block(0, 2);
+
+    // Heights 3 and 4 are skipped, so the chunk is applied at height 5.
+    env.produce_block(0, 5);
+    let block_hash = *env.clients[0].chain.get_block_by_height(5).unwrap().hash();
+    env.produce_block(0, 6);
+
+    let final_outcome = env.clients[0].chain.get_final_transaction_result(&tx_hash).unwrap();
+    assert!(matches!(final_outcome.status, FinalExecutionStatus::SuccessValue(_)));
+    assert_eq!(final_outcome.transaction_outcome.block_hash, block_hash);
+}
+
 /// If someone produce a block with Utc::now() + 1 min, we should produce a block with valid timestamp
 #[test]
 fn test_time_attack() {
@@ -2645,6 +2688,8 @@ fn test_wasmer2_upgrade() {
 
         nonce: 0,
         block_hash: CryptoHash::default(),
+        valid_from_height: None,
+        valid_until_height: None,
     };
 
     // Run the transaction & collect the logs.
@@ -2736,6 +2781,8 @@ fn test_execution_metadata() {
 
             nonce: 10,
             block_hash: tip.last_block_hash,
+            valid_from_height: None,
+            valid_until_height: None,
         }
         .sign(&signer);
         let tx_hash = tx.get_hash();
@@ -3295,6 +3342,157 @@ fn test_congestion_receipt_execution() {
     }
 }
 
+/// Calls to a hot contract congest its shard. Receipts sent to it over the per-chunk limit wait
+/// in the delayed receipts queue of the sending shard, which keeps executing its other receipts.
+#[test]
+#[cfg(feature = "protocol_feature_congestion_control")]
+fn test_hot_contract_congestion() {
+    use near_primitives::congestion_control::CONGESTED_DELAYED_RECEIPTS_GAS;
+    use near_primitives::shard_layout::{account_id_to_shard_id, ShardLayout};
+    use near_primitives::types::ShardId;
+
+    init_test_logger();
+    let accounts: Vec<AccountId> = (0..6).map(|i| format!("test{}", i).parse().unwrap()).collect();
+    let mut genesis = Genesis::test_sharded(accounts, 1, vec![1, 1]);
+    genesis.config.gas_limit = 300_000_000_000_000;
+    let chain_genesis = ChainGenesis::from(&genesis);
+    let mut env = TestEnv::builder(chain_genesis)
+        .runtime_adapters(create_nightshade_runtimes(&genesis, 1))
+        .build();
+
+    let shard_layout = ShardLayout::v0(2, 0);
+    let hot: AccountId = "test0".parse().unwrap();
+    let relay: AccountId = "test2".parse().unwrap();
+    let sender: AccountId = "test4".parse().unwrap();
+    let hot_shard_id = account_id_to_shard_id(&hot, &shard_layout);
+    let other_shard_id = account_id_to_shard_id(&relay, &shard_layout);
+    assert_ne!(hot_shard_id, other_shard_id);
+    assert_eq!(account_id_to_shard_id(&sender, &shard_layout), other_shard_id);
+    let sender_signer = InMemorySigner::from_seed(sender.clone(), KeyType::ED25519, "test4");
+    let relay_signer = InMemorySigner::from_seed(relay.clone(), KeyType::ED25519, "test2");
+
+    let mut height =
+        deploy_test_contract(&mut env, hot.clone(), near_test_contracts::rs_contract(), 3, 1);
+    height = deploy_test_contract(
+        &mut env,
+        relay.clone(),
+        near_test_contracts::rs_contract(),
+        3,
+        height,
+    );
+
+    // Flood the hot contract until its shard is congested.
+    let last_block_hash = env.clients[0].chain.head().unwrap().last_block_hash;
+    for i in 0..80 {
+        let tx = SignedTransaction::from_actions(
+            10 + i,
+            sender.clone(),
+            hot.clone(),
+            &sender_signer,
+            vec![Action::FunctionCall(FunctionCallAction {
+                method_name: "loop_forever".to_string(),
+                args: vec![],
+                gas: 250_000_000_000_000,
+                deposit: 0,
+            })],
+            last_block_hash,
+        );
+        env.clients[0].process_tx(tx, false, false);
+    }
+    let delayed_receipts_gas = |env: &mut TestEnv, height: BlockHeight, shard_id: ShardId| {
+        let block = env.clients[0].chain.get_block_by_height(height).unwrap();
+        block.chunks()[shard_id as usize].delayed_receipts_gas().unwrap_or_default()
+    };
+    let congested_height = (height..height + 10)
+        .find(|&h| {
+            env.produce_block(0, h);
+            delayed_receipts_gas(&mut env, h, hot_shard_id) > CONGESTED_DELAYED_RECEIPTS_GAS
+        })
+        .expect("the hot contract shard should get congested");
+    height = congested_height + 1;
+
+    // Calls through the relay on the other shard create receipts to the hot contract. Only two of
+    // them fit into the limit of each chunk, the others are stalled.
+    let last_block_hash = env.clients[0].chain.head().unwrap().last_block_hash;
+    let args = serde_json::json!([
+        {"create": {
+        "account_id": hot.as_ref(),
+        "method_name": "loop_forever",
+        "arguments": [],
+        "amount": "0",
+        "gas": 150_000_000_000_000u64,
+        }, "id": 0 }
+    ]);
+    let mut relay_tx_hashes = vec![];
+    for i in 0..10 {
+        let tx = SignedTransaction::from_actions(
+            100 + i,
+            sender.clone(),
+            relay.clone(),
+            &sender_signer,
+            vec![Action::FunctionCall(FunctionCallAction {
+                method_name: "call_promise".to_string(),
+                args: serde_json::to_vec(&args).unwrap(),
+                gas: 200_000_000_000_000,
+                deposit: 0,
+            })],
+            last_block_hash,
+        );
+        relay_tx_hashes.push(tx.get_hash());
+        env.clients[0].process_tx(tx, false, false);
+    }
+    let transfer = SignedTransaction::send_money(
+        100,
+        relay.clone(),
+        sender.clone(),
+        &relay_signer,
+        1,
+        last_block_hash,
+    );
+    let transfer_hash = transfer.get_hash();
+    env.clients[0].process_tx(transfer, false, false);
+
+    let mut max_stalled_gas = 0;
+    for _ in 0..10 {
+        let congested = delayed_receipts_gas(&mut env, height - 1, hot_shard_id)
+            > CONGESTED_DELAYED_RECEIPTS_GAS;
+        env.produce_block(0, height);
+        let block_hash = *env.clients[0].chain.get_block_by_height(height).unwrap().hash();
+        let sent_to_hot = env.clients[0]
+            .chain
+            .mut_store()
+            .get_outgoing_receipts(&block_hash, other_shard_id)
+            .map(|receipts| receipts.iter().filter(|receipt| receipt.receiver_id == hot).count())
+            .unwrap_or_default();
+        if congested {
+            assert!(sent_to_hot <= 2, "{} receipts sent to a congested shard", sent_to_hot);
+        }
+        max_stalled_gas =
+            max_stalled_gas.max(delayed_receipts_gas(&mut env, height, other_shard_id));
+        height += 1;
+    }
+    assert!(max_stalled_gas > 0);
+    // The transfer on the other shard isn't held back by the stalled receipts.
+    let final_outcome = env.clients[0].chain.get_final_transaction_result(&transfer_hash).unwrap();
+    assert!(matches!(final_outcome.status, FinalExecutionStatus::SuccessValue(_)));
+    for receipt_outcome in &final_outcome.receipts_outcome {
+        let header = env.clients[0].chain.get_block_header(&receipt_outcome.block_hash).unwrap();
+        assert!(header.height() <= congested_height + 3);
+    }
+
+    // Eventually every call reaches the hot contract.
+    for i in height..height + 100 {
+        env.produce_block(0, i);
+    }
+    for tx_hash in &relay_tx_hashes {
+        let final_outcome = env.clients[0].chain.get_final_transaction_result(tx_hash).unwrap();
+        assert!(final_outcome
+            .receipts_outcome
+            .iter()
+            .any(|outcome| outcome.outcome.executor_id == hot));
+    }
+}
+
 #[test]
 fn test_validator_stake_host_function() {
     init_test_logger();
@@ -3382,6 +3580,8 @@ fn test_limit_contract_functions_number_upgrade() {
 
         nonce: 0,
         block_hash: CryptoHash::default(),
+        valid_from_height: None,
+        valid_until_height: None,
     };
 
     // Run the transaction & get tx outcome.
@@ -3494,6 +3694,8 @@ fn test_deploy_cost_increased() {
         actions: vec![Action::DeployContract(DeployContractAction { code: test_contract })],
         nonce: 0,
         block_hash: CryptoHash::default(),
+        valid_from_height: None,
+        valid_until_height: None,
     };
 
     // Run the transaction & get tx outcome in a closure.
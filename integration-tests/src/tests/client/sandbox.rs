@@ -9,7 +9,7 @@ use near_primitives::account::Account;
 use near_primitives::serialize::{from_base64, to_base64};
 use near_primitives::state_record::StateRecord;
 use near_primitives::transaction::{
-    Action, DeployContractAction, FunctionCallAction, SignedTransaction,
+    Action, DeployContractAction, FunctionCallAction, SignedTransaction, TransferAction,
 };
 use near_primitives::types::{AccountId, BlockHeight, Nonce};
 use near_store::test_utils::create_test_store;
@@ -107,3 +107,51 @@ fn test_patch_account() {
     let test1_after = env.query_account("test1".parse().unwrap());
     assert_eq!(test1_after.amount, 10);
 }
+
+#[test]
+fn test_snapshot_revert() {
+    let (mut env, signer) = test_setup();
+    let snapshot = env.clients[0].chain.head().unwrap();
+    let state = env.query_state("test0".parse().unwrap());
+
+    env.clients[0].chain.patch_state(vec![StateRecord::Data {
+        account_id: "test0".parse().unwrap(),
+        data_key: from_base64(&state[0].key).unwrap(),
+        value: b"world".to_vec(),
+    }]);
+    do_blocks(&mut env, 9, 20);
+    assert_eq!(env.query_state("test0".parse().unwrap())[0].value, to_base64(b"world"));
+    send_tx(
+        &mut env,
+        3,
+        "test0".parse().unwrap(),
+        "test1".parse().unwrap(),
+        &signer,
+        vec![Action::Transfer(TransferAction { deposit: 1 })],
+    );
+    assert!(env.clients[0].shards_mgr.get_pool_iterator(0).is_some());
+
+    env.clients[0].sandbox_revert(&snapshot.last_block_hash).unwrap();
+    assert_eq!(env.clients[0].chain.head().unwrap(), snapshot);
+    assert_eq!(env.clients[0].chain.header_head().unwrap(), snapshot);
+    assert!(env.clients[0].shards_mgr.get_pool_iterator(0).is_none());
+    assert_eq!(env.query_state("test0".parse().unwrap()), state);
+
+    // Heights produced before the revert are skipped.
+    do_blocks(&mut env, 20, 25);
+    let head = env.clients[0].chain.head().unwrap();
+    assert_eq!(head.height, 24);
+    assert_eq!(env.query_state("test0".parse().unwrap()), state);
+}
+
+#[test]
+fn test_revert_to_garbage_collected_block() {
+    let (mut env, _signer) = test_setup();
+    let snapshot = env.clients[0].chain.head().unwrap();
+    do_blocks(&mut env, 9, 100);
+    assert!(!env.clients[0].chain.block_exists(&snapshot.last_block_hash).unwrap());
+
+    let err = env.clients[0].sandbox_revert(&snapshot.last_block_hash).unwrap_err();
+    assert!(err.to_string().contains("garbage collected"), "{}", err);
+    assert_eq!(env.clients[0].chain.head().unwrap().height, 99);
+}
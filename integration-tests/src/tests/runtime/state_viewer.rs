@@ -1,9 +1,12 @@
 use crate::runtime_utils::{get_runtime_and_trie, get_test_trie_viewer, TEST_SHARD_UID};
+use near_crypto::{KeyType, PublicKey};
 use near_primitives::{
     account::Account,
     hash::hash as sha256,
     hash::CryptoHash,
-    views::{StateItem, ViewApplyState},
+    receipt::{ActionReceipt, DelayedReceiptIndices, Receipt, ReceiptEnum},
+    transaction::{Action, FunctionCallAction},
+    views::{ReceiptView, StateItem, StoragePrefixView, ViewApplyState},
 };
 use near_primitives::{
     test_utils::MockEpochInfoProvider,
@@ -11,9 +14,11 @@ use near_primitives::{
     types::{EpochId, StateChangeCause},
     version::PROTOCOL_VERSION,
 };
-use near_store::set_account;
+use near_store::{set, set_account};
+use node_runtime::config::RuntimeConfig;
 use node_runtime::state_viewer::errors;
 use node_runtime::state_viewer::*;
+use node_runtime::Runtime;
 use testlib::runtime_utils::{alice_account, encode_int};
 
 #[test]
@@ -179,6 +184,114 @@ fn test_view_state_with_large_contract() {
     assert!(result.is_ok());
 }
 
+#[test]
+fn test_view_storage_breakdown() {
+    let (_, tries, root) = get_runtime_and_trie();
+    let config = RuntimeConfig::test().transaction_costs.storage_usage_config;
+    let trie_viewer = TrieViewer::default();
+    let state_update = tries.new_trie_update(TEST_SHARD_UID, root);
+    let result =
+        trie_viewer.view_storage_breakdown(&state_update, &alice_account(), None, &config).unwrap();
+    assert_eq!(result.account, config.num_bytes_account);
+    assert_eq!(result.contract_code, 0);
+    assert_eq!(result.num_access_keys, 1);
+    assert_eq!(result.contract_data, vec![]);
+    assert_eq!(result.total, result.stored_total);
+    let stored_total = result.stored_total;
+
+    // Data written directly to the state is not charged to the account.
+    let mut state_update = tries.new_trie_update(TEST_SHARD_UID, root);
+    for (key, value) in
+        [(b"aa".to_vec(), b"1".to_vec()), (b"ab".to_vec(), b"12".to_vec()), (b"b".to_vec(), vec![])]
+    {
+        state_update.set(TrieKey::ContractData { account_id: alice_account(), key }, value);
+    }
+    state_update.commit(StateChangeCause::InitialState);
+    let trie_changes = state_update.finalize().unwrap().0;
+    let (db_changes, new_root) = tries.apply_all(&trie_changes, TEST_SHARD_UID).unwrap();
+    db_changes.commit().unwrap();
+
+    let state_update = tries.new_trie_update(TEST_SHARD_UID, new_root);
+    let result =
+        trie_viewer.view_storage_breakdown(&state_update, &alice_account(), None, &config).unwrap();
+    let extra = config.num_extra_bytes_record;
+    assert_eq!(
+        result.contract_data,
+        vec![
+            StoragePrefixView {
+                prefix: b"a".to_vec().into(),
+                num_records: 2,
+                storage_usage: 2 * extra + 7
+            },
+            StoragePrefixView {
+                prefix: b"b".to_vec().into(),
+                num_records: 1,
+                storage_usage: extra + 1
+            },
+        ]
+    );
+    assert_eq!(result.stored_total, stored_total);
+    assert_eq!(result.total, stored_total + 3 * extra + 8);
+
+    let result = trie_viewer
+        .view_storage_breakdown(&state_update, &alice_account(), Some(2), &config)
+        .unwrap();
+    let prefixes: Vec<_> = result.contract_data.iter().map(|data| data.prefix.clone()).collect();
+    assert_eq!(prefixes, vec![b"aa".to_vec().into(), b"ab".to_vec().into(), b"b".to_vec().into()]);
+}
+
+#[test]
+fn test_view_delayed_receipts() {
+    let (_, tries, root) = get_runtime_and_trie();
+    let mut state_update = tries.new_trie_update(TEST_SHARD_UID, root);
+    let mut indices = DelayedReceiptIndices::default();
+    let receipts: Vec<Receipt> = (0..3u8)
+        .map(|i| Receipt {
+            predecessor_id: alice_account(),
+            receiver_id: "test.contract".parse().unwrap(),
+            receipt_id: sha256(&[i]),
+            receipt: ReceiptEnum::Action(ActionReceipt {
+                signer_id: alice_account(),
+                signer_public_key: PublicKey::empty(KeyType::ED25519),
+                gas_price: 100,
+                output_data_receivers: vec![],
+                input_data_ids: vec![],
+                actions: vec![Action::FunctionCall(FunctionCallAction {
+                    method_name: "run_test".to_string(),
+                    args: vec![],
+                    gas: 1_000,
+                    deposit: 0,
+                })],
+            }),
+        })
+        .collect();
+    for receipt in &receipts {
+        Runtime::delay_receipt(&mut state_update, &mut indices, receipt).unwrap();
+    }
+    // The first receipt has already been processed.
+    state_update.remove(TrieKey::DelayedReceipt { index: 0 });
+    indices.first_index = 1;
+    set(&mut state_update, TrieKey::DelayedReceiptIndices, &indices);
+    set(&mut state_update, TrieKey::DelayedReceiptsGas, &5_000u64);
+
+    let trie_viewer = TrieViewer::default();
+    let result = trie_viewer.view_delayed_receipts(&state_update, 0, None, 1).unwrap();
+    assert_eq!(result.first_index, 1);
+    assert_eq!(result.length, 2);
+    assert_eq!(result.total_gas, 5_000);
+    assert_eq!(result.oldest_receipt_id, Some(receipts[1].receipt_id));
+    assert_eq!(result.receipts, vec![ReceiptView::from(receipts[1].clone())]);
+
+    let result = trie_viewer.view_delayed_receipts(&state_update, 0, Some(2), 10).unwrap();
+    assert_eq!(result.receipts, vec![ReceiptView::from(receipts[2].clone())]);
+
+    // Receipts outside of the requested page aren't read.
+    state_update.remove(TrieKey::DelayedReceipt { index: 2 });
+    let result = trie_viewer.view_delayed_receipts(&state_update, 0, None, 1).unwrap();
+    assert_eq!(result.length, 2);
+    assert_eq!(result.receipts, vec![ReceiptView::from(receipts[1].clone())]);
+}
+
 #[test]
 fn test_log_when_panic() {
     let (viewer, root) = get_test_trie_viewer();
@@ -139,10 +139,12 @@ impl RuntimeUser {
     fn apply_state(&self) -> ApplyState {
         ApplyState {
             block_index: 1,
+            transaction_validity_height: 1,
             prev_block_hash: Default::default(),
             block_hash: Default::default(),
             block_timestamp: 0,
             epoch_height: 0,
+            shard_id: 0,
             gas_price: MIN_GAS_PRICE,
             gas_limit: None,
             random_seed: Default::default(),
@@ -153,6 +155,7 @@ impl RuntimeUser {
             is_new_chunk: true,
             migration_data: Arc::new(MigrationData::default()),
             migration_flags: MigrationFlags::default(),
+            congestion_info: Default::default(),
         }
     }
 
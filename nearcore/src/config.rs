@@ -1,6 +1,8 @@
+use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
 use std::io::{Read, Write};
+use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
@@ -25,7 +27,9 @@ use near_crypto::{InMemorySigner, KeyFile, KeyType, PublicKey, Signer};
 #[cfg(feature = "json_rpc")]
 use near_jsonrpc::RpcConfig;
 use near_network::test_utils::open_port;
-use near_network_primitives::types::{NetworkConfig, ROUTED_MESSAGE_TTL};
+use near_network_primitives::types::{
+    MessageRateLimit, MessageRateLimits, NetworkConfig, ROUTED_MESSAGE_TTL,
+};
 use near_primitives::account::{AccessKey, Account};
 use near_primitives::hash::CryptoHash;
 #[cfg(test)]
@@ -197,11 +201,20 @@ fn default_peer_stats_period() -> Duration {
     Duration::from_secs(5)
 }
 
+fn default_rate_limited_messages_window() -> Duration {
+    Duration::from_secs(60)
+}
+
+fn default_allow_unencrypted_peers() -> bool {
+    true
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Network {
     /// Address to listen for incoming connections.
     pub addr: String,
-    /// Address to advertise to peers for them to connect.
+    /// Comma separated list of addresses to advertise to peers for them to connect.
+    /// Validators announce them to other validators to keep direct connections with them.
     /// If empty, will use the same port as the addr, and will introspect on the listener.
     pub external_address: String,
     /// Comma separated list of nodes to connect to.
@@ -247,6 +260,30 @@ pub struct Network {
     /// Period to check on peer status
     #[serde(default = "default_peer_stats_period")]
     pub peer_stats_period: Duration,
+    /// Limits on the rate of messages received from a single peer, keyed by message type
+    /// (`PeerMessage` variant, or `RoutedMessageBody` variant for routed messages).
+    #[serde(default)]
+    pub message_rate_limits: HashMap<String, MessageRateLimitConfig>,
+    /// Number of messages dropped due to `message_rate_limits` within
+    /// `rate_limited_messages_window` after which the peer is banned.
+    /// If not set, messages over the limits are dropped, but the peer is not banned.
+    #[serde(default)]
+    pub max_rate_limited_messages_before_ban: Option<u64>,
+    /// Period in which the dropped messages are counted towards the ban.
+    #[serde(default = "default_rate_limited_messages_window")]
+    pub rate_limited_messages_window: Duration,
+    /// Whether to keep connections with peers whose protocol version doesn't support the
+    /// encrypted transport. Peers which support it always use it.
+    #[serde(default = "default_allow_unencrypted_peers")]
+    pub allow_unencrypted_peers: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MessageRateLimitConfig {
+    /// Maximum number of messages accepted in a burst.
+    pub burst: u32,
+    /// Number of messages per second accepted in the long run.
+    pub qps: f64,
 }
 
 impl Default for Network {
@@ -269,7 +306,26 @@ impl Default for Network {
             blacklist: vec![],
             ttl_account_id_router: default_ttl_account_id_router(),
             peer_stats_period: default_peer_stats_period(),
+            message_rate_limits: HashMap::default(),
+            max_rate_limited_messages_before_ban: None,
+            rate_limited_messages_window: default_rate_limited_messages_window(),
+            allow_unencrypted_peers: default_allow_unencrypted_peers(),
+        }
+    }
+}
+
+impl Network {
+    /// Parses `external_address` into the list of addresses announced by this node.
+    fn public_addrs(&self) -> anyhow::Result<Vec<SocketAddr>> {
+        if self.external_address.is_empty() {
+            return Ok(vec![]);
         }
+        self.external_address
+            .split(',')
+            .map(|addr| {
+                addr.parse().with_context(|| format!("Failed to parse external address {}", addr))
+            })
+            .collect()
     }
 }
 
@@ -458,6 +514,17 @@ pub struct Config {
     pub db_migration_snapshot_path: Option<PathBuf>,
     #[serde(default = "default_enable_rocksdb_statistics")]
     pub enable_rocksdb_statistics: bool,
+    /// Execute receipts with different receivers within a chunk in parallel.
+    #[serde(default)]
+    pub parallel_receipts: bool,
+    /// Record the host function calls made by contracts, returned in the metadata of the
+    /// `EXPERIMENTAL_tx_status` receipt outcomes. Slows down function calls.
+    #[serde(default)]
+    pub trace_host_functions: bool,
+    /// Collect the amounts burnt by each account while applying chunks, so that the balance
+    /// mismatch report breaks them down by account. Adds bookkeeping to every chunk.
+    #[serde(default)]
+    pub balance_mismatch_breakdown: bool,
 }
 
 impl Default for Config {
@@ -487,6 +554,9 @@ impl Default for Config {
             db_migration_snapshot_path: None,
             use_db_migration_snapshot: true,
             enable_rocksdb_statistics: false,
+            parallel_receipts: false,
+            trace_host_functions: false,
+            balance_mismatch_breakdown: false,
         }
     }
 }
@@ -496,7 +566,7 @@ impl Config {
         let mut unrecognised_fields = Vec::new();
         let s = std::fs::read_to_string(path)
             .with_context(|| format!("Failed to read config from {}", path.display()))?;
-        let config =
+        let config: Config =
             serde_ignored::deserialize(&mut serde_json::Deserializer::from_str(&s), |path| {
                 unrecognised_fields.push(path.to_string());
             })
@@ -505,6 +575,10 @@ impl Config {
         if !unrecognised_fields.is_empty() {
             warn!("{}: encountered unrecognised fields: {:?}", path.display(), unrecognised_fields);
         }
+        config
+            .network
+            .public_addrs()
+            .with_context(|| format!("Invalid network config in {}", path.display()))?;
 
         Ok(config)
     }
@@ -652,6 +726,8 @@ impl NearConfig {
         network_key_pair: KeyFile,
         validator_signer: Option<Arc<dyn ValidatorSigner>>,
     ) -> Self {
+        // Checked when the config is loaded from file.
+        let public_addrs = config.network.public_addrs().unwrap_or_default();
         NearConfig {
             config: config.clone(),
             client_config: ClientConfig {
@@ -742,6 +818,20 @@ impl NearConfig {
                 blacklist: config.network.blacklist,
                 outbound_disabled: false,
                 archive: config.archive,
+                message_rate_limits: MessageRateLimits {
+                    limits: (config.network.message_rate_limits.into_iter())
+                        .map(|(msg_type, limit)| {
+                            (msg_type, MessageRateLimit { burst: limit.burst, qps: limit.qps })
+                        })
+                        .collect(),
+                    max_dropped_messages_before_ban: config
+                        .network
+                        .max_rate_limited_messages_before_ban,
+                    dropped_messages_window: config.network.rate_limited_messages_window,
+                },
+                allow_unencrypted_peers: config.network.allow_unencrypted_peers,
+                public_addrs,
+                validator_signer: validator_signer.clone(),
             },
             telemetry_config: config.telemetry,
             #[cfg(feature = "json_rpc")]
@@ -875,6 +965,23 @@ fn generate_or_load_key(
     }
 }
 
+#[test]
+fn test_config_external_address() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join(CONFIG_FILENAME);
+
+    let mut config = Config::default();
+    config.network.external_address = "1.2.3.4:24567,[::1]:24567".to_string();
+    config.write_to_file(&path).unwrap();
+    let config = Config::from_file(&path).unwrap();
+    assert_eq!(config.network.public_addrs().unwrap().len(), 2);
+
+    let mut config = Config::default();
+    config.network.external_address = "1.2.3.4:24567,localhost".to_string();
+    config.write_to_file(&path).unwrap();
+    assert!(Config::from_file(&path).is_err());
+}
+
 #[test]
 fn test_generate_or_load_key() {
     let tmp = tempfile::tempdir().unwrap();
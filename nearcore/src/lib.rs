@@ -449,7 +449,7 @@ pub fn start_with_config_and_synchronization(
     let network_config = config.network_config;
     let routing_table_addr =
         start_routing_table_actor(PeerId::new(network_config.public_key.clone()), store.clone());
-    #[cfg(all(feature = "json_rpc", feature = "test_features"))]
+    #[cfg(feature = "json_rpc")]
     let routing_table_addr2 = routing_table_addr.clone();
     let network_actor = PeerManagerActor::start_in_arbiter(&arbiter.handle(), move |_ctx| {
         PeerManagerActor::new(
@@ -471,7 +471,6 @@ pub fn start_with_config_and_synchronization(
             view_client.clone(),
             #[cfg(feature = "test_features")]
             network_actor.clone(),
-            #[cfg(feature = "test_features")]
             routing_table_addr2,
         ));
     }
@@ -78,6 +78,7 @@ fn apply_block_at_height(
             prev_block.header().gas_price(),
             chunk_header.gas_limit(),
             block.header().challenges_result(),
+            &Default::default(),
             *block.header().random_value(),
             true,
             is_first_block_with_chunk_of_version,
@@ -245,6 +246,7 @@ pub fn migrate_19_to_20(path: &Path, near_config: &NearConfig) {
                             block.header().gas_price(),
                             new_extra.gas_limit(),
                             block.header().challenges_result(),
+                            &Default::default(),
                             *block.header().random_value(),
                             // doesn't really matter here since the old blocks are on the old version
                             false,
@@ -315,6 +317,7 @@ pub fn migrate_22_to_23(path: &Path, near_config: &NearConfig) {
                         block.header().gas_price(),
                         chunk_header.gas_limit(),
                         block.header().challenges_result(),
+                        &Default::default(),
                         *block.header().random_value(),
                         true,
                         false,
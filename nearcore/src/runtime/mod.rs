@@ -21,6 +21,7 @@ use near_pool::types::PoolIterator;
 use near_primitives::account::{AccessKey, Account};
 use near_primitives::block::{Approval, ApprovalInner};
 use near_primitives::challenge::ChallengesResult;
+use near_primitives::congestion_control::BlockCongestionInfo;
 use near_primitives::contract::ContractCode;
 use near_primitives::epoch_manager::block_info::BlockInfo;
 use near_primitives::epoch_manager::epoch_info::EpochInfo;
@@ -40,8 +41,8 @@ use near_primitives::types::{
 };
 use near_primitives::version::ProtocolVersion;
 use near_primitives::views::{
-    AccessKeyInfoView, CallResult, EpochValidatorInfo, QueryRequest, QueryResponse,
-    QueryResponseKind, ViewApplyState, ViewStateResult,
+    AccessKeyInfoView, CallResult, DelayedReceiptsView, EpochValidatorInfo, QueryRequest,
+    QueryResponse, QueryResponseKind, StorageBreakdownView, ViewApplyState, ViewStateResult,
 };
 use near_store::{
     get_genesis_hash, get_genesis_state_roots, set_genesis_hash, set_genesis_state_roots,
@@ -62,6 +63,7 @@ use crate::shard_tracker::{ShardTracker, TrackedConfig};
 use crate::NearConfig;
 use errors::FromStateViewerErrors;
 use near_primitives::runtime::config_store::{RuntimeConfigStore, INITIAL_TESTNET_CONFIG};
+use near_primitives::runtime::fees::StorageUsageConfig;
 use near_primitives::runtime::migration_data::{MigrationData, MigrationFlags};
 use near_primitives::shard_layout::{
     account_id_to_shard_id, account_id_to_shard_uid, ShardLayout, ShardUId,
@@ -151,7 +153,7 @@ impl NightshadeRuntime {
         trie_viewer_state_size_limit: Option<u64>,
         max_gas_burnt_view: Option<Gas>,
     ) -> Self {
-        Self::new(
+        let mut runtime = Self::new(
             home_dir,
             store,
             &config.genesis,
@@ -159,7 +161,12 @@ impl NightshadeRuntime {
             trie_viewer_state_size_limit,
             max_gas_burnt_view,
             None,
-        )
+        );
+        runtime.runtime = Runtime::new()
+            .with_parallel_receipts(config.config.parallel_receipts)
+            .with_host_function_tracing(config.config.trace_host_functions)
+            .with_balance_mismatch_breakdown(config.config.balance_mismatch_breakdown);
+        runtime
     }
 
     pub fn new(
@@ -421,6 +428,7 @@ impl NightshadeRuntime {
         gas_price: Balance,
         gas_limit: Gas,
         challenges_result: &ChallengesResult,
+        congestion_info: &BlockCongestionInfo,
         random_seed: CryptoHash,
         is_new_chunk: bool,
         is_first_block_with_chunk_of_version: bool,
@@ -510,6 +518,10 @@ impl NightshadeRuntime {
         let current_protocol_version = self.get_epoch_protocol_version(&epoch_id)?;
         let prev_block_protocol_version = self.get_epoch_protocol_version(&prev_block_epoch_id)?;
         let is_first_block_of_version = current_protocol_version != prev_block_protocol_version;
+        // Transactions are checked at the height the chunk producer checked them at, so that a
+        // chunk included after skipped heights doesn't become invalid.
+        let transaction_validity_height =
+            *self.epoch_manager.read().get_block_info(prev_block_hash)?.height() + 1;
 
         debug!(target: "runtime",
                "epoch height: {:?}, epoch id: {:?}, current_protocol_version: {:?}, is_first_block_of_version: {}",
@@ -518,10 +530,12 @@ impl NightshadeRuntime {
 
         let apply_state = ApplyState {
             block_index: block_height,
+            transaction_validity_height,
             prev_block_hash: *prev_block_hash,
             block_hash: *block_hash,
             epoch_id,
             epoch_height,
+            shard_id,
             gas_price,
             block_timestamp,
             gas_limit: Some(gas_limit),
@@ -535,6 +549,7 @@ impl NightshadeRuntime {
                 is_first_block_of_version,
                 is_first_block_with_chunk_of_version,
             },
+            congestion_info: congestion_info.clone(),
         };
 
         let instant = Instant::now();
@@ -602,11 +617,57 @@ impl NightshadeRuntime {
             total_balance_burnt,
             proof: apply_result.proof,
             processed_delayed_receipts: apply_result.processed_delayed_receipts,
+            delayed_receipts_gas: apply_result.delayed_receipts_gas,
         };
 
         Ok(result)
     }
 
+    /// Applies the chunk like `apply_transactions`, but on top of the given trie, which may hold
+    /// changes not committed to the store (see `Trie::with_changes`). Used by tools to replay
+    /// chunks on a modified state.
+    pub fn apply_transactions_on_trie(
+        &self,
+        trie: Trie,
+        shard_id: ShardId,
+        state_root: &StateRoot,
+        height: BlockHeight,
+        block_timestamp: u64,
+        prev_block_hash: &CryptoHash,
+        block_hash: &CryptoHash,
+        receipts: &[Receipt],
+        transactions: &[SignedTransaction],
+        last_validator_proposals: ValidatorStakeIter,
+        gas_price: Balance,
+        gas_limit: Gas,
+        challenges: &ChallengesResult,
+        congestion_info: &BlockCongestionInfo,
+        random_seed: CryptoHash,
+        is_new_chunk: bool,
+        is_first_block_with_chunk_of_version: bool,
+    ) -> Result<ApplyTransactionResult, Error> {
+        self.process_state_update(
+            trie,
+            *state_root,
+            shard_id,
+            height,
+            block_hash,
+            block_timestamp,
+            prev_block_hash,
+            receipts,
+            transactions,
+            last_validator_proposals,
+            gas_price,
+            gas_limit,
+            challenges,
+            congestion_info,
+            random_seed,
+            is_new_chunk,
+            is_first_block_with_chunk_of_version,
+            None,
+        )
+    }
+
     fn precompile_contracts(
         &self,
         epoch_id: &EpochId,
@@ -726,6 +787,7 @@ impl RuntimeAdapter for NightshadeRuntime {
         &self,
         gas_price: Balance,
         state_root: Option<StateRoot>,
+        next_block_height: BlockHeight,
         transaction: &SignedTransaction,
         verify_signature: bool,
         epoch_id: &EpochId,
@@ -737,6 +799,7 @@ impl RuntimeAdapter for NightshadeRuntime {
             let shard_uid =
                 self.account_id_to_shard_uid(&transaction.transaction.signer_id, epoch_id)?;
             let mut state_update = self.tries.new_trie_update(shard_uid, state_root);
+            let epoch_height = self.epoch_manager.read().get_epoch_info(epoch_id)?.epoch_height();
 
             match verify_and_charge_transaction(
                 runtime_config,
@@ -744,9 +807,8 @@ impl RuntimeAdapter for NightshadeRuntime {
                 gas_price,
                 transaction,
                 verify_signature,
-                // here we do not know which block the transaction will be included
-                // and therefore skip the check on the nonce upper bound.
-                None,
+                next_block_height,
+                Some(epoch_height),
                 current_protocol_version,
             ) {
                 Ok(_) => Ok(None),
@@ -804,6 +866,7 @@ impl RuntimeAdapter for NightshadeRuntime {
         let mut num_checked_transactions = 0;
 
         let runtime_config = self.runtime_config_store.get_config(current_protocol_version);
+        let epoch_height = self.epoch_manager.read().get_epoch_info(epoch_id)?.epoch_height();
 
         while total_gas_burnt < transactions_gas_limit {
             if let Some(iter) = pool_iterator.next() {
@@ -818,7 +881,8 @@ impl RuntimeAdapter for NightshadeRuntime {
                             gas_price,
                             &tx,
                             false,
-                            Some(next_block_height),
+                            next_block_height,
+                            Some(epoch_height),
                             current_protocol_version,
                         ) {
                             Ok(verification_result) => {
@@ -1371,6 +1435,7 @@ impl RuntimeAdapter for NightshadeRuntime {
         gas_price: Balance,
         gas_limit: Gas,
         challenges: &ChallengesResult,
+        congestion_info: &BlockCongestionInfo,
         random_seed: CryptoHash,
         generate_storage_proof: bool,
         is_new_chunk: bool,
@@ -1393,6 +1458,7 @@ impl RuntimeAdapter for NightshadeRuntime {
             gas_price,
             gas_limit,
             challenges,
+            congestion_info,
             random_seed,
             is_new_chunk,
             is_first_block_with_chunk_of_version,
@@ -1423,6 +1489,7 @@ impl RuntimeAdapter for NightshadeRuntime {
         gas_price: Balance,
         gas_limit: Gas,
         challenges: &ChallengesResult,
+        congestion_info: &BlockCongestionInfo,
         random_value: CryptoHash,
         is_new_chunk: bool,
         is_first_block_with_chunk_of_version: bool,
@@ -1442,6 +1509,7 @@ impl RuntimeAdapter for NightshadeRuntime {
             gas_price,
             gas_limit,
             challenges,
+            congestion_info,
             random_value,
             is_new_chunk,
             is_first_block_with_chunk_of_version,
@@ -1544,6 +1612,45 @@ impl RuntimeAdapter for NightshadeRuntime {
                     block_hash: *block_hash,
                 })
             }
+            QueryRequest::ViewStorageBreakdown { account_id, data_prefix_len } => {
+                let protocol_version = self
+                    .epoch_manager
+                    .read()
+                    .get_epoch_info(epoch_id)
+                    .map_err(|err| {
+                        near_chain::near_chain_primitives::error::QueryError::from_epoch_error(
+                            err,
+                            block_height,
+                            *block_hash,
+                        )
+                    })?
+                    .protocol_version();
+                let storage_usage_config = &self
+                    .runtime_config_store
+                    .get_config(protocol_version)
+                    .transaction_costs
+                    .storage_usage_config;
+                let storage_breakdown = self
+                    .view_storage_breakdown(
+                        &shard_uid,
+                        *state_root,
+                        account_id,
+                        *data_prefix_len,
+                        storage_usage_config,
+                    )
+                    .map_err(|err| {
+                        near_chain::near_chain_primitives::error::QueryError::from_view_state_error(
+                            err,
+                            block_height,
+                            *block_hash,
+                        )
+                    })?;
+                Ok(QueryResponse {
+                    kind: QueryResponseKind::StorageBreakdown(storage_breakdown),
+                    block_height,
+                    block_hash: *block_hash,
+                })
+            }
             QueryRequest::ViewAccessKeyList { account_id } => {
                 let access_key_list =
                     self.view_access_keys(&shard_uid, *state_root, account_id).map_err(|err| {
@@ -1586,6 +1693,17 @@ impl RuntimeAdapter for NightshadeRuntime {
         }
     }
 
+    fn get_delayed_receipts(
+        &self,
+        shard_uid: ShardUId,
+        state_root: &StateRoot,
+        from_index: Option<u64>,
+        limit: u64,
+    ) -> Result<DelayedReceiptsView, Error> {
+        self.view_delayed_receipts(&shard_uid, *state_root, from_index, limit)
+            .map_err(|err| ErrorKind::Other(err.to_string()).into())
+    }
+
     fn get_validator_info(
         &self,
         epoch_id: ValidatorInfoIdentifier,
@@ -1954,6 +2072,40 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
         let state_update = self.tries.new_trie_update_view(*shard_uid, state_root);
         self.trie_viewer.view_state(&state_update, account_id, prefix)
     }
+
+    fn view_delayed_receipts(
+        &self,
+        shard_uid: &ShardUId,
+        state_root: MerkleHash,
+        from_index: Option<u64>,
+        limit: u64,
+    ) -> Result<DelayedReceiptsView, node_runtime::state_viewer::errors::ViewDelayedReceiptsError>
+    {
+        let state_update = self.tries.new_trie_update_view(*shard_uid, state_root);
+        self.trie_viewer.view_delayed_receipts(
+            &state_update,
+            shard_uid.shard_id as ShardId,
+            from_index,
+            limit,
+        )
+    }
+
+    fn view_storage_breakdown(
+        &self,
+        shard_uid: &ShardUId,
+        state_root: MerkleHash,
+        account_id: &AccountId,
+        data_prefix_len: Option<u32>,
+        storage_usage_config: &StorageUsageConfig,
+    ) -> Result<StorageBreakdownView, node_runtime::state_viewer::errors::ViewStateError> {
+        let state_update = self.tries.new_trie_update_view(*shard_uid, state_root);
+        self.trie_viewer.view_storage_breakdown(
+            &state_update,
+            account_id,
+            data_prefix_len,
+            storage_usage_config,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -2028,6 +2180,7 @@ mod test {
                     gas_price,
                     gas_limit,
                     challenges,
+                    &Default::default(),
                     CryptoHash::default(),
                     true,
                     false,
@@ -228,6 +228,15 @@ pub enum HostError {
     /// Items limit error for alt_bn128_g1_multiexp
     #[cfg(feature = "protocol_feature_alt_bn128")]
     AltBn128MaxNumberOfItemsExceeded,
+    /// Invalid input to ed25519 signature verification functions
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    Ed25519VerifyInvalidInput { msg: String },
+    /// Invalid input to secp256k1 signature verification function
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    Secp256k1VerifyInvalidInput { msg: String },
+    /// Invalid input to BLS12-381 functions
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381InvalidInput { msg: String },
 }
 
 #[derive(Debug, PartialEq)]
@@ -430,6 +439,12 @@ impl std::fmt::Display for HostError {
             AltBn128SerializationError { msg } => write!(f, "AltBn128 serialization error: {}", msg),
             #[cfg(feature = "protocol_feature_alt_bn128")]
             AltBn128MaxNumberOfItemsExceeded => write!(f, "AltBn128 multi exp max items exceeded."),
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            Ed25519VerifyInvalidInput { msg } => write!(f, "ED25519 signature verification error: {}", msg),
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            Secp256k1VerifyInvalidInput { msg } => write!(f, "SECP256K1 signature verification error: {}", msg),
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Bls12381InvalidInput { msg } => write!(f, "BLS12-381 invalid input: {}", msg),
             ECRecoverError { msg } => write!(f, "ECDSA recover error: {}", msg),
         }
     }
@@ -0,0 +1,416 @@
+//! Operations on the BLS12-381 curve.
+//!
+//! Points are encoded in the uncompressed form of the ZCash serialization format: G1 points are
+//! `x || y` (96 bytes), G2 points are `x.c1 || x.c0 || y.c1 || y.c0` (192 bytes), where all
+//! coordinates are big-endian. The point at infinity has only the second most significant bit of
+//! the first byte set. Points must be on the curve and in the prime order subgroup.
+//!
+//! Scalars are 32-byte little-endian integers and are not required to be reduced.
+use blst::{
+    blst_bendian_from_fp, blst_final_exp, blst_fp, blst_fp12, blst_fp12_is_one, blst_fp12_mul,
+    blst_fp12_one, blst_fp2, blst_fp_from_bendian, blst_map_to_g1, blst_map_to_g2,
+    blst_miller_loop, blst_p1, blst_p1_add_or_double, blst_p1_affine, blst_p1_affine_in_g1,
+    blst_p1_affine_is_inf, blst_p1_cneg, blst_p1_deserialize, blst_p1_from_affine, blst_p1_mult,
+    blst_p1_serialize, blst_p2, blst_p2_add_or_double, blst_p2_affine, blst_p2_affine_in_g2,
+    blst_p2_affine_is_inf, blst_p2_cneg, blst_p2_deserialize, blst_p2_from_affine, blst_p2_mult,
+    blst_p2_serialize, BLST_ERROR,
+};
+use near_vm_errors::VMLogicError;
+
+use crate::HostError;
+
+pub const FP_LEN: usize = 48;
+pub const FP2_LEN: usize = 2 * FP_LEN;
+pub const G1_LEN: usize = 2 * FP_LEN;
+pub const G2_LEN: usize = 2 * FP2_LEN;
+pub const SCALAR_LEN: usize = 32;
+
+/// Compression flag of the ZCash serialization format.
+const COMPRESSION_FLAG: u8 = 0x80;
+
+fn invalid_input(msg: String) -> VMLogicError {
+    VMLogicError::HostError(HostError::Bls12381InvalidInput { msg })
+}
+
+/// Splits `data` into items of `item_len` bytes.
+fn split_items<'a>(
+    data: &'a [u8],
+    item_len: usize,
+    name: &str,
+) -> Result<std::slice::ChunksExact<'a, u8>, VMLogicError> {
+    if data.len() % item_len != 0 {
+        return Err(invalid_input(format!(
+            "input length {} is not a multiple of the {} size {}",
+            data.len(),
+            name,
+            item_len
+        )));
+    }
+    Ok(data.chunks_exact(item_len))
+}
+
+fn read_sign(byte: u8) -> Result<bool, VMLogicError> {
+    match byte {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(invalid_input(format!("invalid sign byte {}, expected 0 or 1", byte))),
+    }
+}
+
+fn read_g1(bytes: &[u8]) -> Result<blst_p1_affine, VMLogicError> {
+    debug_assert_eq!(bytes.len(), G1_LEN);
+    if bytes[0] & COMPRESSION_FLAG != 0 {
+        return Err(invalid_input("G1 point must be uncompressed".to_string()));
+    }
+    let mut point = blst_p1_affine::default();
+    // SAFETY: `bytes` holds the 96 bytes of an uncompressed point.
+    let res = unsafe { blst_p1_deserialize(&mut point, bytes.as_ptr()) };
+    if res != BLST_ERROR::BLST_SUCCESS {
+        return Err(invalid_input(format!("invalid G1 point: {:?}", res)));
+    }
+    // SAFETY: `point` is initialized above.
+    if !unsafe { blst_p1_affine_in_g1(&point) } {
+        return Err(invalid_input("G1 point is not in the subgroup".to_string()));
+    }
+    Ok(point)
+}
+
+fn read_g2(bytes: &[u8]) -> Result<blst_p2_affine, VMLogicError> {
+    debug_assert_eq!(bytes.len(), G2_LEN);
+    if bytes[0] & COMPRESSION_FLAG != 0 {
+        return Err(invalid_input("G2 point must be uncompressed".to_string()));
+    }
+    let mut point = blst_p2_affine::default();
+    // SAFETY: `bytes` holds the 192 bytes of an uncompressed point.
+    let res = unsafe { blst_p2_deserialize(&mut point, bytes.as_ptr()) };
+    if res != BLST_ERROR::BLST_SUCCESS {
+        return Err(invalid_input(format!("invalid G2 point: {:?}", res)));
+    }
+    // SAFETY: `point` is initialized above.
+    if !unsafe { blst_p2_affine_in_g2(&point) } {
+        return Err(invalid_input("G2 point is not in the subgroup".to_string()));
+    }
+    Ok(point)
+}
+
+fn read_fp(bytes: &[u8]) -> Result<blst_fp, VMLogicError> {
+    debug_assert_eq!(bytes.len(), FP_LEN);
+    let mut fp = blst_fp::default();
+    let mut canonical = [0u8; FP_LEN];
+    // SAFETY: `bytes` and `canonical` hold 48 bytes.
+    unsafe {
+        blst_fp_from_bendian(&mut fp, bytes.as_ptr());
+        blst_bendian_from_fp(canonical.as_mut_ptr(), &fp);
+    }
+    // Values not less than the modulus are reduced by `blst_fp_from_bendian`.
+    if canonical != bytes {
+        return Err(invalid_input("field element is not less than the modulus".to_string()));
+    }
+    Ok(fp)
+}
+
+fn write_g1(point: &blst_p1) -> Vec<u8> {
+    let mut res = vec![0u8; G1_LEN];
+    // SAFETY: `res` holds 96 bytes.
+    unsafe { blst_p1_serialize(res.as_mut_ptr(), point) };
+    res
+}
+
+fn write_g2(point: &blst_p2) -> Vec<u8> {
+    let mut res = vec![0u8; G2_LEN];
+    // SAFETY: `res` holds 192 bytes.
+    unsafe { blst_p2_serialize(res.as_mut_ptr(), point) };
+    res
+}
+
+/// Computes the sum of signed G1 points, `\sum_i (-1)^{sign_i} p_i`.
+///
+/// # Arguments
+///
+/// * `data` - concatenation of `sign: u8 || p: G1` items, where sign is 0 for addition and 1 for
+///   subtraction.
+///
+/// # Errors
+///
+/// If the data can't be split into items or a point is invalid, returns `Bls12381InvalidInput`.
+pub fn bls12381_p1_sum(data: &[u8]) -> crate::logic::Result<Vec<u8>> {
+    let mut acc = blst_p1::default();
+    for item in split_items(data, 1 + G1_LEN, "signed G1 point")? {
+        let negate = read_sign(item[0])?;
+        let point = read_g1(&item[1..])?;
+        let mut point_p1 = blst_p1::default();
+        // SAFETY: all points are initialized.
+        unsafe {
+            blst_p1_from_affine(&mut point_p1, &point);
+            blst_p1_cneg(&mut point_p1, negate);
+            let prev = acc;
+            blst_p1_add_or_double(&mut acc, &prev, &point_p1);
+        }
+    }
+    Ok(write_g1(&acc))
+}
+
+/// Computes the sum of signed G2 points, `\sum_i (-1)^{sign_i} p_i`.
+///
+/// # Arguments
+///
+/// * `data` - concatenation of `sign: u8 || p: G2` items, where sign is 0 for addition and 1 for
+///   subtraction.
+///
+/// # Errors
+///
+/// If the data can't be split into items or a point is invalid, returns `Bls12381InvalidInput`.
+pub fn bls12381_p2_sum(data: &[u8]) -> crate::logic::Result<Vec<u8>> {
+    let mut acc = blst_p2::default();
+    for item in split_items(data, 1 + G2_LEN, "signed G2 point")? {
+        let negate = read_sign(item[0])?;
+        let point = read_g2(&item[1..])?;
+        let mut point_p2 = blst_p2::default();
+        // SAFETY: all points are initialized.
+        unsafe {
+            blst_p2_from_affine(&mut point_p2, &point);
+            blst_p2_cneg(&mut point_p2, negate);
+            let prev = acc;
+            blst_p2_add_or_double(&mut acc, &prev, &point_p2);
+        }
+    }
+    Ok(write_g2(&acc))
+}
+
+/// Computes `\sum_i s_i * p_i` for G1 points `p_i` and scalars `s_i`.
+///
+/// # Arguments
+///
+/// * `data` - concatenation of `p: G1 || s: scalar` items.
+///
+/// # Errors
+///
+/// If the data can't be split into items or a point is invalid, returns `Bls12381InvalidInput`.
+pub fn bls12381_g1_multiexp(data: &[u8]) -> crate::logic::Result<Vec<u8>> {
+    let mut acc = blst_p1::default();
+    for item in split_items(data, G1_LEN + SCALAR_LEN, "G1 point and scalar")? {
+        let point = read_g1(&item[..G1_LEN])?;
+        let scalar = &item[G1_LEN..];
+        let mut point_p1 = blst_p1::default();
+        let mut product = blst_p1::default();
+        // SAFETY: all points are initialized and `scalar` holds 256 bits.
+        unsafe {
+            blst_p1_from_affine(&mut point_p1, &point);
+            blst_p1_mult(&mut product, &point_p1, scalar.as_ptr(), 8 * SCALAR_LEN);
+            let prev = acc;
+            blst_p1_add_or_double(&mut acc, &prev, &product);
+        }
+    }
+    Ok(write_g1(&acc))
+}
+
+/// Computes `\sum_i s_i * p_i` for G2 points `p_i` and scalars `s_i`.
+///
+/// # Arguments
+///
+/// * `data` - concatenation of `p: G2 || s: scalar` items.
+///
+/// # Errors
+///
+/// If the data can't be split into items or a point is invalid, returns `Bls12381InvalidInput`.
+pub fn bls12381_g2_multiexp(data: &[u8]) -> crate::logic::Result<Vec<u8>> {
+    let mut acc = blst_p2::default();
+    for item in split_items(data, G2_LEN + SCALAR_LEN, "G2 point and scalar")? {
+        let point = read_g2(&item[..G2_LEN])?;
+        let scalar = &item[G2_LEN..];
+        let mut point_p2 = blst_p2::default();
+        let mut product = blst_p2::default();
+        // SAFETY: all points are initialized and `scalar` holds 256 bits.
+        unsafe {
+            blst_p2_from_affine(&mut point_p2, &point);
+            blst_p2_mult(&mut product, &point_p2, scalar.as_ptr(), 8 * SCALAR_LEN);
+            let prev = acc;
+            blst_p2_add_or_double(&mut acc, &prev, &product);
+        }
+    }
+    Ok(write_g2(&acc))
+}
+
+/// Maps field elements to G1 points with the simplified SWU map followed by cofactor clearing,
+/// as in the `map_to_curve` and `clear_cofactor` steps of hash-to-curve (RFC 9380).
+///
+/// # Arguments
+///
+/// * `data` - concatenation of big-endian field elements of 48 bytes each.
+///
+/// Returns the concatenation of the resulting points.
+///
+/// # Errors
+///
+/// If the data can't be split into field elements or an element is not less than the modulus,
+/// returns `Bls12381InvalidInput`.
+pub fn bls12381_map_fp_to_g1(data: &[u8]) -> crate::logic::Result<Vec<u8>> {
+    let items = split_items(data, FP_LEN, "field element")?;
+    let mut res = Vec::with_capacity(items.len() * G1_LEN);
+    for item in items {
+        let fp = read_fp(item)?;
+        let mut point = blst_p1::default();
+        // SAFETY: `fp` is initialized, `v` is optional.
+        unsafe { blst_map_to_g1(&mut point, &fp, std::ptr::null()) };
+        res.extend(write_g1(&point));
+    }
+    Ok(res)
+}
+
+/// Maps elements of the quadratic extension field to G2 points with the simplified SWU map
+/// followed by cofactor clearing, as in hash-to-curve (RFC 9380).
+///
+/// # Arguments
+///
+/// * `data` - concatenation of `c1 || c0` elements, both big-endian field elements.
+///
+/// Returns the concatenation of the resulting points.
+///
+/// # Errors
+///
+/// If the data can't be split into elements or a field element is not less than the modulus,
+/// returns `Bls12381InvalidInput`.
+pub fn bls12381_map_fp2_to_g2(data: &[u8]) -> crate::logic::Result<Vec<u8>> {
+    let items = split_items(data, FP2_LEN, "extension field element")?;
+    let mut res = Vec::with_capacity(items.len() * G2_LEN);
+    for item in items {
+        let fp2 = blst_fp2 { fp: [read_fp(&item[FP_LEN..])?, read_fp(&item[..FP_LEN])?] };
+        let mut point = blst_p2::default();
+        // SAFETY: `fp2` is initialized, `v` is optional.
+        unsafe { blst_map_to_g2(&mut point, &fp2, std::ptr::null()) };
+        res.extend(write_g2(&point));
+    }
+    Ok(res)
+}
+
+/// Checks that `\prod_i e(p_i, q_i)` equals one, where `e` is the optimal Ate pairing.
+///
+/// # Arguments
+///
+/// * `data` - concatenation of `p: G1 || q: G2` items.
+///
+/// # Errors
+///
+/// If the data can't be split into items or a point is invalid, returns `Bls12381InvalidInput`.
+pub fn bls12381_pairing_check(data: &[u8]) -> crate::logic::Result<bool> {
+    // SAFETY: `blst_fp12_one` returns a pointer to a static value.
+    let mut acc: blst_fp12 = unsafe { *blst_fp12_one() };
+    for item in split_items(data, G1_LEN + G2_LEN, "G1 and G2 points")? {
+        let p = read_g1(&item[..G1_LEN])?;
+        let q = read_g2(&item[G1_LEN..])?;
+        // SAFETY: points are initialized.
+        if unsafe { blst_p1_affine_is_inf(&p) || blst_p2_affine_is_inf(&q) } {
+            // The pairing with the point at infinity is one.
+            continue;
+        }
+        let mut pairing = blst_fp12::default();
+        // SAFETY: all values are initialized.
+        unsafe {
+            blst_miller_loop(&mut pairing, &q, &p);
+            let prev = acc;
+            blst_fp12_mul(&mut acc, &prev, &pairing);
+        }
+    }
+    let mut res = blst_fp12::default();
+    // SAFETY: `acc` is initialized.
+    unsafe {
+        blst_final_exp(&mut res, &acc);
+        Ok(blst_fp12_is_one(&res))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const P: &str = "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab";
+    const G1_X: &str = "17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb";
+    const G1_Y: &str = "08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1";
+    const G2_X_C0: &str = "024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8";
+    const G2_X_C1: &str = "13e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e";
+    const G2_Y_C0: &str = "0ce5d527727d6e118cc9cdc6da2e351aadfd9baa8cbdd3a76d429a695160d12c923ac9cc3baca289e193548608b82801";
+    const G2_Y_C1: &str = "0606c4a02ea734cc32acd2b02bc28b99cb3e287e85a763af267492ab572e99ab3f370d275cec1da1aaa9075ff05f79be";
+
+    fn g1() -> Vec<u8> {
+        hex::decode([G1_X, G1_Y].concat()).unwrap()
+    }
+
+    fn g2() -> Vec<u8> {
+        hex::decode([G2_X_C1, G2_X_C0, G2_Y_C1, G2_Y_C0].concat()).unwrap()
+    }
+
+    fn g1_infinity() -> Vec<u8> {
+        let mut res = vec![0u8; G1_LEN];
+        res[0] = 0x40;
+        res
+    }
+
+    fn scalar(value: u8) -> Vec<u8> {
+        let mut res = vec![0u8; SCALAR_LEN];
+        res[0] = value;
+        res
+    }
+
+    #[test]
+    fn test_p1_sum() {
+        let sum = |items: &[(u8, Vec<u8>)]| {
+            let data: Vec<u8> =
+                items.iter().flat_map(|(sign, point)| [&[*sign][..], point].concat()).collect();
+            bls12381_p1_sum(&data)
+        };
+        assert_eq!(sum(&[]).unwrap(), g1_infinity());
+        assert_eq!(sum(&[(0, g1())]).unwrap(), g1());
+        assert_eq!(sum(&[(0, g1()), (1, g1())]).unwrap(), g1_infinity());
+        assert_eq!(
+            sum(&[(0, g1()), (0, g1())]).unwrap(),
+            bls12381_g1_multiexp(&[g1(), scalar(2)].concat()).unwrap()
+        );
+        assert!(sum(&[(2, g1())]).is_err());
+
+        let mut not_on_curve = g1();
+        not_on_curve[G1_LEN - 1] ^= 1;
+        assert!(sum(&[(0, not_on_curve)]).is_err());
+        assert!(bls12381_p1_sum(&g1()).is_err());
+    }
+
+    #[test]
+    fn test_p2_sum() {
+        let data = [&[0][..], &g2(), &[1], &g2()].concat();
+        let res = bls12381_p2_sum(&data).unwrap();
+        assert_eq!(res[0], 0x40);
+        assert!(res[1..].iter().all(|byte| *byte == 0));
+        assert_eq!(bls12381_p2_sum(&[&[0][..], &g2()].concat()).unwrap(), g2());
+    }
+
+    #[test]
+    fn test_multiexp() {
+        assert_eq!(bls12381_g1_multiexp(&[g1(), scalar(1)].concat()).unwrap(), g1());
+        assert_eq!(bls12381_g1_multiexp(&[g1(), scalar(0)].concat()).unwrap(), g1_infinity());
+        assert_eq!(bls12381_g2_multiexp(&[g2(), scalar(1)].concat()).unwrap(), g2());
+        assert!(bls12381_g1_multiexp(&g1()).is_err());
+    }
+
+    #[test]
+    fn test_map_to_curve() {
+        let res = bls12381_map_fp_to_g1(&[vec![0u8; FP_LEN], vec![1u8; FP_LEN]].concat()).unwrap();
+        assert_eq!(res.len(), 2 * G1_LEN);
+        read_g1(&res[..G1_LEN]).unwrap();
+        read_g1(&res[G1_LEN..]).unwrap();
+        assert!(bls12381_map_fp_to_g1(&hex::decode(P).unwrap()).is_err());
+
+        let res = bls12381_map_fp2_to_g2(&[1u8; FP2_LEN]).unwrap();
+        read_g2(&res).unwrap();
+        assert!(bls12381_map_fp2_to_g2(&[1u8; FP_LEN]).is_err());
+    }
+
+    #[test]
+    fn test_pairing_check() {
+        assert!(bls12381_pairing_check(&[]).unwrap());
+        assert!(!bls12381_pairing_check(&[g1(), g2()].concat()).unwrap());
+
+        let minus_g1 = bls12381_p1_sum(&[&[1][..], &g1()].concat()).unwrap();
+        let data = [g1(), g2(), minus_g1, g2()].concat();
+        assert!(bls12381_pairing_check(&data).unwrap());
+        assert!(bls12381_pairing_check(&[g1_infinity(), g2()].concat()).unwrap());
+    }
+}
@@ -61,6 +61,10 @@ pub struct VMContext {
     /// How many `DataReceipt`'s should receive this execution result. This should be empty if
     /// this function call is a part of a batch and it is not the last action.
     pub output_data_receivers: Vec<AccountId>,
+    /// If true, every host function call made by the contract is recorded in
+    /// `VMOutcome::host_function_calls`. Used for debugging contracts.
+    #[serde(default)]
+    pub trace_host_functions: bool,
 }
 
 impl VMContext {
@@ -0,0 +1,138 @@
+use near_crypto::{ED25519PublicKey, KeyType, PublicKey, Signature};
+use near_vm_errors::VMLogicError;
+
+use crate::HostError;
+
+pub const SIGNATURE_LEN: usize = 64;
+pub const PUBLIC_KEY_LEN: usize = 32;
+const MESSAGE_LEN_LEN: usize = std::mem::size_of::<u32>();
+
+fn invalid_input(msg: String) -> VMLogicError {
+    VMLogicError::HostError(HostError::Ed25519VerifyInvalidInput { msg })
+}
+
+/// Signature, public key and message of a single entry of a batch.
+#[derive(Debug, PartialEq)]
+pub struct BatchItem<'a> {
+    pub signature: &'a [u8],
+    pub public_key: &'a [u8],
+    pub message: &'a [u8],
+}
+
+/// Verifies an ed25519 `signature` of `message` made with `public_key`.
+///
+/// Returns an error if the signature or the public key have invalid lengths. Signatures that can't
+/// be decoded and public keys that are not valid curve points don't verify.
+pub fn ed25519_verify(
+    signature: &[u8],
+    message: &[u8],
+    public_key: &[u8],
+) -> Result<bool, VMLogicError> {
+    if signature.len() != SIGNATURE_LEN {
+        return Err(invalid_input(format!(
+            "invalid signature length {}, expected {}",
+            signature.len(),
+            SIGNATURE_LEN
+        )));
+    }
+    let public_key: [u8; PUBLIC_KEY_LEN] = public_key.try_into().map_err(|_| {
+        invalid_input(format!(
+            "invalid public key length {}, expected {}",
+            public_key.len(),
+            PUBLIC_KEY_LEN
+        ))
+    })?;
+    let signature = match Signature::from_parts(KeyType::ED25519, signature) {
+        Ok(signature) => signature,
+        Err(_) => return Ok(false),
+    };
+    Ok(signature.verify(message, &PublicKey::ED25519(ED25519PublicKey(public_key))))
+}
+
+/// Splits the input of `ed25519_verify_batch` into entries.
+///
+/// Input is a concatenation of entries, each encoded as
+/// `signature: [u8; 64] | public_key: [u8; 32] | message_len: u32 (little endian) | message`.
+pub fn split_batch(mut data: &[u8]) -> Result<Vec<BatchItem<'_>>, VMLogicError> {
+    let mut items = vec![];
+    while !data.is_empty() {
+        let header_len = SIGNATURE_LEN + PUBLIC_KEY_LEN + MESSAGE_LEN_LEN;
+        if data.len() < header_len {
+            return Err(invalid_input(format!(
+                "batch entry {} is truncated: {} bytes left, expected at least {}",
+                items.len(),
+                data.len(),
+                header_len
+            )));
+        }
+        let (signature, rest) = data.split_at(SIGNATURE_LEN);
+        let (public_key, rest) = rest.split_at(PUBLIC_KEY_LEN);
+        let (message_len, rest) = rest.split_at(MESSAGE_LEN_LEN);
+        let message_len = u32::from_le_bytes(message_len.try_into().unwrap()) as usize;
+        if rest.len() < message_len {
+            return Err(invalid_input(format!(
+                "batch entry {} is truncated: message of {} bytes, but {} bytes left",
+                items.len(),
+                message_len,
+                rest.len()
+            )));
+        }
+        let (message, rest) = rest.split_at(message_len);
+        items.push(BatchItem { signature, public_key, message });
+        data = rest;
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_crypto::SecretKey;
+
+    fn batch_entry(secret_key: &SecretKey, message: &[u8]) -> Vec<u8> {
+        let signature = match secret_key.sign(message) {
+            Signature::ED25519(signature) => signature.to_bytes(),
+            _ => unreachable!(),
+        };
+        let public_key = match secret_key.public_key() {
+            PublicKey::ED25519(public_key) => public_key.0,
+            _ => unreachable!(),
+        };
+        let mut entry = signature.to_vec();
+        entry.extend_from_slice(&public_key);
+        entry.extend_from_slice(&(message.len() as u32).to_le_bytes());
+        entry.extend_from_slice(message);
+        entry
+    }
+
+    #[test]
+    fn test_ed25519_verify() {
+        let secret_key = SecretKey::from_seed(KeyType::ED25519, "test");
+        let entry = batch_entry(&secret_key, b"message");
+        let items = split_batch(&entry).unwrap();
+        assert_eq!(items.len(), 1);
+        let BatchItem { signature, public_key, message } = &items[0];
+        assert_eq!(*message, b"message");
+
+        assert!(ed25519_verify(signature, message, public_key).unwrap());
+        assert!(!ed25519_verify(signature, b"other message", public_key).unwrap());
+        assert!(!ed25519_verify(&[0; SIGNATURE_LEN], message, public_key).unwrap());
+        assert!(ed25519_verify(&signature[1..], message, public_key).is_err());
+        assert!(ed25519_verify(signature, message, &public_key[1..]).is_err());
+    }
+
+    #[test]
+    fn test_split_batch() {
+        let secret_key = SecretKey::from_seed(KeyType::ED25519, "test");
+        let mut data = batch_entry(&secret_key, b"first");
+        data.extend(batch_entry(&secret_key, b""));
+        let items = split_batch(&data).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].message, b"first");
+        assert_eq!(items[1].message, b"");
+        assert_eq!(split_batch(&[]).unwrap(), vec![]);
+
+        assert!(split_batch(&data[..data.len() - 1]).is_err());
+        assert!(split_batch(&data[..SIGNATURE_LEN + PUBLIC_KEY_LEN + 5]).is_err());
+    }
+}
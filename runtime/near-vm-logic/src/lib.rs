@@ -2,8 +2,12 @@
 
 #[cfg(feature = "protocol_feature_alt_bn128")]
 pub mod alt_bn128;
+#[cfg(feature = "protocol_feature_bls12381")]
+pub mod bls12381;
 mod context;
 mod dependencies;
+#[cfg(feature = "protocol_feature_ed25519_verify")]
+pub mod ed25519;
 pub mod gas_counter;
 mod logic;
 pub mod mocks;
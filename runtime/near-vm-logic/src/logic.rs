@@ -14,6 +14,7 @@ use near_primitives_core::profile::ProfileData;
 use near_primitives_core::runtime::fees::{
     transfer_exec_fee, transfer_send_fee, RuntimeFeesConfig,
 };
+use near_primitives_core::trace::{HostFunctionCall, MAX_TRACED_HOST_FUNCTION_CALLS};
 use near_primitives_core::types::{
     AccountId, Balance, EpochHeight, Gas, ProtocolVersion, StorageUsage,
 };
@@ -21,7 +22,7 @@ use near_primitives_core::types::{
 use near_primitives_core::types::{GasDistribution, GasWeight};
 use near_vm_errors::InconsistentStateError;
 use near_vm_errors::{HostError, VMLogicError};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::mem::size_of;
 
 pub type Result<T> = ::std::result::Result<T, VMLogicError>;
@@ -69,6 +70,13 @@ pub struct VMLogic<'a> {
 
     /// Handles the receipts generated through execution.
     receipt_manager: ReceiptManager,
+
+    /// Host function calls recorded so far, if `VMContext::trace_host_functions` is set.
+    host_function_calls: Option<Vec<HostFunctionCall>>,
+    /// The host function call that is currently being recorded.
+    traced_call: Option<HostFunctionCall>,
+    /// Number of executions of each code block instrumented for coverage.
+    coverage_hits: BTreeMap<u32, u64>,
 }
 
 /// Promises API allows to create a DAG-structure that defines dependencies between smart contract
@@ -127,6 +135,7 @@ impl<'a> VMLogic<'a> {
             context.prepaid_gas,
             context.is_view(),
         );
+        let host_function_calls = if context.trace_host_functions { Some(vec![]) } else { None };
         Self {
             ext,
             context,
@@ -145,6 +154,9 @@ impl<'a> VMLogic<'a> {
             total_log_length: 0,
             current_protocol_version,
             receipt_manager: ReceiptManager::default(),
+            host_function_calls,
+            traced_call: None,
+            coverage_hits: BTreeMap::new(),
         }
     }
 
@@ -245,6 +257,9 @@ impl<'a> VMLogic<'a> {
         {
             return Err(HostError::MemoryAccessViolation.into());
         }
+        if let Some(call) = &mut self.traced_call {
+            call.registers_written.push((register_id, data.len() as u64));
+        }
         self.registers.insert(register_id, data);
 
         // Calculate the new memory usage.
@@ -877,6 +892,224 @@ impl<'a> VMLogic<'a> {
         Ok(crate::alt_bn128::alt_bn128_pairing_check(&value_buf)? as u64)
     }
 
+    /// Compute signed sum of points in the G1 group of the BLS12-381 curve.
+    /// See more detailed description at `bls12381::bls12381_p1_sum`.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers use more memory than
+    /// the limit with `MemoryAccessViolation`.
+    ///
+    /// Bls12381InvalidInput
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes + bls12381_p1_sum_base +
+    /// bls12381_p1_sum_element * num_points`
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub fn bls12381_p1_sum(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(base)?;
+        self.gas_counter.pay_base(bls12381_p1_sum_base)?;
+        let value_buf = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+        let elements = value_buf.len() / (1 + crate::bls12381::G1_LEN);
+        self.gas_counter.pay_per(bls12381_p1_sum_element, elements as u64)?;
+
+        let res = crate::bls12381::bls12381_p1_sum(&value_buf)?;
+
+        self.internal_write_register(register_id, res)
+    }
+
+    /// Compute signed sum of points in the G2 group of the BLS12-381 curve.
+    /// See more detailed description at `bls12381::bls12381_p2_sum`.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers use more memory than
+    /// the limit with `MemoryAccessViolation`.
+    ///
+    /// Bls12381InvalidInput
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes + bls12381_p2_sum_base +
+    /// bls12381_p2_sum_element * num_points`
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub fn bls12381_p2_sum(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(base)?;
+        self.gas_counter.pay_base(bls12381_p2_sum_base)?;
+        let value_buf = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+        let elements = value_buf.len() / (1 + crate::bls12381::G2_LEN);
+        self.gas_counter.pay_per(bls12381_p2_sum_element, elements as u64)?;
+
+        let res = crate::bls12381::bls12381_p2_sum(&value_buf)?;
+
+        self.internal_write_register(register_id, res)
+    }
+
+    /// Compute multiexp in the G1 group of the BLS12-381 curve.
+    /// See more detailed description at `bls12381::bls12381_g1_multiexp`.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers use more memory than
+    /// the limit with `MemoryAccessViolation`.
+    ///
+    /// Bls12381InvalidInput
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes + bls12381_g1_multiexp_base +
+    /// bls12381_g1_multiexp_element * num_pairs`
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub fn bls12381_g1_multiexp(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(base)?;
+        self.gas_counter.pay_base(bls12381_g1_multiexp_base)?;
+        let value_buf = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+        let elements = value_buf.len() / (crate::bls12381::G1_LEN + crate::bls12381::SCALAR_LEN);
+        self.gas_counter.pay_per(bls12381_g1_multiexp_element, elements as u64)?;
+
+        let res = crate::bls12381::bls12381_g1_multiexp(&value_buf)?;
+
+        self.internal_write_register(register_id, res)
+    }
+
+    /// Compute multiexp in the G2 group of the BLS12-381 curve.
+    /// See more detailed description at `bls12381::bls12381_g2_multiexp`.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers use more memory than
+    /// the limit with `MemoryAccessViolation`.
+    ///
+    /// Bls12381InvalidInput
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes + bls12381_g2_multiexp_base +
+    /// bls12381_g2_multiexp_element * num_pairs`
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub fn bls12381_g2_multiexp(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(base)?;
+        self.gas_counter.pay_base(bls12381_g2_multiexp_base)?;
+        let value_buf = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+        let elements = value_buf.len() / (crate::bls12381::G2_LEN + crate::bls12381::SCALAR_LEN);
+        self.gas_counter.pay_per(bls12381_g2_multiexp_element, elements as u64)?;
+
+        let res = crate::bls12381::bls12381_g2_multiexp(&value_buf)?;
+
+        self.internal_write_register(register_id, res)
+    }
+
+    /// Map base field elements to points in the G1 group of the BLS12-381 curve.
+    /// See more detailed description at `bls12381::bls12381_map_fp_to_g1`.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers use more memory than
+    /// the limit with `MemoryAccessViolation`.
+    ///
+    /// Bls12381InvalidInput
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes + bls12381_map_fp_to_g1_base +
+    /// bls12381_map_fp_to_g1_element * num_elements`
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub fn bls12381_map_fp_to_g1(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(base)?;
+        self.gas_counter.pay_base(bls12381_map_fp_to_g1_base)?;
+        let value_buf = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+        let elements = value_buf.len() / (crate::bls12381::FP_LEN);
+        self.gas_counter.pay_per(bls12381_map_fp_to_g1_element, elements as u64)?;
+
+        let res = crate::bls12381::bls12381_map_fp_to_g1(&value_buf)?;
+
+        self.internal_write_register(register_id, res)
+    }
+
+    /// Map quadratic extension field elements to points in the G2 group of the BLS12-381 curve.
+    /// See more detailed description at `bls12381::bls12381_map_fp2_to_g2`.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers use more memory than
+    /// the limit with `MemoryAccessViolation`.
+    ///
+    /// Bls12381InvalidInput
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes + bls12381_map_fp2_to_g2_base +
+    /// bls12381_map_fp2_to_g2_element * num_elements`
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub fn bls12381_map_fp2_to_g2(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(base)?;
+        self.gas_counter.pay_base(bls12381_map_fp2_to_g2_base)?;
+        let value_buf = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+        let elements = value_buf.len() / (crate::bls12381::FP2_LEN);
+        self.gas_counter.pay_per(bls12381_map_fp2_to_g2_element, elements as u64)?;
+
+        let res = crate::bls12381::bls12381_map_fp2_to_g2(&value_buf)?;
+
+        self.internal_write_register(register_id, res)
+    }
+
+    /// Check that the product of pairings of G1 and G2 points of the BLS12-381 curve is one.
+    /// See more detailed description at `bls12381::bls12381_pairing_check`.
+    ///
+    /// Returns `1` if the check passes and `0` otherwise.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers use more memory than
+    /// the limit with `MemoryAccessViolation`.
+    ///
+    /// Bls12381InvalidInput
+    ///
+    /// # Cost
+    ///
+    /// `base + bls12381_pairing_base + bls12381_pairing_element * num_pairs`
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub fn bls12381_pairing_check(&mut self, value_len: u64, value_ptr: u64) -> Result<u64> {
+        self.gas_counter.pay_base(base)?;
+        self.gas_counter.pay_base(bls12381_pairing_base)?;
+        let value_buf = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+        let elements = value_buf.len() / (crate::bls12381::G1_LEN + crate::bls12381::G2_LEN);
+        self.gas_counter.pay_per(bls12381_pairing_element, elements as u64)?;
+
+        Ok(crate::bls12381::bls12381_pairing_check(&value_buf)? as u64)
+    }
+
     /// Writes random seed into the register.
     ///
     /// # Errors
@@ -1078,6 +1311,125 @@ impl<'a> VMLogic<'a> {
         Ok(false as u64)
     }
 
+    /// Verifies an ed25519 `signature` of the message with `public_key`.
+    ///
+    /// Returns `1` if the signature is valid and `0` otherwise.
+    ///
+    /// # Errors
+    ///
+    /// * If `signature_ptr`, `message_ptr` or `public_key_ptr` point outside the memory or the
+    ///   registers use more memory than the limit, then returns `MemoryAccessViolation`.
+    /// * If the signature is not 64 bytes long or the public key is not 32 bytes long, then
+    ///   returns `Ed25519VerifyInvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + ed25519_verify_base + ed25519_verify_byte * num_message_bytes`
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    pub fn ed25519_verify(
+        &mut self,
+        signature_len: u64,
+        signature_ptr: u64,
+        message_len: u64,
+        message_ptr: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+    ) -> Result<u64> {
+        self.gas_counter.pay_base(base)?;
+        self.gas_counter.pay_base(ed25519_verify_base)?;
+        let signature = self.get_vec_from_memory_or_register(signature_ptr, signature_len)?;
+        let message = self.get_vec_from_memory_or_register(message_ptr, message_len)?;
+        self.gas_counter.pay_per(ed25519_verify_byte, message.len() as u64)?;
+        let public_key = self.get_vec_from_memory_or_register(public_key_ptr, public_key_len)?;
+
+        Ok(crate::ed25519::ed25519_verify(&signature, &message, &public_key)? as u64)
+    }
+
+    /// Verifies a batch of ed25519 signatures.
+    /// See `ed25519::split_batch` for the encoding of the batch.
+    ///
+    /// Signatures are verified one by one, so the result is exactly the same as calling
+    /// `ed25519_verify` for each entry, saving only the overhead of the calls.
+    ///
+    /// Returns `1` if all signatures are valid and `0` otherwise.
+    ///
+    /// # Errors
+    ///
+    /// * If `value_len + value_ptr` points outside the memory or the registers use more memory
+    ///   than the limit, then returns `MemoryAccessViolation`.
+    /// * If the batch is malformed or has signatures or public keys of invalid lengths, then
+    ///   returns `Ed25519VerifyInvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + ed25519_verify_base * num_entries + ed25519_verify_byte * num_message_bytes`
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    pub fn ed25519_verify_batch(&mut self, value_len: u64, value_ptr: u64) -> Result<u64> {
+        self.gas_counter.pay_base(base)?;
+        let value_buf = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+        let items = crate::ed25519::split_batch(&value_buf)?;
+        self.gas_counter.pay_per(ed25519_verify_base, items.len() as u64)?;
+        let message_bytes = items.iter().map(|item| item.message.len() as u64).sum();
+        self.gas_counter.pay_per(ed25519_verify_byte, message_bytes)?;
+
+        for item in items {
+            if !crate::ed25519::ed25519_verify(item.signature, item.message, item.public_key)? {
+                return Ok(false as u64);
+            }
+        }
+        Ok(true as u64)
+    }
+
+    /// Verifies a secp256k1 ECDSA `signature` of the 32-byte message hash with `public_key`.
+    ///
+    /// The signature is 64 bytes `r | s` and the public key is the 64-byte uncompressed point
+    /// without the `0x04` prefix, the same format `ecrecover` writes to the register. Signatures
+    /// with `s` in the upper half of the curve order don't verify.
+    ///
+    /// Returns `1` if the signature is valid and `0` otherwise.
+    ///
+    /// # Errors
+    ///
+    /// * If `signature_ptr`, `hash_ptr` or `public_key_ptr` point outside the memory or the
+    ///   registers use more memory than the limit, then returns `MemoryAccessViolation`.
+    /// * If the signature is not 64 bytes long, the hash is not 32 bytes long or the public key is
+    ///   not 64 bytes long, then returns `Secp256k1VerifyInvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + secp256k1_verify_base`
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    pub fn secp256k1_verify(
+        &mut self,
+        signature_len: u64,
+        signature_ptr: u64,
+        hash_len: u64,
+        hash_ptr: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+    ) -> Result<u64> {
+        self.gas_counter.pay_base(base)?;
+        self.gas_counter.pay_base(secp256k1_verify_base)?;
+        let invalid_input = |what: &str, len: usize, expected: usize| {
+            VMLogicError::HostError(HostError::Secp256k1VerifyInvalidInput {
+                msg: format!("invalid {} length {}, expected {}", what, len, expected),
+            })
+        };
+        let signature = self.get_vec_from_memory_or_register(signature_ptr, signature_len)?;
+        let signature: [u8; 64] = signature
+            .as_slice()
+            .try_into()
+            .map_err(|_| invalid_input("signature", signature.len(), 64))?;
+        let hash = self.get_vec_from_memory_or_register(hash_ptr, hash_len)?;
+        let hash: [u8; 32] =
+            hash.as_slice().try_into().map_err(|_| invalid_input("hash", hash.len(), 32))?;
+        let public_key = self.get_vec_from_memory_or_register(public_key_ptr, public_key_len)?;
+        let public_key = near_crypto::Secp256K1PublicKey::try_from(public_key.as_slice())
+            .map_err(|_| invalid_input("public key", public_key.len(), 64))?;
+
+        Ok(public_key.verify_compact(&signature, hash) as u64)
+    }
+
     /// Called by gas metering injected into Wasm. Counts both towards `burnt_gas` and `used_gas`.
     ///
     /// # Errors
@@ -2318,6 +2670,7 @@ impl<'a> VMLogic<'a> {
         }
         self.gas_counter.pay_base(storage_write_base)?;
         let key = self.get_vec_from_memory_or_register(key_ptr, key_len)?;
+        self.trace_storage_key(&key);
         if key.len() as u64 > self.config.limit_config.max_length_storage_key {
             return Err(HostError::KeyLengthExceeded {
                 length: key.len() as u64,
@@ -2408,6 +2761,7 @@ impl<'a> VMLogic<'a> {
         self.gas_counter.pay_base(base)?;
         self.gas_counter.pay_base(storage_read_base)?;
         let key = self.get_vec_from_memory_or_register(key_ptr, key_len)?;
+        self.trace_storage_key(&key);
         if key.len() as u64 > self.config.limit_config.max_length_storage_key {
             return Err(HostError::KeyLengthExceeded {
                 length: key.len() as u64,
@@ -2458,6 +2812,7 @@ impl<'a> VMLogic<'a> {
         }
         self.gas_counter.pay_base(storage_remove_base)?;
         let key = self.get_vec_from_memory_or_register(key_ptr, key_len)?;
+        self.trace_storage_key(&key);
         if key.len() as u64 > self.config.limit_config.max_length_storage_key {
             return Err(HostError::KeyLengthExceeded {
                 length: key.len() as u64,
@@ -2509,6 +2864,7 @@ impl<'a> VMLogic<'a> {
         self.gas_counter.pay_base(base)?;
         self.gas_counter.pay_base(storage_has_key_base)?;
         let key = self.get_vec_from_memory_or_register(key_ptr, key_len)?;
+        self.trace_storage_key(&key);
         if key.len() as u64 > self.config.limit_config.max_length_storage_key {
             return Err(HostError::KeyLengthExceeded {
                 length: key.len() as u64,
@@ -2541,6 +2897,18 @@ impl<'a> VMLogic<'a> {
         Ok(())
     }
 
+    /// Counts an execution of a code block. Calls to this function are inserted into contracts
+    /// compiled while coverage collection is enabled, see `near_vm_runner::coverage`.
+    ///
+    /// # Cost
+    ///
+    /// 0
+    #[cfg(feature = "sandbox")]
+    pub fn sandbox_coverage_hit(&mut self, block: u32) -> Result<()> {
+        *self.coverage_hits.entry(block).or_default() += 1;
+        Ok(())
+    }
+
     /// DEPRECATED
     /// Creates an iterator object inside the host. Returns the identifier that uniquely
     /// differentiates the given iterator from other iterators that can be simultaneously created.
@@ -2666,6 +3034,8 @@ impl<'a> VMLogic<'a> {
             logs: self.logs,
             profile,
             action_receipts: self.receipt_manager.action_receipts,
+            host_function_calls: self.host_function_calls.unwrap_or_default(),
+            coverage_hits: self.coverage_hits,
         }
     }
 
@@ -2680,6 +3050,45 @@ impl<'a> VMLogic<'a> {
         self.gas_counter.gas_counter_raw_ptr()
     }
 
+    /// Calls the host function `f`, recording the call in `VMOutcome::host_function_calls` if
+    /// `VMContext::trace_host_functions` is set. Only the first `MAX_TRACED_HOST_FUNCTION_CALLS`
+    /// calls are recorded.
+    pub fn trace_host_function<T>(
+        &mut self,
+        name: &str,
+        args: &[(&str, u64)],
+        f: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        match &self.host_function_calls {
+            Some(calls) if calls.len() < MAX_TRACED_HOST_FUNCTION_CALLS => {}
+            _ => return f(self),
+        }
+        self.traced_call = Some(HostFunctionCall {
+            name: name.to_string(),
+            args: args.iter().map(|(name, value)| (name.to_string(), *value)).collect(),
+            burnt_gas_before: self.gas_counter.burnt_gas(),
+            burnt_gas_after: 0,
+            storage_keys: vec![],
+            registers_written: vec![],
+            error: None,
+        });
+        let result = f(self);
+        if let Some(mut call) = self.traced_call.take() {
+            call.burnt_gas_after = self.gas_counter.burnt_gas();
+            call.error = result.as_ref().err().map(|err| err.to_string());
+            if let Some(calls) = &mut self.host_function_calls {
+                calls.push(call);
+            }
+        }
+        result
+    }
+
+    fn trace_storage_key(&mut self, key: &[u8]) {
+        if let Some(call) = &mut self.traced_call {
+            call.storage_keys.push(key.to_vec());
+        }
+    }
+
     /// Properly handles gas limit exceeded error.
     pub fn process_gas_limit(&mut self) -> HostError {
         let new_burn_gas = self.gas_counter.burnt_gas();
@@ -2699,6 +3108,11 @@ pub struct VMOutcome {
     /// Data collected from making a contract call
     pub profile: ProfileData,
     pub action_receipts: Vec<(AccountId, ReceiptMetadata)>,
+    /// Host function calls made by the contract, recorded only if
+    /// `VMContext::trace_host_functions` is set.
+    pub host_function_calls: Vec<HostFunctionCall>,
+    /// Number of executions of each code block instrumented for coverage, by block index.
+    pub coverage_hits: BTreeMap<u32, u64>,
 }
 
 impl std::fmt::Debug for VMOutcome {
@@ -22,5 +22,6 @@ pub fn get_context(input: Vec<u8>, is_view: bool) -> VMContext {
             false => None,
         },
         output_data_receivers: vec![],
+        trace_host_functions: false,
     }
 }
@@ -618,6 +618,168 @@ fn test_ecrecover() {
     }
 }
 
+#[test]
+#[cfg(feature = "protocol_feature_ed25519_verify")]
+fn test_ed25519_verify() {
+    use near_crypto::{KeyType, PublicKey, SecretKey, Signature};
+    use near_vm_errors::VMLogicError;
+
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], false));
+
+    let secret_key = SecretKey::from_seed(KeyType::ED25519, "test");
+    let message = b"message";
+    let signature = match secret_key.sign(message) {
+        Signature::ED25519(signature) => signature.to_bytes(),
+        _ => unreachable!(),
+    };
+    let public_key = match secret_key.public_key() {
+        PublicKey::ED25519(public_key) => public_key.0,
+        _ => unreachable!(),
+    };
+
+    let res = logic
+        .ed25519_verify(
+            signature.len() as _,
+            signature.as_ptr() as _,
+            message.len() as _,
+            message.as_ptr() as _,
+            public_key.len() as _,
+            public_key.as_ptr() as _,
+        )
+        .unwrap();
+    assert_eq!(res, 1);
+    assert_costs(map! {
+        ExtCosts::base: 1,
+        ExtCosts::read_memory_base: 3,
+        ExtCosts::read_memory_byte: 64 + 7 + 32,
+        ExtCosts::ed25519_verify_base: 1,
+        ExtCosts::ed25519_verify_byte: 7,
+    });
+    reset_costs_counter();
+
+    let mut batch = vec![];
+    for message in [&message[..], b"other message"] {
+        batch.extend_from_slice(&signature);
+        batch.extend_from_slice(&public_key);
+        batch.extend_from_slice(&(message.len() as u32).to_le_bytes());
+        batch.extend_from_slice(message);
+    }
+    let res = logic.ed25519_verify_batch(batch.len() as _, batch.as_ptr() as _).unwrap();
+    assert_eq!(res, 0);
+    assert_costs(map! {
+        ExtCosts::base: 1,
+        ExtCosts::read_memory_base: 1,
+        ExtCosts::read_memory_byte: batch.len() as u64,
+        ExtCosts::ed25519_verify_base: 2,
+        ExtCosts::ed25519_verify_byte: 7 + 13,
+    });
+
+    let res = logic.ed25519_verify_batch(batch.len() as u64 - 1, batch.as_ptr() as _);
+    assert!(matches!(
+        res,
+        Err(VMLogicError::HostError(HostError::Ed25519VerifyInvalidInput { .. }))
+    ));
+}
+
+#[test]
+#[cfg(feature = "protocol_feature_ed25519_verify")]
+fn test_secp256k1_verify() {
+    use near_crypto::{KeyType, PublicKey, SecretKey, Signature};
+    use near_vm_errors::VMLogicError;
+
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], false));
+
+    let secret_key = SecretKey::from_seed(KeyType::SECP256K1, "test");
+    let hash = [42u8; 32];
+    let signature: [u8; 64] = match secret_key.sign(&hash) {
+        Signature::SECP256K1(signature) => <[u8; 65]>::from(signature)[..64].try_into().unwrap(),
+        _ => unreachable!(),
+    };
+    let public_key: [u8; 64] = match secret_key.public_key() {
+        PublicKey::SECP256K1(public_key) => public_key.into(),
+        _ => unreachable!(),
+    };
+
+    let mut verify = |signature: &[u8], hash: &[u8]| {
+        logic.secp256k1_verify(
+            signature.len() as _,
+            signature.as_ptr() as _,
+            hash.len() as _,
+            hash.as_ptr() as _,
+            public_key.len() as _,
+            public_key.as_ptr() as _,
+        )
+    };
+    assert_eq!(verify(&signature, &hash).unwrap(), 1);
+    assert_costs(map! {
+        ExtCosts::base: 1,
+        ExtCosts::read_memory_base: 3,
+        ExtCosts::read_memory_byte: 64 + 32 + 64,
+        ExtCosts::secp256k1_verify_base: 1,
+    });
+    assert_eq!(verify(&signature, &[0; 32]).unwrap(), 0);
+    assert!(matches!(
+        verify(&signature, &hash[..31]),
+        Err(VMLogicError::HostError(HostError::Secp256k1VerifyInvalidInput { .. }))
+    ));
+    assert!(matches!(
+        verify(&signature[..63], &hash),
+        Err(VMLogicError::HostError(HostError::Secp256k1VerifyInvalidInput { .. }))
+    ));
+}
+
+#[test]
+#[cfg(feature = "protocol_feature_bls12381")]
+fn test_bls12381() {
+    use near_vm_errors::VMLogicError;
+
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], false));
+
+    let g1 = Vec::from_hex(
+        "17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb\
+         08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1",
+    )
+    .unwrap();
+    let mut data = vec![0];
+    data.extend_from_slice(&g1);
+    data.push(1);
+    data.extend_from_slice(&g1);
+    logic.bls12381_p1_sum(data.len() as _, data.as_ptr() as _, 0).unwrap();
+    let mut infinity = vec![0u8; 96];
+    infinity[0] = 0x40;
+    let res = vec![0u8; 96];
+    logic.read_register(0, res.as_ptr() as _).unwrap();
+    assert_eq!(res, infinity);
+    assert_costs(map! {
+        ExtCosts::base: 2,
+        ExtCosts::read_memory_base: 1,
+        ExtCosts::read_memory_byte: data.len() as u64,
+        ExtCosts::write_register_base: 1,
+        ExtCosts::write_register_byte: 96,
+        ExtCosts::read_register_base: 1,
+        ExtCosts::read_register_byte: 96,
+        ExtCosts::write_memory_base: 1,
+        ExtCosts::write_memory_byte: 96,
+        ExtCosts::bls12381_p1_sum_base: 1,
+        ExtCosts::bls12381_p1_sum_element: 2,
+    });
+    reset_costs_counter();
+
+    let res = logic.bls12381_pairing_check(0, data.as_ptr() as _).unwrap();
+    assert_eq!(res, 1);
+    assert_costs(map! {
+        ExtCosts::base: 1,
+        ExtCosts::read_memory_base: 1,
+        ExtCosts::bls12381_pairing_base: 1,
+    });
+
+    let res = logic.bls12381_p1_sum(data.len() as u64 - 1, data.as_ptr() as _, 0);
+    assert!(matches!(res, Err(VMLogicError::HostError(HostError::Bls12381InvalidInput { .. }))));
+}
+
 #[test]
 fn test_hash256_register() {
     let mut logic_builder = VMLogicBuilder::default();
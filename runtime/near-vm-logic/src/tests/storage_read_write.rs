@@ -1,6 +1,6 @@
 use crate::tests::fixtures::get_context;
 use crate::tests::vm_logic_builder::VMLogicBuilder;
-use crate::External;
+use crate::{External, VMLogic};
 
 #[test]
 fn test_storage_write_with_register() {
@@ -71,3 +71,43 @@ fn test_storage_has_key_with_register() {
 
     assert_eq!(logic.storage_has_key(u64::MAX, 1 as _), Ok(1));
 }
+
+#[test]
+fn test_storage_write_is_traced() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut context = get_context(vec![], false);
+    context.trace_host_functions = true;
+    let mut logic = logic_builder.build(context);
+
+    let key: &[u8] = b"foo";
+    let val: &[u8] = b"bar";
+
+    let write = |logic: &mut VMLogic| {
+        logic.trace_host_function("storage_write", &[("key_len", key.len() as u64)], |logic| {
+            logic.storage_write(
+                key.len() as _,
+                key.as_ptr() as _,
+                val.len() as _,
+                val.as_ptr() as _,
+                0,
+            )
+        })
+    };
+    write(&mut logic).expect("storage write ok");
+    write(&mut logic).expect("storage write ok");
+    logic
+        .trace_host_function("storage_read", &[], |logic| logic.storage_read(u64::MAX, 1 as _, 0))
+        .unwrap_err();
+
+    let calls = logic.compute_outcome_and_distribute_gas().host_function_calls;
+    assert_eq!(calls.len(), 3);
+    assert_eq!(calls[0].name, "storage_write");
+    assert_eq!(calls[0].args, vec![("key_len".to_string(), 3)]);
+    assert_eq!(calls[0].storage_keys, vec![key.to_vec()]);
+    assert!(calls[0].registers_written.is_empty());
+    assert!(calls[0].burnt_gas() > 0);
+    assert_eq!(calls[0].burnt_gas_after, calls[1].burnt_gas_before);
+    // Overwriting the value evicts the old one into the register.
+    assert_eq!(calls[1].registers_written, vec![(0, 3)]);
+    assert_eq!(calls[2].error.as_deref(), Some("HostError(InvalidRegisterId { register_id: 1 })"));
+}
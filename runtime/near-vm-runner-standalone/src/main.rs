@@ -2,10 +2,14 @@
 
 mod script;
 
-use crate::script::Script;
+use crate::script::{Contract, Scenario, Script};
 use clap::Parser;
+use near_primitives::runtime::config_store::RuntimeConfigStore;
+use near_primitives::trace::HostFunctionCall;
+use near_primitives::version::PROTOCOL_VERSION;
 use near_vm_logic::ProtocolVersion;
 use near_vm_logic::VMOutcome;
+use near_vm_runner::analysis::{check_contract, compile_cost, summarize_contract, ContractIssue};
 use near_vm_runner::internal::VMKind;
 use near_vm_runner::VMResult;
 use serde::{
@@ -70,8 +74,16 @@ struct CliArgs {
     #[clap(long)]
     input: Option<String>,
     /// The name of the method to call on the smart contract.
-    #[clap(long)]
-    method_name: String,
+    #[clap(long, required_unless_present_any = &["scenario-file", "analyze"])]
+    method_name: Option<String>,
+    /// Runs the sequence of calls described in the JSON file instead of a single method, see
+    /// README for the format.
+    #[clap(long, conflicts_with = "method-name")]
+    scenario_file: Option<PathBuf>,
+    /// Checks whether the contract passes validation at every protocol version and prints its
+    /// imports, methods and limits instead of running it.
+    #[clap(long, conflicts_with_all = &["method-name", "scenario-file"])]
+    analyze: bool,
     /// Key-value state in JSON base64 format for the smart contract as HashMap.
     #[clap(long)]
     state: Option<String>,
@@ -101,6 +113,9 @@ struct CliArgs {
     /// Protocol version.
     #[clap(long)]
     protocol_version: Option<ProtocolVersion>,
+    /// Records every host function call made by the contract and prints the trace.
+    #[clap(long)]
+    trace: bool,
 }
 
 #[allow(unused)]
@@ -144,9 +159,22 @@ fn main() {
     }
 
     let code = fs::read(&cli_args.wasm_file).unwrap();
+
+    if cli_args.analyze {
+        let protocol_version = cli_args.protocol_version.unwrap_or(PROTOCOL_VERSION);
+        if !print_contract_analysis(&code, protocol_version) {
+            std::process::exit(1);
+        }
+        return;
+    }
     let contract = script.contract(code);
 
-    let step = script.step(contract, &cli_args.method_name);
+    if let Some(path) = &cli_args.scenario_file {
+        run_scenario(script, contract, &Scenario::from_file(path));
+        return;
+    }
+
+    let step = script.step(contract, cli_args.method_name.as_deref().unwrap());
 
     if let Some(value) = &cli_args.context {
         step.context(serde_json::from_str(value).unwrap());
@@ -162,6 +190,7 @@ fn main() {
     let promise_results =
         cli_args.promise_results.iter().map(|it| serde_json::from_str(it).unwrap()).collect();
     step.promise_results(promise_results);
+    step.trace_host_functions(cli_args.trace);
 
     let mut results = script.run();
     let last_result = results.outcomes.pop().unwrap();
@@ -184,6 +213,126 @@ fn main() {
                 println!("{}\n", log);
             }
             println!("{:#?}", outcome.profile);
+
+            if cli_args.trace {
+                println!("\nHost function calls:");
+                for call in &outcome.host_function_calls {
+                    print_host_function_call(call);
+                }
+            }
+        }
+    }
+}
+
+/// Prints the contract summary and the validation issues per range of protocol versions up to
+/// `protocol_version`. Returns whether the contract passes validation at `protocol_version`.
+fn print_contract_analysis(code: &[u8], protocol_version: ProtocolVersion) -> bool {
+    let summary = match summarize_contract(code) {
+        Ok(summary) => summary,
+        Err(err) => {
+            println!("Failed to parse the contract: {}", err);
+            return false;
+        }
+    };
+    let config_store = RuntimeConfigStore::new(None);
+    let config = &config_store.get_config(protocol_version).wasm_config;
+
+    println!("Code size: {} bytes", summary.code_size);
+    println!("Compile cost: {} gas", compile_cost(summary.code_size, config));
+    match &summary.memory {
+        Some(memory) => println!(
+            "Memory: {} {} pages, maximum {:?}, allowed {} pages, maximum {}",
+            if memory.imported { "imported" } else { "declared" },
+            memory.initial,
+            memory.maximum,
+            config.limit_config.initial_memory_pages,
+            config.limit_config.max_memory_pages
+        ),
+        None => println!("Memory: none"),
+    }
+    println!(
+        "Functions: {} (limit {:?}), locals: {}, at most {} per function",
+        summary.functions,
+        config.limit_config.max_functions_number_per_contract,
+        summary.locals,
+        summary.max_function_locals
+    );
+    println!("\nImports:");
+    for import in &summary.imports {
+        println!(
+            "{}::{}({}) -> ({})",
+            import.module,
+            import.name,
+            import.params.join(", "),
+            import.returns.join(", ")
+        );
+    }
+    println!("\nMethods:");
+    for method in &summary.methods {
+        println!("{}", method);
+    }
+
+    // Protocol versions with the same issues are grouped into ranges.
+    let mut ranges: Vec<(ProtocolVersion, ProtocolVersion, Vec<ContractIssue>)> = Vec::new();
+    for version in 1..=protocol_version {
+        let config = &config_store.get_config(version).wasm_config;
+        let issues = check_contract(code, config, version);
+        match ranges.last_mut() {
+            Some((_, end, last_issues)) if *last_issues == issues => *end = version,
+            _ => ranges.push((version, version, issues)),
+        }
+    }
+    println!("\nProtocol versions:");
+    for (start, end, issues) in &ranges {
+        if issues.is_empty() {
+            println!("{}..={}: ok", start, end);
+        }
+        for issue in issues {
+            println!("{}..={}: {}", start, end, issue);
         }
     }
+    ranges.last().map_or(true, |(_, _, issues)| issues.is_empty())
+}
+
+fn run_scenario(mut script: Script, contract: Contract, scenario: &Scenario) {
+    if let Some(state) = &scenario.state {
+        script.initial_state(state.clone());
+    }
+    script.carry_over(true);
+    script.scenario(contract, scenario);
+
+    let results = script.run();
+    let mut failed = 0;
+    for (i, (step, result)) in scenario.steps.iter().zip(&results.outcomes).enumerate() {
+        let failures = step.expect.check(result);
+        println!("#{} {}: {}", i, step.method, if failures.is_empty() { "ok" } else { "FAILED" });
+        for log in result.outcome().map_or(&[][..], |outcome| &outcome.logs[..]) {
+            println!("    log: {}", log);
+        }
+        for failure in &failures {
+            println!("    {}", failure);
+        }
+        if !failures.is_empty() {
+            failed += 1;
+        }
+    }
+    println!("\n{} passed, {} failed", scenario.steps.len() - failed, failed);
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+fn print_host_function_call(call: &HostFunctionCall) {
+    let args: Vec<String> =
+        call.args.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+    println!("{}({}) burnt {} gas", call.name, args.join(", "), call.burnt_gas());
+    for key in &call.storage_keys {
+        println!("    storage key: {}", base64::encode(key));
+    }
+    for (register_id, len) in &call.registers_written {
+        println!("    register {}: {} bytes", register_id, len);
+    }
+    if let Some(error) = &call.error {
+        println!("    error: {}", error);
+    }
 }
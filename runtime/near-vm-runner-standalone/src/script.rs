@@ -6,10 +6,11 @@ use near_primitives::runtime::config_store::RuntimeConfigStore;
 use near_primitives::types::CompiledContractCache;
 use near_primitives::version::PROTOCOL_VERSION;
 use near_vm_logic::mocks::mock_external::MockedExternal;
-use near_vm_logic::types::PromiseResult;
-use near_vm_logic::{ProtocolVersion, VMConfig, VMContext};
+use near_vm_logic::types::{AccountId, Balance, PromiseResult};
+use near_vm_logic::{HostError, ProtocolVersion, ReturnData, VMConfig, VMContext, VMLogicError};
 use near_vm_runner::internal::VMKind;
 use near_vm_runner::{MockCompiledContractCache, VMResult};
+use serde::Deserialize;
 
 use crate::State;
 
@@ -25,6 +26,7 @@ pub struct Script {
     protocol_version: ProtocolVersion,
     contract_cache: Option<Box<dyn CompiledContractCache>>,
     initial_state: Option<State>,
+    carry_over: bool,
     steps: Vec<Step>,
 }
 
@@ -53,6 +55,7 @@ impl Default for Script {
             protocol_version,
             contract_cache: None,
             initial_state: None,
+            carry_over: false,
             steps: Vec::new(),
         }
     }
@@ -105,6 +108,35 @@ impl Script {
         self.initial_state(state)
     }
 
+    /// Makes every call see the account balance and storage usage left by the previous one,
+    /// credits the attached deposit to the account before the call and reverts the state changes
+    /// of failed calls, like the runtime does.
+    pub(crate) fn carry_over(&mut self, yes: bool) {
+        self.carry_over = yes;
+    }
+
+    /// Adds a step for every call of the scenario. Block height and timestamp advance
+    /// cumulatively, starting from the scenario context.
+    pub(crate) fn scenario(&mut self, contract: Contract, scenario: &Scenario) {
+        let mut context = scenario.context.clone().unwrap_or_else(default_vm_context);
+        for call in &scenario.steps {
+            context.block_index += call.advance_blocks;
+            context.block_timestamp += call.advance_timestamp_ns;
+            let mut step_context = context.clone();
+            step_context.input = call.input.as_bytes().to_vec();
+            step_context.attached_deposit = call.attached_deposit;
+            if let Some(account_id) = &call.predecessor_account_id {
+                step_context.predecessor_account_id = account_id.clone();
+            }
+            if let Some(account_id) = &call.signer_account_id {
+                step_context.signer_account_id = account_id.clone();
+            }
+            self.step(contract, &call.method)
+                .context(step_context)
+                .promise_results(call.promise_results.clone());
+        }
+    }
+
     pub(crate) fn step(&mut self, contract: Contract, method: &str) -> &mut Step {
         self.steps.push(Step::new(contract, method.to_string()));
         self.steps.last_mut().unwrap()
@@ -120,18 +152,52 @@ impl Script {
         let runtime_fees_config = &config_store.get_config(self.protocol_version).transaction_costs;
         let mut outcomes = Vec::new();
         if let Some(runtime) = self.vm_kind.runtime(self.vm_config.clone()) {
+            let mut account = None;
             for step in &self.steps {
                 for _ in 0..step.repeat {
+                    let mut context = step.vm_context.clone();
+                    let state = if self.carry_over {
+                        if let Some((balance, storage_usage)) = account {
+                            context.account_balance = balance;
+                            context.storage_usage = storage_usage;
+                        }
+                        context.account_balance =
+                            match context.account_balance.checked_add(context.attached_deposit) {
+                                Some(balance) => balance,
+                                None => {
+                                    // The deposit can't be credited, so the call doesn't run.
+                                    let err = VMLogicError::HostError(HostError::IntegerOverflow);
+                                    outcomes.push(VMResult::NotRun(err.into()));
+                                    continue;
+                                }
+                            };
+                        Some(external.fake_trie.clone())
+                    } else {
+                        None
+                    };
                     let res = runtime.run(
                         &self.contracts[step.contract.0],
                         &step.method,
                         &mut external,
-                        step.vm_context.clone(),
+                        context.clone(),
                         runtime_fees_config,
                         &step.promise_results,
                         self.protocol_version,
                         self.contract_cache.as_deref(),
                     );
+                    if let Some(state) = state {
+                        match &res {
+                            VMResult::Ok(outcome) => {
+                                account = Some((outcome.balance, outcome.storage_usage));
+                            }
+                            _ => {
+                                // The deposit of a failed call is refunded.
+                                external.fake_trie = state;
+                                let balance = context.account_balance - context.attached_deposit;
+                                account = Some((balance, context.storage_usage));
+                            }
+                        }
+                    }
                     outcomes.push(res);
                 }
             }
@@ -170,6 +236,10 @@ impl Step {
         self.promise_results = promise_results;
         self
     }
+    pub(crate) fn trace_host_functions(&mut self, yes: bool) -> &mut Step {
+        self.vm_context.trace_host_functions = yes;
+        self
+    }
     #[allow(unused)]
     pub(crate) fn repeat(&mut self, n: u32) -> &mut Step {
         self.repeat = n;
@@ -177,6 +247,102 @@ impl Step {
     }
 }
 
+/// A sequence of calls to a single contract, run one after another against the same state.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Scenario {
+    /// Context of the first call, see `VMContext`.
+    #[serde(default)]
+    pub(crate) context: Option<VMContext>,
+    /// Initial key-value state of the contract in JSON base64 format.
+    #[serde(default)]
+    pub(crate) state: Option<State>,
+    pub(crate) steps: Vec<ScenarioStep>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ScenarioStep {
+    pub(crate) method: String,
+    #[serde(default)]
+    pub(crate) input: String,
+    #[serde(default, with = "near_primitives::serialize::u128_dec_format")]
+    pub(crate) attached_deposit: Balance,
+    #[serde(default)]
+    pub(crate) predecessor_account_id: Option<AccountId>,
+    #[serde(default)]
+    pub(crate) signer_account_id: Option<AccountId>,
+    #[serde(default)]
+    pub(crate) promise_results: Vec<PromiseResult>,
+    /// Number of blocks produced since the previous call.
+    #[serde(default)]
+    pub(crate) advance_blocks: u64,
+    /// Nanoseconds passed since the previous call.
+    #[serde(default)]
+    pub(crate) advance_timestamp_ns: u64,
+    #[serde(default)]
+    pub(crate) expect: Expectation,
+}
+
+/// Checks on the result of a scenario call. Unless `error` is set, the call must succeed.
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Expectation {
+    /// The value returned by the method, as a UTF-8 string.
+    #[serde(default)]
+    pub(crate) return_value: Option<String>,
+    #[serde(default)]
+    pub(crate) logs: Option<Vec<String>>,
+    /// A substring of the error the call fails with.
+    #[serde(default)]
+    pub(crate) error: Option<String>,
+}
+
+impl Scenario {
+    pub(crate) fn from_file(path: &Path) -> Scenario {
+        let data = fs::read(path).unwrap();
+        serde_json::from_slice(&data).unwrap()
+    }
+}
+
+impl Expectation {
+    /// Returns a description of every expectation the result doesn't meet.
+    pub(crate) fn check(&self, result: &VMResult) -> Vec<String> {
+        let mut failures = Vec::new();
+        match (result.error(), &self.error) {
+            (None, None) => {}
+            (Some(err), Some(expected)) if err.to_string().contains(expected.as_str()) => {}
+            (Some(err), _) => failures.push(format!("unexpected error: {}", err)),
+            (None, Some(expected)) => {
+                failures.push(format!("expected error containing {:?}", expected))
+            }
+        }
+        let outcome = match result.outcome() {
+            Some(outcome) => outcome,
+            None => return failures,
+        };
+        if let Some(expected) = &self.return_value {
+            match &outcome.return_data {
+                ReturnData::Value(value) if value == expected.as_bytes() => {}
+                ReturnData::Value(value) => failures.push(format!(
+                    "expected return value {:?}, got {:?}",
+                    expected,
+                    String::from_utf8_lossy(value)
+                )),
+                other => {
+                    failures.push(format!("expected return value {:?}, got {:?}", expected, other))
+                }
+            }
+        }
+        if let Some(expected) = &self.logs {
+            if expected != &outcome.logs {
+                failures.push(format!("expected logs {:?}, got {:?}", expected, outcome.logs));
+            }
+        }
+        failures
+    }
+}
+
 fn default_vm_context() -> VMContext {
     VMContext {
         current_account_id: "alice".parse().unwrap(),
@@ -194,14 +360,13 @@ fn default_vm_context() -> VMContext {
         random_seed: vec![0, 1, 2],
         view_config: None,
         output_data_receivers: vec![],
+        trace_host_functions: false,
         epoch_height: 1,
     }
 }
 
 #[test]
 fn vm_script_smoke_test() {
-    use near_vm_logic::ReturnData;
-
     tracing_span_tree::span_tree().enable();
 
     let mut script = Script::default();
@@ -247,6 +412,94 @@ fn profile_data_is_per_outcome() {
     );
 }
 
+#[test]
+fn host_function_calls_are_traced() {
+    let mut script = Script::default();
+
+    let contract = script.contract(near_test_contracts::rs_contract().to_vec());
+
+    let input: Vec<u8> = [1u64.to_le_bytes(), 2u64.to_le_bytes()].concat();
+    script.step(contract, "write_key_value").input(input.clone()).trace_host_functions(true);
+    script.step(contract, "write_key_value").input(input);
+    let res = script.run();
+
+    let calls = &res.outcomes[0].outcome().unwrap().host_function_calls;
+    let write = calls.iter().find(|call| call.name == "storage_write").unwrap();
+    assert_eq!(write.storage_keys, vec![1u64.to_le_bytes().to_vec()]);
+    assert_eq!(calls[0].name, "input");
+    assert_eq!(calls[0].registers_written, vec![(0, 16)]);
+    assert!(write.burnt_gas() > 0);
+    assert!(res.outcomes[1].outcome().unwrap().host_function_calls.is_empty());
+}
+
+#[test]
+fn scenario_state_is_shared_between_calls() {
+    let scenario: Scenario = serde_json::from_str(
+        r#"{
+            "steps": [
+                {"method": "write_key_value", "input": "key00001value001", "attached_deposit": "10"},
+                {"method": "read_value", "input": "key00001", "advance_blocks": 2,
+                 "expect": {"return_value": "value001"}},
+                {"method": "panic_after_logging",
+                 "expect": {"logs": ["hello"], "error": "WAT?"}},
+                {"method": "log_something", "expect": {"logs": ["bye"]}}
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let mut script = Script::default();
+    let contract = script.contract(near_test_contracts::rs_contract().to_vec());
+    script.carry_over(true);
+    script.scenario(contract, &scenario);
+    let res = script.run();
+
+    assert_eq!(res.outcomes.len(), 4);
+    let checks: Vec<_> = scenario
+        .steps
+        .iter()
+        .zip(&res.outcomes)
+        .map(|(step, res)| step.expect.check(res))
+        .collect();
+    assert!(checks[..3].iter().all(|failures| failures.is_empty()), "{:?}", checks);
+    assert_eq!(checks[3], vec![r#"expected logs ["bye"], got ["hello"]"#.to_string()]);
+
+    let first = res.outcomes[0].outcome().unwrap();
+    let second = res.outcomes[1].outcome().unwrap();
+    assert_eq!(first.balance, default_vm_context().account_balance + 10);
+    assert_eq!(second.storage_usage, first.storage_usage);
+    assert!(second.storage_usage > default_vm_context().storage_usage);
+}
+
+#[test]
+fn scenario_deposit_overflow_is_an_error() {
+    let scenario: Scenario = serde_json::from_str(&format!(
+        r#"{{
+            "steps": [
+                {{"method": "log_something", "attached_deposit": "{}"}},
+                {{"method": "log_something"}}
+            ]
+        }}"#,
+        Balance::MAX
+    ))
+    .unwrap();
+
+    let mut script = Script::default();
+    let contract = script.contract(near_test_contracts::rs_contract().to_vec());
+    script.carry_over(true);
+    script.scenario(contract, &scenario);
+    let res = script.run();
+
+    assert_eq!(res.outcomes.len(), 2);
+    assert!(matches!(res.outcomes[0], VMResult::NotRun(_)));
+    assert_eq!(
+        res.outcomes[0].error(),
+        Some(&VMLogicError::HostError(HostError::IntegerOverflow).into())
+    );
+    let second = res.outcomes[1].outcome().unwrap();
+    assert_eq!(second.balance, default_vm_context().account_balance);
+}
+
 #[cfg(feature = "no_cache")]
 #[test]
 fn test_evm_slow_deserialize_repro() {
@@ -0,0 +1,267 @@
+//! Static analysis of contracts, to find out before deployment whether a contract passes the
+//! validation done by the runtime.
+//!
+//! Deploying a contract doesn't validate the code, so a contract that fails validation is only
+//! noticed when its methods are called. [`check_contract`] runs the same checks ahead of time.
+
+use std::fmt;
+
+use near_primitives::types::Gas;
+use near_vm_errors::PrepareError;
+use near_vm_logic::{ProtocolVersion, VMConfig};
+use wasmparser::{ExternalKind, ImportSectionEntryType, MemoryType, Parser, Payload, TypeDef};
+
+use crate::imports::available_host_functions;
+use crate::prepare::prepare_contract;
+
+/// Properties of a contract that don't depend on the protocol version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractSummary {
+    /// Size of the code in bytes.
+    pub code_size: u64,
+    /// Functions imported by the contract.
+    pub imports: Vec<ImportedFunction>,
+    /// Names of the exported functions, i.e. the methods that can be called.
+    pub methods: Vec<String>,
+    /// Memory declared or imported by the contract.
+    pub memory: Option<MemoryLimits>,
+    /// Number of functions defined by the contract, imports excluded.
+    pub functions: u64,
+    /// Number of locals of all functions, parameters excluded.
+    pub locals: u64,
+    /// Largest number of locals of a single function, parameters excluded.
+    pub max_function_locals: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedFunction {
+    pub module: String,
+    pub name: String,
+    /// Wasm types of the parameters, e.g. `i64`.
+    pub params: Vec<String>,
+    /// Wasm types of the results.
+    pub returns: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryLimits {
+    /// Whether the memory is imported rather than declared by the contract.
+    pub imported: bool,
+    /// Initial number of pages.
+    pub initial: u64,
+    /// Maximum number of pages, if any.
+    pub maximum: Option<u64>,
+}
+
+/// A reason for a contract to fail when deployed or called.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContractIssue {
+    /// The code is larger than `max_contract_size`, so it can't be deployed.
+    ContractSizeExceeded { size: u64, limit: u64 },
+    /// The code is rejected by `prepare_contract`.
+    Prepare(PrepareError),
+    /// The contract imports a function the runtime doesn't provide.
+    UnknownImport { module: String, name: String },
+    /// The contract imports a host function with a different signature.
+    ImportSignatureMismatch { name: String, expected_params: Vec<String> },
+    /// The method name is longer than `max_length_method_name`, so it can't be called.
+    MethodNameTooLong { name: String, limit: u64 },
+}
+
+impl fmt::Display for ContractIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractIssue::ContractSizeExceeded { size, limit } => {
+                write!(f, "contract size {} exceeds the limit of {} bytes", size, limit)
+            }
+            ContractIssue::Prepare(err) => write!(f, "contract validation failed: {}", err),
+            ContractIssue::UnknownImport { module, name } => {
+                write!(f, "unknown import `{}::{}`", module, name)
+            }
+            ContractIssue::ImportSignatureMismatch { name, expected_params } => write!(
+                f,
+                "host function `{}` is imported with a wrong signature, expected params ({})",
+                name,
+                expected_params.join(", ")
+            ),
+            ContractIssue::MethodNameTooLong { name, limit } => {
+                write!(f, "method name `{}` is longer than {} bytes", name, limit)
+            }
+        }
+    }
+}
+
+/// Parses the contract and collects its imports, exports, memory and function statistics.
+pub fn summarize_contract(code: &[u8]) -> Result<ContractSummary, PrepareError> {
+    summarize(code).map_err(|_| PrepareError::Deserialization)
+}
+
+fn summarize(code: &[u8]) -> Result<ContractSummary, wasmparser::BinaryReaderError> {
+    let mut summary = ContractSummary {
+        code_size: code.len() as u64,
+        imports: vec![],
+        methods: vec![],
+        memory: None,
+        functions: 0,
+        locals: 0,
+        max_function_locals: 0,
+    };
+    let mut types = vec![];
+    for payload in Parser::new(0).parse_all(code) {
+        match payload? {
+            Payload::TypeSection(reader) => {
+                for ty in reader {
+                    types.push(match ty? {
+                        TypeDef::Func(func_type) => Some(func_type),
+                        _ => None,
+                    });
+                }
+            }
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import?;
+                    match import.ty {
+                        ImportSectionEntryType::Function(type_idx) => {
+                            let func_type = types.get(type_idx as usize).and_then(Option::as_ref);
+                            let type_names = |types: Option<&[wasmparser::Type]>| {
+                                types
+                                    .unwrap_or(&[])
+                                    .iter()
+                                    .map(|ty| wasm_type_name(*ty).to_string())
+                                    .collect()
+                            };
+                            summary.imports.push(ImportedFunction {
+                                module: import.module.to_string(),
+                                name: import.field.unwrap_or_default().to_string(),
+                                params: type_names(func_type.map(|ty| &ty.params[..])),
+                                returns: type_names(func_type.map(|ty| &ty.returns[..])),
+                            });
+                        }
+                        ImportSectionEntryType::Memory(memory) => {
+                            summary.memory = Some(memory_limits(memory, true));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Payload::MemorySection(reader) => {
+                for memory in reader {
+                    summary.memory = Some(memory_limits(memory?, false));
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let export = export?;
+                    if let ExternalKind::Function = export.kind {
+                        summary.methods.push(export.field.to_string());
+                    }
+                }
+            }
+            Payload::CodeSectionEntry(body) => {
+                let mut locals = 0u64;
+                for local in body.get_locals_reader()? {
+                    locals = locals.saturating_add(local?.0 as u64);
+                }
+                summary.functions += 1;
+                summary.locals = summary.locals.saturating_add(locals);
+                summary.max_function_locals = summary.max_function_locals.max(locals);
+            }
+            _ => {}
+        }
+    }
+    Ok(summary)
+}
+
+fn memory_limits(memory: MemoryType, imported: bool) -> MemoryLimits {
+    let (initial, maximum) = match memory {
+        MemoryType::M32 { limits, .. } => (limits.initial as u64, limits.maximum.map(u64::from)),
+        MemoryType::M64 { limits, .. } => (limits.initial, limits.maximum),
+    };
+    MemoryLimits { imported, initial, maximum }
+}
+
+fn wasm_type_name(ty: wasmparser::Type) -> &'static str {
+    match ty {
+        wasmparser::Type::I32 => "i32",
+        wasmparser::Type::I64 => "i64",
+        wasmparser::Type::F32 => "f32",
+        wasmparser::Type::F64 => "f64",
+        _ => "other",
+    }
+}
+
+/// Wasm type of a host function parameter or result declared in `imports!`.
+fn host_type_name(rust_type: &str) -> &'static str {
+    match rust_type {
+        "u32" | "i32" => "i32",
+        _ => "i64",
+    }
+}
+
+/// Gas charged for loading the contract on every function call.
+pub fn compile_cost(code_size: u64, config: &VMConfig) -> Gas {
+    config
+        .ext_costs
+        .contract_compile_base
+        .saturating_add(config.ext_costs.contract_compile_bytes.saturating_mul(code_size))
+}
+
+/// Finds every reason for the contract to fail when deployed or called under the given config
+/// and protocol version. An empty result means the contract passes validation.
+pub fn check_contract(
+    code: &[u8],
+    config: &VMConfig,
+    protocol_version: ProtocolVersion,
+) -> Vec<ContractIssue> {
+    let mut issues = vec![];
+    let size = code.len() as u64;
+    if size > config.limit_config.max_contract_size {
+        issues.push(ContractIssue::ContractSizeExceeded {
+            size,
+            limit: config.limit_config.max_contract_size,
+        });
+    }
+    if let Err(err) = prepare_contract(code, config) {
+        issues.push(ContractIssue::Prepare(err));
+    }
+    let summary = match summarize_contract(code) {
+        Ok(summary) => summary,
+        // Already reported by `prepare_contract`.
+        Err(_) => return issues,
+    };
+
+    let host_functions = available_host_functions(protocol_version);
+    for import in &summary.imports {
+        let host_function = host_functions
+            .iter()
+            .find(|host_function| import.module == "env" && host_function.name == import.name);
+        let host_function = match host_function {
+            Some(host_function) => host_function,
+            None => {
+                issues.push(ContractIssue::UnknownImport {
+                    module: import.module.clone(),
+                    name: import.name.clone(),
+                });
+                continue;
+            }
+        };
+        let expected_params: Vec<_> =
+            host_function.params.iter().map(|ty| host_type_name(ty).to_string()).collect();
+        let expected_returns: Vec<_> =
+            host_function.returns.iter().map(|ty| host_type_name(ty).to_string()).collect();
+        if import.params != expected_params || import.returns != expected_returns {
+            issues.push(ContractIssue::ImportSignatureMismatch {
+                name: import.name.clone(),
+                expected_params,
+            });
+        }
+    }
+
+    let limit = config.limit_config.max_length_method_name;
+    for method in &summary.methods {
+        if method.len() as u64 > limit {
+            issues.push(ContractIssue::MethodNameTooLong { name: method.clone(), limit });
+        }
+    }
+    issues
+}
@@ -57,9 +57,16 @@ pub fn get_contract_cache_key(
     config: &VMConfig,
 ) -> CryptoHash {
     let _span = tracing::debug_span!(target: "vm", "get_key").entered();
+    #[allow(unused_mut)]
+    let mut vm_config_non_crypto_hash = config.non_crypto_hash();
+    // Contracts instrumented for coverage must not be mixed up with regular ones.
+    #[cfg(feature = "sandbox")]
+    if crate::coverage::is_enabled() {
+        vm_config_non_crypto_hash = vm_config_non_crypto_hash.wrapping_add(1);
+    }
     let key = ContractCacheKey::Version4 {
         code_hash: *code.hash(),
-        vm_config_non_crypto_hash: config.non_crypto_hash(),
+        vm_config_non_crypto_hash,
         vm_kind,
         vm_hash: vm_hash(vm_kind),
     };
@@ -0,0 +1,291 @@
+//! Code coverage of contracts executed by a sandbox node.
+//!
+//! While coverage collection is enabled, [`prepare_contract`](crate::prepare::prepare_contract)
+//! instruments contracts with calls to the `sandbox_coverage_hit` host function at the start of
+//! every code block: the body of each function and the bodies of its `block`, `loop`, `if` and
+//! `else` instructions. Blocks are numbered in the order they appear in the code section.
+//!
+//! The execution counts are accumulated per contract code hash until [`reset`] is called, and can
+//! be exported with [`report`] or in the LCOV format with [`to_lcov`]. Functions are named after
+//! the Wasm name section of the contract, if present.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use near_primitives::contract::ContractCode;
+use near_primitives::hash::CryptoHash;
+use near_vm_errors::PrepareError;
+use once_cell::sync::Lazy;
+use pwasm_utils::parity_wasm::elements;
+use wasmparser::{Name, NameSectionReader, Operator, Parser, Payload};
+
+/// Name of the host function counting executions of code blocks.
+const HIT_FUNCTION: &str = "sandbox_coverage_hit";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static COVERAGE: Lazy<Mutex<HashMap<CryptoHash, CoverageEntry>>> = Lazy::new(Default::default);
+
+/// Execution counts of the code blocks of a contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractCoverage {
+    pub code_hash: CryptoHash,
+    /// Functions defined by the contract, in the order of the code section.
+    pub functions: Vec<FunctionCoverage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionCoverage {
+    /// Index of the function in the function index space, imported functions included.
+    pub index: u32,
+    /// Name of the function from the name section.
+    pub name: Option<String>,
+    /// Index of the first block of the function among the blocks of the contract.
+    pub first_block: u32,
+    /// Execution counts of the blocks of the function. The first block is the function body, so
+    /// its count is the number of calls.
+    pub blocks: Vec<u64>,
+}
+
+impl FunctionCoverage {
+    pub fn calls(&self) -> u64 {
+        self.blocks.first().copied().unwrap_or(0)
+    }
+
+    fn display_name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| format!("func{}", self.index))
+    }
+}
+
+struct CoverageEntry {
+    coverage: ContractCoverage,
+    /// Function of each block of the contract, as an index into `coverage.functions`.
+    block_functions: Vec<u32>,
+}
+
+/// Enables or disables instrumentation of contracts compiled from now on. Contracts compiled
+/// with and without instrumentation are cached separately.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// Discards the counts collected so far.
+pub fn reset() {
+    COVERAGE.lock().unwrap().clear();
+}
+
+/// Returns the counts collected so far for all executed contracts, ordered by code hash.
+pub fn report() -> Vec<ContractCoverage> {
+    let coverage = COVERAGE.lock().unwrap();
+    let mut contracts: Vec<_> = coverage.values().map(|entry| entry.coverage.clone()).collect();
+    contracts.sort_by(|a, b| a.code_hash.cmp(&b.code_hash));
+    contracts
+}
+
+/// Formats coverage in the LCOV tracefile format. Each contract is reported as a source file named
+/// after its code hash, and block `i` of the contract as line `i + 1`.
+pub fn to_lcov(contracts: &[ContractCoverage]) -> String {
+    let mut out = String::new();
+    for contract in contracts {
+        writeln!(out, "TN:").unwrap();
+        writeln!(out, "SF:{}.wasm", contract.code_hash).unwrap();
+        for function in &contract.functions {
+            writeln!(out, "FN:{},{}", function.first_block + 1, function.display_name()).unwrap();
+        }
+        for function in &contract.functions {
+            writeln!(out, "FNDA:{},{}", function.calls(), function.display_name()).unwrap();
+        }
+        let functions_hit = contract.functions.iter().filter(|f| f.calls() > 0).count();
+        writeln!(out, "FNF:{}", contract.functions.len()).unwrap();
+        writeln!(out, "FNH:{}", functions_hit).unwrap();
+        let (mut lines, mut lines_hit) = (0, 0);
+        for function in &contract.functions {
+            for (i, count) in function.blocks.iter().enumerate() {
+                writeln!(out, "DA:{},{}", function.first_block as usize + i + 1, count).unwrap();
+                lines += 1;
+                lines_hit += (*count > 0) as usize;
+            }
+        }
+        writeln!(out, "LF:{}", lines).unwrap();
+        writeln!(out, "LH:{}", lines_hit).unwrap();
+        writeln!(out, "end_of_record").unwrap();
+    }
+    out
+}
+
+/// Adds the block execution counts of a single call of the contract.
+pub(crate) fn record(code: &ContractCode, hits: &BTreeMap<u32, u64>) {
+    if hits.is_empty() {
+        return;
+    }
+    let mut coverage = COVERAGE.lock().unwrap();
+    let entry = coverage.entry(*code.hash()).or_insert_with(|| {
+        CoverageEntry::new(*code.hash(), code.code()).unwrap_or_else(|_| CoverageEntry {
+            coverage: ContractCoverage { code_hash: *code.hash(), functions: vec![] },
+            block_functions: vec![],
+        })
+    });
+    for (&block, &count) in hits {
+        // Contracts can call `sandbox_coverage_hit` themselves, ignore made up blocks.
+        let function = match entry.block_functions.get(block as usize) {
+            Some(&function) => &mut entry.coverage.functions[function as usize],
+            None => continue,
+        };
+        let counter = &mut function.blocks[(block - function.first_block) as usize];
+        *counter = counter.saturating_add(count);
+    }
+}
+
+impl CoverageEntry {
+    fn new(code_hash: CryptoHash, code: &[u8]) -> Result<Self, wasmparser::BinaryReaderError> {
+        let mut functions = vec![];
+        let mut block_functions = vec![];
+        let mut imported_functions = 0;
+        let mut names = HashMap::new();
+        for payload in Parser::new(0).parse_all(code) {
+            match payload? {
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        if let wasmparser::ImportSectionEntryType::Function(_) = import?.ty {
+                            imported_functions += 1;
+                        }
+                    }
+                }
+                Payload::CodeSectionEntry(body) => {
+                    let mut blocks = 1;
+                    for operator in body.get_operators_reader()? {
+                        if let Operator::Block { .. }
+                        | Operator::Loop { .. }
+                        | Operator::If { .. }
+                        | Operator::Else = operator?
+                        {
+                            blocks += 1;
+                        }
+                    }
+                    let index = imported_functions + functions.len() as u32;
+                    let first_block = block_functions.len() as u32;
+                    block_functions.extend(std::iter::repeat(functions.len() as u32).take(blocks));
+                    functions.push(FunctionCoverage {
+                        index,
+                        name: None,
+                        first_block,
+                        blocks: vec![0; blocks],
+                    });
+                }
+                Payload::CustomSection { name: "name", data, data_offset, .. } => {
+                    // A malformed name section doesn't prevent execution, so it is ignored too.
+                    let _ = read_function_names(data, data_offset, &mut names);
+                }
+                _ => {}
+            }
+        }
+        for function in &mut functions {
+            function.name = names.remove(&function.index);
+        }
+        Ok(Self { coverage: ContractCoverage { code_hash, functions }, block_functions })
+    }
+}
+
+fn read_function_names(
+    data: &[u8],
+    data_offset: usize,
+    names: &mut HashMap<u32, String>,
+) -> Result<(), wasmparser::BinaryReaderError> {
+    for name in NameSectionReader::new(data, data_offset)? {
+        if let Name::Function(function_names) = name? {
+            let mut map = function_names.get_map()?;
+            for _ in 0..map.get_count() {
+                let naming = map.read()?;
+                names.insert(naming.index, naming.name.to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Inserts a call to `sandbox_coverage_hit` at the start of every code block, passing the index of
+/// the block. The host function is appended to the imported functions, so the indices of the
+/// functions defined by the contract are shifted by one.
+pub(crate) fn instrument(mut module: elements::Module) -> Result<elements::Module, PrepareError> {
+    use elements::{External, ImportEntry, Instruction, Internal, Section, Type, ValueType};
+
+    if module.code_section().map_or(true, |code| code.bodies().is_empty()) {
+        return Ok(module);
+    }
+
+    let hit_type = elements::FunctionType::new(vec![ValueType::I32], vec![]);
+    let types = module.type_section_mut().ok_or(PrepareError::Instantiate)?.types_mut();
+    let type_idx = match types.iter().position(|Type::Function(ty)| *ty == hit_type) {
+        Some(idx) => idx as u32,
+        None => {
+            types.push(Type::Function(hit_type));
+            types.len() as u32 - 1
+        }
+    };
+    let hit_func = module.import_count(elements::ImportCountType::Function) as u32;
+    module.import_section_mut().ok_or(PrepareError::Instantiate)?.entries_mut().push(
+        ImportEntry::new("env".to_string(), HIT_FUNCTION.to_string(), External::Function(type_idx)),
+    );
+
+    let shift = |idx: &mut u32| {
+        if *idx >= hit_func {
+            *idx += 1;
+        }
+    };
+    let mut next_block = 0u32;
+    for section in module.sections_mut() {
+        match section {
+            Section::Code(code) => {
+                for body in code.bodies_mut() {
+                    let instructions = std::mem::take(body.code_mut().elements_mut());
+                    let mut instrumented = Vec::with_capacity(instructions.len() * 2);
+                    let mut hit = |instrumented: &mut Vec<Instruction>| {
+                        instrumented.extend([
+                            Instruction::I32Const(next_block as i32),
+                            Instruction::Call(hit_func),
+                        ]);
+                        next_block += 1;
+                    };
+                    hit(&mut instrumented);
+                    for mut instruction in instructions {
+                        if let Instruction::Call(idx) = &mut instruction {
+                            shift(idx);
+                        }
+                        let starts_block = matches!(
+                            instruction,
+                            Instruction::Block(_)
+                                | Instruction::Loop(_)
+                                | Instruction::If(_)
+                                | Instruction::Else
+                        );
+                        instrumented.push(instruction);
+                        if starts_block {
+                            hit(&mut instrumented);
+                        }
+                    }
+                    *body.code_mut().elements_mut() = instrumented;
+                }
+            }
+            Section::Export(exports) => {
+                for export in exports.entries_mut() {
+                    if let Internal::Function(idx) = export.internal_mut() {
+                        shift(idx);
+                    }
+                }
+            }
+            Section::Element(segments) => {
+                for segment in segments.entries_mut() {
+                    segment.members_mut().iter_mut().for_each(shift);
+                }
+            }
+            Section::Start(idx) => shift(idx),
+            _ => {}
+        }
+    }
+    Ok(module)
+}
@@ -68,6 +68,25 @@ macro_rules! imports {
     }
 }
 
+/// Calls host function `$func` on `$logic`, recording the call if host function tracing is enabled
+/// for the execution. Calls to `gas` and `sandbox_coverage_hit` are inserted by instrumentation
+/// and too frequent to be worth recording.
+macro_rules! call_host_function {
+    ($logic:expr, $func:ident, [ $( $arg_name:ident ),* ]) => {{
+        const UNTRACED: bool = str_eq(stringify!($func), "gas")
+            || str_eq(stringify!($func), "sandbox_coverage_hit");
+        if UNTRACED {
+            $logic.$func( $( $arg_name, )* )
+        } else {
+            $logic.trace_host_function(
+                stringify!($func),
+                &[ $( (stringify!($arg_name), $arg_name as u64) ),* ],
+                |logic| logic.$func( $( $arg_name, )* ),
+            )
+        }
+    }};
+}
+
 imports! {
     // #############
     // # Registers #
@@ -228,10 +247,56 @@ imports! {
     #["protocol_feature_alt_bn128", AltBn128] alt_bn128_g1_multiexp<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
     #["protocol_feature_alt_bn128", AltBn128] alt_bn128_g1_sum<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
     #["protocol_feature_alt_bn128", AltBn128] alt_bn128_pairing_check<[value_len: u64, value_ptr: u64] -> [u64]>,
+    // ##########################
+    // # Signature verification #
+    // ##########################
+    #["protocol_feature_ed25519_verify", Ed25519Verify] ed25519_verify<[signature_len: u64, signature_ptr: u64, message_len: u64, message_ptr: u64, public_key_len: u64, public_key_ptr: u64] -> [u64]>,
+    #["protocol_feature_ed25519_verify", Ed25519Verify] ed25519_verify_batch<[value_len: u64, value_ptr: u64] -> [u64]>,
+    #["protocol_feature_ed25519_verify", Ed25519Verify] secp256k1_verify<[signature_len: u64, signature_ptr: u64, hash_len: u64, hash_ptr: u64, public_key_len: u64, public_key_ptr: u64] -> [u64]>,
+    // #############
+    // # BLS12-381 #
+    // #############
+    #["protocol_feature_bls12381", Bls12381] bls12381_p1_sum<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_p2_sum<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_g1_multiexp<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_g2_multiexp<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_map_fp_to_g1<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_map_fp2_to_g2<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_pairing_check<[value_len: u64, value_ptr: u64] -> [u64]>,
     // #############
     // #  Sandbox  #
     // #############
     ##["sandbox"] sandbox_debug_log<[len: u64, ptr: u64] -> []>,
+    ##["sandbox"] sandbox_coverage_hit<[block: u32] -> []>,
+}
+
+/// A host function contracts can import from the `env` module, with the Rust types of its
+/// parameters and results.
+pub(crate) struct HostFunction {
+    pub(crate) name: &'static str,
+    pub(crate) params: &'static [&'static str],
+    pub(crate) returns: &'static [&'static str],
+}
+
+/// Host functions available to contracts at `protocol_version`, used to check the imports of a
+/// contract without instantiating it.
+pub(crate) fn available_host_functions(
+    protocol_version: near_vm_logic::ProtocolVersion,
+) -> Vec<HostFunction> {
+    let mut functions = Vec::new();
+    macro_rules! add_import {
+        (
+          $func:ident < [ $( $arg_name:ident : $arg_type:ident ),* ] -> [ $( $returns:ident ),* ] >
+        ) => {
+            functions.push(HostFunction {
+                name: stringify!($func),
+                params: &[ $( stringify!($arg_type) ),* ],
+                returns: &[ $( stringify!($returns) ),* ],
+            });
+        };
+    }
+    for_each_available_import!(protocol_version, add_import);
+    functions
 }
 
 #[cfg(all(feature = "wasmer0_vm", target_arch = "x86_64"))]
@@ -274,7 +339,7 @@ pub(crate) mod wasmer {
                         Some(tracing::trace_span!(target: "host-function", stringify!($func)).entered())
                     };
                     let logic: &mut VMLogic<'_> = unsafe { &mut *(ctx.data as *mut VMLogic<'_>) };
-                    logic.$func( $( $arg_name, )* )
+                    call_host_function!(logic, $func, [ $( $arg_name ),* ])
                 }
 
                 ns.insert(stringify!($func), wasmer_runtime::func!($func));
@@ -374,7 +439,7 @@ pub(crate) mod wasmer2 {
                             // lifetime and so it is safe to dereference the `env` pointer which is
                             // known to be derived from a valid `&'vmlogic mut VMLogic<'_>` in the
                             // first place.
-                            unsafe { (*env).$func( $( $arg_name, )* ) }
+                            unsafe { call_host_function!((*env), $func, [ $( $arg_name ),* ]) }
                         }));
                         // We want to ensure that the only kind of error that host function calls
                         // return are VMLogicError. This is important because we later attempt to
@@ -487,7 +552,7 @@ pub(crate) mod wasmtime {
                         crate::wasmtime_runner::CALLER.with(|runner_caller| *runner_caller.borrow_mut() = std::mem::transmute(caller));
                     }
                     let logic: &mut VMLogic<'_> = unsafe { &mut *(data as *mut VMLogic<'_>) };
-                    match logic.$func( $( $arg_name as $arg_type, )* ) {
+                    match call_host_function!(logic, $func, [ $( $arg_name ),* ]) {
                         Ok(result) => Ok(result as ($( $returns ),* ) ),
                         Err(err) => {
                             // Wasmtime doesn't have proper mechanism for wrapping custom errors
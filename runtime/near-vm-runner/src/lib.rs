@@ -1,6 +1,9 @@
 #![doc = include_str!("../README.md")]
 
+pub mod analysis;
 mod cache;
+#[cfg(feature = "sandbox")]
+pub mod coverage;
 mod errors;
 mod imports;
 #[cfg(all(feature = "wasmer0_vm", target_arch = "x86_64"))]
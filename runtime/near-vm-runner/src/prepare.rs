@@ -21,6 +21,33 @@ pub(crate) const WASM_FEATURES: wasmparser::WasmFeatures = wasmparser::WasmFeatu
     memory64: false,
 };
 
+/// Features enabled in the compilers.
+///
+/// Proposals gated by protocol version are enabled here as soon as they are compiled in: contracts
+/// are validated against the features of their protocol version in `prepare_contract` before they
+/// ever reach a compiler.
+pub(crate) const COMPILER_FEATURES: wasmparser::WasmFeatures = wasmparser::WasmFeatures {
+    bulk_memory: cfg!(feature = "protocol_feature_wasm_bulk_memory"),
+    ..WASM_FEATURES
+};
+
+/// Features contracts may use under the given config.
+///
+/// Multi-value and reference types stay disabled: wasmer singlepass can't compile the former and
+/// our instrumentation can't parse the latter.
+fn wasm_features(config: &VMConfig) -> wasmparser::WasmFeatures {
+    #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+    return wasmparser::WasmFeatures {
+        bulk_memory: config.limit_config.wasm_bulk_memory,
+        ..WASM_FEATURES
+    };
+    #[cfg(not(feature = "protocol_feature_wasm_bulk_memory"))]
+    {
+        let _ = config;
+        WASM_FEATURES
+    }
+}
+
 /// Loads the given module given in `original_code`, performs some checks on it and
 /// does some preprocessing.
 ///
@@ -34,7 +61,7 @@ pub(crate) const WASM_FEATURES: wasmparser::WasmFeatures = wasmparser::WasmFeatu
 /// The preprocessing includes injecting code for gas metering and metering the height of stack.
 pub fn prepare_contract(original_code: &[u8], config: &VMConfig) -> Result<Vec<u8>, PrepareError> {
     wasmparser::Validator::new()
-        .wasm_features(WASM_FEATURES)
+        .wasm_features(wasm_features(config))
         .validate_all(original_code)
         .map_err(|_| PrepareError::Deserialization)?;
 
@@ -49,6 +76,8 @@ pub fn prepare_contract(original_code: &[u8], config: &VMConfig) -> Result<Vec<u
             .standardize_mem()
             .ensure_no_internal_memory()?
             .inject_gas_metering()?
+            .inject_bulk_memory_metering()?
+            .inject_coverage()?
             .inject_stack_height_metering()?
             .scan_imports()?
             .into_wasm_code(),
@@ -108,13 +137,129 @@ impl<'a> ContractModule<'a> {
         if config.regular_op_cost == 0 {
             return Ok(Self { module, config });
         }
-        let gas_rules = pwasm_utils::rules::Set::new(1, Default::default())
+        let gas_rules = pwasm_utils::rules::Set::new(1, instruction_costs(config).collect())
             .with_grow_cost(config.grow_mem_cost);
         let module = pwasm_utils::inject_gas_counter(module, &gas_rules, "env")
             .map_err(|_| PrepareError::GasInstrumentation)?;
         Ok(Self { module, config })
     }
 
+    /// Charges for the bytes processed by `memory.copy`, `memory.fill` and `memory.init`.
+    ///
+    /// Regular gas metering charges these instructions as a single operation, so before each of
+    /// them the length operand is passed to the `gas` import, scaled by `bulk_memory_page_cost`.
+    #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+    fn inject_bulk_memory_metering(self) -> Result<Self, PrepareError> {
+        use elements::{BulkInstruction, Instruction, Local, Type, ValueType};
+
+        let Self { mut module, config } = self;
+        if !config.limit_config.wasm_bulk_memory || config.regular_op_cost == 0 {
+            return Ok(Self { module, config });
+        }
+
+        // `inject_gas_metering` appends the `gas` function to the imports.
+        let mut gas_func = None;
+        let mut imported_functions = 0;
+        for import in module.import_section().map(elements::ImportSection::entries).unwrap_or(&[]) {
+            if let External::Function(_) = import.external() {
+                if import.module() == "env" && import.field() == "gas" {
+                    gas_func = Some(imported_functions);
+                }
+                imported_functions += 1;
+            }
+        }
+        let gas_func = gas_func.ok_or(PrepareError::GasInstrumentation)?;
+
+        let param_counts: Vec<u32> = {
+            let types = module.type_section().map(elements::TypeSection::types).unwrap_or(&[]);
+            let functions =
+                module.function_section().map(elements::FunctionSection::entries).unwrap_or(&[]);
+            functions
+                .iter()
+                .map(|func| match types.get(func.type_ref() as usize) {
+                    Some(Type::Function(func_type)) => Ok(func_type.params().len() as u32),
+                    None => Err(PrepareError::GasInstrumentation),
+                })
+                .collect::<Result<_, _>>()?
+        };
+        let bodies = match module.code_section_mut() {
+            Some(code) => code.bodies_mut(),
+            None => return Ok(Self { module, config }),
+        };
+        for (body, params) in bodies.iter_mut().zip(param_counts) {
+            let is_bulk = |instruction: &Instruction| {
+                matches!(
+                    instruction,
+                    Instruction::Bulk(
+                        BulkInstruction::MemoryCopy
+                            | BulkInstruction::MemoryFill
+                            | BulkInstruction::MemoryInit(_)
+                    )
+                )
+            };
+            if !body.code().elements().iter().any(is_bulk) {
+                continue;
+            }
+            let len_local =
+                body.locals().iter().fold(params, |acc, local| acc.saturating_add(local.count()));
+            let charge_local = len_local.saturating_add(1);
+            body.locals_mut().push(Local::new(1, ValueType::I32));
+            body.locals_mut().push(Local::new(1, ValueType::I64));
+
+            let code = std::mem::take(body.code_mut().elements_mut());
+            let mut instrumented = Vec::with_capacity(code.len());
+            for instruction in code {
+                if is_bulk(&instruction) {
+                    // The length is the topmost operand of all three instructions. The charge is
+                    // `len * bulk_memory_page_cost / page_size`, in units of regular operations.
+                    // Both factors are `u32`, so the product fits into `u64`; the charge is
+                    // capped at `u32::MAX` instead of being truncated when passed to `gas`.
+                    instrumented.extend([
+                        Instruction::TeeLocal(len_local),
+                        Instruction::GetLocal(len_local),
+                        Instruction::I64ExtendUI32,
+                        Instruction::I64Const(i64::from(config.bulk_memory_page_cost)),
+                        Instruction::I64Mul,
+                        Instruction::I64Const(16),
+                        Instruction::I64ShrU,
+                        Instruction::TeeLocal(charge_local),
+                        Instruction::I64Const(i64::from(u32::MAX)),
+                        Instruction::GetLocal(charge_local),
+                        Instruction::I64Const(i64::from(u32::MAX)),
+                        Instruction::I64LtU,
+                        Instruction::Select,
+                        Instruction::I32WrapI64,
+                        Instruction::Call(gas_func),
+                    ]);
+                }
+                instrumented.push(instruction);
+            }
+            *body.code_mut().elements_mut() = instrumented;
+        }
+        Ok(Self { module, config })
+    }
+
+    #[cfg(not(feature = "protocol_feature_wasm_bulk_memory"))]
+    fn inject_bulk_memory_metering(self) -> Result<Self, PrepareError> {
+        Ok(self)
+    }
+
+    /// Instruments the contract to count executions of its code blocks if coverage collection is
+    /// enabled, see [`crate::coverage`]. Done after gas metering so that it doesn't affect gas.
+    #[cfg(feature = "sandbox")]
+    fn inject_coverage(self) -> Result<Self, PrepareError> {
+        let Self { module, config } = self;
+        if !crate::coverage::is_enabled() {
+            return Ok(Self { module, config });
+        }
+        Ok(Self { module: crate::coverage::instrument(module)?, config })
+    }
+
+    #[cfg(not(feature = "sandbox"))]
+    fn inject_coverage(self) -> Result<Self, PrepareError> {
+        Ok(self)
+    }
+
     fn inject_stack_height_metering(self) -> Result<Self, PrepareError> {
         let Self { module, config } = self;
         let module =
@@ -201,6 +346,52 @@ impl<'a> ContractModule<'a> {
     }
 }
 
+/// Number of `regular_op_cost` units charged for each instruction type, as configured by
+/// `op_cost_table`. Instruction types not listed cost a single unit.
+#[cfg(feature = "protocol_feature_wasm_op_cost_table")]
+fn instruction_costs(
+    config: &VMConfig,
+) -> impl Iterator<Item = (pwasm_utils::rules::InstructionType, pwasm_utils::rules::Metering)> {
+    use pwasm_utils::rules::{InstructionType, Metering};
+
+    let table = &config.op_cost_table;
+    let costs = if table.is_flat() {
+        vec![]
+    } else {
+        vec![
+            (InstructionType::Bit, table.bit),
+            (InstructionType::Add, table.add),
+            (InstructionType::Mul, table.mul),
+            (InstructionType::Div, table.div),
+            (InstructionType::Load, table.load),
+            (InstructionType::Store, table.store),
+            (InstructionType::Const, table.constant),
+            (InstructionType::FloatConst, table.float_const),
+            (InstructionType::Local, table.local),
+            (InstructionType::Global, table.global),
+            (InstructionType::ControlFlow, table.control_flow),
+            (InstructionType::IntegerComparison, table.integer_comparison),
+            (InstructionType::FloatComparison, table.float_comparison),
+            (InstructionType::Float, table.float),
+            (InstructionType::Conversion, table.conversion),
+            (InstructionType::FloatConversion, table.float_conversion),
+            (InstructionType::Reinterpretation, table.reinterpretation),
+            (InstructionType::Unreachable, table.unreachable),
+            (InstructionType::Nop, table.nop),
+            (InstructionType::CurrentMemory, table.current_memory),
+            (InstructionType::GrowMemory, table.grow_memory),
+        ]
+    };
+    costs.into_iter().map(|(instruction_type, units)| (instruction_type, Metering::Fixed(units)))
+}
+
+#[cfg(not(feature = "protocol_feature_wasm_op_cost_table"))]
+fn instruction_costs(
+    _config: &VMConfig,
+) -> impl Iterator<Item = (pwasm_utils::rules::InstructionType, pwasm_utils::rules::Metering)> {
+    std::iter::empty()
+}
+
 /// Legacy validation for old protocol versions.
 mod pwasm_12 {
     use near_vm_errors::PrepareError;
@@ -456,4 +647,71 @@ mod tests {
         assert_matches!(r, Err(Error::Instantiate));
         */
     }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+    fn bulk_memory_metering() {
+        let wasm = wat::parse_str(
+            r#"(module
+          (import "env" "memory" (memory 1 1))
+          (func (export "fill") (param i32 i32 i32)
+            local.get 0
+            local.get 1
+            local.get 2
+            memory.fill)
+        )"#,
+        )
+        .unwrap();
+        let mut config = VMConfig::test();
+        assert_matches!(prepare_contract(&wasm, &config), Err(PrepareError::Deserialization));
+
+        config.limit_config.wasm_bulk_memory = true;
+        let prepared = prepare_contract(&wasm, &config).unwrap();
+        let module =
+            pwasm_utils::parity_wasm::deserialize_buffer::<elements::Module>(&prepared).unwrap();
+        let gas_calls_before_fill: Vec<_> = module
+            .code_section()
+            .unwrap()
+            .bodies()
+            .iter()
+            .flat_map(|body| body.code().elements().windows(2))
+            .filter(|pair| {
+                pair[1] == elements::Instruction::Bulk(elements::BulkInstruction::MemoryFill)
+            })
+            .map(|pair| pair[0].clone())
+            .collect();
+        assert_eq!(gas_calls_before_fill.len(), 1);
+        assert_matches!(gas_calls_before_fill[0], elements::Instruction::Call(_));
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_wasm_op_cost_table")]
+    fn op_cost_table_metering() {
+        let wasm = wat::parse_str(
+            r#"(module
+          (func (export "div") (param i64 i64) (result i64)
+            local.get 0
+            local.get 1
+            i64.div_u)
+        )"#,
+        )
+        .unwrap();
+        // The gas injection pass charges the whole function body upfront.
+        let charged_units = |config: &VMConfig| {
+            let prepared = prepare_contract(&wasm, config).unwrap();
+            let module =
+                pwasm_utils::parity_wasm::deserialize_buffer::<elements::Module>(&prepared)
+                    .unwrap();
+            match module.code_section().unwrap().bodies()[0].code().elements()[0] {
+                elements::Instruction::I32Const(units) => units,
+                ref instruction => panic!("expected gas charge, got {:?}", instruction),
+            }
+        };
+        let mut config = VMConfig::test();
+        let flat = charged_units(&config);
+        config.op_cost_table.div = 10;
+        assert_eq!(charged_units(&config), flat + 9);
+        config.op_cost_table.local = 3;
+        assert_eq!(charged_units(&config), flat + 9 + 2 * 2);
+    }
 }
@@ -36,7 +36,7 @@ pub fn run(
 ) -> VMResult {
     let vm_kind = VMKind::for_protocol_version(current_protocol_version);
     if let Some(runtime) = vm_kind.runtime(wasm_config.clone()) {
-        runtime.run(
+        let result = runtime.run(
             code,
             method_name,
             ext,
@@ -45,7 +45,12 @@ pub fn run(
             promise_results,
             current_protocol_version,
             cache,
-        )
+        );
+        #[cfg(feature = "sandbox")]
+        if let Some(outcome) = result.outcome() {
+            crate::coverage::record(code, &outcome.coverage_hits);
+        }
+        result
     } else {
         panic!("the {:?} runtime has not been enabled at compile time", vm_kind);
     }
@@ -1,6 +1,9 @@
+mod analysis;
 mod cache;
 mod compile_errors;
 mod contract_preload;
+#[cfg(feature = "sandbox")]
+mod coverage;
 mod rs_contract;
 mod runtime_errors;
 mod ts_contract;
@@ -52,6 +55,7 @@ fn create_context(input: Vec<u8>) -> VMContext {
         random_seed: vec![0, 1, 2],
         view_config: None,
         output_data_receivers: vec![],
+        trace_host_functions: false,
     }
 }
 
@@ -60,11 +64,26 @@ fn make_simple_contract_call_with_gas_vm(
     method_name: &str,
     prepaid_gas: u64,
     vm_kind: VMKind,
+) -> (Option<VMOutcome>, Option<VMError>) {
+    make_simple_contract_call_with_config_vm(
+        code,
+        method_name,
+        prepaid_gas,
+        VMConfig::test(),
+        vm_kind,
+    )
+}
+
+fn make_simple_contract_call_with_config_vm(
+    code: &[u8],
+    method_name: &str,
+    prepaid_gas: u64,
+    config: VMConfig,
+    vm_kind: VMKind,
 ) -> (Option<VMOutcome>, Option<VMError>) {
     let mut fake_external = MockedExternal::new();
     let mut context = create_context(vec![]);
     context.prepaid_gas = prepaid_gas;
-    let config = VMConfig::test();
     let fees = RuntimeFeesConfig::test();
 
     let promise_results = vec![];
@@ -0,0 +1,59 @@
+use near_primitives::version::PROTOCOL_VERSION;
+use near_vm_errors::PrepareError;
+use near_vm_logic::VMConfig;
+
+use crate::analysis::{check_contract, summarize_contract, ContractIssue, ImportedFunction};
+
+#[test]
+fn test_analyze_rs_contract() {
+    let code = near_test_contracts::rs_contract();
+    let summary = summarize_contract(code).unwrap();
+    assert_eq!(summary.code_size, code.len() as u64);
+    assert!(summary.methods.contains(&"log_something".to_string()));
+    assert!(summary.imports.contains(&ImportedFunction {
+        module: "env".to_string(),
+        name: "log_utf8".to_string(),
+        params: vec!["i64".to_string(), "i64".to_string()],
+        returns: vec![],
+    }));
+    assert!(summary.functions > 0);
+    assert_eq!(check_contract(code, &VMConfig::test(), PROTOCOL_VERSION), vec![]);
+}
+
+#[test]
+fn test_analyze_invalid_contract() {
+    let code = wat::parse_str(
+        r#"
+            (module
+              (import "env" "foo" (func))
+              (import "env" "log_utf8" (func (param i32 i32)))
+              (memory 1)
+              (func (export "a_very_long_method_name") (local i64 i64))
+            )"#,
+    )
+    .unwrap();
+    let summary = summarize_contract(&code).unwrap();
+    assert_eq!(summary.methods, vec!["a_very_long_method_name".to_string()]);
+    assert_eq!(summary.locals, 2);
+    assert!(summary.memory.is_some());
+
+    let mut config = VMConfig::test();
+    config.limit_config.max_length_method_name = 10;
+    config.limit_config.max_contract_size = 10;
+    assert_eq!(
+        check_contract(&code, &config, PROTOCOL_VERSION),
+        vec![
+            ContractIssue::ContractSizeExceeded { size: code.len() as u64, limit: 10 },
+            ContractIssue::Prepare(PrepareError::InternalMemoryDeclared),
+            ContractIssue::UnknownImport { module: "env".to_string(), name: "foo".to_string() },
+            ContractIssue::ImportSignatureMismatch {
+                name: "log_utf8".to_string(),
+                expected_params: vec!["i64".to_string(), "i64".to_string()],
+            },
+            ContractIssue::MethodNameTooLong {
+                name: "a_very_long_method_name".to_string(),
+                limit: 10
+            },
+        ]
+    );
+}
@@ -35,6 +35,7 @@ fn default_vm_context() -> VMContext {
         random_seed: vec![0, 1, 2],
         view_config: None,
         output_data_receivers: vec![],
+        trace_host_functions: false,
         epoch_height: 1,
     };
 }
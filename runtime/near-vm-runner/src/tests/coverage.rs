@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+
+use near_primitives::contract::ContractCode;
+use pwasm_utils::parity_wasm::elements::{self, Instruction, Internal};
+
+use crate::coverage::{instrument, record, report, to_lcov};
+
+const CONTRACT: &str = r#"
+    (module
+      (import "env" "input" (func $input (param i64)))
+      (func $helper (param i32) (result i32)
+        local.get 0
+        if (result i32)
+          i32.const 1
+        else
+          i32.const 2
+        end
+      )
+      (func (export "main")
+        i32.const 0
+        call $helper
+        drop
+        loop
+        end
+      )
+    )"#;
+
+#[test]
+fn test_record_coverage() {
+    let code = ContractCode::new(wat::parse_str(CONTRACT).unwrap(), None);
+    // Blocks of `$helper`: body, `if`, `else`. Blocks of `main`: body, `loop`.
+    let hits: BTreeMap<u32, u64> = [(0, 1), (2, 1), (3, 1), (4, 1), (100, 1)].into();
+    record(&code, &hits);
+    record(&code, &hits);
+
+    let coverage = report().into_iter().find(|c| c.code_hash == *code.hash()).unwrap();
+    let functions: Vec<_> = coverage
+        .functions
+        .iter()
+        .map(|f| (f.index, f.name.as_deref(), f.first_block, f.blocks.clone()))
+        .collect();
+    assert_eq!(functions, vec![(1, Some("helper"), 0, vec![2, 0, 2]), (2, None, 3, vec![2, 2])]);
+
+    let lcov = to_lcov(&[coverage]);
+    let expected = format!(
+        "TN:\nSF:{}.wasm\nFN:1,helper\nFN:4,func2\nFNDA:2,helper\nFNDA:2,func2\nFNF:2\nFNH:2\n\
+         DA:1,2\nDA:2,0\nDA:3,2\nDA:4,2\nDA:5,2\nLF:5\nLH:4\nend_of_record\n",
+        code.hash()
+    );
+    assert_eq!(lcov, expected);
+}
+
+#[test]
+fn test_instrument_contract() {
+    let code = wat::parse_str(CONTRACT).unwrap();
+    let module = instrument(elements::deserialize_buffer(&code).unwrap()).unwrap();
+    let instrumented = elements::serialize(module.clone()).unwrap();
+    wasmparser::validate(&instrumented).unwrap();
+
+    let imports = module.import_section().unwrap().entries();
+    assert_eq!(imports[1].field(), "sandbox_coverage_hit");
+    // The host function takes index 1, so `$helper` moves to 2 and `main` to 3.
+    let export = &module.export_section().unwrap().entries()[0];
+    assert_eq!(export.internal(), &Internal::Function(3));
+    let bodies = module.code_section().unwrap().bodies();
+    let main = bodies[1].code().elements();
+    assert_eq!(&main[..2], &[Instruction::I32Const(3), Instruction::Call(1)]);
+    assert_eq!(main[3], Instruction::Call(2));
+    let hits = bodies
+        .iter()
+        .flat_map(|body| body.code().elements())
+        .filter(|instruction| **instruction == Instruction::Call(1))
+        .count();
+    assert_eq!(hits, 5);
+}
@@ -1,4 +1,6 @@
 use super::gas_and_error_match;
+#[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+use crate::tests::make_simple_contract_call_with_config_vm;
 use crate::tests::{
     make_simple_contract_call_vm, make_simple_contract_call_with_gas_vm,
     make_simple_contract_call_with_protocol_version_vm, with_vm_variants,
@@ -563,6 +565,37 @@ fn test_memory_grow() {
     });
 }
 
+#[test]
+#[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+fn test_bulk_memory_charge_overflow() {
+    // Filling two pages at `2^31` regular operations per page is a charge of exactly `2^32`
+    // operations, which must run out of gas instead of wrapping to a free out of bounds fill.
+    let code = wat::parse_str(
+        r#"
+            (module
+              (func (export "main")
+                (memory.fill (i32.const 0) (i32.const 0) (i32.const 131072)))
+              (memory 1)
+            )"#,
+    )
+    .unwrap();
+    with_vm_variants(|vm_kind: VMKind| {
+        // Bulk memory instructions aren't compiled by Wasmer 0.x.
+        if matches!(vm_kind, VMKind::Wasmer0) {
+            return;
+        }
+        let mut config = near_vm_logic::VMConfig::test();
+        config.limit_config.wasm_bulk_memory = true;
+        config.bulk_memory_page_cost = 1 << 31;
+        let (_, err) =
+            make_simple_contract_call_with_config_vm(&code, "main", 10u64.pow(14), config, vm_kind);
+        assert_eq!(
+            err,
+            Some(VMError::FunctionCallError(FunctionCallError::HostError(HostError::GasExceeded)))
+        );
+    });
+}
+
 fn bad_import_global(env: &str) -> Vec<u8> {
     wat::parse_str(format!(
         r#"
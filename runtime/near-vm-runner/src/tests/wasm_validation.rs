@@ -90,8 +90,11 @@ static EXPECTED_UNSUPPORTED: &[(&str, &str)] = &[
     ("multi_memory", MULTI_MEMORY),
     // ("module_linking", MODULE_LINKING),
     ("tail_call", TAIL_CALL),
+    // Not supported by the wasmer singlepass compiler.
     ("multi_value", MULTI_VALUE),
+    // Only allowed from the protocol version enabling `wasm_bulk_memory` in the config.
     ("bulk_memory", BULK_MEMORY),
+    // Can't be parsed by the gas and stack height instrumentation.
     ("reference_types", REFERENCE_TYPES),
     ("threads", THREADS),
     ("simd", SIMD),
@@ -120,3 +123,29 @@ fn ensure_fails_execution() {
         }
     });
 }
+
+#[test]
+#[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+fn bulk_memory_is_protocol_gated() {
+    let wasm = wat::parse_str(BULK_MEMORY).expect("parsing test wat should succeed");
+    let mut config = VMConfig::test();
+    assert!(prepare_contract(&wasm, &config).is_err());
+    config.limit_config.wasm_bulk_memory = true;
+    prepare_contract(&wasm, &config).expect("bulk memory should be enabled by the config");
+}
+
+#[test]
+#[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+fn multi_value_and_reference_types_stay_disabled() {
+    let mut config = VMConfig::test();
+    config.limit_config.wasm_bulk_memory = true;
+    for (feature_name, wat) in [("multi_value", MULTI_VALUE), ("reference_types", REFERENCE_TYPES)]
+    {
+        let wasm = wat::parse_str(wat).expect("parsing test wat should succeed");
+        assert!(
+            prepare_contract(&wasm, &config).is_err(),
+            "{} should not be enabled along with bulk memory",
+            feature_name
+        );
+    }
+}
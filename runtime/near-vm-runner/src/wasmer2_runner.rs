@@ -1,6 +1,6 @@
 use crate::cache::into_vm_result;
 use crate::imports::wasmer2::Wasmer2Imports;
-use crate::prepare::WASM_FEATURES;
+use crate::prepare::COMPILER_FEATURES;
 use crate::runner::VMResult;
 use crate::{cache, imports};
 use memoffset::offset_of;
@@ -24,16 +24,16 @@ use wasmer_types::{
 use wasmer_vm::{LinearMemory, LinearTable, Memory, MemoryStyle, TrapCode, VMExtern, VMMemory};
 
 const WASMER_FEATURES: Features = Features {
-    threads: WASM_FEATURES.threads,
-    reference_types: WASM_FEATURES.reference_types,
-    simd: WASM_FEATURES.simd,
-    bulk_memory: WASM_FEATURES.bulk_memory,
-    multi_value: WASM_FEATURES.multi_value,
-    tail_call: WASM_FEATURES.tail_call,
-    module_linking: WASM_FEATURES.module_linking,
-    multi_memory: WASM_FEATURES.multi_memory,
-    memory64: WASM_FEATURES.memory64,
-    exceptions: WASM_FEATURES.exceptions,
+    threads: COMPILER_FEATURES.threads,
+    reference_types: COMPILER_FEATURES.reference_types,
+    simd: COMPILER_FEATURES.simd,
+    bulk_memory: COMPILER_FEATURES.bulk_memory,
+    multi_value: COMPILER_FEATURES.multi_value,
+    tail_call: COMPILER_FEATURES.tail_call,
+    module_linking: COMPILER_FEATURES.module_linking,
+    multi_memory: COMPILER_FEATURES.multi_memory,
+    memory64: COMPILER_FEATURES.memory64,
+    exceptions: COMPILER_FEATURES.exceptions,
     // singlepass does not support signals.
     signal_less: true,
 };
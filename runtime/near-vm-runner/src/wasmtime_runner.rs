@@ -1,5 +1,5 @@
 use crate::errors::IntoVMError;
-use crate::prepare::WASM_FEATURES;
+use crate::prepare::COMPILER_FEATURES;
 use crate::runner::VMResult;
 use crate::{imports, prepare};
 use near_primitives::config::VMConfig;
@@ -152,13 +152,13 @@ pub fn get_engine(config: &mut wasmtime::Config) -> Engine {
 
 pub(super) fn default_config() -> wasmtime::Config {
     let mut config = wasmtime::Config::default();
-    config.wasm_threads(WASM_FEATURES.threads);
-    config.wasm_reference_types(WASM_FEATURES.reference_types);
-    config.wasm_simd(WASM_FEATURES.simd);
-    config.wasm_bulk_memory(WASM_FEATURES.bulk_memory);
-    config.wasm_multi_value(WASM_FEATURES.multi_value);
-    config.wasm_multi_memory(WASM_FEATURES.multi_memory);
-    config.wasm_module_linking(WASM_FEATURES.module_linking);
+    config.wasm_threads(COMPILER_FEATURES.threads);
+    config.wasm_reference_types(COMPILER_FEATURES.reference_types);
+    config.wasm_simd(COMPILER_FEATURES.simd);
+    config.wasm_bulk_memory(COMPILER_FEATURES.bulk_memory);
+    config.wasm_multi_value(COMPILER_FEATURES.multi_value);
+    config.wasm_multi_memory(COMPILER_FEATURES.multi_memory);
+    config.wasm_module_linking(COMPILER_FEATURES.module_linking);
     config
 }
 
@@ -161,6 +161,54 @@ pub enum Cost {
     /// attempt to cause slow loads and stores. The total time spent in the
     /// runtime is divided by the number of executed instructions.
     WasmInstruction,
+    /// Estimates the unit of `wasm_config.op_cost_table`, the cost of the
+    /// cheapest Wasm instructions, `local.get` and `local.set`. With the table
+    /// enabled, this becomes `regular_op_cost` and the cost of every other
+    /// instruction type is a multiple of it.
+    ///
+    /// Estimation: Run a loop repeating `local.get` and `local.set` many times,
+    /// without gas metering. Subtract the time of the same loop with an empty
+    /// body and divide by the number of executed instructions.
+    WasmOpLocal,
+    /// Estimates `op_cost_table.add`, which is also used for bitwise
+    /// operations, integer comparisons and conversions.
+    ///
+    /// Estimation: Like `WasmOpLocal`, with the loop repeating the instruction
+    /// along with the local and constant instructions providing its operands.
+    /// The cost of these is subtracted using `WasmOpLocal`.
+    WasmOpAdd,
+    /// Estimates `op_cost_table.mul`, which is also used for float operations.
+    ///
+    /// Estimation: See `WasmOpAdd`.
+    WasmOpMul,
+    /// Estimates `op_cost_table.div`.
+    ///
+    /// Estimation: See `WasmOpAdd`.
+    WasmOpDiv,
+    /// Estimates `op_cost_table.load`.
+    ///
+    /// Estimation: See `WasmOpAdd`.
+    WasmOpLoad,
+    /// Estimates `op_cost_table.store`.
+    ///
+    /// Estimation: See `WasmOpAdd`.
+    WasmOpStore,
+    /// Estimates `op_cost_table.global`.
+    ///
+    /// Estimation: See `WasmOpAdd`.
+    WasmOpGlobal,
+    /// Estimates `op_cost_table.control_flow`, which is also used for
+    /// `unreachable` and memory size instructions.
+    ///
+    /// Estimation: See `WasmOpAdd`, measuring a call of an empty function.
+    WasmOpCall,
+    /// Estimates `wasm_config.bulk_memory_page_cost`, which is charged for
+    /// each page of memory copied or filled by `memory.copy`, `memory.fill`
+    /// and `memory.init`, in units of `regular_op_cost`.
+    ///
+    /// Estimation: See `WasmOpAdd`, measuring `memory.copy` of a whole page.
+    /// Copying reads and writes memory, so it is at least as slow as filling.
+    WasmBulkMemoryPage,
 
     // # Reading and writing memory
     // The hosting runtime sometimes copies data between in and out of WASM
@@ -505,6 +553,99 @@ pub enum Cost {
     AltBn128PairingCheckByte,
     AltBn128G1SumBase,
     AltBn128G1SumByte,
+    /// Estimates `ed25519_verify_base`, the cost of verifying a single ed25519
+    /// signature in `ed25519_verify` and per entry of `ed25519_verify_batch`.
+    ///
+    /// Estimation: Verify a signature of a small message many times.
+    Ed25519VerifyBase,
+    /// Estimates `ed25519_verify_byte`, the cost of verifying an ed25519
+    /// signature per byte of the signed message.
+    ///
+    /// Estimation: Verify a signature of a 16KiB message many times and
+    /// divide by the number of bytes.
+    Ed25519VerifyByte,
+    /// Estimates `secp256k1_verify_base`, the cost of verifying a single
+    /// secp256k1 signature in `secp256k1_verify`.
+    ///
+    /// Estimation: Verify a signature of a 32-byte hash many times.
+    Secp256k1VerifyBase,
+    /// Estimates `bls12381_p1_sum_base`, the base cost of the sum of signed G1 points in
+    /// `bls12381_p1_sum`.
+    ///
+    /// Estimation: Call the host function on one point many times.
+    Bls12381P1SumBase,
+    /// Estimates `bls12381_p1_sum_element`, the cost of the sum of signed G1 points per
+    /// point.
+    ///
+    /// Estimation: Call the host function on ten points many times
+    /// and divide by the number of points.
+    Bls12381P1SumElement,
+    /// Estimates `bls12381_p2_sum_base`, the base cost of the sum of signed G2 points in
+    /// `bls12381_p2_sum`.
+    ///
+    /// Estimation: Call the host function on one point many times.
+    Bls12381P2SumBase,
+    /// Estimates `bls12381_p2_sum_element`, the cost of the sum of signed G2 points per
+    /// point.
+    ///
+    /// Estimation: Call the host function on ten points many times
+    /// and divide by the number of points.
+    Bls12381P2SumElement,
+    /// Estimates `bls12381_g1_multiexp_base`, the base cost of G1 multiexp in
+    /// `bls12381_g1_multiexp`.
+    ///
+    /// Estimation: Call the host function on one pair many times.
+    Bls12381G1MultiexpBase,
+    /// Estimates `bls12381_g1_multiexp_element`, the cost of G1 multiexp per
+    /// (point, scalar) pair.
+    ///
+    /// Estimation: Call the host function on ten pairs many times
+    /// and divide by the number of pairs.
+    Bls12381G1MultiexpElement,
+    /// Estimates `bls12381_g2_multiexp_base`, the base cost of G2 multiexp in
+    /// `bls12381_g2_multiexp`.
+    ///
+    /// Estimation: Call the host function on one pair many times.
+    Bls12381G2MultiexpBase,
+    /// Estimates `bls12381_g2_multiexp_element`, the cost of G2 multiexp per
+    /// (point, scalar) pair.
+    ///
+    /// Estimation: Call the host function on ten pairs many times
+    /// and divide by the number of pairs.
+    Bls12381G2MultiexpElement,
+    /// Estimates `bls12381_map_fp_to_g1_base`, the base cost of mapping a field element to G1 in
+    /// `bls12381_map_fp_to_g1`.
+    ///
+    /// Estimation: Call the host function on one element many times.
+    Bls12381MapFpToG1Base,
+    /// Estimates `bls12381_map_fp_to_g1_element`, the cost of mapping a field element to G1 per
+    /// element.
+    ///
+    /// Estimation: Call the host function on ten elements many times
+    /// and divide by the number of elements.
+    Bls12381MapFpToG1Element,
+    /// Estimates `bls12381_map_fp2_to_g2_base`, the base cost of mapping a field element to G2 in
+    /// `bls12381_map_fp2_to_g2`.
+    ///
+    /// Estimation: Call the host function on one element many times.
+    Bls12381MapFp2ToG2Base,
+    /// Estimates `bls12381_map_fp2_to_g2_element`, the cost of mapping a field element to G2 per
+    /// element.
+    ///
+    /// Estimation: Call the host function on ten elements many times
+    /// and divide by the number of elements.
+    Bls12381MapFp2ToG2Element,
+    /// Estimates `bls12381_pairing_base`, the base cost of pairing check in
+    /// `bls12381_pairing_check`.
+    ///
+    /// Estimation: Call the host function on one pair many times.
+    Bls12381PairingBase,
+    /// Estimates `bls12381_pairing_element`, the cost of pairing check per
+    /// (G1, G2) pair.
+    ///
+    /// Estimation: Call the host function on ten pairs many times
+    /// and divide by the number of pairs.
+    Bls12381PairingElement,
 
     // Costs used only in estimator
     //
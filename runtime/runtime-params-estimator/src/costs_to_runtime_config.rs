@@ -6,6 +6,8 @@ use near_primitives::runtime::fees::{
 };
 use near_primitives::types::Gas;
 use near_primitives::version::PROTOCOL_VERSION;
+#[cfg(feature = "protocol_feature_wasm_op_cost_table")]
+use near_vm_logic::WasmOpCostTable;
 use near_vm_logic::{ExtCostsConfig, VMConfig};
 use node_runtime::config::RuntimeConfig;
 
@@ -24,6 +26,15 @@ pub fn costs_to_runtime_config(cost_table: &CostTable) -> anyhow::Result<Runtime
     let regular_op_cost = cost_table
         .get(Cost::WasmInstruction)
         .with_context(|| format!("undefined cost: {}", Cost::WasmInstruction))?;
+    // With the cost table, `regular_op_cost` is the cost of the cheapest instructions.
+    #[cfg(feature = "protocol_feature_wasm_op_cost_table")]
+    let (regular_op_cost, op_cost_table) = op_cost_table(cost_table)?;
+    // Charged in units of `regular_op_cost`.
+    #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+    let bulk_memory_page_cost = cost_table
+        .get(Cost::WasmBulkMemoryPage)
+        .with_context(|| format!("undefined cost: {}", Cost::WasmBulkMemoryPage))?
+        / regular_op_cost.max(1);
 
     // Take latest VM limit config, because estimation doesn't affect it.
     // Note that if you run estimation against stable version, it doesn't catch updates of nightly
@@ -40,6 +51,10 @@ pub fn costs_to_runtime_config(cost_table: &CostTable) -> anyhow::Result<Runtime
             ext_costs: ext_costs_config(cost_table)?,
             grow_mem_cost: 1,
             regular_op_cost: u32::try_from(regular_op_cost).unwrap(),
+            #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+            bulk_memory_page_cost: u32::try_from(bulk_memory_page_cost).unwrap(),
+            #[cfg(feature = "protocol_feature_wasm_op_cost_table")]
+            op_cost_table,
             limit_config: vm_limit_config,
         },
         account_creation_config: AccountCreationConfig::default(),
@@ -47,6 +62,48 @@ pub fn costs_to_runtime_config(cost_table: &CostTable) -> anyhow::Result<Runtime
     Ok(res)
 }
 
+/// Expresses the estimated costs of Wasm instructions in units of the cheapest
+/// instruction cost, which is returned along with the table.
+#[cfg(feature = "protocol_feature_wasm_op_cost_table")]
+fn op_cost_table(cost_table: &CostTable) -> anyhow::Result<(Gas, WasmOpCostTable)> {
+    let get = |cost: Cost| -> anyhow::Result<Gas> {
+        cost_table.get(cost).with_context(|| format!("undefined cost: {}", cost))
+    };
+    let unit = get(Cost::WasmOpLocal)?.max(1);
+    let units = |cost: Cost| -> anyhow::Result<u32> {
+        let units = (get(cost)? + unit / 2) / unit;
+        Ok(u32::try_from(units.max(1)).unwrap())
+    };
+
+    let add = units(Cost::WasmOpAdd)?;
+    let mul = units(Cost::WasmOpMul)?;
+    let call = units(Cost::WasmOpCall)?;
+    let table = WasmOpCostTable {
+        bit: add,
+        add,
+        mul,
+        div: units(Cost::WasmOpDiv)?,
+        load: units(Cost::WasmOpLoad)?,
+        store: units(Cost::WasmOpStore)?,
+        constant: 1,
+        float_const: 1,
+        local: 1,
+        global: units(Cost::WasmOpGlobal)?,
+        control_flow: call,
+        integer_comparison: add,
+        float_comparison: mul,
+        float: mul,
+        conversion: add,
+        float_conversion: mul,
+        reinterpretation: add,
+        unreachable: call,
+        nop: 1,
+        current_memory: call,
+        grow_memory: call,
+    };
+    Ok((unit, table))
+}
+
 fn runtime_fees_config(cost_table: &CostTable) -> anyhow::Result<RuntimeFeesConfig> {
     let fee = |cost: Cost| -> anyhow::Result<Fee> {
         let total_gas =
@@ -78,6 +135,9 @@ fn runtime_fees_config(cost_table: &CostTable) -> anyhow::Result<RuntimeFeesConf
             },
             delete_key_cost: fee(Cost::ActionDeleteKey)?,
             delete_account_cost: fee(Cost::ActionDeleteAccount)?,
+            // Not estimated yet, the relayed actions are charged separately.
+            #[cfg(feature = "protocol_feature_delegate_action")]
+            delegate_cost: actual_fees_config.action_creation_config.delegate_cost.clone(),
         },
         ..actual_fees_config.clone()
     };
@@ -159,6 +219,40 @@ fn ext_costs_config(cost_table: &CostTable) -> anyhow::Result<ExtCostsConfig> {
         alt_bn128_pairing_check_base: get(Cost::AltBn128PairingCheckBase)?,
         #[cfg(feature = "protocol_feature_alt_bn128")]
         alt_bn128_pairing_check_byte: get(Cost::AltBn128PairingCheckByte)?,
+        #[cfg(feature = "protocol_feature_ed25519_verify")]
+        ed25519_verify_base: get(Cost::Ed25519VerifyBase)?,
+        #[cfg(feature = "protocol_feature_ed25519_verify")]
+        ed25519_verify_byte: get(Cost::Ed25519VerifyByte)?,
+        #[cfg(feature = "protocol_feature_ed25519_verify")]
+        secp256k1_verify_base: get(Cost::Secp256k1VerifyBase)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_p1_sum_base: get(Cost::Bls12381P1SumBase)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_p1_sum_element: get(Cost::Bls12381P1SumElement)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_p2_sum_base: get(Cost::Bls12381P2SumBase)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_p2_sum_element: get(Cost::Bls12381P2SumElement)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_g1_multiexp_base: get(Cost::Bls12381G1MultiexpBase)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_g1_multiexp_element: get(Cost::Bls12381G1MultiexpElement)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_g2_multiexp_base: get(Cost::Bls12381G2MultiexpBase)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_g2_multiexp_element: get(Cost::Bls12381G2MultiexpElement)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_map_fp_to_g1_base: get(Cost::Bls12381MapFpToG1Base)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_map_fp_to_g1_element: get(Cost::Bls12381MapFpToG1Element)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_map_fp2_to_g2_base: get(Cost::Bls12381MapFp2ToG2Base)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_map_fp2_to_g2_element: get(Cost::Bls12381MapFp2ToG2Element)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_pairing_base: get(Cost::Bls12381PairingBase)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_pairing_element: get(Cost::Bls12381PairingElement)?,
     };
 
     Ok(res)
@@ -28,6 +28,7 @@ pub(crate) struct CachedCosts {
     pub(crate) compile_cost_base_per_byte: Option<(GasCost, GasCost)>,
     pub(crate) compile_cost_base_per_byte_v2: Option<(GasCost, GasCost)>,
     pub(crate) gas_metering_cost_base_per_op: Option<(GasCost, GasCost)>,
+    pub(crate) wasm_op_local: Option<GasCost>,
     pub(crate) apply_block: Option<GasCost>,
     pub(crate) touching_trie_node_read: Option<GasCost>,
     pub(crate) touching_trie_node_write: Option<GasCost>,
@@ -70,6 +70,7 @@ pub mod testbed;
 pub mod config;
 mod function_call;
 mod gas_metering;
+mod wasm_op_cost;
 
 use std::convert::{TryFrom, TryInto};
 use std::iter;
@@ -100,6 +101,7 @@ use utils::{
     transaction_cost, transaction_cost_ext,
 };
 use vm_estimator::{compile_single_contract_cost, compute_compile_cost_vm};
+use wasm_op_cost::wasm_op_cost;
 
 use crate::config::Config;
 use crate::cost_table::format_gas;
@@ -134,6 +136,15 @@ static ALL_COSTS: &[(Cost, fn(&mut EstimatorContext) -> GasCost)] = &[
     (Cost::ActionFunctionCallPerByteV2, action_function_call_per_byte_v2),
     (Cost::HostFunctionCall, host_function_call),
     (Cost::WasmInstruction, wasm_instruction),
+    (Cost::WasmOpLocal, wasm_op_local),
+    (Cost::WasmOpAdd, wasm_op_add),
+    (Cost::WasmOpMul, wasm_op_mul),
+    (Cost::WasmOpDiv, wasm_op_div),
+    (Cost::WasmOpLoad, wasm_op_load),
+    (Cost::WasmOpStore, wasm_op_store),
+    (Cost::WasmOpGlobal, wasm_op_global),
+    (Cost::WasmOpCall, wasm_op_call),
+    (Cost::WasmBulkMemoryPage, wasm_bulk_memory_page),
     (Cost::DataReceiptCreationBase, data_receipt_creation_base),
     (Cost::DataReceiptCreationPerByte, data_receipt_creation_per_byte),
     (Cost::ReadMemoryBase, read_memory_base),
@@ -166,6 +177,23 @@ static ALL_COSTS: &[(Cost, fn(&mut EstimatorContext) -> GasCost)] = &[
     (Cost::AltBn128G1SumByte, alt_bn128g1_sum_byte),
     (Cost::AltBn128PairingCheckBase, alt_bn128_pairing_check_base),
     (Cost::AltBn128PairingCheckByte, alt_bn128_pairing_check_byte),
+    (Cost::Ed25519VerifyBase, ed25519_verify_base),
+    (Cost::Ed25519VerifyByte, ed25519_verify_byte),
+    (Cost::Secp256k1VerifyBase, secp256k1_verify_base),
+    (Cost::Bls12381P1SumBase, bls12381_p1_sum_base),
+    (Cost::Bls12381P1SumElement, bls12381_p1_sum_element),
+    (Cost::Bls12381P2SumBase, bls12381_p2_sum_base),
+    (Cost::Bls12381P2SumElement, bls12381_p2_sum_element),
+    (Cost::Bls12381G1MultiexpBase, bls12381_g1_multiexp_base),
+    (Cost::Bls12381G1MultiexpElement, bls12381_g1_multiexp_element),
+    (Cost::Bls12381G2MultiexpBase, bls12381_g2_multiexp_base),
+    (Cost::Bls12381G2MultiexpElement, bls12381_g2_multiexp_element),
+    (Cost::Bls12381MapFpToG1Base, bls12381_map_fp_to_g1_base),
+    (Cost::Bls12381MapFpToG1Element, bls12381_map_fp_to_g1_element),
+    (Cost::Bls12381MapFp2ToG2Base, bls12381_map_fp2_to_g2_base),
+    (Cost::Bls12381MapFp2ToG2Element, bls12381_map_fp2_to_g2_element),
+    (Cost::Bls12381PairingBase, bls12381_pairing_base),
+    (Cost::Bls12381PairingElement, bls12381_pairing_element),
     (Cost::StorageHasKeyBase, storage_has_key_base),
     (Cost::StorageHasKeyByte, storage_has_key_byte),
     (Cost::StorageReadBase, storage_read_base),
@@ -777,6 +805,49 @@ fn wasm_instruction(ctx: &mut EstimatorContext) -> GasCost {
     per_instruction
 }
 
+fn wasm_op_local(ctx: &mut EstimatorContext) -> GasCost {
+    if let Some(cached) = ctx.cached.wasm_op_local.clone() {
+        return cached;
+    }
+    let zero = GasCost::zero(ctx.config.metric);
+    let cost = wasm_op_cost(ctx.config, &wasm_op_cost::LOCAL, &zero);
+    ctx.cached.wasm_op_local = Some(cost.clone());
+    cost
+}
+
+fn wasm_op(ctx: &mut EstimatorContext, snippet: &wasm_op_cost::OpSnippet) -> GasCost {
+    let local_cost = wasm_op_local(ctx);
+    wasm_op_cost(ctx.config, snippet, &local_cost)
+}
+
+fn wasm_op_add(ctx: &mut EstimatorContext) -> GasCost {
+    wasm_op(ctx, &wasm_op_cost::ADD)
+}
+fn wasm_op_mul(ctx: &mut EstimatorContext) -> GasCost {
+    wasm_op(ctx, &wasm_op_cost::MUL)
+}
+fn wasm_op_div(ctx: &mut EstimatorContext) -> GasCost {
+    wasm_op(ctx, &wasm_op_cost::DIV)
+}
+fn wasm_op_load(ctx: &mut EstimatorContext) -> GasCost {
+    wasm_op(ctx, &wasm_op_cost::LOAD)
+}
+fn wasm_op_store(ctx: &mut EstimatorContext) -> GasCost {
+    wasm_op(ctx, &wasm_op_cost::STORE)
+}
+fn wasm_op_global(ctx: &mut EstimatorContext) -> GasCost {
+    wasm_op(ctx, &wasm_op_cost::GLOBAL)
+}
+fn wasm_op_call(ctx: &mut EstimatorContext) -> GasCost {
+    wasm_op(ctx, &wasm_op_cost::CALL)
+}
+fn wasm_bulk_memory_page(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+    return wasm_op(ctx, &wasm_op_cost::MEMORY_COPY_PAGE);
+    #[cfg(not(feature = "protocol_feature_wasm_bulk_memory"))]
+    return GasCost::zero(ctx.config.metric);
+}
+
 fn read_memory_base(ctx: &mut EstimatorContext) -> GasCost {
     fn_cost(ctx, "read_memory_10b_10k", ExtCosts::read_memory_base, 10_000)
 }
@@ -939,6 +1010,152 @@ fn alt_bn128_pairing_check_byte(ctx: &mut EstimatorContext) -> GasCost {
     return GasCost::zero(ctx.config.metric);
 }
 
+fn ed25519_verify_base(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    return fn_cost(ctx, "ed25519_verify_32b_1k", ExtCosts::ed25519_verify_base, 1000);
+    #[cfg(not(feature = "protocol_feature_ed25519_verify"))]
+    return GasCost::zero(ctx.config.metric);
+}
+fn ed25519_verify_byte(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    return fn_cost(
+        ctx,
+        "ed25519_verify_16kib_1k",
+        ExtCosts::ed25519_verify_byte,
+        16 * 1024 * 1000,
+    );
+    #[cfg(not(feature = "protocol_feature_ed25519_verify"))]
+    return GasCost::zero(ctx.config.metric);
+}
+
+fn secp256k1_verify_base(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    return fn_cost(ctx, "secp256k1_verify_1k", ExtCosts::secp256k1_verify_base, 1000);
+    #[cfg(not(feature = "protocol_feature_ed25519_verify"))]
+    return GasCost::zero(ctx.config.metric);
+}
+
+fn bls12381_p1_sum_base(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_bls12381")]
+    return fn_cost(ctx, "bls12381_p1_sum_1_1k", ExtCosts::bls12381_p1_sum_base, 1000);
+    #[cfg(not(feature = "protocol_feature_bls12381"))]
+    return GasCost::zero(ctx.config.metric);
+}
+fn bls12381_p1_sum_element(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_bls12381")]
+    return fn_cost(ctx, "bls12381_p1_sum_10_1k", ExtCosts::bls12381_p1_sum_element, 10 * 1000);
+    #[cfg(not(feature = "protocol_feature_bls12381"))]
+    return GasCost::zero(ctx.config.metric);
+}
+
+fn bls12381_p2_sum_base(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_bls12381")]
+    return fn_cost(ctx, "bls12381_p2_sum_1_1k", ExtCosts::bls12381_p2_sum_base, 1000);
+    #[cfg(not(feature = "protocol_feature_bls12381"))]
+    return GasCost::zero(ctx.config.metric);
+}
+fn bls12381_p2_sum_element(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_bls12381")]
+    return fn_cost(ctx, "bls12381_p2_sum_10_1k", ExtCosts::bls12381_p2_sum_element, 10 * 1000);
+    #[cfg(not(feature = "protocol_feature_bls12381"))]
+    return GasCost::zero(ctx.config.metric);
+}
+
+fn bls12381_g1_multiexp_base(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_bls12381")]
+    return fn_cost(ctx, "bls12381_g1_multiexp_1_1k", ExtCosts::bls12381_g1_multiexp_base, 1000);
+    #[cfg(not(feature = "protocol_feature_bls12381"))]
+    return GasCost::zero(ctx.config.metric);
+}
+fn bls12381_g1_multiexp_element(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_bls12381")]
+    return fn_cost(
+        ctx,
+        "bls12381_g1_multiexp_10_1k",
+        ExtCosts::bls12381_g1_multiexp_element,
+        10 * 1000,
+    );
+    #[cfg(not(feature = "protocol_feature_bls12381"))]
+    return GasCost::zero(ctx.config.metric);
+}
+
+fn bls12381_g2_multiexp_base(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_bls12381")]
+    return fn_cost(ctx, "bls12381_g2_multiexp_1_1k", ExtCosts::bls12381_g2_multiexp_base, 1000);
+    #[cfg(not(feature = "protocol_feature_bls12381"))]
+    return GasCost::zero(ctx.config.metric);
+}
+fn bls12381_g2_multiexp_element(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_bls12381")]
+    return fn_cost(
+        ctx,
+        "bls12381_g2_multiexp_10_1k",
+        ExtCosts::bls12381_g2_multiexp_element,
+        10 * 1000,
+    );
+    #[cfg(not(feature = "protocol_feature_bls12381"))]
+    return GasCost::zero(ctx.config.metric);
+}
+
+fn bls12381_map_fp_to_g1_base(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_bls12381")]
+    return fn_cost(ctx, "bls12381_map_fp_to_g1_1_1k", ExtCosts::bls12381_map_fp_to_g1_base, 1000);
+    #[cfg(not(feature = "protocol_feature_bls12381"))]
+    return GasCost::zero(ctx.config.metric);
+}
+fn bls12381_map_fp_to_g1_element(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_bls12381")]
+    return fn_cost(
+        ctx,
+        "bls12381_map_fp_to_g1_10_1k",
+        ExtCosts::bls12381_map_fp_to_g1_element,
+        10 * 1000,
+    );
+    #[cfg(not(feature = "protocol_feature_bls12381"))]
+    return GasCost::zero(ctx.config.metric);
+}
+
+fn bls12381_map_fp2_to_g2_base(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_bls12381")]
+    return fn_cost(
+        ctx,
+        "bls12381_map_fp2_to_g2_1_1k",
+        ExtCosts::bls12381_map_fp2_to_g2_base,
+        1000,
+    );
+    #[cfg(not(feature = "protocol_feature_bls12381"))]
+    return GasCost::zero(ctx.config.metric);
+}
+fn bls12381_map_fp2_to_g2_element(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_bls12381")]
+    return fn_cost(
+        ctx,
+        "bls12381_map_fp2_to_g2_10_1k",
+        ExtCosts::bls12381_map_fp2_to_g2_element,
+        10 * 1000,
+    );
+    #[cfg(not(feature = "protocol_feature_bls12381"))]
+    return GasCost::zero(ctx.config.metric);
+}
+
+fn bls12381_pairing_base(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_bls12381")]
+    return fn_cost(ctx, "bls12381_pairing_check_1_1k", ExtCosts::bls12381_pairing_base, 1000);
+    #[cfg(not(feature = "protocol_feature_bls12381"))]
+    return GasCost::zero(ctx.config.metric);
+}
+fn bls12381_pairing_element(ctx: &mut EstimatorContext) -> GasCost {
+    #[cfg(feature = "protocol_feature_bls12381")]
+    return fn_cost(
+        ctx,
+        "bls12381_pairing_check_10_1k",
+        ExtCosts::bls12381_pairing_element,
+        10 * 1000,
+    );
+    #[cfg(not(feature = "protocol_feature_bls12381"))]
+    return GasCost::zero(ctx.config.metric);
+}
+
 fn storage_has_key_base(ctx: &mut EstimatorContext) -> GasCost {
     fn_cost_with_setup(
         ctx,
@@ -64,11 +64,13 @@ impl RuntimeTestbed {
         let apply_state = ApplyState {
             // Put each runtime into a separate shard.
             block_index: 1,
+            transaction_validity_height: 1,
             // Epoch length is long enough to avoid corner cases.
             prev_block_hash: Default::default(),
             block_hash: Default::default(),
             epoch_id: Default::default(),
             epoch_height: 0,
+            shard_id: 0,
             gas_price: 0,
             block_timestamp: 0,
             gas_limit: None,
@@ -79,6 +81,7 @@ impl RuntimeTestbed {
             is_new_chunk: true,
             migration_data: Arc::new(MigrationData::default()),
             migration_flags: MigrationFlags::default(),
+            congestion_info: Default::default(),
         };
 
         Self {
@@ -116,6 +119,7 @@ impl RuntimeTestbed {
         self.root = root;
         store_update.commit().unwrap();
         self.apply_state.block_index += 1;
+        self.apply_state.transaction_validity_height += 1;
 
         let mut total_burnt_gas = 0;
         if !allow_failures {
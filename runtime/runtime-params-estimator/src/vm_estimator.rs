@@ -36,6 +36,7 @@ pub(crate) fn create_context(input: Vec<u8>) -> VMContext {
         random_seed: vec![0, 1, 2],
         view_config: None,
         output_data_receivers: vec![],
+        trace_host_functions: false,
     }
 }
 
@@ -0,0 +1,133 @@
+use crate::config::Config;
+use crate::gas_cost::{GasCost, NonNegativeTolerance};
+use crate::vm_estimator::create_context;
+use near_primitives::config::VMConfig;
+use near_primitives::contract::ContractCode;
+use near_primitives::runtime::fees::RuntimeFeesConfig;
+use near_primitives::version::PROTOCOL_VERSION;
+use near_vm_logic::mocks::mock_external::MockedExternal;
+use near_vm_runner::MockCompiledContractCache;
+
+/// Number of copies of the measured snippet in the loop body.
+const SNIPPETS_PER_ITER: u64 = 100;
+/// Number of loop iterations in a single function call.
+const LOOP_ITERS: u64 = 10_000;
+
+/// A sequence of Wasm instructions leaving the stack unchanged, measured by
+/// repeating it in a loop.
+pub(crate) struct OpSnippet {
+    code: &'static str,
+    /// Number of measured instructions in the snippet.
+    ops: u64,
+    /// Number of `local.get`, `local.set` and constant instructions providing
+    /// operands and consuming results of the measured instructions.
+    helpers: u64,
+}
+
+pub(crate) const LOCAL: OpSnippet =
+    OpSnippet { code: "local.get 1 local.set 1", ops: 2, helpers: 0 };
+pub(crate) const ADD: OpSnippet =
+    OpSnippet { code: "local.get 1 i64.const 3 i64.add local.set 1", ops: 1, helpers: 3 };
+pub(crate) const MUL: OpSnippet =
+    OpSnippet { code: "local.get 1 i64.const 3 i64.mul local.set 1", ops: 1, helpers: 3 };
+pub(crate) const DIV: OpSnippet =
+    OpSnippet { code: "local.get 1 i64.const 3 i64.div_u local.set 1", ops: 1, helpers: 3 };
+pub(crate) const LOAD: OpSnippet =
+    OpSnippet { code: "i32.const 64 i64.load local.set 1", ops: 1, helpers: 2 };
+pub(crate) const STORE: OpSnippet =
+    OpSnippet { code: "i32.const 64 local.get 1 i64.store", ops: 1, helpers: 2 };
+pub(crate) const GLOBAL: OpSnippet =
+    OpSnippet { code: "global.get $g global.set $g", ops: 2, helpers: 0 };
+pub(crate) const CALL: OpSnippet = OpSnippet { code: "call $empty", ops: 1, helpers: 0 };
+/// Copies the first page of memory to the second one.
+#[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+pub(crate) const MEMORY_COPY_PAGE: OpSnippet = OpSnippet {
+    code: "i32.const 65536 i32.const 0 i32.const 65536 memory.copy",
+    ops: 1,
+    helpers: 3,
+};
+
+/// Estimates the cost of a single instruction of the snippet.
+///
+/// The snippet is executed without gas metering, in a loop repeating it many
+/// times. The time of the same loop with an empty body is subtracted, and so is
+/// `local_cost` for each helper instruction of the snippet.
+pub(crate) fn wasm_op_cost(config: &Config, snippet: &OpSnippet, local_cost: &GasCost) -> GasCost {
+    let with_snippet = measure_loop(config, snippet.code);
+    let empty = measure_loop(config, "");
+    let snippets = config.iter_per_block as u64 * LOOP_ITERS * SNIPPETS_PER_ITER;
+    let per_snippet =
+        with_snippet.saturating_sub(&empty, &NonNegativeTolerance::PER_MILLE) / snippets;
+    per_snippet
+        .saturating_sub(&(local_cost.clone() * snippet.helpers), &NonNegativeTolerance::PER_MILLE)
+        / snippet.ops
+}
+
+fn make_loop_contract(snippet: &str) -> ContractCode {
+    let body = vec![snippet; SNIPPETS_PER_ITER as usize].join("\n");
+    let code = format!(
+        "
+        (module
+            (memory 2)
+            (global $g (mut i64) (i64.const 0))
+            (func $empty)
+            (func (export \"main\")
+                (local i32 i64)
+                i32.const {}
+                local.set 0
+                i64.const 7
+                local.set 1
+                loop
+                    {}
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    local.tee 0
+                    br_if 0
+                end
+            )
+        )",
+        LOOP_ITERS, body
+    );
+    ContractCode::new(wat::parse_str(code).unwrap(), None)
+}
+
+/// Total time of `iter_per_block` calls of the loop contract, without gas
+/// metering.
+fn measure_loop(config: &Config, snippet: &str) -> GasCost {
+    let contract = make_loop_contract(snippet);
+    #[allow(unused_mut)]
+    let mut vm_config = VMConfig::free();
+    #[cfg(feature = "protocol_feature_wasm_bulk_memory")]
+    {
+        vm_config.limit_config.wasm_bulk_memory = true;
+    }
+    let runtime = config.vm_kind.runtime(vm_config).expect("runtime has not been enabled");
+    let fees = RuntimeFeesConfig::free();
+    let cache = MockCompiledContractCache::default();
+    let mut fake_external = MockedExternal::new();
+    let promise_results = vec![];
+
+    let mut run = || {
+        let result = runtime.run(
+            &contract,
+            "main",
+            &mut fake_external,
+            create_context(vec![]),
+            &fees,
+            &promise_results,
+            PROTOCOL_VERSION,
+            Some(&cache),
+        );
+        assert!(result.error().is_none());
+    };
+
+    for _ in 0..config.warmup_iters_per_block {
+        run();
+    }
+    let start = GasCost::measure(config.metric);
+    for _ in 0..config.iter_per_block {
+        run();
+    }
+    start.elapsed()
+}
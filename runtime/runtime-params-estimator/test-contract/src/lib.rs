@@ -43,6 +43,38 @@ extern "C" {
     fn alt_bn128_g1_sum(value_len: u64, value_ptr: u64, register_id: u64);
     #[cfg(feature = "protocol_feature_alt_bn128")]
     fn alt_bn128_pairing_check(value_len: u64, value_ptr: u64) -> u64;
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    fn ed25519_verify(
+        signature_len: u64,
+        signature_ptr: u64,
+        message_len: u64,
+        message_ptr: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+    ) -> u64;
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    fn secp256k1_verify(
+        signature_len: u64,
+        signature_ptr: u64,
+        hash_len: u64,
+        hash_ptr: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+    ) -> u64;
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn bls12381_p1_sum(value_len: u64, value_ptr: u64, register_id: u64);
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn bls12381_p2_sum(value_len: u64, value_ptr: u64, register_id: u64);
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn bls12381_g1_multiexp(value_len: u64, value_ptr: u64, register_id: u64);
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn bls12381_g2_multiexp(value_len: u64, value_ptr: u64, register_id: u64);
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn bls12381_map_fp_to_g1(value_len: u64, value_ptr: u64, register_id: u64);
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn bls12381_map_fp2_to_g2(value_len: u64, value_ptr: u64, register_id: u64);
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn bls12381_pairing_check(value_len: u64, value_ptr: u64) -> u64;
     fn random_seed(register_id: u64);
     fn sha256(value_len: u64, value_ptr: u64, register_id: u64);
     fn keccak256(value_len: u64, value_ptr: u64, register_id: u64);
@@ -761,6 +793,312 @@ pub unsafe fn alt_bn128_pairing_check_10_1k() {
     }
 }
 
+// Generators of the G1 and G2 groups of BLS12-381 in the uncompressed serialization format.
+#[cfg(feature = "protocol_feature_bls12381")]
+const BLS12381_G1: [u8; 96] = [
+    23, 241, 211, 167, 49, 151, 215, 148, 38, 149, 99, 140, 79, 169, 172, 15, 195, 104, 140, 79,
+    151, 116, 185, 5, 161, 78, 58, 63, 23, 27, 172, 88, 108, 85, 232, 63, 249, 122, 26, 239, 251,
+    58, 240, 10, 219, 34, 198, 187, 8, 179, 244, 129, 227, 170, 160, 241, 160, 158, 48, 237, 116,
+    29, 138, 228, 252, 245, 224, 149, 213, 208, 10, 246, 0, 219, 24, 203, 44, 4, 179, 237, 208, 60,
+    199, 68, 162, 136, 138, 228, 12, 170, 35, 41, 70, 197, 231, 225,
+];
+#[cfg(feature = "protocol_feature_bls12381")]
+const BLS12381_G2: [u8; 192] = [
+    19, 224, 43, 96, 82, 113, 159, 96, 125, 172, 211, 160, 136, 39, 79, 101, 89, 107, 208, 208,
+    153, 32, 182, 26, 181, 218, 97, 187, 220, 127, 80, 73, 51, 76, 241, 18, 19, 148, 93, 87, 229,
+    172, 125, 5, 93, 4, 43, 126, 2, 74, 162, 178, 240, 143, 10, 145, 38, 8, 5, 39, 45, 197, 16, 81,
+    198, 228, 122, 212, 250, 64, 59, 2, 180, 81, 11, 100, 122, 227, 209, 119, 11, 172, 3, 38, 168,
+    5, 187, 239, 212, 128, 86, 200, 193, 33, 189, 184, 6, 6, 196, 160, 46, 167, 52, 204, 50, 172,
+    210, 176, 43, 194, 139, 153, 203, 62, 40, 126, 133, 167, 99, 175, 38, 116, 146, 171, 87, 46,
+    153, 171, 63, 55, 13, 39, 92, 236, 29, 161, 170, 169, 7, 95, 240, 95, 121, 190, 12, 229, 213,
+    39, 114, 125, 110, 17, 140, 201, 205, 198, 218, 46, 53, 26, 173, 253, 155, 170, 140, 189, 211,
+    167, 109, 66, 154, 105, 81, 96, 209, 44, 146, 58, 201, 204, 59, 172, 162, 137, 225, 147, 84,
+    134, 8, 184, 40, 1,
+];
+
+// Fills a buffer of `N` bytes with repetitions of the concatenation of `parts`.
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_items<const N: usize>(parts: &[&[u8]]) -> [u8; N] {
+    let mut buffer = [0u8; N];
+    let mut offset = 0;
+    while offset < N {
+        for part in parts {
+            buffer[offset..offset + part.len()].copy_from_slice(part);
+            offset += part.len();
+        }
+    }
+    buffer
+}
+
+// Function to measure `bls12381_p1_sum_base`. Also measures `base`, `read_memory_base`,
+// `read_memory_byte`, `write_register_base` and `write_register_byte`, but the curve operations are
+// much more expensive.
+// Compute signed sum of 1 point 1k times.
+#[cfg(feature = "protocol_feature_bls12381")]
+#[no_mangle]
+pub unsafe fn bls12381_p1_sum_1_1k() {
+    let buffer: [u8; 97] = bls12381_items(&[&[0], &BLS12381_G1]);
+    for _ in 0..1_000 {
+        bls12381_p1_sum(buffer.len() as u64, buffer.as_ptr() as u64, 0);
+    }
+}
+
+// Function to measure `bls12381_p1_sum_base` and `bls12381_p1_sum_element`. Also measures `base`,
+// `read_memory_base`, `read_memory_byte`, `write_register_base` and `write_register_byte`, but the
+// curve operations are much more expensive.
+// Compute signed sum of 10 points 1k times.
+#[cfg(feature = "protocol_feature_bls12381")]
+#[no_mangle]
+pub unsafe fn bls12381_p1_sum_10_1k() {
+    let buffer: [u8; 970] = bls12381_items(&[&[0], &BLS12381_G1]);
+    for _ in 0..1_000 {
+        bls12381_p1_sum(buffer.len() as u64, buffer.as_ptr() as u64, 0);
+    }
+}
+
+// Function to measure `bls12381_p2_sum_base`. Also measures `base`, `read_memory_base`,
+// `read_memory_byte`, `write_register_base` and `write_register_byte`, but the curve operations are
+// much more expensive.
+// Compute signed sum of 1 point 1k times.
+#[cfg(feature = "protocol_feature_bls12381")]
+#[no_mangle]
+pub unsafe fn bls12381_p2_sum_1_1k() {
+    let buffer: [u8; 193] = bls12381_items(&[&[0], &BLS12381_G2]);
+    for _ in 0..1_000 {
+        bls12381_p2_sum(buffer.len() as u64, buffer.as_ptr() as u64, 0);
+    }
+}
+
+// Function to measure `bls12381_p2_sum_base` and `bls12381_p2_sum_element`. Also measures `base`,
+// `read_memory_base`, `read_memory_byte`, `write_register_base` and `write_register_byte`, but the
+// curve operations are much more expensive.
+// Compute signed sum of 10 points 1k times.
+#[cfg(feature = "protocol_feature_bls12381")]
+#[no_mangle]
+pub unsafe fn bls12381_p2_sum_10_1k() {
+    let buffer: [u8; 1930] = bls12381_items(&[&[0], &BLS12381_G2]);
+    for _ in 0..1_000 {
+        bls12381_p2_sum(buffer.len() as u64, buffer.as_ptr() as u64, 0);
+    }
+}
+
+// Function to measure `bls12381_g1_multiexp_base`. Also measures `base`, `read_memory_base`,
+// `read_memory_byte`, `write_register_base` and `write_register_byte`, but the curve operations are
+// much more expensive.
+// Compute G1 multiexp of 1 pair 1k times.
+#[cfg(feature = "protocol_feature_bls12381")]
+#[no_mangle]
+pub unsafe fn bls12381_g1_multiexp_1_1k() {
+    let buffer: [u8; 128] = bls12381_items(&[&BLS12381_G1, &[0xff; 32]]);
+    for _ in 0..1_000 {
+        bls12381_g1_multiexp(buffer.len() as u64, buffer.as_ptr() as u64, 0);
+    }
+}
+
+// Function to measure `bls12381_g1_multiexp_base` and `bls12381_g1_multiexp_element`. Also measures
+// `base`, `read_memory_base`, `read_memory_byte`, `write_register_base` and `write_register_byte`,
+// but the curve operations are much more expensive.
+// Compute G1 multiexp of 10 pairs 1k times.
+#[cfg(feature = "protocol_feature_bls12381")]
+#[no_mangle]
+pub unsafe fn bls12381_g1_multiexp_10_1k() {
+    let buffer: [u8; 1280] = bls12381_items(&[&BLS12381_G1, &[0xff; 32]]);
+    for _ in 0..1_000 {
+        bls12381_g1_multiexp(buffer.len() as u64, buffer.as_ptr() as u64, 0);
+    }
+}
+
+// Function to measure `bls12381_g2_multiexp_base`. Also measures `base`, `read_memory_base`,
+// `read_memory_byte`, `write_register_base` and `write_register_byte`, but the curve operations are
+// much more expensive.
+// Compute G2 multiexp of 1 pair 1k times.
+#[cfg(feature = "protocol_feature_bls12381")]
+#[no_mangle]
+pub unsafe fn bls12381_g2_multiexp_1_1k() {
+    let buffer: [u8; 224] = bls12381_items(&[&BLS12381_G2, &[0xff; 32]]);
+    for _ in 0..1_000 {
+        bls12381_g2_multiexp(buffer.len() as u64, buffer.as_ptr() as u64, 0);
+    }
+}
+
+// Function to measure `bls12381_g2_multiexp_base` and `bls12381_g2_multiexp_element`. Also measures
+// `base`, `read_memory_base`, `read_memory_byte`, `write_register_base` and `write_register_byte`,
+// but the curve operations are much more expensive.
+// Compute G2 multiexp of 10 pairs 1k times.
+#[cfg(feature = "protocol_feature_bls12381")]
+#[no_mangle]
+pub unsafe fn bls12381_g2_multiexp_10_1k() {
+    let buffer: [u8; 2240] = bls12381_items(&[&BLS12381_G2, &[0xff; 32]]);
+    for _ in 0..1_000 {
+        bls12381_g2_multiexp(buffer.len() as u64, buffer.as_ptr() as u64, 0);
+    }
+}
+
+// Function to measure `bls12381_map_fp_to_g1_base`. Also measures `base`, `read_memory_base`,
+// `read_memory_byte`, `write_register_base` and `write_register_byte`, but the curve operations are
+// much more expensive.
+// Map 1 element to G1 1k times.
+#[cfg(feature = "protocol_feature_bls12381")]
+#[no_mangle]
+pub unsafe fn bls12381_map_fp_to_g1_1_1k() {
+    let buffer: [u8; 48] = bls12381_items(&[&[1; 48]]);
+    for _ in 0..1_000 {
+        bls12381_map_fp_to_g1(buffer.len() as u64, buffer.as_ptr() as u64, 0);
+    }
+}
+
+// Function to measure `bls12381_map_fp_to_g1_base` and `bls12381_map_fp_to_g1_element`. Also
+// measures `base`, `read_memory_base`, `read_memory_byte`, `write_register_base` and
+// `write_register_byte`, but the curve operations are much more expensive.
+// Map 10 elements to G1 1k times.
+#[cfg(feature = "protocol_feature_bls12381")]
+#[no_mangle]
+pub unsafe fn bls12381_map_fp_to_g1_10_1k() {
+    let buffer: [u8; 480] = bls12381_items(&[&[1; 48]]);
+    for _ in 0..1_000 {
+        bls12381_map_fp_to_g1(buffer.len() as u64, buffer.as_ptr() as u64, 0);
+    }
+}
+
+// Function to measure `bls12381_map_fp2_to_g2_base`. Also measures `base`, `read_memory_base`,
+// `read_memory_byte`, `write_register_base` and `write_register_byte`, but the curve operations are
+// much more expensive.
+// Map 1 element to G2 1k times.
+#[cfg(feature = "protocol_feature_bls12381")]
+#[no_mangle]
+pub unsafe fn bls12381_map_fp2_to_g2_1_1k() {
+    let buffer: [u8; 96] = bls12381_items(&[&[1; 96]]);
+    for _ in 0..1_000 {
+        bls12381_map_fp2_to_g2(buffer.len() as u64, buffer.as_ptr() as u64, 0);
+    }
+}
+
+// Function to measure `bls12381_map_fp2_to_g2_base` and `bls12381_map_fp2_to_g2_element`. Also
+// measures `base`, `read_memory_base`, `read_memory_byte`, `write_register_base` and
+// `write_register_byte`, but the curve operations are much more expensive.
+// Map 10 elements to G2 1k times.
+#[cfg(feature = "protocol_feature_bls12381")]
+#[no_mangle]
+pub unsafe fn bls12381_map_fp2_to_g2_10_1k() {
+    let buffer: [u8; 960] = bls12381_items(&[&[1; 96]]);
+    for _ in 0..1_000 {
+        bls12381_map_fp2_to_g2(buffer.len() as u64, buffer.as_ptr() as u64, 0);
+    }
+}
+
+// Function to measure `bls12381_pairing_base`. Also measures `base`, `read_memory_base` and
+// `read_memory_byte`, but the curve operations are much more expensive.
+// Compute pairing check of 1 pair 1k times.
+#[cfg(feature = "protocol_feature_bls12381")]
+#[no_mangle]
+pub unsafe fn bls12381_pairing_check_1_1k() {
+    let buffer: [u8; 288] = bls12381_items(&[&BLS12381_G1, &BLS12381_G2]);
+    for _ in 0..1_000 {
+        bls12381_pairing_check(buffer.len() as u64, buffer.as_ptr() as u64);
+    }
+}
+
+// Function to measure `bls12381_pairing_base` and `bls12381_pairing_element`. Also measures `base`,
+// `read_memory_base` and `read_memory_byte`, but the curve operations are much more expensive.
+// Compute pairing check of 10 pairs 1k times.
+#[cfg(feature = "protocol_feature_bls12381")]
+#[no_mangle]
+pub unsafe fn bls12381_pairing_check_10_1k() {
+    let buffer: [u8; 2880] = bls12381_items(&[&BLS12381_G1, &BLS12381_G2]);
+    for _ in 0..1_000 {
+        bls12381_pairing_check(buffer.len() as u64, buffer.as_ptr() as u64);
+    }
+}
+
+#[cfg(feature = "protocol_feature_ed25519_verify")]
+const ED25519_PUBLIC_KEY: [u8; 32] = [
+    8, 210, 133, 167, 222, 75, 82, 228, 179, 63, 206, 38, 196, 12, 35, 200, 61, 154, 200, 20, 217,
+    8, 5, 83, 111, 145, 90, 177, 183, 36, 223, 243,
+];
+
+// Function to measure `ed25519_verify_base`. Also measures `base`, `read_memory_base` and
+// `read_memory_byte`, but signature verification is much more expensive so we are okay
+// overcharging it.
+// Verify a signature of a 32 bytes message 1k times.
+#[cfg(feature = "protocol_feature_ed25519_verify")]
+#[no_mangle]
+pub unsafe fn ed25519_verify_32b_1k() {
+    let message = [42u8; 32];
+    let signature: [u8; 64] = [
+        148, 98, 65, 238, 250, 195, 14, 245, 242, 212, 195, 194, 141, 234, 165, 236, 26, 26, 214,
+        59, 118, 69, 227, 244, 226, 225, 80, 138, 63, 143, 233, 4, 29, 26, 204, 100, 200, 247, 236,
+        243, 11, 123, 168, 213, 5, 91, 220, 110, 235, 231, 53, 27, 252, 40, 70, 225, 113, 147, 220,
+        144, 170, 80, 81, 14,
+    ];
+    for _ in 0..1_000 {
+        ed25519_verify(
+            signature.len() as u64,
+            signature.as_ptr() as u64,
+            message.len() as u64,
+            message.as_ptr() as u64,
+            ED25519_PUBLIC_KEY.len() as u64,
+            ED25519_PUBLIC_KEY.as_ptr() as u64,
+        );
+    }
+}
+
+// Function to measure `ed25519_verify_byte`. Also measures `ed25519_verify_base`, `base`,
+// `read_memory_base` and `read_memory_byte`, but hashing the message dominates the cost.
+// Verify a signature of a 16KiB message 1k times.
+#[cfg(feature = "protocol_feature_ed25519_verify")]
+#[no_mangle]
+pub unsafe fn ed25519_verify_16kib_1k() {
+    let message = [42u8; 16 * 1024];
+    let signature: [u8; 64] = [
+        123, 82, 131, 14, 133, 71, 129, 161, 10, 189, 21, 231, 88, 230, 100, 6, 203, 197, 133, 116,
+        196, 231, 42, 22, 160, 255, 204, 28, 245, 4, 140, 117, 121, 166, 230, 60, 179, 93, 140,
+        112, 42, 124, 37, 21, 200, 54, 37, 54, 48, 115, 134, 161, 168, 49, 8, 191, 163, 88, 152,
+        42, 170, 55, 25, 5,
+    ];
+    for _ in 0..1_000 {
+        ed25519_verify(
+            signature.len() as u64,
+            signature.as_ptr() as u64,
+            message.len() as u64,
+            message.as_ptr() as u64,
+            ED25519_PUBLIC_KEY.len() as u64,
+            ED25519_PUBLIC_KEY.as_ptr() as u64,
+        );
+    }
+}
+
+// Function to measure `secp256k1_verify_base`. Also measures `base`, `read_memory_base` and
+// `read_memory_byte`, but signature verification is much more expensive so we are okay
+// overcharging it.
+// Verify a signature of a 32 bytes hash 1k times.
+#[cfg(feature = "protocol_feature_ed25519_verify")]
+#[no_mangle]
+pub unsafe fn secp256k1_verify_1k() {
+    let hash = [42u8; 32];
+    let signature: [u8; 64] = [
+        40, 243, 155, 198, 88, 179, 230, 252, 255, 25, 71, 148, 79, 147, 232, 1, 133, 238, 67, 40,
+        230, 62, 197, 246, 45, 56, 183, 207, 110, 1, 83, 94, 61, 19, 33, 175, 35, 86, 241, 134,
+        182, 188, 125, 131, 144, 136, 104, 127, 177, 178, 132, 78, 111, 32, 169, 254, 122, 113,
+        183, 103, 63, 248, 136, 86,
+    ];
+    let public_key: [u8; 64] = [
+        9, 98, 240, 232, 86, 66, 144, 194, 236, 42, 208, 203, 246, 153, 131, 19, 177, 157, 167,
+        189, 66, 57, 223, 249, 41, 51, 210, 126, 208, 189, 92, 232, 107, 116, 169, 25, 147, 49, 70,
+        254, 237, 37, 81, 13, 251, 61, 246, 7, 72, 93, 179, 132, 223, 195, 56, 97, 15, 17, 54, 80,
+        98, 43, 57, 144,
+    ];
+    for _ in 0..1_000 {
+        secp256k1_verify(
+            signature.len() as u64,
+            signature.as_ptr() as u64,
+            hash.len() as u64,
+            hash.as_ptr() as u64,
+            public_key.len() as u64,
+            public_key.as_ptr() as u64,
+        );
+    }
+}
+
 // ###############
 // # Storage API #
 // ###############
@@ -1,7 +1,9 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use near_crypto::PublicKey;
-use near_primitives::account::{AccessKey, AccessKeyPermission, Account};
+#[cfg(feature = "protocol_feature_access_key_expiration")]
+use near_primitives::account::AccessKeyPermission;
+use near_primitives::account::{AccessKey, Account};
 use near_primitives::checked_feature;
 use near_primitives::contract::ContractCode;
 use near_primitives::errors::{ActionError, ActionErrorKind, ContractCallError, RuntimeError};
@@ -13,7 +15,13 @@ use near_primitives::transaction::{
     Action, AddKeyAction, DeleteAccountAction, DeleteKeyAction, DeployContractAction,
     FunctionCallAction, StakeAction, TransferAction,
 };
+#[cfg(feature = "protocol_feature_global_contracts")]
+use near_primitives::transaction::{DeployGlobalContractAction, UseGlobalContractAction};
+#[cfg(feature = "protocol_feature_global_contracts")]
+use near_primitives::trie_key::TrieKey;
 use near_primitives::types::validator_stake::ValidatorStake;
+#[cfg(feature = "protocol_feature_global_contracts")]
+use near_primitives::types::Balance;
 use near_primitives::types::{AccountId, BlockHeight, EpochInfoProvider};
 use near_primitives::utils::create_random_seed;
 use near_primitives::version::{
@@ -21,19 +29,30 @@ use near_primitives::version::{
     DELETE_KEY_STORAGE_USAGE_PROTOCOL_VERSION,
 };
 use near_store::{
-    get_access_key, get_code, remove_access_key, remove_account, set_access_key, set_code,
-    StorageError, TrieUpdate,
+    get_access_key, get_code_storage_usage, remove_access_key, remove_account, set_access_key,
+    set_code, StorageError, TrieUpdate,
 };
+#[cfg(feature = "protocol_feature_global_contracts")]
+use near_store::{has_global_code, set_global_code};
 use near_vm_errors::{
     AnyError, CacheError, CompilationError, FunctionCallError, InconsistentStateError, VMError,
 };
 use near_vm_logic::types::PromiseResult;
 use near_vm_logic::VMContext;
 
+#[cfg(feature = "protocol_feature_delegate_action")]
+use crate::config::{
+    delegate_receipt_exec_fees, delegate_receipt_send_fees, inner_actions, total_deposit,
+    total_prepaid_gas,
+};
 use crate::config::{safe_add_gas, RuntimeConfig};
 use crate::ext::{ExternalError, RuntimeExt};
+#[cfg(feature = "protocol_feature_delegate_action")]
+use crate::verifier::validate_delegate_action_key;
 use crate::{ActionResult, ApplyState};
 use near_primitives::config::ViewConfig;
+#[cfg(feature = "protocol_feature_delegate_action")]
+use near_primitives::transaction::SignedDelegateAction;
 use near_vm_runner::{precompile_contract, VMResult};
 
 /// Runs given function call with given context / apply state.
@@ -49,6 +68,7 @@ pub(crate) fn execute_function_call(
     config: &RuntimeConfig,
     is_last_action: bool,
     view_config: Option<ViewConfig>,
+    trace_host_functions: bool,
 ) -> VMResult {
     let account_id = runtime_ext.account_id();
     let code = match runtime_ext.get_code(account.code_hash()) {
@@ -96,6 +116,7 @@ pub(crate) fn execute_function_call(
         random_seed,
         view_config,
         output_data_receivers,
+        trace_host_functions,
     };
 
     // TODO (#5920): enable chunk caching in the protocol. Also consider using RAII for switching the state back
@@ -129,6 +150,7 @@ pub(crate) fn action_function_call(
     config: &RuntimeConfig,
     is_last_action: bool,
     epoch_info_provider: &dyn EpochInfoProvider,
+    trace_host_functions: bool,
 ) -> Result<(), RuntimeError> {
     if account.amount().checked_add(function_call.deposit).is_none() {
         return Err(StorageError::StorageInconsistentState(
@@ -158,6 +180,7 @@ pub(crate) fn action_function_call(
         config,
         is_last_action,
         None,
+        trace_host_functions,
     )
     .outcome_error();
     let execution_succeeded = match err {
@@ -251,6 +274,7 @@ pub(crate) fn action_function_call(
         result.gas_used = safe_add_gas(result.gas_used, outcome.used_gas)?;
         result.logs.extend(outcome.logs.into_iter());
         result.profile.merge(&outcome.profile);
+        result.host_function_calls.extend(outcome.host_function_calls);
         if execution_succeeded {
             account.set_amount(outcome.balance);
             account.set_storage_usage(outcome.storage_usage);
@@ -328,11 +352,20 @@ pub(crate) fn try_refund_allowance(
 ) -> Result<(), StorageError> {
     if let Some(mut access_key) = get_access_key(state_update, account_id, public_key)? {
         let mut updated = false;
-        if let AccessKeyPermission::FunctionCall(function_call_permission) =
-            &mut access_key.permission
+        #[cfg(feature = "protocol_feature_access_key_expiration")]
+        let allowance_cap = match &access_key.permission {
+            AccessKeyPermission::ExpiringFunctionCall(permission) => permission.allowance_per_epoch,
+            _ => None,
+        };
+        if let Some(function_call_permission) = access_key.permission.function_call_permission_mut()
         {
             if let Some(allowance) = function_call_permission.allowance.as_mut() {
                 let new_allowance = allowance.saturating_add(transfer.deposit);
+                // A refund never raises the allowance of an expiring key above its per-epoch
+                // allowance.
+                #[cfg(feature = "protocol_feature_access_key_expiration")]
+                let new_allowance = allowance_cap
+                    .map_or(new_allowance, |cap| std::cmp::min(new_allowance, cap.max(*allowance)));
                 if new_allowance > *allowance {
                     *allowance = new_allowance;
                     updated = true;
@@ -458,8 +491,7 @@ pub(crate) fn action_deploy_contract(
 ) -> Result<(), StorageError> {
     let _span = tracing::debug_span!(target: "runtime", "action_deploy_contract").entered();
     let code = ContractCode::new(deploy_contract.code.clone(), None);
-    let prev_code = get_code(state_update, account_id, Some(account.code_hash()))?;
-    let prev_code_length = prev_code.map(|code| code.code().len() as u64).unwrap_or_default();
+    let prev_code_length = get_code_storage_usage(state_update, account_id)?;
     account.set_storage_usage(account.storage_usage().saturating_sub(prev_code_length));
     account.set_storage_usage(
         account.storage_usage().checked_add(code.code().len() as u64).ok_or_else(|| {
@@ -484,6 +516,70 @@ pub(crate) fn action_deploy_contract(
     Ok(())
 }
 
+/// Stores the code in the shard once, deduplicated by its hash. The code is not a part of the
+/// storage usage of any account, so the deployer burns the storage cost of the code instead of
+/// staking it, and the code stays paid for after the deployer is deleted.
+#[cfg(feature = "protocol_feature_global_contracts")]
+pub(crate) fn action_deploy_global_contract(
+    state_update: &mut TrieUpdate,
+    account: &mut Account,
+    account_id: &AccountId,
+    deploy_global_contract: &DeployGlobalContractAction,
+    apply_state: &ApplyState,
+    result: &mut ActionResult,
+) -> Result<(), StorageError> {
+    let _span = tracing::debug_span!(target: "runtime", "action_deploy_global_contract").entered();
+    let code = ContractCode::new(deploy_global_contract.code.clone(), None);
+    if has_global_code(state_update, *code.hash())? {
+        return Ok(());
+    }
+    let amount = apply_state.config.storage_amount_per_byte * code.code().len() as Balance;
+    match account.amount().checked_sub(amount) {
+        Some(balance) => account.set_amount(balance),
+        None => {
+            result.result = Err(ActionErrorKind::LackBalanceForGlobalContract {
+                account_id: account_id.clone(),
+                amount,
+            }
+            .into());
+            return Ok(());
+        }
+    }
+    result.other_burnt_amount = amount;
+    set_global_code(state_update, &code);
+    // The compiled code is cached by the code hash, so it is shared by all accounts using it.
+    precompile_contract(
+        &code,
+        &apply_state.config.wasm_config,
+        apply_state.current_protocol_version,
+        apply_state.cache.as_deref(),
+    )
+    .ok();
+    Ok(())
+}
+
+/// Makes the account use the global contract code with the given hash instead of its own code.
+#[cfg(feature = "protocol_feature_global_contracts")]
+pub(crate) fn action_use_global_contract(
+    state_update: &mut TrieUpdate,
+    account: &mut Account,
+    account_id: &AccountId,
+    use_global_contract: &UseGlobalContractAction,
+    result: &mut ActionResult,
+) -> Result<(), StorageError> {
+    let _span = tracing::debug_span!(target: "runtime", "action_use_global_contract").entered();
+    let code_hash = use_global_contract.code_hash;
+    if !has_global_code(state_update, code_hash)? {
+        result.result = Err(ActionErrorKind::GlobalContractDoesNotExist { code_hash }.into());
+        return Ok(());
+    }
+    let prev_code_length = get_code_storage_usage(state_update, account_id)?;
+    account.set_storage_usage(account.storage_usage().saturating_sub(prev_code_length));
+    account.set_code_hash(code_hash);
+    state_update.remove(TrieKey::ContractCode { account_id: account_id.clone() });
+    Ok(())
+}
+
 pub(crate) fn action_delete_account(
     state_update: &mut TrieUpdate,
     account: &mut Option<Account>,
@@ -497,13 +593,10 @@ pub(crate) fn action_delete_account(
     if current_protocol_version >= ProtocolFeature::DeleteActionRestriction.protocol_version() {
         let account = account.as_ref().unwrap();
         let mut account_storage_usage = account.storage_usage();
-        let contract_code = get_code(state_update, account_id, Some(account.code_hash()))?;
-        if let Some(code) = contract_code {
-            // account storage usage should be larger than code size
-            let code_len = code.code().len() as u64;
-            debug_assert!(account_storage_usage > code_len);
-            account_storage_usage = account_storage_usage.saturating_sub(code_len);
-        }
+        // account storage usage should be larger than code size
+        let code_len = get_code_storage_usage(state_update, account_id)?;
+        debug_assert!(account_storage_usage > code_len);
+        account_storage_usage = account_storage_usage.saturating_sub(code_len);
         if account_storage_usage > Account::MAX_ACCOUNT_DELETION_STORAGE_USAGE {
             result.result = Err(ActionErrorKind::DeleteAccountWithLargeState {
                 account_id: account_id.clone(),
@@ -608,6 +701,96 @@ pub(crate) fn action_add_key(
     Ok(())
 }
 
+/// Executes the actions of a delegate action as if they were sent by its `sender_id`. The actions
+/// are validated against the sender's access key and forwarded in a new receipt. The deposits of
+/// the actions are taken from the sender, the gas is paid for by the signer of the transaction
+/// carrying the delegate action.
+#[cfg(feature = "protocol_feature_delegate_action")]
+pub(crate) fn apply_delegate_action(
+    state_update: &mut TrieUpdate,
+    apply_state: &ApplyState,
+    action_receipt: &ActionReceipt,
+    sender: &mut Account,
+    sender_id: &AccountId,
+    signed_delegate_action: &SignedDelegateAction,
+    result: &mut ActionResult,
+) -> Result<(), RuntimeError> {
+    let delegate_action = &signed_delegate_action.delegate_action;
+
+    if !signed_delegate_action.verify() {
+        result.result = Err(ActionErrorKind::DelegateActionInvalidSignature.into());
+        return Ok(());
+    }
+    if apply_state.block_index > delegate_action.max_block_height {
+        result.result = Err(ActionErrorKind::DelegateActionExpired.into());
+        return Ok(());
+    }
+    if delegate_action.sender_id != *sender_id {
+        result.result = Err(ActionErrorKind::DelegateActionSenderDoesNotMatchTxReceiver {
+            sender_id: delegate_action.sender_id.clone(),
+            receiver_id: sender_id.clone(),
+        }
+        .into());
+        return Ok(());
+    }
+
+    validate_delegate_action_key(state_update, apply_state, delegate_action, result)?;
+    if result.result.is_err() {
+        return Ok(());
+    }
+
+    let actions = inner_actions(delegate_action);
+    let deposit = total_deposit(&actions)?;
+    match sender.amount().checked_sub(deposit) {
+        Some(balance) => sender.set_amount(balance),
+        None => {
+            result.result = Err(ActionErrorKind::DelegateActionLackBalance {
+                sender_id: sender_id.clone(),
+                amount: deposit,
+            }
+            .into());
+            return Ok(());
+        }
+    }
+    let prepaid_gas = total_prepaid_gas(&actions)?;
+    let new_receipt = Receipt {
+        predecessor_id: sender_id.clone(),
+        receiver_id: delegate_action.receiver_id.clone(),
+        // Actual receipt ID is set in the Runtime.apply_action_receipt(...) in the
+        // "Generating receipt IDs" section
+        receipt_id: CryptoHash::default(),
+        receipt: ReceiptEnum::Action(ActionReceipt {
+            signer_id: action_receipt.signer_id.clone(),
+            signer_public_key: action_receipt.signer_public_key.clone(),
+            gas_price: action_receipt.gas_price,
+            output_data_receivers: vec![],
+            input_data_ids: vec![],
+            actions,
+        }),
+    };
+
+    let transaction_costs = &apply_state.config.transaction_costs;
+    let send_fees = delegate_receipt_send_fees(
+        transaction_costs,
+        delegate_action,
+        apply_state.current_protocol_version,
+    )?;
+    let exec_fees = delegate_receipt_exec_fees(
+        transaction_costs,
+        delegate_action,
+        apply_state.current_protocol_version,
+    )?;
+    // The send fees are burnt now, the rest of the prepaid gas is passed on to the new receipt.
+    // Both are counted as used, otherwise they would be refunded to the relayer.
+    result.gas_burnt = safe_add_gas(result.gas_burnt, send_fees)?;
+    result.gas_used = safe_add_gas(result.gas_used, send_fees)?;
+    result.gas_used = safe_add_gas(result.gas_used, exec_fees)?;
+    result.gas_used = safe_add_gas(result.gas_used, prepaid_gas)?;
+    result.new_receipts.push(new_receipt);
+
+    Ok(())
+}
+
 pub(crate) fn check_actor_permissions(
     action: &Action,
     account: &Option<Account>,
@@ -650,7 +833,19 @@ pub(crate) fn check_actor_permissions(
                 .into());
             }
         }
+        #[cfg(feature = "protocol_feature_global_contracts")]
+        Action::DeployGlobalContract(_) | Action::UseGlobalContract(_) => {
+            if actor_id != account_id {
+                return Err(ActionErrorKind::ActorNoPermission {
+                    account_id: account_id.clone(),
+                    actor_id: actor_id.clone(),
+                }
+                .into());
+            }
+        }
         Action::CreateAccount(_) | Action::FunctionCall(_) | Action::Transfer(_) => (),
+        #[cfg(feature = "protocol_feature_delegate_action")]
+        Action::Delegate(_) => (),
     };
     Ok(())
 }
@@ -734,6 +929,24 @@ pub(crate) fn check_account_existence(
                 .into());
             }
         }
+        #[cfg(feature = "protocol_feature_delegate_action")]
+        Action::Delegate(_) => {
+            if account.is_none() {
+                return Err(ActionErrorKind::AccountDoesNotExist {
+                    account_id: account_id.clone(),
+                }
+                .into());
+            }
+        }
+        #[cfg(feature = "protocol_feature_global_contracts")]
+        Action::DeployGlobalContract(_) | Action::UseGlobalContract(_) => {
+            if account.is_none() {
+                return Err(ActionErrorKind::AccountDoesNotExist {
+                    account_id: account_id.clone(),
+                }
+                .into());
+            }
+        }
     };
     Ok(())
 }
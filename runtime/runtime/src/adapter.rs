@@ -3,11 +3,12 @@ use near_crypto::PublicKey;
 use near_primitives::account::{AccessKey, Account};
 use near_primitives::contract::ContractCode;
 use near_primitives::hash::CryptoHash;
+use near_primitives::runtime::fees::StorageUsageConfig;
 use near_primitives::types::{
     AccountId, BlockHeight, EpochHeight, EpochId, EpochInfoProvider, MerkleHash,
 };
 use near_primitives::version::ProtocolVersion;
-use near_primitives::views::ViewStateResult;
+use near_primitives::views::{DelayedReceiptsView, StorageBreakdownView, ViewStateResult};
 
 /// Adapter for querying runtime.
 pub trait ViewRuntimeAdapter {
@@ -65,4 +66,21 @@ pub trait ViewRuntimeAdapter {
         account_id: &AccountId,
         prefix: &[u8],
     ) -> Result<ViewStateResult, crate::state_viewer::errors::ViewStateError>;
+
+    fn view_delayed_receipts(
+        &self,
+        shard_uid: &ShardUId,
+        state_root: MerkleHash,
+        from_index: Option<u64>,
+        limit: u64,
+    ) -> Result<DelayedReceiptsView, crate::state_viewer::errors::ViewDelayedReceiptsError>;
+
+    fn view_storage_breakdown(
+        &self,
+        shard_uid: &ShardUId,
+        state_root: MerkleHash,
+        account_id: &AccountId,
+        data_prefix_len: Option<u32>,
+        storage_usage_config: &StorageUsageConfig,
+    ) -> Result<StorageBreakdownView, crate::state_viewer::errors::ViewStateError>;
 }
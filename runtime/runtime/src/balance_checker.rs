@@ -2,20 +2,98 @@ use crate::safe_add_balance_apply;
 
 use crate::config::{
     safe_add_balance, safe_add_gas, safe_gas_to_balance, total_deposit, total_prepaid_exec_fees,
-    total_prepaid_gas,
+    total_prepaid_gas, tx_cost,
 };
 use crate::{ApplyStats, DelayedReceiptIndices, ValidatorAccountsUpdate};
 use near_primitives::errors::{
     BalanceMismatchError, IntegerOverflowError, RuntimeError, StorageError,
 };
+use near_primitives::hash::CryptoHash;
 use near_primitives::receipt::{Receipt, ReceiptEnum};
 use near_primitives::runtime::fees::RuntimeFeesConfig;
+use near_primitives::serialize::{option_u128_dec_format, u128_dec_format};
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::trie_key::TrieKey;
 use near_primitives::types::{AccountId, Balance};
 use near_primitives::version::ProtocolVersion;
 use near_store::{get, get_account, get_postponed_receipt, TrieUpdate};
-use std::collections::HashSet;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+/// Where the balance of a receipt is accounted for by the balance checker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub(crate) enum ReceiptBalanceKind {
+    Incoming,
+    ProcessedDelayed,
+    InitialPostponed,
+    Outgoing,
+    NewDelayed,
+    FinalPostponed,
+}
+
+/// The balance of a single receipt taken into account by the balance checker.
+#[derive(Debug, Serialize)]
+pub(crate) struct ReceiptBalanceReport {
+    pub receipt_id: CryptoHash,
+    pub predecessor_id: AccountId,
+    pub receiver_id: AccountId,
+    pub kind: ReceiptBalanceKind,
+    /// Refunds are sent by the system account and only carry a deposit.
+    pub is_refund: bool,
+    #[serde(with = "u128_dec_format")]
+    pub balance: Balance,
+}
+
+/// The balances of an account which took part in the chunk. Received, delayed and postponed
+/// receipts are attributed to their receiver, outgoing receipts to their predecessor and
+/// transactions to their signer.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct AccountBalanceReport {
+    // Inputs
+    #[serde(with = "u128_dec_format")]
+    pub initial_balance: Balance,
+    #[serde(with = "u128_dec_format")]
+    pub validator_reward: Balance,
+    #[serde(with = "u128_dec_format")]
+    pub received_receipts_balance: Balance,
+    #[serde(with = "u128_dec_format")]
+    pub initial_postponed_receipts_balance: Balance,
+    // Outputs
+    #[serde(with = "u128_dec_format")]
+    pub final_balance: Balance,
+    #[serde(with = "u128_dec_format")]
+    pub sent_receipts_balance: Balance,
+    #[serde(with = "u128_dec_format")]
+    pub new_delayed_receipts_balance: Balance,
+    #[serde(with = "u128_dec_format")]
+    pub final_postponed_receipts_balance: Balance,
+    /// The cost of the signed transactions, including the amount burnt to convert them into
+    /// receipts.
+    #[serde(with = "u128_dec_format")]
+    pub transactions_cost: Balance,
+    /// The amount burnt by the executed receipts and slashed from the stake. Only known when
+    /// the runtime collects the balance mismatch breakdown.
+    #[serde(with = "option_u128_dec_format", skip_serializing_if = "Option::is_none")]
+    pub burnt_amount: Option<Balance>,
+}
+
+/// A breakdown of the balances which don't match, emitted by the balance checker on failure
+/// to find the accounts and receipts whose accounting is off.
+#[derive(Debug, Serialize)]
+pub(crate) struct BalanceMismatchReport {
+    pub totals: BalanceMismatchError,
+    #[serde(with = "u128_dec_format")]
+    pub gas_deficit_amount: Balance,
+    pub accounts: BTreeMap<AccountId, AccountBalanceReport>,
+    pub receipts: Vec<ReceiptBalanceReport>,
+}
+
+impl std::fmt::Display for BalanceMismatchReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let report = serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?;
+        write!(f, "Balance mismatch report: {}", report)
+    }
+}
 
 pub(crate) fn check_balance(
     transaction_costs: &RuntimeFeesConfig,
@@ -26,8 +104,43 @@ pub(crate) fn check_balance(
     transactions: &[SignedTransaction],
     outgoing_receipts: &[Receipt],
     stats: &ApplyStats,
+    gas_price: Balance,
     current_protocol_version: ProtocolVersion,
 ) -> Result<(), RuntimeError> {
+    match check_balance_with_report(
+        transaction_costs,
+        initial_state,
+        final_state,
+        validator_accounts_update,
+        incoming_receipts,
+        transactions,
+        outgoing_receipts,
+        stats,
+        gas_price,
+        current_protocol_version,
+    )? {
+        None => Ok(()),
+        Some(report) => {
+            tracing::error!(target: "runtime", "{}", report);
+            Err(report.totals.into())
+        }
+    }
+}
+
+/// Checks that the balance of the chunk adds up. On a mismatch, the balances are broken down by
+/// account and by receipt into the returned report.
+fn check_balance_with_report(
+    transaction_costs: &RuntimeFeesConfig,
+    initial_state: &TrieUpdate,
+    final_state: &TrieUpdate,
+    validator_accounts_update: &Option<ValidatorAccountsUpdate>,
+    incoming_receipts: &[Receipt],
+    transactions: &[SignedTransaction],
+    outgoing_receipts: &[Receipt],
+    stats: &ApplyStats,
+    gas_price: Balance,
+    current_protocol_version: ProtocolVersion,
+) -> Result<Option<BalanceMismatchReport>, RuntimeError> {
     // Delayed receipts
     let initial_delayed_receipt_indices: DelayedReceiptIndices =
         get(initial_state, &TrieKey::DelayedReceiptIndices)?.unwrap_or_default();
@@ -81,15 +194,17 @@ pub(crate) fn check_balance(
         } else {
             0
         };
+    let account_balance =
+        |state: &TrieUpdate, account_id: &AccountId| -> Result<Balance, RuntimeError> {
+            get_account(state, account_id)?.map_or(Ok(0), |a| {
+                safe_add_balance(a.amount(), a.locked())
+                    .map_err(|_| RuntimeError::UnexpectedIntegerOverflow)
+            })
+        };
     let total_accounts_balance = |state| -> Result<Balance, RuntimeError> {
         Ok(all_accounts_ids
             .iter()
-            .map(|account_id| {
-                get_account(state, account_id)?.map_or(Ok(0), |a| {
-                    safe_add_balance(a.amount(), a.locked())
-                        .map_err(|_| RuntimeError::UnexpectedIntegerOverflow)
-                })
-            })
+            .map(|account_id| account_balance(state, account_id))
             .collect::<Result<Vec<Balance>, RuntimeError>>()?
             .into_iter()
             .try_fold(0u128, safe_add_balance)?)
@@ -164,19 +279,19 @@ pub(crate) fn check_balance(
         .filter_map(|x| x)
         .collect::<HashSet<_>>();
 
-    let total_postponed_receipts_cost = |state| -> Result<Balance, RuntimeError> {
+    let postponed_receipts = |state| -> Result<Vec<Receipt>, StorageError> {
         Ok(all_potential_postponed_receipt_ids
             .iter()
-            .map(|(account_id, receipt_id)| {
-                Ok(get_postponed_receipt(state, account_id, *receipt_id)?
-                    .map_or(Ok(0), |r| receipt_cost(&r))?)
-            })
-            .collect::<Result<Vec<Balance>, RuntimeError>>()?
+            .map(|(account_id, receipt_id)| get_postponed_receipt(state, account_id, *receipt_id))
+            .collect::<Result<Vec<Option<Receipt>>, StorageError>>()?
             .into_iter()
-            .try_fold(0u128, safe_add_balance)?)
+            .flatten()
+            .collect())
     };
-    let initial_postponed_receipts_balance = total_postponed_receipts_cost(initial_state)?;
-    let final_postponed_receipts_balance = total_postponed_receipts_cost(final_state)?;
+    let initial_postponed_receipts = postponed_receipts(initial_state)?;
+    let final_postponed_receipts = postponed_receipts(final_state)?;
+    let initial_postponed_receipts_balance = receipts_cost(&initial_postponed_receipts)?;
+    let final_postponed_receipts_balance = receipts_cost(&final_postponed_receipts)?;
     // Sum it up
 
     let initial_balance = safe_add_balance_apply!(
@@ -195,27 +310,107 @@ pub(crate) fn check_balance(
         stats.slashed_burnt_amount,
         stats.other_burnt_amount
     );
-    if initial_balance != final_balance {
-        Err(BalanceMismatchError {
-            // Inputs
-            incoming_validator_rewards,
-            initial_accounts_balance,
-            incoming_receipts_balance,
-            processed_delayed_receipts_balance,
-            initial_postponed_receipts_balance,
-            // Outputs
-            final_accounts_balance,
-            outgoing_receipts_balance,
-            new_delayed_receipts_balance,
-            final_postponed_receipts_balance,
-            tx_burnt_amount: stats.tx_burnt_amount,
-            slashed_burnt_amount: stats.slashed_burnt_amount,
-            other_burnt_amount: stats.other_burnt_amount,
+    if initial_balance == final_balance {
+        return Ok(None);
+    }
+
+    let totals = BalanceMismatchError {
+        // Inputs
+        incoming_validator_rewards,
+        initial_accounts_balance,
+        incoming_receipts_balance,
+        processed_delayed_receipts_balance,
+        initial_postponed_receipts_balance,
+        // Outputs
+        final_accounts_balance,
+        outgoing_receipts_balance,
+        new_delayed_receipts_balance,
+        final_postponed_receipts_balance,
+        tx_burnt_amount: stats.tx_burnt_amount,
+        slashed_burnt_amount: stats.slashed_burnt_amount,
+        other_burnt_amount: stats.other_burnt_amount,
+    };
+    let mut accounts = BTreeMap::new();
+    for account_id in all_accounts_ids.iter() {
+        let validator_reward = validator_accounts_update
+            .as_ref()
+            .and_then(|update| update.validator_rewards.get(account_id).copied())
+            .unwrap_or_default();
+        accounts.insert(
+            account_id.clone(),
+            AccountBalanceReport {
+                initial_balance: account_balance(initial_state, account_id)?,
+                validator_reward,
+                final_balance: account_balance(final_state, account_id)?,
+                burnt_amount: stats.burnt_amount_by_account.as_ref().map(|burnt_amounts| {
+                    burnt_amounts.get(account_id).copied().unwrap_or_default()
+                }),
+                ..Default::default()
+            },
+        );
+    }
+    for tx in transactions {
+        let transaction = &tx.transaction;
+        let cost = tx_cost(
+            transaction_costs,
+            transaction,
+            gas_price,
+            transaction.signer_id == transaction.receiver_id,
+            current_protocol_version,
+        )?
+        .total_cost;
+        if let Some(account) = accounts.get_mut(&transaction.signer_id) {
+            account.transactions_cost = account.transactions_cost.saturating_add(cost);
         }
-        .into())
-    } else {
-        Ok(())
     }
+    let mut receipts = vec![];
+    for (kind, kind_receipts) in [
+        (ReceiptBalanceKind::Incoming, incoming_receipts),
+        (ReceiptBalanceKind::ProcessedDelayed, &processed_delayed_receipts[..]),
+        (ReceiptBalanceKind::InitialPostponed, &initial_postponed_receipts[..]),
+        (ReceiptBalanceKind::Outgoing, outgoing_receipts),
+        (ReceiptBalanceKind::NewDelayed, &new_delayed_receipts[..]),
+        (ReceiptBalanceKind::FinalPostponed, &final_postponed_receipts[..]),
+    ] {
+        for receipt in kind_receipts {
+            let balance = receipt_cost(receipt)?;
+            let account_id = if kind == ReceiptBalanceKind::Outgoing {
+                &receipt.predecessor_id
+            } else {
+                &receipt.receiver_id
+            };
+            if let Some(account) = accounts.get_mut(account_id) {
+                let account_total = match kind {
+                    ReceiptBalanceKind::Incoming | ReceiptBalanceKind::ProcessedDelayed => {
+                        &mut account.received_receipts_balance
+                    }
+                    ReceiptBalanceKind::InitialPostponed => {
+                        &mut account.initial_postponed_receipts_balance
+                    }
+                    ReceiptBalanceKind::Outgoing => &mut account.sent_receipts_balance,
+                    ReceiptBalanceKind::NewDelayed => &mut account.new_delayed_receipts_balance,
+                    ReceiptBalanceKind::FinalPostponed => {
+                        &mut account.final_postponed_receipts_balance
+                    }
+                };
+                *account_total = account_total.saturating_add(balance);
+            }
+            receipts.push(ReceiptBalanceReport {
+                receipt_id: receipt.receipt_id,
+                predecessor_id: receipt.predecessor_id.clone(),
+                receiver_id: receipt.receiver_id.clone(),
+                kind,
+                is_refund: AccountId::is_system(&receipt.predecessor_id),
+                balance,
+            });
+        }
+    }
+    Ok(Some(BalanceMismatchReport {
+        totals,
+        gas_deficit_amount: stats.gas_deficit_amount,
+        accounts,
+        receipts,
+    }))
 }
 
 #[cfg(test)]
@@ -259,6 +454,7 @@ mod tests {
             &[],
             &[],
             &ApplyStats::default(),
+            0,
             PROTOCOL_VERSION,
         )
         .unwrap();
@@ -280,12 +476,54 @@ mod tests {
             &[],
             &[],
             &ApplyStats::default(),
+            0,
             PROTOCOL_VERSION,
         )
         .unwrap_err();
         assert_matches!(err, RuntimeError::BalanceMismatchError(_));
     }
 
+    #[test]
+    fn test_check_balance_mismatch_report() {
+        let tries = create_tries();
+        let root = MerkleHash::default();
+        let account_id = alice_account();
+
+        let mut initial_state = tries.new_trie_update(ShardUId::single_shard(), root);
+        let account = account_new(TESTING_INIT_BALANCE, hash(&[]));
+        set_account(&mut initial_state, account_id.clone(), &account);
+        initial_state.commit(StateChangeCause::NotWritableToDisk);
+        let mut final_state = tries.new_trie_update(ShardUId::single_shard(), root);
+        set_account(&mut final_state, account_id.clone(), &account);
+        final_state.commit(StateChangeCause::NotWritableToDisk);
+
+        let refund = Receipt::new_balance_refund(&account_id, 1000);
+        let report = check_balance_with_report(
+            &RuntimeFeesConfig::test(),
+            &initial_state,
+            &final_state,
+            &None,
+            &[refund.clone()],
+            &[],
+            &[],
+            &ApplyStats::default(),
+            0,
+            PROTOCOL_VERSION,
+        )
+        .unwrap()
+        .expect("balances should mismatch");
+        assert_eq!(report.totals.incoming_receipts_balance, 1000);
+        let account_report = &report.accounts[&account_id];
+        assert_eq!(account_report.initial_balance, TESTING_INIT_BALANCE);
+        assert_eq!(account_report.final_balance, TESTING_INIT_BALANCE);
+        assert_eq!(account_report.received_receipts_balance, 1000);
+        assert_eq!(report.receipts.len(), 1);
+        assert_eq!(report.receipts[0].receipt_id, refund.receipt_id);
+        assert_eq!(report.receipts[0].kind, ReceiptBalanceKind::Incoming);
+        assert!(report.receipts[0].is_refund);
+        assert_eq!(report.receipts[0].balance, 1000);
+    }
+
     #[test]
     fn test_check_balance_refund() {
         let tries = create_tries();
@@ -315,6 +553,7 @@ mod tests {
             &[],
             &[],
             &ApplyStats::default(),
+            0,
             PROTOCOL_VERSION,
         )
         .unwrap();
@@ -388,12 +627,105 @@ mod tests {
                 gas_deficit_amount: 0,
                 other_burnt_amount: 0,
                 slashed_burnt_amount: 0,
+                ..Default::default()
             },
+            gas_price,
             PROTOCOL_VERSION,
         )
         .unwrap();
     }
 
+    #[test]
+    fn test_check_balance_mismatch_report_tx_cost() {
+        let tries = create_tries();
+        let root = MerkleHash::default();
+        let alice_id = alice_account();
+        let bob_id = bob_account();
+        let gas_price = 100;
+        let deposit = 1000;
+        let cfg = RuntimeFeesConfig::test();
+
+        let mut initial_state = tries.new_trie_update(ShardUId::single_shard(), root);
+        set_account(
+            &mut initial_state,
+            alice_id.clone(),
+            &account_new(TESTING_INIT_BALANCE, hash(&[])),
+        );
+        set_account(
+            &mut initial_state,
+            bob_id.clone(),
+            &account_new(TESTING_INIT_BALANCE, hash(&[])),
+        );
+        initial_state.commit(StateChangeCause::NotWritableToDisk);
+
+        let signer =
+            InMemorySigner::from_seed(alice_id.clone(), KeyType::ED25519, alice_id.as_ref());
+        let tx = SignedTransaction::send_money(
+            1,
+            alice_id.clone(),
+            bob_id.clone(),
+            &signer,
+            deposit,
+            CryptoHash::default(),
+        );
+        let cost = tx_cost(&cfg, &tx.transaction, gas_price, false, PROTOCOL_VERSION).unwrap();
+        let receipt = Receipt {
+            predecessor_id: alice_id.clone(),
+            receiver_id: bob_id.clone(),
+            receipt_id: Default::default(),
+            receipt: ReceiptEnum::Action(ActionReceipt {
+                signer_id: alice_id.clone(),
+                signer_public_key: tx.transaction.public_key.clone(),
+                gas_price: cost.receipt_gas_price,
+                output_data_receivers: vec![],
+                input_data_ids: vec![],
+                actions: vec![Action::Transfer(TransferAction { deposit })],
+            }),
+        };
+
+        // Alice is charged one more than the cost of the transaction.
+        let mut final_state = tries.new_trie_update(ShardUId::single_shard(), root);
+        set_account(
+            &mut final_state,
+            alice_id.clone(),
+            &account_new(TESTING_INIT_BALANCE - cost.total_cost - 1, hash(&[])),
+        );
+        set_account(
+            &mut final_state,
+            bob_id.clone(),
+            &account_new(TESTING_INIT_BALANCE, hash(&[])),
+        );
+        final_state.commit(StateChangeCause::NotWritableToDisk);
+
+        let stats = ApplyStats {
+            tx_burnt_amount: cost.burnt_amount,
+            burnt_amount_by_account: Some(BTreeMap::new()),
+            ..Default::default()
+        };
+        let report = check_balance_with_report(
+            &cfg,
+            &initial_state,
+            &final_state,
+            &None,
+            &[],
+            &[tx],
+            &[receipt],
+            &stats,
+            gas_price,
+            PROTOCOL_VERSION,
+        )
+        .unwrap()
+        .expect("balances should mismatch");
+        let alice = &report.accounts[&alice_id];
+        assert_eq!(alice.transactions_cost, cost.total_cost);
+        assert_eq!(alice.initial_balance - alice.final_balance, alice.transactions_cost + 1);
+        assert_eq!(alice.sent_receipts_balance, cost.total_cost - cost.burnt_amount);
+        assert_eq!(alice.burnt_amount, Some(0));
+        assert!(!report.accounts.contains_key(&bob_id));
+        assert_eq!(report.receipts.len(), 1);
+        assert_eq!(report.receipts[0].kind, ReceiptBalanceKind::Outgoing);
+    }
+
     #[test]
     fn test_total_balance_overflow_returns_unexpected_overflow() {
         let tries = create_tries();
@@ -442,6 +774,7 @@ mod tests {
                 &[tx],
                 &[],
                 &ApplyStats::default(),
+                gas_price,
                 PROTOCOL_VERSION,
             ),
             Err(RuntimeError::UnexpectedIntegerOverflow)
@@ -4,12 +4,15 @@ use num_bigint::BigUint;
 use num_traits::cast::ToPrimitive;
 use num_traits::pow::Pow;
 
-use near_primitives::account::AccessKeyPermission;
 use near_primitives::errors::IntegerOverflowError;
 // Just re-exporting RuntimeConfig for backwards compatibility.
 pub use near_primitives::num_rational::Rational;
 pub use near_primitives::runtime::config::RuntimeConfig;
 use near_primitives::runtime::fees::{transfer_exec_fee, transfer_send_fee, RuntimeFeesConfig};
+#[cfg(feature = "protocol_feature_delegate_action")]
+use near_primitives::transaction::DelegateAction;
+#[cfg(feature = "protocol_feature_global_contracts")]
+use near_primitives::transaction::DeployGlobalContractAction;
 use near_primitives::transaction::{
     Action, AddKeyAction, DeployContractAction, FunctionCallAction, Transaction,
 };
@@ -101,27 +104,50 @@ pub fn total_send_fees(
             Stake(_) => cfg.stake_cost.send_fee(sender_is_receiver),
             #[cfg(feature = "protocol_feature_chunk_only_producers")]
             StakeChunkOnly(_) => cfg.stake_cost.send_fee(sender_is_receiver),
-            AddKey(AddKeyAction { access_key, .. }) => match &access_key.permission {
-                AccessKeyPermission::FunctionCall(call_perm) => {
-                    let num_bytes = call_perm
-                        .method_names
-                        .iter()
-                        // Account for null-terminating characters.
-                        .map(|name| name.as_bytes().len() as u64 + 1)
-                        .sum::<u64>();
-                    cfg.add_key_cost.function_call_cost.send_fee(sender_is_receiver)
-                        + num_bytes
-                            * cfg
-                                .add_key_cost
-                                .function_call_cost_per_byte
-                                .send_fee(sender_is_receiver)
+            AddKey(AddKeyAction { access_key, .. }) => {
+                match access_key.permission.function_call_permission() {
+                    Some(call_perm) => {
+                        let num_bytes = call_perm
+                            .method_names
+                            .iter()
+                            // Account for null-terminating characters.
+                            .map(|name| name.as_bytes().len() as u64 + 1)
+                            .sum::<u64>();
+                        cfg.add_key_cost.function_call_cost.send_fee(sender_is_receiver)
+                            + num_bytes
+                                * cfg
+                                    .add_key_cost
+                                    .function_call_cost_per_byte
+                                    .send_fee(sender_is_receiver)
+                    }
+                    None => cfg.add_key_cost.full_access_cost.send_fee(sender_is_receiver),
                 }
-                AccessKeyPermission::FullAccess => {
-                    cfg.add_key_cost.full_access_cost.send_fee(sender_is_receiver)
-                }
-            },
+            }
             DeleteKey(_) => cfg.delete_key_cost.send_fee(sender_is_receiver),
             DeleteAccount(_) => cfg.delete_account_cost.send_fee(sender_is_receiver),
+            #[cfg(feature = "protocol_feature_delegate_action")]
+            Delegate(signed_delegate_action) => {
+                // The inner actions travel to the sender of the delegate action together with it.
+                let delegate_action = &signed_delegate_action.delegate_action;
+                safe_add_gas(
+                    cfg.delegate_cost.send_fee(sender_is_receiver),
+                    total_send_fees(
+                        config,
+                        sender_is_receiver,
+                        &inner_actions(delegate_action),
+                        &delegate_action.receiver_id,
+                        current_protocol_version,
+                    )?,
+                )?
+            }
+            #[cfg(feature = "protocol_feature_global_contracts")]
+            DeployGlobalContract(DeployGlobalContractAction { code }) => {
+                let num_bytes = code.len() as u64;
+                cfg.deploy_contract_cost.send_fee(sender_is_receiver)
+                    + cfg.deploy_contract_cost_per_byte.send_fee(sender_is_receiver) * num_bytes
+            }
+            #[cfg(feature = "protocol_feature_global_contracts")]
+            UseGlobalContract(_) => cfg.deploy_contract_cost.send_fee(sender_is_receiver),
         };
         result = safe_add_gas(result, delta)?;
     }
@@ -159,21 +185,34 @@ pub fn exec_fee(
         Stake(_) => cfg.stake_cost.exec_fee(),
         #[cfg(feature = "protocol_feature_chunk_only_producers")]
         StakeChunkOnly(_) => cfg.stake_cost.exec_fee(),
-        AddKey(AddKeyAction { access_key, .. }) => match &access_key.permission {
-            AccessKeyPermission::FunctionCall(call_perm) => {
-                let num_bytes = call_perm
-                    .method_names
-                    .iter()
-                    // Account for null-terminating characters.
-                    .map(|name| name.as_bytes().len() as u64 + 1)
-                    .sum::<u64>();
-                cfg.add_key_cost.function_call_cost.exec_fee()
-                    + num_bytes * cfg.add_key_cost.function_call_cost_per_byte.exec_fee()
+        AddKey(AddKeyAction { access_key, .. }) => {
+            match access_key.permission.function_call_permission() {
+                Some(call_perm) => {
+                    let num_bytes = call_perm
+                        .method_names
+                        .iter()
+                        // Account for null-terminating characters.
+                        .map(|name| name.as_bytes().len() as u64 + 1)
+                        .sum::<u64>();
+                    cfg.add_key_cost.function_call_cost.exec_fee()
+                        + num_bytes * cfg.add_key_cost.function_call_cost_per_byte.exec_fee()
+                }
+                None => cfg.add_key_cost.full_access_cost.exec_fee(),
             }
-            AccessKeyPermission::FullAccess => cfg.add_key_cost.full_access_cost.exec_fee(),
-        },
+        }
         DeleteKey(_) => cfg.delete_key_cost.exec_fee(),
         DeleteAccount(_) => cfg.delete_account_cost.exec_fee(),
+        #[cfg(feature = "protocol_feature_delegate_action")]
+        Delegate(_) => cfg.delegate_cost.exec_fee(),
+        #[cfg(feature = "protocol_feature_global_contracts")]
+        DeployGlobalContract(DeployGlobalContractAction { code }) => {
+            let num_bytes = code.len() as u64;
+            cfg.deploy_contract_cost.exec_fee()
+                + cfg.deploy_contract_cost_per_byte.exec_fee() * num_bytes
+        }
+        // The code is already stored and compiled, so only the base deployment cost is charged.
+        #[cfg(feature = "protocol_feature_global_contracts")]
+        UseGlobalContract(_) => cfg.deploy_contract_cost.exec_fee(),
     }
 }
 
@@ -245,9 +284,65 @@ pub fn total_prepaid_exec_fees(
     for action in actions {
         let delta = exec_fee(config, action, receiver_id, current_protocol_version);
         result = safe_add_gas(result, delta)?;
+        // The receipt created by a delegate action is paid for upfront, including its send fees.
+        #[cfg(feature = "protocol_feature_delegate_action")]
+        if let Action::Delegate(signed_delegate_action) = action {
+            let delegate_action = &signed_delegate_action.delegate_action;
+            let delta = safe_add_gas(
+                delegate_receipt_send_fees(config, delegate_action, current_protocol_version)?,
+                delegate_receipt_exec_fees(config, delegate_action, current_protocol_version)?,
+            )?;
+            result = safe_add_gas(result, delta)?;
+        }
     }
     Ok(result)
 }
+
+/// Total sum of gas that needs to be burnt to send the receipt created by the given delegate
+/// action. It is purchased together with the transaction carrying the delegate action.
+#[cfg(feature = "protocol_feature_delegate_action")]
+pub fn delegate_receipt_send_fees(
+    config: &RuntimeFeesConfig,
+    delegate_action: &DelegateAction,
+    current_protocol_version: ProtocolVersion,
+) -> Result<Gas, IntegerOverflowError> {
+    let sender_is_receiver = delegate_action.sender_id == delegate_action.receiver_id;
+    safe_add_gas(
+        config.action_receipt_creation_config.send_fee(sender_is_receiver),
+        total_send_fees(
+            config,
+            sender_is_receiver,
+            &inner_actions(delegate_action),
+            &delegate_action.receiver_id,
+            current_protocol_version,
+        )?,
+    )
+}
+
+/// Total sum of gas that would need to be burnt before we start executing the receipt created
+/// by the given delegate action, excluding the prepaid gas of its function calls.
+#[cfg(feature = "protocol_feature_delegate_action")]
+pub fn delegate_receipt_exec_fees(
+    config: &RuntimeFeesConfig,
+    delegate_action: &DelegateAction,
+    current_protocol_version: ProtocolVersion,
+) -> Result<Gas, IntegerOverflowError> {
+    safe_add_gas(
+        config.action_receipt_creation_config.exec_fee(),
+        total_prepaid_exec_fees(
+            config,
+            &inner_actions(delegate_action),
+            &delegate_action.receiver_id,
+            current_protocol_version,
+        )?,
+    )
+}
+
+#[cfg(feature = "protocol_feature_delegate_action")]
+pub(crate) fn inner_actions(delegate_action: &DelegateAction) -> Vec<Action> {
+    delegate_action.actions.iter().map(|action| action.action().clone()).collect()
+}
+
 /// Get the total sum of deposits for given actions.
 pub fn total_deposit(actions: &[Action]) -> Result<Balance, IntegerOverflowError> {
     let mut total_balance: Balance = 0;
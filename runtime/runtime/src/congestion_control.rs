@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use near_primitives::congestion_control::{
+    BlockCongestionInfo, MAX_OUTGOING_GAS_TO_CONGESTED_SHARD,
+};
+use near_primitives::errors::{IntegerOverflowError, RuntimeError};
+use near_primitives::receipt::{DelayedReceiptIndices, Receipt, ReceiptEnum};
+use near_primitives::runtime::fees::RuntimeFeesConfig;
+use near_primitives::shard_layout::account_id_to_shard_id;
+use near_primitives::trie_key::TrieKey;
+use near_primitives::types::{Gas, ShardId};
+use near_primitives::version::ProtocolVersion;
+use near_store::{get, StorageError, TrieUpdate};
+
+use crate::config::{safe_add_gas, total_prepaid_exec_fees, total_prepaid_gas};
+
+/// Gas a receipt accounts for in congestion control: the gas prepaid for its actions plus their
+/// execution fees. Data receipts don't account for any gas.
+pub(crate) fn receipt_congestion_gas(
+    fees_config: &RuntimeFeesConfig,
+    receipt: &Receipt,
+    current_protocol_version: ProtocolVersion,
+) -> Result<Gas, IntegerOverflowError> {
+    match &receipt.receipt {
+        ReceiptEnum::Action(action_receipt) => safe_add_gas(
+            total_prepaid_gas(&action_receipt.actions)?,
+            total_prepaid_exec_fees(
+                fees_config,
+                &action_receipt.actions,
+                &receipt.receiver_id,
+                current_protocol_version,
+            )?,
+        ),
+        ReceiptEnum::Data(_) => Ok(0),
+    }
+}
+
+/// Computes the gas of the delayed receipts queue by reading every receipt in it. Used when the
+/// state doesn't track the gas yet, i.e. right after the protocol upgrade or after resharding.
+pub(crate) fn compute_delayed_receipts_gas(
+    state_update: &TrieUpdate,
+    fees_config: &RuntimeFeesConfig,
+    delayed_receipts_indices: &DelayedReceiptIndices,
+    current_protocol_version: ProtocolVersion,
+) -> Result<Gas, RuntimeError> {
+    let mut delayed_receipts_gas: Gas = 0;
+    for index in delayed_receipts_indices.first_index..delayed_receipts_indices.next_available_index
+    {
+        let receipt: Receipt =
+            get(state_update, &TrieKey::DelayedReceipt { index })?.ok_or_else(|| {
+                StorageError::StorageInconsistentState(format!(
+                    "Delayed receipt #{} should be in the state",
+                    index
+                ))
+            })?;
+        delayed_receipts_gas = safe_add_gas(
+            delayed_receipts_gas,
+            receipt_congestion_gas(fees_config, &receipt, current_protocol_version)?,
+        )?;
+    }
+    Ok(delayed_receipts_gas)
+}
+
+/// Accounts the receipts a chunk sends to congested shards. Once the chunk sent
+/// `MAX_OUTGOING_GAS_TO_CONGESTED_SHARD` to one of them, the remaining receipts to that shard are
+/// stalled in the delayed receipts queue of the sending shard and forwarded by later chunks, while
+/// the receipts to other shards are still sent.
+pub(crate) struct OutgoingReceiptsLimit<'a> {
+    congestion_info: &'a BlockCongestionInfo,
+    shard_id: ShardId,
+    enabled: bool,
+    sent_gas: HashMap<ShardId, Gas>,
+}
+
+impl<'a> OutgoingReceiptsLimit<'a> {
+    pub(crate) fn new(
+        congestion_info: &'a BlockCongestionInfo,
+        shard_id: ShardId,
+        enabled: bool,
+    ) -> Self {
+        Self { congestion_info, shard_id, enabled, sent_gas: HashMap::new() }
+    }
+
+    /// Whether the receipt is addressed to another shard. Such receipts are only found in the
+    /// delayed receipts queue when they were stalled, and are forwarded instead of executed.
+    pub(crate) fn is_stalled(&self, receipt: &Receipt) -> bool {
+        self.enabled
+            && account_id_to_shard_id(&receipt.receiver_id, self.congestion_info.shard_layout())
+                != self.shard_id
+    }
+
+    /// Records the receipt as sent, unless the chunk already sent the limit to the congested
+    /// shard of its receiver. Returns whether the receipt can be sent.
+    pub(crate) fn try_send(
+        &mut self,
+        fees_config: &RuntimeFeesConfig,
+        receipt: &Receipt,
+        current_protocol_version: ProtocolVersion,
+    ) -> Result<bool, IntegerOverflowError> {
+        if !self.enabled {
+            return Ok(true);
+        }
+        let receiver_shard_id = match self.congestion_info.congested_shard(&receipt.receiver_id) {
+            Some(shard_id) if shard_id != self.shard_id => shard_id,
+            _ => return Ok(true),
+        };
+        let sent_gas = self.sent_gas.entry(receiver_shard_id).or_default();
+        if *sent_gas >= MAX_OUTGOING_GAS_TO_CONGESTED_SHARD {
+            return Ok(false);
+        }
+        let gas = receipt_congestion_gas(fees_config, receipt, current_protocol_version)?;
+        *sent_gas = safe_add_gas(*sent_gas, gas)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_crypto::{KeyType, PublicKey};
+    use near_primitives::congestion_control::CONGESTED_DELAYED_RECEIPTS_GAS;
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::receipt::ActionReceipt;
+    use near_primitives::shard_layout::{account_id_to_shard_id, ShardLayout};
+    use near_primitives::transaction::{Action, FunctionCallAction};
+    use near_primitives::types::AccountId;
+    use near_primitives::version::PROTOCOL_VERSION;
+
+    use super::*;
+
+    fn function_call_receipt(receiver_id: AccountId, gas: Gas) -> Receipt {
+        Receipt {
+            predecessor_id: "alice.near".parse().unwrap(),
+            receiver_id,
+            receipt_id: CryptoHash::default(),
+            receipt: ReceiptEnum::Action(ActionReceipt {
+                signer_id: "alice.near".parse().unwrap(),
+                signer_public_key: PublicKey::empty(KeyType::ED25519),
+                gas_price: 0,
+                output_data_receivers: vec![],
+                input_data_ids: vec![],
+                actions: vec![Action::FunctionCall(FunctionCallAction {
+                    method_name: "hot".to_string(),
+                    args: vec![],
+                    gas,
+                    deposit: 0,
+                })],
+            }),
+        }
+    }
+
+    #[test]
+    fn test_outgoing_receipts_limit() {
+        let fees_config = RuntimeFeesConfig::test();
+        let shard_layout = ShardLayout::v0(2, 0);
+        let congested_account: AccountId = "bob.near".parse().unwrap();
+        let congested_shard_id = account_id_to_shard_id(&congested_account, &shard_layout);
+        let congestion_info = BlockCongestionInfo::new(
+            shard_layout,
+            [(congested_shard_id, CONGESTED_DELAYED_RECEIPTS_GAS + 1)].into_iter().collect(),
+        );
+        let half_limit = function_call_receipt(
+            congested_account.clone(),
+            MAX_OUTGOING_GAS_TO_CONGESTED_SHARD / 2,
+        );
+
+        let other_account: AccountId = "alice.near".parse().unwrap();
+        let other = function_call_receipt(other_account, MAX_OUTGOING_GAS_TO_CONGESTED_SHARD);
+
+        // Receipts sent within the congested shard are not limited.
+        let mut limit = OutgoingReceiptsLimit::new(&congestion_info, congested_shard_id, true);
+        for _ in 0..3 {
+            assert!(limit.try_send(&fees_config, &half_limit, PROTOCOL_VERSION).unwrap());
+        }
+
+        // Nothing is limited while congestion control is disabled.
+        let mut limit = OutgoingReceiptsLimit::new(&congestion_info, 1 - congested_shard_id, false);
+        for _ in 0..3 {
+            assert!(limit.try_send(&fees_config, &half_limit, PROTOCOL_VERSION).unwrap());
+        }
+        assert!(!limit.is_stalled(&half_limit));
+
+        // Only the receipts to the congested shard are stalled once the limit is reached.
+        let mut limit = OutgoingReceiptsLimit::new(&congestion_info, 1 - congested_shard_id, true);
+        assert!(limit.try_send(&fees_config, &half_limit, PROTOCOL_VERSION).unwrap());
+        assert!(limit.try_send(&fees_config, &half_limit, PROTOCOL_VERSION).unwrap());
+        assert!(!limit.try_send(&fees_config, &half_limit, PROTOCOL_VERSION).unwrap());
+        assert!(limit.try_send(&fees_config, &other, PROTOCOL_VERSION).unwrap());
+        assert!(limit.is_stalled(&half_limit));
+    }
+}
@@ -1,5 +1,5 @@
 use std::cmp::max;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::rc::Rc;
 use std::sync::Arc;
 
@@ -16,6 +16,7 @@ pub use near_primitives::runtime::apply_state::ApplyState;
 use near_primitives::runtime::fees::RuntimeFeesConfig;
 use near_primitives::runtime::get_insufficient_storage_stake;
 use near_primitives::runtime::migration_data::{MigrationData, MigrationFlags};
+use near_primitives::trace::HostFunctionCall;
 use near_primitives::transaction::ExecutionMetadata;
 use near_primitives::version::{
     is_implicit_account_creation_enabled, ProtocolFeature, ProtocolVersion,
@@ -23,7 +24,7 @@ use near_primitives::version::{
 use near_primitives::{
     account::Account,
     checked_feature,
-    errors::{ActionError, ActionErrorKind, RuntimeError, TxExecutionError},
+    errors::{ActionError, ActionErrorKind, IntegerOverflowError, RuntimeError, TxExecutionError},
     hash::CryptoHash,
     receipt::{
         ActionReceipt, DataReceipt, DelayedReceiptIndices, Receipt, ReceiptEnum, ReceivedData,
@@ -59,6 +60,9 @@ use crate::config::{
     exec_fee, safe_add_balance, safe_add_gas, safe_gas_to_balance, total_deposit,
     total_prepaid_exec_fees, total_prepaid_gas, RuntimeConfig,
 };
+use crate::congestion_control::{
+    compute_delayed_receipts_gas, receipt_congestion_gas, OutgoingReceiptsLimit,
+};
 use crate::genesis::{GenesisStateApplier, StorageComputer};
 use crate::verifier::validate_receipt;
 pub use crate::verifier::{validate_transaction, verify_and_charge_transaction};
@@ -68,9 +72,11 @@ pub mod adapter;
 mod balance_checker;
 pub mod cache;
 pub mod config;
+mod congestion_control;
 pub mod ext;
 mod genesis;
 mod metrics;
+mod parallel;
 pub mod state_viewer;
 mod verifier;
 
@@ -111,6 +117,48 @@ pub struct ApplyStats {
     /// This is a negative amount. This amount was not charged from the account that issued
     /// the transaction. It's likely due to the delayed queue of the receipts.
     pub gas_deficit_amount: Balance,
+    /// The amounts burnt by the receipts executed by each account and slashed from its stake,
+    /// reported by the balance checker on a mismatch. Only collected by runtimes with the
+    /// balance mismatch breakdown enabled.
+    pub burnt_amount_by_account: Option<BTreeMap<AccountId, Balance>>,
+}
+
+impl ApplyStats {
+    fn add_burnt_amount(
+        &mut self,
+        account_id: &AccountId,
+        amount: Balance,
+    ) -> Result<(), IntegerOverflowError> {
+        if let Some(burnt_amount_by_account) = self.burnt_amount_by_account.as_mut() {
+            if amount > 0 {
+                let burnt_amount = burnt_amount_by_account.entry(account_id.clone()).or_default();
+                *burnt_amount = safe_add_balance(*burnt_amount, amount)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, other: ApplyStats) -> Result<(), IntegerOverflowError> {
+        for (account_id, amount) in other.burnt_amount_by_account.iter().flatten() {
+            self.add_burnt_amount(account_id, *amount)?;
+        }
+        self.tx_burnt_amount = safe_add_balance(self.tx_burnt_amount, other.tx_burnt_amount)?;
+        self.slashed_burnt_amount =
+            safe_add_balance(self.slashed_burnt_amount, other.slashed_burnt_amount)?;
+        self.other_burnt_amount =
+            safe_add_balance(self.other_burnt_amount, other.other_burnt_amount)?;
+        self.gas_deficit_amount =
+            safe_add_balance(self.gas_deficit_amount, other.gas_deficit_amount)?;
+        Ok(())
+    }
+}
+
+/// Everything a single receipt produced, except for state changes.
+struct ExecutedReceipt {
+    outcome: Option<ExecutionOutcomeWithId>,
+    outgoing_receipts: Vec<Receipt>,
+    validator_proposals: Vec<ValidatorStake>,
+    stats: ApplyStats,
 }
 
 pub struct ApplyResult {
@@ -123,6 +171,9 @@ pub struct ApplyResult {
     pub stats: ApplyStats,
     pub processed_delayed_receipts: Vec<Receipt>,
     pub proof: Option<PartialStorage>,
+    /// Gas of the delayed receipts queue after applying the chunk, if congestion control is
+    /// enabled.
+    pub delayed_receipts_gas: Option<Gas>,
 }
 
 #[derive(Debug)]
@@ -135,6 +186,10 @@ pub struct ActionResult {
     pub new_receipts: Vec<Receipt>,
     pub validator_proposals: Vec<ValidatorStake>,
     pub profile: ProfileData,
+    /// Host function calls made by contracts, only recorded if host function tracing is enabled.
+    pub host_function_calls: Vec<HostFunctionCall>,
+    /// Tokens burnt by the actions on top of the gas, e.g. to pay for global contract code.
+    pub other_burnt_amount: Balance,
 }
 
 impl ActionResult {
@@ -152,7 +207,10 @@ impl ActionResult {
             next_result.gas_burnt_for_function_call,
         )?;
         self.gas_used = safe_add_gas(self.gas_used, next_result.gas_used)?;
+        self.other_burnt_amount =
+            safe_add_balance(self.other_burnt_amount, next_result.other_burnt_amount)?;
         self.profile.merge(&next_result.profile);
+        self.host_function_calls.append(&mut next_result.host_function_calls);
         self.result = next_result.result;
         self.logs.append(&mut next_result.logs);
         if let Ok(ReturnData::ReceiptIndex(ref mut receipt_index)) = self.result {
@@ -181,15 +239,53 @@ impl Default for ActionResult {
             new_receipts: vec![],
             validator_proposals: vec![],
             profile: Default::default(),
+            host_function_calls: vec![],
+            other_burnt_amount: 0,
         }
     }
 }
 
-pub struct Runtime {}
+pub struct Runtime {
+    parallel_receipts: bool,
+    trace_host_functions: bool,
+    balance_mismatch_breakdown: bool,
+}
 
 impl Runtime {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            parallel_receipts: false,
+            trace_host_functions: false,
+            balance_mismatch_breakdown: false,
+        }
+    }
+
+    /// Executes receipts with different receivers in parallel. The results are identical to
+    /// sequential execution.
+    pub fn with_parallel_receipts(mut self, parallel_receipts: bool) -> Self {
+        self.parallel_receipts = parallel_receipts;
+        self
+    }
+
+    /// Records the host function calls made by contracts in the metadata of the receipt
+    /// outcomes. Slows down function calls, so it's meant for sandbox and debugging nodes.
+    pub fn with_host_function_tracing(mut self, trace_host_functions: bool) -> Self {
+        self.trace_host_functions = trace_host_functions;
+        self
+    }
+
+    /// Collects the amounts burnt by each account, so that a balance mismatch report can break
+    /// the burnt amounts down by account.
+    pub fn with_balance_mismatch_breakdown(mut self, balance_mismatch_breakdown: bool) -> Self {
+        self.balance_mismatch_breakdown = balance_mismatch_breakdown;
+        self
+    }
+
+    fn new_stats(&self) -> ApplyStats {
+        ApplyStats {
+            burnt_amount_by_account: self.balance_mismatch_breakdown.then(BTreeMap::new),
+            ..Default::default()
+        }
     }
 
     fn print_log(log: &[LogEntry]) {
@@ -232,7 +328,8 @@ impl Runtime {
             apply_state.gas_price,
             signed_transaction,
             true,
-            Some(apply_state.block_index),
+            apply_state.transaction_validity_height,
+            Some(apply_state.epoch_height),
             apply_state.current_protocol_version,
         ) {
             Ok(verification_result) => {
@@ -371,6 +468,7 @@ impl Runtime {
                     &apply_state.config,
                     action_index + 1 == actions.len(),
                     epoch_info_provider,
+                    self.trace_host_functions,
                 )?;
             }
             Action::Transfer(transfer) => {
@@ -466,6 +564,42 @@ impl Runtime {
                     true,
                 )?;
             }
+            #[cfg(feature = "protocol_feature_delegate_action")]
+            Action::Delegate(signed_delegate_action) => {
+                metrics::ACTION_DELEGATE_TOTAL.inc();
+                apply_delegate_action(
+                    state_update,
+                    apply_state,
+                    action_receipt,
+                    account.as_mut().expect(EXPECT_ACCOUNT_EXISTS),
+                    account_id,
+                    signed_delegate_action,
+                    &mut result,
+                )?;
+            }
+            #[cfg(feature = "protocol_feature_global_contracts")]
+            Action::DeployGlobalContract(deploy_global_contract) => {
+                metrics::ACTION_DEPLOY_GLOBAL_CONTRACT_TOTAL.inc();
+                action_deploy_global_contract(
+                    state_update,
+                    account.as_mut().expect(EXPECT_ACCOUNT_EXISTS),
+                    account_id,
+                    deploy_global_contract,
+                    apply_state,
+                    &mut result,
+                )?;
+            }
+            #[cfg(feature = "protocol_feature_global_contracts")]
+            Action::UseGlobalContract(use_global_contract) => {
+                metrics::ACTION_USE_GLOBAL_CONTRACT_TOTAL.inc();
+                action_use_global_contract(
+                    state_update,
+                    account.as_mut().expect(EXPECT_ACCOUNT_EXISTS),
+                    account_id,
+                    use_global_contract,
+                    &mut result,
+                )?;
+            }
         };
         Ok(result)
     }
@@ -596,10 +730,9 @@ impl Runtime {
 
             // If the refund fails tokens are burned.
             if result.result.is_err() {
-                stats.other_burnt_amount = safe_add_balance(
-                    stats.other_burnt_amount,
-                    total_deposit(&action_receipt.actions)?,
-                )?
+                let deposit = total_deposit(&action_receipt.actions)?;
+                stats.other_burnt_amount = safe_add_balance(stats.other_burnt_amount, deposit)?;
+                stats.add_burnt_amount(account_id, deposit)?;
             }
             0
         } else {
@@ -621,6 +754,9 @@ impl Runtime {
         // Committing or rolling back state.
         match &result.result {
             Ok(_) => {
+                stats.other_burnt_amount =
+                    safe_add_balance(stats.other_burnt_amount, result.other_burnt_amount)?;
+                stats.add_burnt_amount(account_id, result.other_burnt_amount)?;
                 state_update.commit(StateChangeCause::ReceiptProcessing {
                     receipt_hash: receipt.get_hash(),
                 });
@@ -664,6 +800,7 @@ impl Runtime {
         }
 
         stats.tx_burnt_amount = safe_add_balance(stats.tx_burnt_amount, tx_burnt_amount)?;
+        stats.add_burnt_amount(account_id, tx_burnt_amount)?;
 
         // Generating outgoing data
         // A {
@@ -747,6 +884,12 @@ impl Runtime {
 
         Self::print_log(&result.logs);
 
+        let metadata = if result.host_function_calls.is_empty() {
+            ExecutionMetadata::V2(result.profile)
+        } else {
+            ExecutionMetadata::V3(result.profile, result.host_function_calls)
+        };
+
         Ok(ExecutionOutcomeWithId {
             id: receipt.receipt_id,
             outcome: ExecutionOutcome {
@@ -756,7 +899,7 @@ impl Runtime {
                 gas_burnt: result.gas_burnt,
                 tokens_burnt,
                 executor_id: account_id.clone(),
-                metadata: ExecutionMetadata::V2(result.profile),
+                metadata,
             },
         })
     }
@@ -988,6 +1131,32 @@ impl Runtime {
         Ok(None)
     }
 
+    /// Processes a receipt and collects what it produced separately from the rest of the chunk,
+    /// so receipts can be executed out of order.
+    fn execute_receipt(
+        &self,
+        state_update: &mut TrieUpdate,
+        apply_state: &ApplyState,
+        receipt: &Receipt,
+        epoch_info_provider: &dyn EpochInfoProvider,
+    ) -> Result<ExecutedReceipt, RuntimeError> {
+        let _span = tracing::debug_span!(target: "runtime", "Runtime::process_receipt", receipt_id = %receipt.receipt_id, node_counter = state_update.trie.get_touched_nodes_count()).entered();
+        let mut outgoing_receipts = vec![];
+        let mut validator_proposals = vec![];
+        let mut stats = self.new_stats();
+        let outcome = self.process_receipt(
+            state_update,
+            apply_state,
+            receipt,
+            &mut outgoing_receipts,
+            &mut validator_proposals,
+            &mut stats,
+            epoch_info_provider,
+        );
+        tracing::debug!(target: "runtime", node_counter = state_update.trie.get_touched_nodes_count());
+        Ok(ExecutedReceipt { outcome: outcome?, outgoing_receipts, validator_proposals, stats })
+    }
+
     /// Iterates over the validators in the current shard and updates their accounts to return stake
     /// and allocate rewards. Also updates protocol treasure account if it belongs to the current
     /// shard.
@@ -1065,6 +1234,7 @@ impl Runtime {
                     .slashed_burnt_amount
                     .checked_add(amount_to_slash)
                     .ok_or_else(|| RuntimeError::UnexpectedIntegerOverflow)?;
+                stats.add_burnt_amount(account_id, amount_to_slash)?;
                 account.set_locked(
                     account
                         .locked()
@@ -1187,7 +1357,7 @@ impl Runtime {
         let initial_state = TrieUpdate::new(trie.clone(), root);
         let mut state_update = TrieUpdate::new(trie.clone(), root);
 
-        let mut stats = ApplyStats::default();
+        let mut stats = self.new_stats();
 
         if let Some(validator_accounts_update) = validator_accounts_update {
             self.update_validator_accounts(
@@ -1229,6 +1399,7 @@ impl Runtime {
                 stats,
                 processed_delayed_receipts: vec![],
                 proof,
+                delayed_receipts_gas: None,
             });
         }
 
@@ -1264,22 +1435,56 @@ impl Runtime {
             get(&state_update, &TrieKey::DelayedReceiptIndices)?.unwrap_or_default();
         let initial_delayed_receipt_indices = delayed_receipts_indices.clone();
 
-        let mut process_receipt = |receipt: &Receipt,
-                                   state_update: &mut TrieUpdate,
-                                   total_gas_burnt: &mut Gas|
+        let protocol_version = apply_state.current_protocol_version;
+        let fees_config = &apply_state.config.transaction_costs;
+        let congestion_control_enabled = checked_feature!(
+            "protocol_feature_congestion_control",
+            CongestionControl,
+            protocol_version
+        );
+        let stored_delayed_receipts_gas: Option<Gas> = if congestion_control_enabled {
+            get(&state_update, &TrieKey::DelayedReceiptsGas)?
+        } else {
+            None
+        };
+        let mut delayed_receipts_gas = match stored_delayed_receipts_gas {
+            Some(gas) => Some(gas),
+            None if congestion_control_enabled => Some(compute_delayed_receipts_gas(
+                &state_update,
+                fees_config,
+                &delayed_receipts_indices,
+                protocol_version,
+            )?),
+            None => None,
+        };
+        let mut outgoing_receipts_limit = OutgoingReceiptsLimit::new(
+            &apply_state.congestion_info,
+            apply_state.shard_id,
+            congestion_control_enabled,
+        );
+        // Receipts to congested shards over the limit, delayed at the end of the chunk.
+        let mut stalled_receipts = vec![];
+
+        let mut record_receipt = |executed: ExecutedReceipt,
+                                  total_gas_burnt: &mut Gas,
+                                  outgoing_receipts_limit: &mut OutgoingReceiptsLimit<'_>|
          -> Result<_, RuntimeError> {
-            let _span = tracing::debug_span!(target: "runtime", "Runtime::process_receipt", receipt_id = %receipt.receipt_id, node_counter = state_update.trie.get_touched_nodes_count()).entered();
-            let result = self.process_receipt(
-                state_update,
-                apply_state,
-                receipt,
-                &mut outgoing_receipts,
-                &mut validator_proposals,
-                &mut stats,
-                epoch_info_provider,
-            );
-            tracing::debug!(target: "runtime", node_counter = state_update.trie.get_touched_nodes_count());
-            result?.into_iter().try_for_each(
+            let ExecutedReceipt {
+                outcome,
+                outgoing_receipts: new_receipts,
+                validator_proposals: mut new_proposals,
+                stats: receipt_stats,
+            } = executed;
+            stats.merge(receipt_stats)?;
+            for receipt in new_receipts {
+                if outgoing_receipts_limit.try_send(fees_config, &receipt, protocol_version)? {
+                    outgoing_receipts.push(receipt);
+                } else {
+                    stalled_receipts.push(receipt);
+                }
+            }
+            validator_proposals.append(&mut new_proposals);
+            outcome.into_iter().try_for_each(
                 |outcome_with_id: ExecutionOutcomeWithId| -> Result<(), RuntimeError> {
                     *total_gas_burnt =
                         safe_add_gas(*total_gas_burnt, outcome_with_id.outcome.gas_burnt)?;
@@ -1291,20 +1496,111 @@ impl Runtime {
         };
 
         let gas_limit = apply_state.gas_limit.unwrap_or(Gas::max_value());
+        // Receipts are executed speculatively in parallel, which would add nodes to the recorded
+        // storage proof.
+        let parallel_receipts = self.parallel_receipts && trie.handle().is_some();
 
         // We first process local receipts. They contain staking, local contract calls, etc.
-        for receipt in local_receipts.iter() {
+        let mut num_processed_local_receipts = 0;
+        if parallel_receipts && total_gas_burnt < gas_limit {
+            num_processed_local_receipts = self.apply_receipts_in_parallel(
+                &mut state_update,
+                apply_state,
+                &local_receipts,
+                None,
+                gas_limit - total_gas_burnt,
+                epoch_info_provider,
+                |_, executed| {
+                    if total_gas_burnt >= gas_limit {
+                        return Ok(false);
+                    }
+                    record_receipt(executed?, &mut total_gas_burnt, &mut outgoing_receipts_limit)?;
+                    Ok(true)
+                },
+            )?;
+        }
+        for receipt in local_receipts[num_processed_local_receipts..].iter() {
             if total_gas_burnt < gas_limit {
                 // NOTE: We don't need to validate the local receipt, because it's just validated in
                 // the `verify_and_charge_transaction`.
-                process_receipt(receipt, &mut state_update, &mut total_gas_burnt)?;
+                let executed = self.execute_receipt(
+                    &mut state_update,
+                    apply_state,
+                    receipt,
+                    epoch_info_provider,
+                )?;
+                record_receipt(executed, &mut total_gas_burnt, &mut outgoing_receipts_limit)?;
             } else {
                 Self::delay_receipt(&mut state_update, &mut delayed_receipts_indices, receipt)?;
+                if let Some(delayed_receipts_gas) = delayed_receipts_gas.as_mut() {
+                    *delayed_receipts_gas = safe_add_gas(
+                        *delayed_receipts_gas,
+                        receipt_congestion_gas(fees_config, receipt, protocol_version)?,
+                    )?;
+                }
             }
         }
 
         // Then we process the delayed receipts. It's a backlog of receipts from the past blocks.
-        while delayed_receipts_indices.first_index < delayed_receipts_indices.next_available_index {
+        while parallel_receipts && total_gas_burnt < gas_limit {
+            // Missing, invalid and stalled receipts end the batch, the loop below handles them.
+            let first_index = delayed_receipts_indices.first_index;
+            let mut batch: Vec<Receipt> = vec![];
+            for index in first_index..delayed_receipts_indices.next_available_index {
+                if batch.len() == parallel::DELAYED_RECEIPTS_BATCH_SIZE {
+                    break;
+                }
+                match get(&state_update, &TrieKey::DelayedReceipt { index }) {
+                    Ok(Some(receipt))
+                        if !outgoing_receipts_limit.is_stalled(&receipt)
+                            && validate_receipt(
+                                &apply_state.config.wasm_config.limit_config,
+                                &receipt,
+                            )
+                            .is_ok() =>
+                    {
+                        batch.push(receipt)
+                    }
+                    _ => break,
+                }
+            }
+            if batch.is_empty() {
+                break;
+            }
+            let num_processed = self.apply_receipts_in_parallel(
+                &mut state_update,
+                apply_state,
+                &batch,
+                Some(first_index),
+                gas_limit - total_gas_burnt,
+                epoch_info_provider,
+                |index, executed| {
+                    if total_gas_burnt >= gas_limit {
+                        return Ok(false);
+                    }
+                    // Math checked above: first_index is less than next_available_index
+                    delayed_receipts_indices.first_index += 1;
+                    if let Some(delayed_receipts_gas) = delayed_receipts_gas.as_mut() {
+                        // Saturating because the fees of the receipt may have changed since it
+                        // was delayed.
+                        *delayed_receipts_gas = delayed_receipts_gas.saturating_sub(
+                            receipt_congestion_gas(fees_config, &batch[index], protocol_version)?,
+                        );
+                    }
+                    record_receipt(executed?, &mut total_gas_burnt, &mut outgoing_receipts_limit)?;
+                    Ok(true)
+                },
+            )?;
+            let batch_size = batch.len();
+            processed_delayed_receipts.extend(batch.into_iter().take(num_processed));
+            if num_processed < batch_size {
+                break;
+            }
+        }
+        // Stalled receipts which still can't be sent are moved to the end of the queue, so each
+        // receipt is visited at most once.
+        let delayed_receipts_end = delayed_receipts_indices.next_available_index;
+        while delayed_receipts_indices.first_index < delayed_receipts_end {
             if total_gas_burnt >= gas_limit {
                 break;
             }
@@ -1329,20 +1625,85 @@ impl Runtime {
             state_update.remove(key);
             // Math checked above: first_index is less than next_available_index
             delayed_receipts_indices.first_index += 1;
-            process_receipt(&receipt, &mut state_update, &mut total_gas_burnt)?;
+            if let Some(delayed_receipts_gas) = delayed_receipts_gas.as_mut() {
+                // Saturating because the fees of the receipt may have changed since it was delayed.
+                *delayed_receipts_gas = delayed_receipts_gas.saturating_sub(
+                    receipt_congestion_gas(fees_config, &receipt, protocol_version)?,
+                );
+            }
+            let executed = if outgoing_receipts_limit.is_stalled(&receipt) {
+                // Forwarded to its shard, or stalled again if the limit is still reached.
+                ExecutedReceipt {
+                    outcome: None,
+                    outgoing_receipts: vec![receipt.clone()],
+                    validator_proposals: vec![],
+                    stats: ApplyStats::default(),
+                }
+            } else {
+                self.execute_receipt(&mut state_update, apply_state, &receipt, epoch_info_provider)?
+            };
+            record_receipt(executed, &mut total_gas_burnt, &mut outgoing_receipts_limit)?;
             processed_delayed_receipts.push(receipt);
         }
 
         // And then we process the new incoming receipts. These are receipts from other shards.
-        for receipt in incoming_receipts.iter() {
+        let mut num_processed_incoming_receipts = 0;
+        if parallel_receipts && total_gas_burnt < gas_limit {
+            // Receipts starting from the first invalid one are left for the loop below, which
+            // fails on it.
+            let num_valid_receipts = incoming_receipts
+                .iter()
+                .take_while(|receipt| {
+                    validate_receipt(&apply_state.config.wasm_config.limit_config, receipt).is_ok()
+                })
+                .count();
+            num_processed_incoming_receipts = self.apply_receipts_in_parallel(
+                &mut state_update,
+                apply_state,
+                &incoming_receipts[..num_valid_receipts],
+                None,
+                gas_limit - total_gas_burnt,
+                epoch_info_provider,
+                |_, executed| {
+                    if total_gas_burnt >= gas_limit {
+                        return Ok(false);
+                    }
+                    record_receipt(executed?, &mut total_gas_burnt, &mut outgoing_receipts_limit)?;
+                    Ok(true)
+                },
+            )?;
+        }
+        for receipt in incoming_receipts[num_processed_incoming_receipts..].iter() {
             // Validating new incoming no matter whether we have available gas or not. We don't
             // want to store invalid receipts in state as delayed.
             validate_receipt(&apply_state.config.wasm_config.limit_config, receipt)
                 .map_err(RuntimeError::ReceiptValidationError)?;
             if total_gas_burnt < gas_limit {
-                process_receipt(receipt, &mut state_update, &mut total_gas_burnt)?;
+                let executed = self.execute_receipt(
+                    &mut state_update,
+                    apply_state,
+                    receipt,
+                    epoch_info_provider,
+                )?;
+                record_receipt(executed, &mut total_gas_burnt, &mut outgoing_receipts_limit)?;
             } else {
                 Self::delay_receipt(&mut state_update, &mut delayed_receipts_indices, receipt)?;
+                if let Some(delayed_receipts_gas) = delayed_receipts_gas.as_mut() {
+                    *delayed_receipts_gas = safe_add_gas(
+                        *delayed_receipts_gas,
+                        receipt_congestion_gas(fees_config, receipt, protocol_version)?,
+                    )?;
+                }
+            }
+        }
+
+        for receipt in stalled_receipts.iter() {
+            Self::delay_receipt(&mut state_update, &mut delayed_receipts_indices, receipt)?;
+            if let Some(delayed_receipts_gas) = delayed_receipts_gas.as_mut() {
+                *delayed_receipts_gas = safe_add_gas(
+                    *delayed_receipts_gas,
+                    receipt_congestion_gas(fees_config, receipt, protocol_version)?,
+                )?;
             }
         }
 
@@ -1350,6 +1711,29 @@ impl Runtime {
             set(&mut state_update, TrieKey::DelayedReceiptIndices, &delayed_receipts_indices);
         }
 
+        if let Some(gas) = delayed_receipts_gas.as_mut() {
+            // Drop the error accumulated from fee changes once the queue is drained.
+            if delayed_receipts_indices.first_index == delayed_receipts_indices.next_available_index
+            {
+                *gas = 0;
+            }
+            if stored_delayed_receipts_gas != Some(*gas) {
+                set(&mut state_update, TrieKey::DelayedReceiptsGas, gas);
+            }
+        }
+
+        let shard_label = apply_state.shard_id.to_string();
+        metrics::DELAYED_RECEIPTS_COUNT.with_label_values(&[&shard_label]).set(
+            (delayed_receipts_indices.next_available_index - delayed_receipts_indices.first_index)
+                as i64,
+        );
+        metrics::DELAYED_RECEIPTS_PROCESSED
+            .with_label_values(&[&shard_label])
+            .set(processed_delayed_receipts.len() as i64);
+        if let Some(gas) = delayed_receipts_gas {
+            metrics::DELAYED_RECEIPTS_GAS.with_label_values(&[&shard_label]).set(gas as i64);
+        }
+
         check_balance(
             &apply_state.config.transaction_costs,
             &initial_state,
@@ -1359,6 +1743,7 @@ impl Runtime {
             transactions,
             &outgoing_receipts,
             &stats,
+            apply_state.gas_price,
             apply_state.current_protocol_version,
         )?;
 
@@ -1395,6 +1780,7 @@ impl Runtime {
             stats,
             processed_delayed_receipts,
             proof,
+            delayed_receipts_gas,
         })
     }
 
@@ -1476,18 +1862,31 @@ impl Runtime {
 
 #[cfg(test)]
 mod tests {
+    use borsh::BorshSerialize;
     use near_crypto::{InMemorySigner, KeyType, Signer};
     use near_primitives::account::AccessKey;
     use near_primitives::contract::ContractCode;
     use near_primitives::hash::hash;
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    use near_primitives::num_rational::Rational;
     use near_primitives::shard_layout::ShardUId;
     use near_primitives::test_utils::{account_new, MockEpochInfoProvider};
     use near_primitives::transaction::DeployContractAction;
     use near_primitives::transaction::{
         AddKeyAction, DeleteKeyAction, FunctionCallAction, TransferAction,
     };
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    use near_primitives::transaction::{DelegateAction, SignedDelegateAction};
+    #[cfg(feature = "protocol_feature_global_contracts")]
+    use near_primitives::transaction::{DeployGlobalContractAction, UseGlobalContractAction};
     use near_primitives::types::MerkleHash;
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    use near_primitives::types::Nonce;
     use near_primitives::version::PROTOCOL_VERSION;
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    use near_store::get_access_key;
+    #[cfg(feature = "protocol_feature_global_contracts")]
+    use near_store::get_code;
     use near_store::set_access_key;
     use near_store::test_utils::create_tries;
     use near_store::StoreCompiledContractCache;
@@ -1593,10 +1992,12 @@ mod tests {
 
         let apply_state = ApplyState {
             block_index: 1,
+            transaction_validity_height: 1,
             prev_block_hash: Default::default(),
             block_hash: Default::default(),
             epoch_id: Default::default(),
             epoch_height: 0,
+            shard_id: 0,
             gas_price: GAS_PRICE,
             block_timestamp: 100,
             gas_limit: Some(gas_limit),
@@ -1607,6 +2008,7 @@ mod tests {
             is_new_chunk: true,
             migration_data: Arc::new(MigrationData::default()),
             migration_flags: MigrationFlags::default(),
+            congestion_info: Default::default(),
         };
 
         (runtime, tries, root, apply_state, signer, MockEpochInfoProvider::default())
@@ -1746,6 +2148,149 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(feature = "protocol_feature_congestion_control")]
+    fn test_apply_delayed_receipts_gas() {
+        let initial_balance = to_yocto(1_000_000);
+        let initial_locked = to_yocto(500_000);
+        let small_transfer = to_yocto(10_000);
+        let gas_limit = 1;
+        let (runtime, tries, mut root, apply_state, _, epoch_info_provider) =
+            setup_runtime(initial_balance, initial_locked, gas_limit);
+
+        let n = 10;
+        let receipts = generate_receipts(small_transfer, n);
+        let receipt_gas = receipt_congestion_gas(
+            &apply_state.config.transaction_costs,
+            &receipts[0],
+            PROTOCOL_VERSION,
+        )
+        .unwrap();
+        assert!(receipt_gas > 0);
+
+        // Every chunk processes a single receipt, the rest stay in the delayed receipts queue.
+        for i in 1..=n + 1 {
+            let prev_receipts: &[Receipt] = if i == 1 { &receipts } else { &[] };
+            let apply_result = runtime
+                .apply(
+                    tries.get_trie_for_shard(ShardUId::single_shard()),
+                    root,
+                    &None,
+                    &apply_state,
+                    prev_receipts,
+                    &[],
+                    &epoch_info_provider,
+                    None,
+                )
+                .unwrap();
+            let (store_update, new_root) =
+                tries.apply_all(&apply_result.trie_changes, ShardUId::single_shard()).unwrap();
+            root = new_root;
+            store_update.commit().unwrap();
+            let expected_gas = receipt_gas * (n - std::cmp::min(i, n));
+            assert_eq!(apply_result.delayed_receipts_gas, Some(expected_gas));
+            let state = tries.new_trie_update(ShardUId::single_shard(), root);
+            let stored_gas: Option<Gas> = get(&state, &TrieKey::DelayedReceiptsGas).unwrap();
+            assert_eq!(stored_gas.unwrap_or_default(), expected_gas);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_congestion_control")]
+    fn test_apply_stalled_receipts() {
+        use near_primitives::congestion_control::{
+            BlockCongestionInfo, CONGESTED_DELAYED_RECEIPTS_GAS,
+            MAX_OUTGOING_GAS_TO_CONGESTED_SHARD,
+        };
+        use near_primitives::shard_layout::{account_id_to_shard_id, ShardLayout};
+
+        let (runtime, tries, root, mut apply_state, _, epoch_info_provider) =
+            setup_runtime(to_yocto(1_000_000), 0, 10u64.pow(15));
+        let shard_layout = ShardLayout::v0(2, 0);
+        let congested_shard_id = account_id_to_shard_id(&bob_account(), &shard_layout);
+        apply_state.shard_id = account_id_to_shard_id(&alice_account(), &shard_layout);
+        assert_ne!(apply_state.shard_id, congested_shard_id);
+        apply_state.congestion_info = BlockCongestionInfo::new(
+            shard_layout,
+            [(congested_shard_id, CONGESTED_DELAYED_RECEIPTS_GAS + 1)].into_iter().collect(),
+        );
+
+        // Receipts to the congested shard stalled by a previous chunk.
+        let stalled: Vec<Receipt> = (0..3u8)
+            .map(|i| Receipt {
+                predecessor_id: alice_account(),
+                receiver_id: bob_account(),
+                receipt_id: hash(&[i]),
+                receipt: ReceiptEnum::Action(ActionReceipt {
+                    signer_id: alice_account(),
+                    signer_public_key: PublicKey::empty(KeyType::ED25519),
+                    gas_price: GAS_PRICE,
+                    output_data_receivers: vec![],
+                    input_data_ids: vec![],
+                    actions: vec![Action::FunctionCall(FunctionCallAction {
+                        method_name: "hot".to_string(),
+                        args: vec![],
+                        gas: MAX_OUTGOING_GAS_TO_CONGESTED_SHARD / 2,
+                        deposit: 0,
+                    })],
+                }),
+            })
+            .collect();
+        let mut state_update = tries.new_trie_update(ShardUId::single_shard(), root);
+        let mut indices = DelayedReceiptIndices::default();
+        for receipt in &stalled {
+            Runtime::delay_receipt(&mut state_update, &mut indices, receipt).unwrap();
+        }
+        set(&mut state_update, TrieKey::DelayedReceiptIndices, &indices);
+        state_update.commit(StateChangeCause::InitialState);
+        let trie_changes = state_update.finalize().unwrap().0;
+        let (store_update, mut root) =
+            tries.apply_all(&trie_changes, ShardUId::single_shard()).unwrap();
+        store_update.commit().unwrap();
+
+        // The limit lets two of them through and the third one is stalled again, while the
+        // receipts of the shard are still executed.
+        let transfers = generate_receipts(to_yocto(1), 1);
+        let apply = |root, receipts: &[Receipt]| {
+            let apply_result = runtime
+                .apply(
+                    tries.get_trie_for_shard(ShardUId::single_shard()),
+                    root,
+                    &None,
+                    &apply_state,
+                    receipts,
+                    &[],
+                    &epoch_info_provider,
+                    None,
+                )
+                .unwrap();
+            let (store_update, root) =
+                tries.apply_all(&apply_result.trie_changes, ShardUId::single_shard()).unwrap();
+            store_update.commit().unwrap();
+            (apply_result, root)
+        };
+        let (apply_result, new_root) = apply(root, &transfers);
+        root = new_root;
+        assert_eq!(apply_result.outgoing_receipts[..2], stalled[..2]);
+        assert!(!apply_result.outgoing_receipts.contains(&stalled[2]));
+        assert_eq!(
+            apply_result.outcomes.iter().map(|o| o.id).collect::<Vec<_>>(),
+            vec![transfers[0].receipt_id]
+        );
+        let state = tries.new_trie_update(ShardUId::single_shard(), root);
+        let indices: DelayedReceiptIndices =
+            get(&state, &TrieKey::DelayedReceiptIndices).unwrap().unwrap();
+        assert_eq!(indices.next_available_index - indices.first_index, 1);
+        let delayed_receipt: Receipt =
+            get(&state, &TrieKey::DelayedReceipt { index: indices.first_index }).unwrap().unwrap();
+        assert_eq!(delayed_receipt, stalled[2]);
+
+        // The next chunk forwards it.
+        let (apply_result, _) = apply(root, &[]);
+        assert_eq!(apply_result.outgoing_receipts, vec![stalled[2].clone()]);
+        assert!(apply_result.outcomes.is_empty());
+    }
+
     #[test]
     fn test_apply_delayed_receipts_add_more_using_chunks() {
         let initial_balance = to_yocto(1_000_000);
@@ -2129,6 +2674,244 @@ mod tests {
         );
     }
 
+    /// Applies the chunks one after another with the sequential and the parallel runtime, checks
+    /// that they produce the same results and returns the number of processed delayed receipts.
+    fn assert_parallel_apply_matches_sequential(
+        tries: &ShardTries,
+        mut root: CryptoHash,
+        apply_state: &ApplyState,
+        epoch_info_provider: &dyn EpochInfoProvider,
+        chunks: &[(Vec<Receipt>, Vec<SignedTransaction>)],
+    ) -> usize {
+        let runtimes = [Runtime::new(), Runtime::new().with_parallel_receipts(true)];
+        let mut num_processed_delayed_receipts = 0;
+        for (receipts, transactions) in chunks {
+            let results: Vec<ApplyResult> = runtimes
+                .iter()
+                .map(|runtime| {
+                    runtime
+                        .apply(
+                            tries.get_trie_for_shard(ShardUId::single_shard()),
+                            root,
+                            &None,
+                            apply_state,
+                            receipts,
+                            transactions,
+                            epoch_info_provider,
+                            None,
+                        )
+                        .unwrap()
+                })
+                .collect();
+            let (sequential, parallel) = (&results[0], &results[1]);
+            assert_eq!(parallel.state_root, sequential.state_root);
+            assert_eq!(parallel.trie_changes, sequential.trie_changes);
+            assert_eq!(
+                parallel.state_changes.try_to_vec().unwrap(),
+                sequential.state_changes.try_to_vec().unwrap()
+            );
+            assert_eq!(parallel.outcomes, sequential.outcomes);
+            assert_eq!(parallel.outgoing_receipts, sequential.outgoing_receipts);
+            assert_eq!(parallel.processed_delayed_receipts, sequential.processed_delayed_receipts);
+            assert_eq!(format!("{:?}", parallel.stats), format!("{:?}", sequential.stats));
+            num_processed_delayed_receipts += sequential.processed_delayed_receipts.len();
+
+            let (store_update, new_root) =
+                tries.apply_all(&sequential.trie_changes, ShardUId::single_shard()).unwrap();
+            store_update.commit().unwrap();
+            root = new_root;
+        }
+        num_processed_delayed_receipts
+    }
+
+    #[test]
+    fn test_apply_parallel_receipts() {
+        let initial_balance = to_yocto(1_000_000);
+        let small_transfer = to_yocto(10_000);
+        let (_, tries, root, mut apply_state, signer, epoch_info_provider) =
+            setup_runtime(initial_balance, 0, 1);
+
+        let receipt_gas_cost = apply_state
+            .config
+            .transaction_costs
+            .action_receipt_creation_config
+            .exec_fee()
+            + apply_state.config.transaction_costs.action_creation_config.transfer_cost.exec_fee();
+        // Transfers to bob and carol fail because their accounts don't exist, which produces
+        // refunds.
+        let receivers: Vec<AccountId> =
+            vec![alice_account(), bob_account(), "carol.near".parse().unwrap()];
+        let receipts: Vec<Receipt> = generate_receipts(small_transfer, 50)
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut receipt)| {
+                receipt.receiver_id = receivers[i % 5 % 3].clone();
+                receipt
+            })
+            .collect();
+        let local_transactions: Vec<SignedTransaction> = (0..20)
+            .map(|i| {
+                SignedTransaction::send_money(
+                    i + 1,
+                    alice_account(),
+                    alice_account(),
+                    &*signer,
+                    small_transfer,
+                    CryptoHash::default(),
+                )
+            })
+            .collect();
+        let chunks: Vec<(Vec<Receipt>, Vec<SignedTransaction>)> = (0..10)
+            .map(|i| {
+                (
+                    receipts.get(i * 10..(i + 1) * 10).unwrap_or_default().to_vec(),
+                    local_transactions.get(i * 4..(i + 1) * 4).unwrap_or_default().to_vec(),
+                )
+            })
+            .collect();
+
+        // Without a gas limit all receipts are processed right away. With it, receipts are
+        // delayed and processed by the following chunks.
+        for gas_limit in [Gas::max_value(), receipt_gas_cost * 7] {
+            apply_state.gas_limit = Some(gas_limit);
+            let num_processed_delayed_receipts = assert_parallel_apply_matches_sequential(
+                &tries,
+                root,
+                &apply_state,
+                &epoch_info_provider,
+                &chunks,
+            );
+            assert_eq!(num_processed_delayed_receipts > 0, gas_limit != Gas::max_value());
+        }
+    }
+
+    #[test]
+    fn test_apply_parallel_function_calls() {
+        let (_, tries, root, mut apply_state, _, epoch_info_provider) =
+            setup_runtime(to_yocto(1_000_000), 0, 1);
+        let code = ContractCode::new(near_test_contracts::rs_contract().to_vec(), None);
+        let receivers: Vec<AccountId> =
+            vec![alice_account(), bob_account(), "carol.near".parse().unwrap()];
+        let mut state_update = tries.new_trie_update(ShardUId::single_shard(), root);
+        for account_id in receivers.iter() {
+            let mut account = get_account(&state_update, account_id)
+                .unwrap()
+                .unwrap_or_else(|| account_new(to_yocto(1_000_000), *code.hash()));
+            account.set_code_hash(*code.hash());
+            set_account(&mut state_update, account_id.clone(), &account);
+            set_code(&mut state_update, account_id.clone(), &code);
+        }
+        state_update.commit(StateChangeCause::InitialState);
+        let trie_changes = state_update.finalize().unwrap().0;
+        let (store_update, root) =
+            tries.apply_all(&trie_changes, ShardUId::single_shard()).unwrap();
+        store_update.commit().unwrap();
+
+        // Writes don't use most of their gas, which is refunded. Endless loops fail and refund
+        // their deposit.
+        let tera_gas = 10u64.pow(12);
+        let receipts: Vec<Receipt> = (0..30u64)
+            .map(|i| {
+                let action = if i % 4 == 3 {
+                    FunctionCallAction {
+                        method_name: "loop_forever".to_string(),
+                        args: vec![],
+                        gas: 5 * tera_gas,
+                        deposit: 1,
+                    }
+                } else {
+                    FunctionCallAction {
+                        method_name: "write_key_value".to_string(),
+                        args: [(i % 5).to_le_bytes(), i.to_le_bytes()].concat(),
+                        gas: 50 * tera_gas,
+                        deposit: 0,
+                    }
+                };
+                Receipt {
+                    predecessor_id: bob_account(),
+                    receiver_id: receivers[i as usize % 3].clone(),
+                    receipt_id: hash(&i.to_le_bytes()),
+                    receipt: ReceiptEnum::Action(ActionReceipt {
+                        signer_id: bob_account(),
+                        signer_public_key: PublicKey::empty(KeyType::ED25519),
+                        gas_price: GAS_PRICE,
+                        output_data_receivers: vec![],
+                        input_data_ids: vec![],
+                        actions: vec![Action::FunctionCall(action)],
+                    }),
+                }
+            })
+            .collect();
+        let mut chunks: Vec<(Vec<Receipt>, Vec<SignedTransaction>)> =
+            receipts.chunks(10).map(|receipts| (receipts.to_vec(), vec![])).collect();
+        chunks.extend((0..5).map(|_| (vec![], vec![])));
+
+        // With the gas limit only a few calls fit into a chunk, which cuts the parallel execution
+        // off, and the rest are delayed.
+        for gas_limit in [Gas::max_value(), 20 * tera_gas] {
+            apply_state.gas_limit = Some(gas_limit);
+            let num_processed_delayed_receipts = assert_parallel_apply_matches_sequential(
+                &tries,
+                root,
+                &apply_state,
+                &epoch_info_provider,
+                &chunks,
+            );
+            assert_eq!(num_processed_delayed_receipts > 0, gas_limit != Gas::max_value());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_tx_validity_window")]
+    fn test_apply_transaction_validity_height() {
+        let (runtime, tries, root, mut apply_state, signer, epoch_info_provider) =
+            setup_runtime(to_yocto(1_000_000), 0, 10u64.pow(15));
+        // The chunk was produced on top of height 2 and ended up in a block after skipped heights.
+        apply_state.transaction_validity_height = 3;
+        apply_state.block_index = 5;
+
+        let transaction = |valid_until_height| {
+            near_primitives::transaction::Transaction {
+                valid_until_height: Some(valid_until_height),
+                ..near_primitives::transaction::Transaction::new(
+                    alice_account(),
+                    signer.public_key(),
+                    bob_account(),
+                    1,
+                    CryptoHash::default(),
+                )
+            }
+            .sign(&*signer)
+        };
+        let apply = |transactions: &[SignedTransaction]| {
+            runtime.apply(
+                tries.get_trie_for_shard(ShardUId::single_shard()),
+                root,
+                &None,
+                &apply_state,
+                &[],
+                transactions,
+                &epoch_info_provider,
+                None,
+            )
+        };
+
+        let apply_result = apply(&[transaction(3)]).unwrap();
+        assert_eq!(apply_result.outcomes.len(), 1);
+        match apply(&[transaction(2)]) {
+            Err(err) => assert_eq!(
+                err,
+                RuntimeError::InvalidTxError(
+                    near_primitives::errors::InvalidTxError::ValidityWindowExpired {
+                        valid_until_height: 2,
+                        height: 3
+                    }
+                ),
+            ),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
     #[test]
     fn test_apply_deficit_gas_for_transfer() {
         let initial_balance = to_yocto(1_000_000);
@@ -2416,4 +3199,411 @@ mod tests {
             .expect("Compiled contract should be cached")
             .expect("Compilation result should be non-empty");
     }
+
+    #[test]
+    fn test_host_function_tracing() {
+        let gas_limit = 10u64.pow(15);
+        let (_, tries, root, apply_state, signer, epoch_info_provider) =
+            setup_runtime(to_yocto(1_000_000), 0, gas_limit);
+        let actions = vec![
+            Action::DeployContract(DeployContractAction {
+                code: near_test_contracts::rs_contract().to_vec(),
+            }),
+            Action::FunctionCall(FunctionCallAction {
+                method_name: "write_key_value".to_string(),
+                args: [1u64.to_le_bytes(), 2u64.to_le_bytes()].concat(),
+                gas: 10u64.pow(14),
+                deposit: 0,
+            }),
+        ];
+        let receipts = create_receipts_with_actions(alice_account(), signer, actions);
+
+        for trace_host_functions in [false, true] {
+            let apply_result = Runtime::new()
+                .with_host_function_tracing(trace_host_functions)
+                .apply(
+                    tries.get_trie_for_shard(ShardUId::single_shard()),
+                    root,
+                    &None,
+                    &apply_state,
+                    &receipts,
+                    &[],
+                    &epoch_info_provider,
+                    None,
+                )
+                .unwrap();
+            let metadata = &apply_result.outcomes[0].outcome.metadata;
+            match metadata {
+                ExecutionMetadata::V3(_, calls) => {
+                    assert!(trace_host_functions);
+                    assert!(calls.iter().any(|call| call.name == "storage_write"));
+                }
+                _ => assert!(!trace_host_functions, "unexpected metadata {:?}", metadata),
+            }
+        }
+    }
+
+    #[test]
+    fn test_balance_mismatch_breakdown() {
+        let gas_limit = 10u64.pow(15);
+        let (_, tries, root, apply_state, signer, epoch_info_provider) =
+            setup_runtime(to_yocto(1_000_000), 0, gas_limit);
+        let actions = vec![Action::DeployContract(DeployContractAction {
+            code: near_test_contracts::rs_contract().to_vec(),
+        })];
+        let receipts = create_receipts_with_actions(alice_account(), signer, actions);
+
+        for balance_mismatch_breakdown in [false, true] {
+            let stats = Runtime::new()
+                .with_balance_mismatch_breakdown(balance_mismatch_breakdown)
+                .apply(
+                    tries.get_trie_for_shard(ShardUId::single_shard()),
+                    root,
+                    &None,
+                    &apply_state,
+                    &receipts,
+                    &[],
+                    &epoch_info_provider,
+                    None,
+                )
+                .unwrap()
+                .stats;
+            match stats.burnt_amount_by_account {
+                Some(burnt_amount_by_account) => {
+                    assert!(balance_mismatch_breakdown);
+                    assert!(burnt_amount_by_account[&alice_account()] > 0);
+                    assert_eq!(
+                        burnt_amount_by_account.values().sum::<Balance>(),
+                        stats.tx_burnt_amount + stats.other_burnt_amount
+                    );
+                }
+                None => assert!(!balance_mismatch_breakdown),
+            }
+        }
+    }
+
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    fn create_signed_delegate_action(
+        signer: &InMemorySigner,
+        receiver_id: AccountId,
+        nonce: Nonce,
+        actions: Vec<Action>,
+    ) -> SignedDelegateAction {
+        let delegate_action = DelegateAction {
+            sender_id: signer.account_id.clone(),
+            receiver_id,
+            actions: actions.into_iter().map(|action| action.try_into().unwrap()).collect(),
+            nonce,
+            max_block_height: 100,
+            public_key: signer.public_key(),
+        };
+        let signature = signer.sign(delegate_action.get_hash().as_ref());
+        SignedDelegateAction { delegate_action, signature }
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    fn test_delegate_action() {
+        let (runtime, tries, root, mut apply_state, relayer_signer, epoch_info_provider) =
+            setup_runtime(to_yocto(1_000_000), 0, 10u64.pow(15));
+        // Receipts are bought at the gas price they are executed at, so the relayer loses exactly
+        // the burnt tokens.
+        let mut config = RuntimeConfig::test();
+        config.transaction_costs.pessimistic_gas_price_inflation_ratio = Rational::from_integer(1);
+        apply_state.config = Arc::new(config);
+
+        let relayer_id = alice_account();
+        let sender_id = bob_account();
+        let receiver_id: AccountId = "carol.near".parse().unwrap();
+        let sender_signer =
+            InMemorySigner::from_seed(sender_id.clone(), KeyType::ED25519, sender_id.as_ref());
+        let mut state_update = tries.new_trie_update(ShardUId::single_shard(), root);
+        for account_id in [&sender_id, &receiver_id] {
+            let mut account = account_new(to_yocto(1_000), hash(&[]));
+            account.set_storage_usage(182);
+            set_account(&mut state_update, account_id.clone(), &account);
+        }
+        set_access_key(
+            &mut state_update,
+            sender_id.clone(),
+            sender_signer.public_key(),
+            &AccessKey::full_access(),
+        );
+        state_update.commit(StateChangeCause::InitialState);
+        let trie_changes = state_update.finalize().unwrap().0;
+        let (store_update, mut root) =
+            tries.apply_all(&trie_changes, ShardUId::single_shard()).unwrap();
+        store_update.commit().unwrap();
+
+        let balance = |root: CryptoHash, account_id: &AccountId| {
+            let state_update = tries.new_trie_update(ShardUId::single_shard(), root);
+            get_account(&state_update, account_id).unwrap().unwrap().amount()
+        };
+        // Applies chunks until the transaction and all the receipts it produces are executed.
+        let run = |root: &mut CryptoHash,
+                   nonce: Nonce,
+                   signed_delegate_action: SignedDelegateAction| {
+            let mut transactions = vec![SignedTransaction::from_actions(
+                nonce,
+                relayer_id.clone(),
+                sender_id.clone(),
+                &*relayer_signer,
+                vec![Action::Delegate(Box::new(signed_delegate_action))],
+                CryptoHash::default(),
+            )];
+            let mut receipts = vec![];
+            let mut outcomes = vec![];
+            let mut all_receipts = vec![];
+            while !transactions.is_empty() || !receipts.is_empty() {
+                let apply_result = runtime
+                    .apply(
+                        tries.get_trie_for_shard(ShardUId::single_shard()),
+                        *root,
+                        &None,
+                        &apply_state,
+                        &receipts,
+                        &transactions,
+                        &epoch_info_provider,
+                        None,
+                    )
+                    .unwrap();
+                let (store_update, new_root) =
+                    tries.apply_all(&apply_result.trie_changes, ShardUId::single_shard()).unwrap();
+                store_update.commit().unwrap();
+                *root = new_root;
+                transactions.clear();
+                outcomes.extend(apply_result.outcomes);
+                receipts = apply_result.outgoing_receipts;
+                all_receipts.extend(receipts.iter().cloned());
+            }
+            (outcomes, all_receipts)
+        };
+        let delegate_status = |outcomes: &[ExecutionOutcomeWithId]| {
+            let outcome = outcomes.iter().find(|o| o.outcome.executor_id == sender_id).unwrap();
+            outcome.outcome.status.clone()
+        };
+
+        let initial_balances =
+            [&relayer_id, &sender_id, &receiver_id].map(|account_id| balance(root, account_id));
+        let deposit = to_yocto(1);
+        let signed_delegate_action = create_signed_delegate_action(
+            &sender_signer,
+            receiver_id.clone(),
+            1,
+            vec![Action::Transfer(TransferAction { deposit })],
+        );
+        // The balance checker inside `apply` verifies the fees of the forwarded receipt.
+        let (outcomes, receipts) = run(&mut root, 1, signed_delegate_action.clone());
+        assert_eq!(delegate_status(&outcomes), ExecutionStatus::SuccessValue(vec![]));
+        let new_receipt = receipts.iter().find(|r| r.receiver_id == receiver_id).unwrap();
+        assert_eq!(new_receipt.predecessor_id, sender_id);
+        match &new_receipt.receipt {
+            ReceiptEnum::Action(action_receipt) => {
+                assert_eq!(action_receipt.signer_id, relayer_id);
+                assert_eq!(
+                    action_receipt.actions,
+                    vec![Action::Transfer(TransferAction { deposit })]
+                );
+            }
+            _ => panic!("expected an action receipt"),
+        }
+        // The relayer pays for the gas, the sender pays the deposit.
+        let tokens_burnt: Balance = outcomes.iter().map(|o| o.outcome.tokens_burnt).sum();
+        assert!(tokens_burnt > 0);
+        assert_eq!(balance(root, &relayer_id), initial_balances[0] - tokens_burnt);
+        assert_eq!(balance(root, &sender_id), initial_balances[1] - deposit);
+        assert_eq!(balance(root, &receiver_id), initial_balances[2] + deposit);
+        let state_update = tries.new_trie_update(ShardUId::single_shard(), root);
+        let access_key = get_access_key(&state_update, &sender_id, &sender_signer.public_key())
+            .unwrap()
+            .unwrap();
+        assert_eq!(access_key.nonce, 1);
+
+        // Replaying the same delegate action fails because of the nonce.
+        let (outcomes, _) = run(&mut root, 2, signed_delegate_action);
+        assert_eq!(
+            delegate_status(&outcomes),
+            ExecutionStatus::Failure(
+                ActionError {
+                    index: Some(0),
+                    kind: ActionErrorKind::DelegateActionInvalidNonce {
+                        delegate_nonce: 1,
+                        ak_nonce: 1
+                    },
+                }
+                .into()
+            )
+        );
+
+        // The sender can't attach more than it has, even if the relayer could pay for it.
+        let sender_balance = balance(root, &sender_id);
+        let deposit = sender_balance + 1;
+        let signed_delegate_action = create_signed_delegate_action(
+            &sender_signer,
+            receiver_id.clone(),
+            2,
+            vec![Action::Transfer(TransferAction { deposit })],
+        );
+        let (outcomes, _) = run(&mut root, 3, signed_delegate_action);
+        assert_eq!(
+            delegate_status(&outcomes),
+            ExecutionStatus::Failure(
+                ActionError {
+                    index: Some(0),
+                    kind: ActionErrorKind::DelegateActionLackBalance {
+                        sender_id: sender_id.clone(),
+                        amount: deposit
+                    },
+                }
+                .into()
+            )
+        );
+        assert_eq!(balance(root, &sender_id), sender_balance);
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_global_contracts")]
+    fn test_apply_parallel_global_contracts() {
+        let (_, tries, root, mut apply_state, signer, epoch_info_provider) =
+            setup_runtime(to_yocto(1_000_000), 0, 10u64.pow(15));
+        apply_state.gas_limit = Some(Gas::max_value());
+        let accounts: Vec<AccountId> =
+            vec![alice_account(), bob_account(), "carol.near".parse().unwrap()];
+        let mut state_update = tries.new_trie_update(ShardUId::single_shard(), root);
+        for account_id in accounts[1..].iter() {
+            set_account(
+                &mut state_update,
+                account_id.clone(),
+                &account_new(to_yocto(1_000_000), CryptoHash::default()),
+            );
+        }
+        state_update.commit(StateChangeCause::InitialState);
+        let trie_changes = state_update.finalize().unwrap().0;
+        let (store_update, root) =
+            tries.apply_all(&trie_changes, ShardUId::single_shard()).unwrap();
+        store_update.commit().unwrap();
+
+        // Sequentially, only the first deploy burns the storage cost and the code can be used
+        // right after it by any account. Executed in parallel by receiver, alice would burn the
+        // cost again and carol wouldn't find the code.
+        let wasm_code = near_test_contracts::rs_contract().to_vec();
+        let code_hash = hash(&wasm_code);
+        let deploy = Action::DeployGlobalContract(DeployGlobalContractAction { code: wasm_code });
+        let use_code = Action::UseGlobalContract(UseGlobalContractAction { code_hash });
+        let mut receipts = generate_receipts(to_yocto(10_000), 4);
+        receipts.extend(
+            [
+                (&accounts[1], deploy.clone()),
+                (&accounts[2], use_code.clone()),
+                (&accounts[0], deploy),
+                (&accounts[0], use_code),
+            ]
+            .into_iter()
+            .enumerate()
+            .map(|(i, (account_id, action))| {
+                let mut receipt =
+                    create_receipts_with_actions(account_id.clone(), signer.clone(), vec![action])
+                        .remove(0);
+                receipt.receipt_id = hash(&i.to_le_bytes());
+                receipt
+            }),
+        );
+
+        assert_parallel_apply_matches_sequential(
+            &tries,
+            root,
+            &apply_state,
+            &epoch_info_provider,
+            &[(receipts, vec![])],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_global_contracts")]
+    fn test_global_contract() {
+        let (runtime, tries, root, apply_state, signer, epoch_info_provider) =
+            setup_runtime(to_yocto(1_000_000), 0, 10u64.pow(15));
+
+        let state_update = tries.new_trie_update(ShardUId::single_shard(), root);
+        let initial_account_state = get_account(&state_update, &alice_account()).unwrap().unwrap();
+
+        let wasm_code = near_test_contracts::rs_contract().to_vec();
+        let code_hash = hash(&wasm_code);
+        let receipts = create_receipts_with_actions(
+            alice_account(),
+            signer.clone(),
+            vec![
+                Action::DeployGlobalContract(DeployGlobalContractAction {
+                    code: wasm_code.clone(),
+                }),
+                Action::UseGlobalContract(UseGlobalContractAction { code_hash }),
+            ],
+        );
+
+        // The balance checker inside `apply` verifies that the storage cost is burnt.
+        let apply_result = runtime
+            .apply(
+                tries.get_trie_for_shard(ShardUId::single_shard()),
+                root,
+                &None,
+                &apply_state,
+                &receipts,
+                &[],
+                &epoch_info_provider,
+                None,
+            )
+            .unwrap();
+        assert_eq!(apply_result.outcomes[0].outcome.status, ExecutionStatus::SuccessValue(vec![]));
+        let storage_cost = apply_state.config.storage_amount_per_byte * wasm_code.len() as Balance;
+        assert_eq!(apply_result.stats.other_burnt_amount, storage_cost);
+        let (store_update, root) =
+            tries.apply_all(&apply_result.trie_changes, ShardUId::single_shard()).unwrap();
+        store_update.commit().unwrap();
+
+        let state_update = tries.new_trie_update(ShardUId::single_shard(), root);
+        let account = get_account(&state_update, &alice_account()).unwrap().unwrap();
+        assert_eq!(account.code_hash(), code_hash);
+        assert_eq!(account.amount(), initial_account_state.amount() - storage_cost);
+        assert_eq!(account.storage_usage(), initial_account_state.storage_usage());
+        assert!(state_update
+            .get(&TrieKey::ContractCode { account_id: alice_account() })
+            .unwrap()
+            .is_none());
+        let code = get_code(&state_update, &alice_account(), Some(code_hash)).unwrap().unwrap();
+        assert_eq!(code.code(), wasm_code.as_slice());
+
+        // Using code which was never deployed fails.
+        let unknown_code_hash = hash(b"unknown");
+        let receipts = create_receipts_with_actions(
+            alice_account(),
+            signer,
+            vec![Action::UseGlobalContract(UseGlobalContractAction {
+                code_hash: unknown_code_hash,
+            })],
+        );
+        let apply_result = runtime
+            .apply(
+                tries.get_trie_for_shard(ShardUId::single_shard()),
+                root,
+                &None,
+                &apply_state,
+                &receipts,
+                &[],
+                &epoch_info_provider,
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            apply_result.outcomes[0].outcome.status,
+            ExecutionStatus::Failure(
+                ActionError {
+                    index: Some(0),
+                    kind: ActionErrorKind::GlobalContractDoesNotExist {
+                        code_hash: unknown_code_hash
+                    },
+                }
+                .into()
+            )
+        );
+    }
 }
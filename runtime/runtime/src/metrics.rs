@@ -1,4 +1,4 @@
-use near_metrics::{try_create_int_counter, IntCounter};
+use near_metrics::{try_create_int_counter, try_create_int_gauge_vec, IntCounter, IntGaugeVec};
 use once_cell::sync::Lazy;
 
 pub static ACTION_CREATE_ACCOUNT_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
@@ -65,6 +65,30 @@ pub static ACTION_DELETE_ACCOUNT_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
     )
     .unwrap()
 });
+#[cfg(feature = "protocol_feature_delegate_action")]
+pub static ACTION_DELEGATE_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    try_create_int_counter(
+        "near_action_delegate_total",
+        "The number of Delegate actions called since starting this node",
+    )
+    .unwrap()
+});
+#[cfg(feature = "protocol_feature_global_contracts")]
+pub static ACTION_DEPLOY_GLOBAL_CONTRACT_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    try_create_int_counter(
+        "near_action_deploy_global_contract_total",
+        "The number of DeployGlobalContract actions called since starting this node",
+    )
+    .unwrap()
+});
+#[cfg(feature = "protocol_feature_global_contracts")]
+pub static ACTION_USE_GLOBAL_CONTRACT_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    try_create_int_counter(
+        "near_action_use_global_contract_total",
+        "The number of UseGlobalContract actions called since starting this node",
+    )
+    .unwrap()
+});
 pub static TRANSACTION_PROCESSED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
     try_create_int_counter(
         "near_transaction_processed_total",
@@ -86,3 +110,27 @@ pub static TRANSACTION_PROCESSED_FAILED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
     )
     .unwrap()
 });
+pub static DELAYED_RECEIPTS_COUNT: Lazy<IntGaugeVec> = Lazy::new(|| {
+    try_create_int_gauge_vec(
+        "near_delayed_receipts_count",
+        "The number of receipts in the delayed receipt queue of the shard after the last applied chunk",
+        &["shard_id"],
+    )
+    .unwrap()
+});
+pub static DELAYED_RECEIPTS_PROCESSED: Lazy<IntGaugeVec> = Lazy::new(|| {
+    try_create_int_gauge_vec(
+        "near_delayed_receipts_processed",
+        "The number of delayed receipts processed in the last applied chunk of the shard",
+        &["shard_id"],
+    )
+    .unwrap()
+});
+pub static DELAYED_RECEIPTS_GAS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    try_create_int_gauge_vec(
+        "near_delayed_receipts_gas",
+        "The gas of the delayed receipt queue of the shard after the last applied chunk",
+        &["shard_id"],
+    )
+    .unwrap()
+});
@@ -0,0 +1,337 @@
+//! Parallel execution of receipts grouped by receiver account.
+//!
+//! A receipt only touches the state of its receiver (and its own entry in the delayed receipts
+//! queue), so receipts of different receivers don't conflict. Each group is executed on its own
+//! fork of the `TrieUpdate`, and the results are accepted in the original order of the receipts,
+//! exactly as if they were executed one by one. Forks don't change the same keys, so merging
+//! them back produces the same state changes as sequential execution. This is checked before
+//! accepting the results: if two groups changed the same key, the receipts are left for
+//! sequential execution.
+//!
+//! Receipts are executed in waves of the receipts which surely fit into the remaining gas of the
+//! chunk, so no receipt is executed only to be discarded.
+//!
+//! Global contract actions read and write the code shared by all the accounts of the shard, so
+//! receipts with them are a barrier: they are executed alone in their own wave, after all the
+//! receipts before them.
+
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use rayon::prelude::*;
+
+use near_primitives::errors::{IntegerOverflowError, RuntimeError};
+use near_primitives::hash::CryptoHash;
+use near_primitives::receipt::{Receipt, ReceiptEnum};
+use near_primitives::runtime::fees::RuntimeFeesConfig;
+#[cfg(feature = "protocol_feature_global_contracts")]
+use near_primitives::transaction::Action;
+use near_primitives::trie_key::TrieKey;
+use near_primitives::types::{AccountId, EpochInfoProvider, Gas, RawStateChanges};
+use near_primitives::version::ProtocolVersion;
+use near_store::{TrieHandle, TrieUpdate, TrieUpdateSnapshot};
+
+use crate::config::safe_add_gas;
+use crate::congestion_control::receipt_congestion_gas;
+use crate::{ApplyState, ExecutedReceipt, Runtime};
+
+/// Maximum number of delayed receipts read from the queue and executed in parallel at once.
+pub(crate) const DELAYED_RECEIPTS_BATCH_SIZE: usize = 128;
+
+struct GroupExecution {
+    /// Indices of the receipts of the group, in the order of execution.
+    indices: Vec<usize>,
+    /// Results of the executed receipts. Execution stops at the first failed receipt.
+    results: Vec<Result<ExecutedReceipt, RuntimeError>>,
+    /// Changes committed by the executed receipts.
+    committed: RawStateChanges,
+}
+
+impl Runtime {
+    /// Executes `receipts` in parallel, one group of receipts per receiver, and passes the
+    /// results to `accept` in the original order until it returns `false` or an error.
+    /// `remaining_gas` is the gas the chunk can still burn, only the receipts which surely fit
+    /// into it are executed at once. The state changes of the accepted receipts are merged into
+    /// `state_update`.
+    ///
+    /// If `first_delayed_index` is set, `receipts` are taken from the delayed receipts queue
+    /// starting at that index and are removed from the queue once accepted.
+    ///
+    /// Returns the number of accepted receipts. Nothing is executed if the trie doesn't allow
+    /// opening more tries over its storage, e.g. when reads are recorded for a storage proof.
+    pub(crate) fn apply_receipts_in_parallel(
+        &self,
+        state_update: &mut TrieUpdate,
+        apply_state: &ApplyState,
+        receipts: &[Receipt],
+        first_delayed_index: Option<u64>,
+        mut remaining_gas: Gas,
+        epoch_info_provider: &dyn EpochInfoProvider,
+        mut accept: impl FnMut(
+            usize,
+            Result<ExecutedReceipt, RuntimeError>,
+        ) -> Result<bool, RuntimeError>,
+    ) -> Result<usize, RuntimeError> {
+        let trie = match state_update.trie().handle() {
+            Some(trie) => trie,
+            None => return Ok(0),
+        };
+        let _span = tracing::debug_span!(
+            target: "runtime",
+            "Runtime::apply_receipts_in_parallel",
+            num_receipts = receipts.len()
+        )
+        .entered();
+        let mut num_accepted = 0;
+        while num_accepted < receipts.len() && remaining_gas > 0 {
+            let first_index = num_accepted;
+            let num_wave_receipts = num_receipts_fitting_gas(
+                &apply_state.config.transaction_costs,
+                &receipts[first_index..],
+                remaining_gas,
+                apply_state.current_protocol_version,
+            )?
+            .min(num_receipts_before_barrier(&receipts[first_index..]));
+            let num_wave_accepted = self.apply_receipts_wave(
+                &trie,
+                state_update,
+                apply_state,
+                &receipts[first_index..first_index + num_wave_receipts],
+                first_delayed_index.map(|index| index + first_index as u64),
+                epoch_info_provider,
+                |index, result| {
+                    let gas_burnt = match &result {
+                        Ok(ExecutedReceipt { outcome: Some(outcome), .. }) => {
+                            outcome.outcome.gas_burnt
+                        }
+                        _ => 0,
+                    };
+                    if !accept(first_index + index, result)? {
+                        return Ok(false);
+                    }
+                    remaining_gas = remaining_gas.saturating_sub(gas_burnt);
+                    Ok(true)
+                },
+            )?;
+            let num_wave_accepted = match num_wave_accepted {
+                Some(num_wave_accepted) => num_wave_accepted,
+                None => {
+                    tracing::debug!(
+                        target: "runtime",
+                        "Receipts of different receivers changed the same keys, executing them sequentially"
+                    );
+                    break;
+                }
+            };
+            num_accepted += num_wave_accepted;
+            if num_wave_accepted < num_wave_receipts {
+                break;
+            }
+        }
+        Ok(num_accepted)
+    }
+
+    /// Executes all `receipts` in parallel and accepts the results like
+    /// `apply_receipts_in_parallel`. Returns `None` without accepting anything if the groups
+    /// changed the same keys.
+    fn apply_receipts_wave(
+        &self,
+        trie: &TrieHandle,
+        state_update: &mut TrieUpdate,
+        apply_state: &ApplyState,
+        receipts: &[Receipt],
+        first_delayed_index: Option<u64>,
+        epoch_info_provider: &dyn EpochInfoProvider,
+        mut accept: impl FnMut(
+            usize,
+            Result<ExecutedReceipt, RuntimeError>,
+        ) -> Result<bool, RuntimeError>,
+    ) -> Result<Option<usize>, RuntimeError> {
+        let root = state_update.get_root();
+        let snapshot = state_update.snapshot();
+        let execute_groups = |groups: Vec<Vec<usize>>| -> Vec<GroupExecution> {
+            groups
+                .into_par_iter()
+                .map(|indices| {
+                    self.execute_receipt_group(
+                        trie,
+                        root,
+                        &snapshot,
+                        apply_state,
+                        receipts,
+                        first_delayed_index,
+                        indices,
+                        epoch_info_provider,
+                    )
+                })
+                .collect()
+        };
+
+        let mut executions = execute_groups(group_by_receiver(receipts));
+        if has_conflicting_changes(&executions) {
+            return Ok(None);
+        }
+        let mut results: Vec<Option<Result<ExecutedReceipt, RuntimeError>>> =
+            receipts.iter().map(|_| None).collect();
+        for execution in executions.iter_mut() {
+            for (index, result) in execution.indices.iter().zip(execution.results.drain(..)) {
+                results[*index] = Some(result);
+            }
+        }
+        let mut num_accepted = 0;
+        for (index, result) in results.into_iter().enumerate() {
+            // Receipts after a failed receipt of the same group are not executed, but the failed
+            // receipt is never accepted, so they are never reached.
+            let result = match result {
+                Some(result) => result,
+                None => break,
+            };
+            if !accept(index, result)? {
+                break;
+            }
+            num_accepted += 1;
+        }
+
+        // Groups with receipts which were not accepted are executed again up to the first such
+        // receipt, to get only the state changes of the accepted ones.
+        let mut partially_accepted = vec![];
+        for execution in executions {
+            let num_group_accepted =
+                execution.indices.iter().take_while(|index| **index < num_accepted).count();
+            if num_group_accepted == execution.indices.len() {
+                state_update.merge(execution.committed);
+            } else if num_group_accepted > 0 {
+                let mut indices = execution.indices;
+                indices.truncate(num_group_accepted);
+                partially_accepted.push(indices);
+            }
+        }
+        for execution in execute_groups(partially_accepted) {
+            state_update.merge(execution.committed);
+        }
+        Ok(Some(num_accepted))
+    }
+
+    fn execute_receipt_group(
+        &self,
+        trie: &TrieHandle,
+        root: CryptoHash,
+        snapshot: &TrieUpdateSnapshot,
+        apply_state: &ApplyState,
+        receipts: &[Receipt],
+        first_delayed_index: Option<u64>,
+        indices: Vec<usize>,
+        epoch_info_provider: &dyn EpochInfoProvider,
+    ) -> GroupExecution {
+        let mut state_update = TrieUpdate::fork(Rc::new(trie.open()), root, snapshot.clone());
+        let mut results = Vec::with_capacity(indices.len());
+        for index in indices.iter() {
+            if let Some(first_delayed_index) = first_delayed_index {
+                // Removed together with the first commit of the receipt, like in `Runtime::apply`.
+                state_update
+                    .remove(TrieKey::DelayedReceipt { index: first_delayed_index + *index as u64 });
+            }
+            let result = self.execute_receipt(
+                &mut state_update,
+                apply_state,
+                &receipts[*index],
+                epoch_info_provider,
+            );
+            let failed = result.is_err();
+            results.push(result);
+            if failed {
+                state_update.rollback();
+                break;
+            }
+        }
+        GroupExecution { indices, results, committed: state_update.into_committed() }
+    }
+}
+
+/// Splits receipt indices into groups with the same receiver, keeping the order of the receipts
+/// within a group.
+fn group_by_receiver(receipts: &[Receipt]) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = vec![];
+    let mut group_by_receiver: HashMap<&AccountId, usize> = HashMap::new();
+    for (index, receipt) in receipts.iter().enumerate() {
+        let group = *group_by_receiver.entry(&receipt.receiver_id).or_insert_with(|| {
+            groups.push(vec![]);
+            groups.len() - 1
+        });
+        groups[group].push(index);
+    }
+    groups
+}
+
+/// Whether groups changed the same key. Sequential execution orders such changes by receipt,
+/// which merging the groups can't reproduce.
+fn has_conflicting_changes(executions: &[GroupExecution]) -> bool {
+    let mut changed_keys = HashSet::new();
+    executions
+        .iter()
+        .any(|execution| execution.committed.keys().any(|key| !changed_keys.insert(key)))
+}
+
+/// Whether the receipt has to be executed after all the receipts before it and before all the
+/// receipts after it, whatever their receivers are.
+#[cfg(feature = "protocol_feature_global_contracts")]
+fn is_barrier(receipt: &Receipt) -> bool {
+    match &receipt.receipt {
+        ReceiptEnum::Action(action_receipt) => action_receipt.actions.iter().any(|action| {
+            matches!(action, Action::DeployGlobalContract(_) | Action::UseGlobalContract(_))
+        }),
+        ReceiptEnum::Data(_) => false,
+    }
+}
+
+#[cfg(not(feature = "protocol_feature_global_contracts"))]
+fn is_barrier(_receipt: &Receipt) -> bool {
+    false
+}
+
+/// Number of the first receipts which can be executed in parallel: the receipts up to the first
+/// barrier, or the barrier alone if it's the first receipt.
+fn num_receipts_before_barrier(receipts: &[Receipt]) -> usize {
+    match receipts.iter().position(is_barrier) {
+        Some(0) => 1,
+        Some(index) => index,
+        None => receipts.len(),
+    }
+}
+
+/// The most gas the receipt can burn, which is all the gas purchased for it.
+fn max_gas_burnt(
+    fees_config: &RuntimeFeesConfig,
+    receipt: &Receipt,
+    current_protocol_version: ProtocolVersion,
+) -> Result<Gas, IntegerOverflowError> {
+    match &receipt.receipt {
+        ReceiptEnum::Action(_) => safe_add_gas(
+            fees_config.action_receipt_creation_config.exec_fee(),
+            receipt_congestion_gas(fees_config, receipt, current_protocol_version)?,
+        ),
+        ReceiptEnum::Data(_) => Ok(0),
+    }
+}
+
+/// Number of the first receipts which are executed whatever gas the previous ones burn, i.e. the
+/// gas all receipts before each of them can burn is below `remaining_gas`. At least one receipt
+/// is returned if `remaining_gas` is positive.
+fn num_receipts_fitting_gas(
+    fees_config: &RuntimeFeesConfig,
+    receipts: &[Receipt],
+    remaining_gas: Gas,
+    current_protocol_version: ProtocolVersion,
+) -> Result<usize, IntegerOverflowError> {
+    let mut max_gas_burnt_before: Gas = 0;
+    for (index, receipt) in receipts.iter().enumerate() {
+        if max_gas_burnt_before >= remaining_gas {
+            return Ok(index);
+        }
+        max_gas_burnt_before = safe_add_gas(
+            max_gas_burnt_before,
+            max_gas_burnt(fees_config, receipt, current_protocol_version)?,
+        )?;
+    }
+    Ok(receipts.len())
+}
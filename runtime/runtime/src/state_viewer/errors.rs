@@ -42,6 +42,12 @@ pub enum ViewStateError {
     InternalError { error_message: String },
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum ViewDelayedReceiptsError {
+    #[error("Internal error: #{error_message}")]
+    InternalError { error_message: String },
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum CallFunctionError {
     #[error("Account ID \"{requested_account_id}\" is invalid")]
@@ -94,6 +100,12 @@ impl From<near_primitives::errors::StorageError> for ViewStateError {
     }
 }
 
+impl From<near_primitives::errors::StorageError> for ViewDelayedReceiptsError {
+    fn from(storage_error: near_primitives::errors::StorageError) -> Self {
+        Self::InternalError { error_message: storage_error.to_string() }
+    }
+}
+
 impl From<near_primitives::errors::StorageError> for CallFunctionError {
     fn from(storage_error: near_primitives::errors::StorageError) -> Self {
         Self::InternalError { error_message: storage_error.to_string() }
@@ -7,20 +7,24 @@ use near_primitives::{
     borsh::BorshDeserialize,
     contract::ContractCode,
     hash::CryptoHash,
-    receipt::ActionReceipt,
+    receipt::{ActionReceipt, DelayedReceiptIndices, Receipt},
     runtime::{
         apply_state::ApplyState,
+        fees::StorageUsageConfig,
         migration_data::{MigrationData, MigrationFlags},
     },
     serialize::to_base64,
     transaction::FunctionCallAction,
-    trie_key::trie_key_parsers,
-    types::{AccountId, EpochInfoProvider, Gas},
-    views::{StateItem, ViewApplyState, ViewStateResult},
+    trie_key::{trie_key_parsers, TrieKey},
+    types::{AccountId, EpochInfoProvider, Gas, ShardId},
+    views::{
+        DelayedReceiptsView, StateItem, StorageBreakdownView, StoragePrefixView, ViewApplyState,
+        ViewStateResult,
+    },
 };
-use near_store::{get_access_key, get_account, get_code, TrieUpdate};
+use near_store::{get, get_access_key, get_account, get_code, get_code_storage_usage, TrieUpdate};
 use near_vm_logic::{ReturnData, ViewConfig};
-use std::{str, sync::Arc, time::Instant};
+use std::{collections::BTreeMap, str, sync::Arc, time::Instant};
 use tracing::debug;
 
 pub mod errors;
@@ -122,9 +126,7 @@ impl TrieViewer {
     ) -> Result<ViewStateResult, errors::ViewStateError> {
         match get_account(state_update, account_id)? {
             Some(account) => {
-                let code_len = get_code(state_update, account_id, Some(account.code_hash()))?
-                    .map(|c| c.code().len() as u64)
-                    .unwrap_or_default();
+                let code_len = get_code_storage_usage(state_update, account_id)?;
                 if let Some(limit) = self.state_size_limit {
                     if account.storage_usage().saturating_sub(code_len) > limit {
                         return Err(errors::ViewStateError::AccountStateTooLarge {
@@ -160,6 +162,128 @@ impl TrieViewer {
         Ok(ViewStateResult { values, proof: vec![] })
     }
 
+    /// Computes the storage used by the account, split into the account record, contract code,
+    /// access keys and contract data grouped by the first `data_prefix_len` bytes of the keys
+    /// (1 if not given). Uses the same accounting as the runtime, so the total should match the
+    /// `storage_usage` stored in the account.
+    pub fn view_storage_breakdown(
+        &self,
+        state_update: &TrieUpdate,
+        account_id: &AccountId,
+        data_prefix_len: Option<u32>,
+        config: &StorageUsageConfig,
+    ) -> Result<StorageBreakdownView, errors::ViewStateError> {
+        let account = get_account(state_update, account_id)?.ok_or_else(|| {
+            errors::ViewStateError::AccountDoesNotExist { requested_account_id: account_id.clone() }
+        })?;
+        let contract_code = get_code_storage_usage(state_update, account_id)?;
+        if let Some(limit) = self.state_size_limit {
+            if account.storage_usage().saturating_sub(contract_code) > limit {
+                return Err(errors::ViewStateError::AccountStateTooLarge {
+                    requested_account_id: account_id.clone(),
+                });
+            }
+        }
+
+        let mut num_access_keys = 0;
+        let mut access_keys = 0;
+        let query = trie_key_parsers::get_raw_prefix_for_access_keys(account_id);
+        let mut iter = state_update.trie.iter(&state_update.get_root())?;
+        iter.seek(&query)?;
+        for item in iter {
+            let (key, value) = item?;
+            if !key.starts_with(query.as_ref()) {
+                break;
+            }
+            num_access_keys += 1;
+            access_keys += config.num_extra_bytes_record
+                + (key.len() - query.len()) as u64
+                + value.len() as u64;
+        }
+
+        let data_prefix_len = data_prefix_len.unwrap_or(1) as usize;
+        let mut contract_data: BTreeMap<Vec<u8>, StoragePrefixView> = BTreeMap::new();
+        let query = trie_key_parsers::get_raw_prefix_for_contract_data(account_id, &[]);
+        let mut iter = state_update.trie.iter(&state_update.get_root())?;
+        iter.seek(&query)?;
+        for item in iter {
+            let (key, value) = item?;
+            if !key.starts_with(query.as_ref()) {
+                break;
+            }
+            let data_key = &key[query.len()..];
+            let prefix = &data_key[..std::cmp::min(data_key.len(), data_prefix_len)];
+            let entry = contract_data.entry(prefix.to_vec()).or_insert_with(|| StoragePrefixView {
+                prefix: prefix.to_vec().into(),
+                num_records: 0,
+                storage_usage: 0,
+            });
+            entry.num_records += 1;
+            entry.storage_usage +=
+                config.num_extra_bytes_record + data_key.len() as u64 + value.len() as u64;
+        }
+        let contract_data: Vec<_> = contract_data.into_values().collect();
+
+        let total = config.num_bytes_account
+            + contract_code
+            + access_keys
+            + contract_data.iter().map(|data| data.storage_usage).sum::<u64>();
+        Ok(StorageBreakdownView {
+            account: config.num_bytes_account,
+            contract_code,
+            num_access_keys,
+            access_keys,
+            contract_data,
+            total,
+            stored_total: account.storage_usage(),
+        })
+    }
+
+    /// Returns the length, total gas and oldest receipt of the delayed receipt queue, along with
+    /// up to `limit` queued receipts starting at `from_index` (or at the head of the queue if not
+    /// given). Only the receipts of the requested page and the oldest receipt are read.
+    pub fn view_delayed_receipts(
+        &self,
+        state_update: &TrieUpdate,
+        shard_id: ShardId,
+        from_index: Option<u64>,
+        limit: u64,
+    ) -> Result<DelayedReceiptsView, errors::ViewDelayedReceiptsError> {
+        let indices: DelayedReceiptIndices =
+            get(state_update, &TrieKey::DelayedReceiptIndices)?.unwrap_or_default();
+        // The counter is maintained by congestion control and is missing before it's enabled.
+        let total_gas: Gas = get(state_update, &TrieKey::DelayedReceiptsGas)?.unwrap_or_default();
+        let get_receipt = |index| -> Result<Receipt, errors::ViewDelayedReceiptsError> {
+            get(state_update, &TrieKey::DelayedReceipt { index })?.ok_or_else(|| {
+                errors::ViewDelayedReceiptsError::InternalError {
+                    error_message: format!("Delayed receipt #{} should be in the state", index),
+                }
+            })
+        };
+
+        let oldest_receipt_id = if indices.first_index < indices.next_available_index {
+            Some(get_receipt(indices.first_index)?.receipt_id)
+        } else {
+            None
+        };
+        let from_index = from_index
+            .map_or(indices.first_index, |index| std::cmp::max(index, indices.first_index));
+        let to_index =
+            std::cmp::min(from_index.saturating_add(limit), indices.next_available_index);
+        let receipts = (from_index..to_index)
+            .map(|index| get_receipt(index).map(Into::into))
+            .collect::<Result<_, _>>()?;
+
+        Ok(DelayedReceiptsView {
+            shard_id,
+            first_index: indices.first_index,
+            length: indices.next_available_index - indices.first_index,
+            total_gas,
+            oldest_receipt_id,
+            receipts,
+        })
+    }
+
     pub fn call_function(
         &self,
         mut state_update: TrieUpdate,
@@ -195,11 +319,14 @@ impl TrieViewer {
         let config = config_store.get_config(PROTOCOL_VERSION);
         let apply_state = ApplyState {
             block_index: view_state.block_height,
+            transaction_validity_height: view_state.block_height,
             // Used for legacy reasons
             prev_block_hash: view_state.prev_block_hash,
             block_hash: view_state.block_hash,
             epoch_id: view_state.epoch_id.clone(),
             epoch_height: view_state.epoch_height,
+            // Not used by view calls
+            shard_id: 0,
             gas_price: 0,
             block_timestamp: view_state.block_timestamp,
             gas_limit: None,
@@ -210,6 +337,7 @@ impl TrieViewer {
             is_new_chunk: false,
             migration_data: Arc::new(MigrationData::default()),
             migration_flags: MigrationFlags::default(),
+            congestion_info: Default::default(),
         };
         let action_receipt = ActionReceipt {
             signer_id: originator_id.clone(),
@@ -237,6 +365,7 @@ impl TrieViewer {
             config,
             true,
             Some(ViewConfig { max_gas_burnt: self.max_gas_burnt_view }),
+            false,
         )
         .outcome_error();
         let elapsed = now.elapsed();
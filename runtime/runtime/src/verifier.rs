@@ -1,7 +1,6 @@
 use near_crypto::key_conversion::is_valid_staking_key;
 use near_primitives::runtime::get_insufficient_storage_stake;
 use near_primitives::{
-    account::AccessKeyPermission,
     config::VMLimitConfig,
     errors::{
         ActionsValidationError, InvalidAccessKeyError, InvalidTxError, ReceiptValidationError,
@@ -10,7 +9,7 @@ use near_primitives::{
     receipt::{ActionReceipt, DataReceipt, Receipt, ReceiptEnum},
     transaction::{
         Action, AddKeyAction, DeployContractAction, FunctionCallAction, SignedTransaction,
-        StakeAction,
+        StakeAction, TransactionVersion,
     },
     types::{AccountId, Balance},
     version::ProtocolVersion,
@@ -19,11 +18,21 @@ use near_store::{
     get_access_key, get_account, set_access_key, set_account, StorageError, TrieUpdate,
 };
 
+#[cfg(feature = "protocol_feature_delegate_action")]
+use crate::config::inner_actions;
 use crate::config::{total_prepaid_gas, tx_cost, TransactionCost};
 use crate::VerificationResult;
+#[cfg(feature = "protocol_feature_delegate_action")]
+use crate::{ActionResult, ApplyState};
+#[cfg(feature = "protocol_feature_access_key_expiration")]
+use near_primitives::account::AccessKeyPermission;
 use near_primitives::checked_feature;
+#[cfg(feature = "protocol_feature_delegate_action")]
+use near_primitives::errors::ActionErrorKind;
 use near_primitives::runtime::config::RuntimeConfig;
-use near_primitives::types::BlockHeight;
+#[cfg(feature = "protocol_feature_delegate_action")]
+use near_primitives::transaction::DelegateAction;
+use near_primitives::types::{BlockHeight, EpochHeight};
 
 /// Validates the transaction without using the state. It allows any node to validate a
 /// transaction before forwarding it to the node that tracks the `signer_id` account.
@@ -55,6 +64,81 @@ pub fn validate_transaction(
         .into());
     }
 
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    if !checked_feature!(
+        "protocol_feature_delegate_action",
+        DelegateAction,
+        current_protocol_version
+    ) && transaction.actions.iter().any(|action| matches!(action, Action::Delegate(_)))
+    {
+        return Err(InvalidTxError::ActionsValidation(
+            ActionsValidationError::UnsupportedProtocolFeature {
+                protocol_feature: "DelegateAction".to_string(),
+                version: current_protocol_version,
+            },
+        )
+        .into());
+    }
+
+    #[cfg(feature = "protocol_feature_access_key_expiration")]
+    if !checked_feature!(
+        "protocol_feature_access_key_expiration",
+        AccessKeyExpiration,
+        current_protocol_version
+    ) && transaction.actions.iter().any(adds_expiring_access_key)
+    {
+        return Err(InvalidTxError::ActionsValidation(
+            ActionsValidationError::UnsupportedProtocolFeature {
+                protocol_feature: "AccessKeyExpiration".to_string(),
+                version: current_protocol_version,
+            },
+        )
+        .into());
+    }
+
+    #[cfg(feature = "protocol_feature_global_contracts")]
+    if !checked_feature!(
+        "protocol_feature_global_contracts",
+        GlobalContracts,
+        current_protocol_version
+    ) && transaction.actions.iter().any(uses_global_contracts)
+    {
+        return Err(InvalidTxError::ActionsValidation(
+            ActionsValidationError::UnsupportedProtocolFeature {
+                protocol_feature: "GlobalContracts".to_string(),
+                version: current_protocol_version,
+            },
+        )
+        .into());
+    }
+
+    if transaction.version() > TransactionVersion::V0
+        && !checked_feature!(
+            "protocol_feature_tx_validity_window",
+            TransactionValidityWindow,
+            current_protocol_version
+        )
+    {
+        return Err(InvalidTxError::ActionsValidation(
+            ActionsValidationError::UnsupportedProtocolFeature {
+                protocol_feature: "TransactionValidityWindow".to_string(),
+                version: current_protocol_version,
+            },
+        )
+        .into());
+    }
+    if let (Some(valid_from_height), Some(valid_until_height)) =
+        (transaction.valid_from_height, transaction.valid_until_height)
+    {
+        if valid_from_height > valid_until_height {
+            return Err(InvalidTxError::InvalidValidityWindow {
+                valid_from_height,
+                valid_until_height,
+            }
+            .into());
+        }
+    }
+
     validate_actions(&config.wasm_config.limit_config, &transaction.actions)
         .map_err(InvalidTxError::ActionsValidation)?;
 
@@ -78,7 +162,8 @@ pub fn verify_and_charge_transaction(
     gas_price: Balance,
     signed_transaction: &SignedTransaction,
     verify_signature: bool,
-    #[allow(unused)] block_height: Option<BlockHeight>,
+    block_height: BlockHeight,
+    #[allow(unused)] epoch_height: Option<EpochHeight>,
     current_protocol_version: ProtocolVersion,
 ) -> Result<VerificationResult, RuntimeError> {
     let TransactionCost { gas_burnt, gas_remaining, receipt_gas_price, total_cost, burnt_amount } =
@@ -92,6 +177,8 @@ pub fn verify_and_charge_transaction(
     let transaction = &signed_transaction.transaction;
     let signer_id = &transaction.signer_id;
 
+    transaction.check_validity_window(block_height)?;
+
     let mut signer = match get_account(state_update, signer_id)? {
         Some(signer) => signer,
         None => {
@@ -119,21 +206,36 @@ pub fn verify_and_charge_transaction(
         .into());
     }
     if checked_feature!("stable", AccessKeyNonceRange, current_protocol_version) {
-        if let Some(height) = block_height {
-            let upper_bound =
-                height * near_primitives::account::AccessKey::ACCESS_KEY_NONCE_RANGE_MULTIPLIER;
-            if transaction.nonce >= upper_bound {
-                return Err(InvalidTxError::NonceTooLarge {
-                    tx_nonce: transaction.nonce,
-                    upper_bound,
-                }
-                .into());
-            }
+        let upper_bound =
+            block_height * near_primitives::account::AccessKey::ACCESS_KEY_NONCE_RANGE_MULTIPLIER;
+        if transaction.nonce >= upper_bound {
+            return Err(
+                InvalidTxError::NonceTooLarge { tx_nonce: transaction.nonce, upper_bound }.into()
+            );
         }
     };
 
     access_key.nonce = transaction.nonce;
 
+    #[cfg(feature = "protocol_feature_access_key_expiration")]
+    if let AccessKeyPermission::ExpiringFunctionCall(ref mut permission) = access_key.permission {
+        if permission.is_expired(block_height) {
+            return Err(InvalidTxError::InvalidAccessKeyError(
+                InvalidAccessKeyError::AccessKeyExpired {
+                    account_id: signer_id.clone(),
+                    public_key: transaction.public_key.clone(),
+                    expiration_block_height: permission
+                        .expiration_block_height
+                        .unwrap_or(block_height),
+                },
+            )
+            .into());
+        }
+        if let Some(epoch_height) = epoch_height {
+            permission.refill_allowance(epoch_height);
+        }
+    }
+
     signer.set_amount(signer.amount().checked_sub(total_cost).ok_or_else(|| {
         InvalidTxError::NotEnoughBalance {
             signer_id: signer_id.clone(),
@@ -142,9 +244,7 @@ pub fn verify_and_charge_transaction(
         }
     })?);
 
-    if let AccessKeyPermission::FunctionCall(ref mut function_call_permission) =
-        access_key.permission
-    {
+    if let Some(function_call_permission) = access_key.permission.function_call_permission_mut() {
         if let Some(ref mut allowance) = function_call_permission.allowance {
             *allowance = allowance.checked_sub(total_cost).ok_or_else(|| {
                 InvalidTxError::InvalidAccessKeyError(InvalidAccessKeyError::NotEnoughAllowance {
@@ -171,7 +271,7 @@ pub fn verify_and_charge_transaction(
         }
     };
 
-    if let AccessKeyPermission::FunctionCall(ref function_call_permission) = access_key.permission {
+    if let Some(function_call_permission) = access_key.permission.function_call_permission() {
         if transaction.actions.len() != 1 {
             return Err(InvalidTxError::InvalidAccessKeyError(
                 InvalidAccessKeyError::RequiresFullAccess,
@@ -221,6 +321,141 @@ pub fn verify_and_charge_transaction(
     Ok(VerificationResult { gas_burnt, gas_remaining, receipt_gas_price, burnt_amount })
 }
 
+/// Validates the access key which was used to sign the given delegate action and updates its
+/// nonce. Problems with the delegate action itself are reported as a failure of the action in
+/// `result`, so the relayer still pays for including it.
+#[cfg(feature = "protocol_feature_delegate_action")]
+pub(crate) fn validate_delegate_action_key(
+    state_update: &mut TrieUpdate,
+    apply_state: &ApplyState,
+    delegate_action: &DelegateAction,
+    result: &mut ActionResult,
+) -> Result<(), RuntimeError> {
+    let sender_id = &delegate_action.sender_id;
+    let public_key = &delegate_action.public_key;
+    let mut access_key = match get_access_key(state_update, sender_id, public_key)? {
+        Some(access_key) => access_key,
+        None => {
+            result.result = Err(ActionErrorKind::DelegateActionAccessKeyError(
+                InvalidAccessKeyError::AccessKeyNotFound {
+                    account_id: sender_id.clone(),
+                    public_key: public_key.clone(),
+                },
+            )
+            .into());
+            return Ok(());
+        }
+    };
+
+    if delegate_action.nonce <= access_key.nonce {
+        result.result = Err(ActionErrorKind::DelegateActionInvalidNonce {
+            delegate_nonce: delegate_action.nonce,
+            ak_nonce: access_key.nonce,
+        }
+        .into());
+        return Ok(());
+    }
+    let upper_bound = apply_state.block_index
+        * near_primitives::account::AccessKey::ACCESS_KEY_NONCE_RANGE_MULTIPLIER;
+    if delegate_action.nonce >= upper_bound {
+        result.result = Err(ActionErrorKind::DelegateActionNonceTooLarge {
+            delegate_nonce: delegate_action.nonce,
+            upper_bound,
+        }
+        .into());
+        return Ok(());
+    }
+
+    access_key.nonce = delegate_action.nonce;
+
+    #[cfg(feature = "protocol_feature_access_key_expiration")]
+    if let AccessKeyPermission::ExpiringFunctionCall(ref permission) = access_key.permission {
+        if permission.is_expired(apply_state.block_index) {
+            result.result = Err(ActionErrorKind::DelegateActionAccessKeyError(
+                InvalidAccessKeyError::AccessKeyExpired {
+                    account_id: sender_id.clone(),
+                    public_key: public_key.clone(),
+                    expiration_block_height: permission
+                        .expiration_block_height
+                        .unwrap_or(apply_state.block_index),
+                },
+            )
+            .into());
+            return Ok(());
+        }
+    }
+
+    if let Some(function_call_permission) = access_key.permission.function_call_permission() {
+        let actions = inner_actions(delegate_action);
+        let access_key_error = if actions.len() != 1 {
+            Some(InvalidAccessKeyError::RequiresFullAccess)
+        } else if let Some(Action::FunctionCall(ref function_call)) = actions.get(0) {
+            if function_call.deposit > 0 {
+                Some(InvalidAccessKeyError::DepositWithFunctionCall)
+            } else if delegate_action.receiver_id.as_ref() != function_call_permission.receiver_id {
+                Some(InvalidAccessKeyError::ReceiverMismatch {
+                    tx_receiver: delegate_action.receiver_id.clone(),
+                    ak_receiver: function_call_permission.receiver_id.clone(),
+                })
+            } else if !function_call_permission.method_names.is_empty()
+                && function_call_permission
+                    .method_names
+                    .iter()
+                    .all(|method_name| &function_call.method_name != method_name)
+            {
+                Some(InvalidAccessKeyError::MethodNameMismatch {
+                    method_name: function_call.method_name.clone(),
+                })
+            } else {
+                None
+            }
+        } else {
+            Some(InvalidAccessKeyError::RequiresFullAccess)
+        };
+        if let Some(access_key_error) = access_key_error {
+            result.result =
+                Err(ActionErrorKind::DelegateActionAccessKeyError(access_key_error).into());
+            return Ok(());
+        }
+    }
+
+    set_access_key(state_update, sender_id.clone(), public_key.clone(), &access_key);
+
+    Ok(())
+}
+
+/// Returns whether the action adds an access key with an expiring permission, including
+/// `AddKey` actions nested inside a delegate action.
+#[cfg(feature = "protocol_feature_access_key_expiration")]
+fn adds_expiring_access_key(action: &Action) -> bool {
+    match action {
+        Action::AddKey(add_key) => {
+            matches!(add_key.access_key.permission, AccessKeyPermission::ExpiringFunctionCall(_))
+        }
+        #[cfg(feature = "protocol_feature_delegate_action")]
+        Action::Delegate(signed_delegate_action) => {
+            inner_actions(&signed_delegate_action.delegate_action)
+                .iter()
+                .any(adds_expiring_access_key)
+        }
+        _ => false,
+    }
+}
+
+/// Returns whether the action deploys or uses global contract code, including such actions
+/// nested inside a delegate action.
+#[cfg(feature = "protocol_feature_global_contracts")]
+fn uses_global_contracts(action: &Action) -> bool {
+    match action {
+        Action::DeployGlobalContract(_) | Action::UseGlobalContract(_) => true,
+        #[cfg(feature = "protocol_feature_delegate_action")]
+        Action::Delegate(signed_delegate_action) => {
+            inner_actions(&signed_delegate_action.delegate_action).iter().any(uses_global_contracts)
+        }
+        _ => false,
+    }
+}
+
 /// Validates a given receipt. Checks validity of the Action or Data receipt.
 pub(crate) fn validate_receipt(
     limit_config: &VMLimitConfig,
@@ -293,6 +528,8 @@ pub(crate) fn validate_actions(
         });
     }
 
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    let mut found_delegate_action = false;
     let mut iter = actions.iter().peekable();
     while let Some(action) = iter.next() {
         if let Action::DeleteAccount(_) = action {
@@ -300,6 +537,13 @@ pub(crate) fn validate_actions(
                 return Err(ActionsValidationError::DeleteActionMustBeFinal);
             }
         }
+        #[cfg(feature = "protocol_feature_delegate_action")]
+        if let Action::Delegate(_) = action {
+            if found_delegate_action {
+                return Err(ActionsValidationError::DelegateActionMustBeOnlyOne);
+            }
+            found_delegate_action = true;
+        }
         validate_action(limit_config, action)?;
     }
 
@@ -331,6 +575,14 @@ pub fn validate_action(
         Action::AddKey(a) => validate_add_key_action(limit_config, a),
         Action::DeleteKey(_) => Ok(()),
         Action::DeleteAccount(_) => Ok(()),
+        #[cfg(feature = "protocol_feature_delegate_action")]
+        Action::Delegate(a) => validate_actions(limit_config, &inner_actions(&a.delegate_action)),
+        #[cfg(feature = "protocol_feature_global_contracts")]
+        Action::DeployGlobalContract(a) => {
+            validate_contract_size(limit_config, a.code.len() as u64)
+        }
+        #[cfg(feature = "protocol_feature_global_contracts")]
+        Action::UseGlobalContract(_) => Ok(()),
     }
 }
 
@@ -339,9 +591,16 @@ fn validate_deploy_contract_action(
     limit_config: &VMLimitConfig,
     action: &DeployContractAction,
 ) -> Result<(), ActionsValidationError> {
-    if action.code.len() as u64 > limit_config.max_contract_size {
+    validate_contract_size(limit_config, action.code.len() as u64)
+}
+
+fn validate_contract_size(
+    limit_config: &VMLimitConfig,
+    size: u64,
+) -> Result<(), ActionsValidationError> {
+    if size > limit_config.max_contract_size {
         return Err(ActionsValidationError::ContractSizeExceeded {
-            size: action.code.len() as u64,
+            size,
             limit: limit_config.max_contract_size,
         });
     }
@@ -394,7 +653,7 @@ fn validate_add_key_action(
     limit_config: &VMLimitConfig,
     action: &AddKeyAction,
 ) -> Result<(), ActionsValidationError> {
-    if let AccessKeyPermission::FunctionCall(fc) = &action.access_key.permission {
+    if let Some(fc) = action.access_key.permission.function_call_permission() {
         // Checking method name length limits
         let mut total_number_of_bytes = 0;
         for method_name in &fc.method_names {
@@ -424,13 +683,24 @@ mod tests {
     use std::sync::Arc;
 
     use near_crypto::{InMemorySigner, KeyType, PublicKey, Signer};
-    use near_primitives::account::{AccessKey, Account, FunctionCallPermission};
+    use near_primitives::account::{
+        AccessKey, AccessKeyPermission, Account, FunctionCallPermission,
+    };
     use near_primitives::hash::{hash, CryptoHash};
     use near_primitives::test_utils::account_new;
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    use near_primitives::transaction::SignedDelegateAction;
+    #[cfg(feature = "protocol_feature_tx_validity_window")]
+    use near_primitives::transaction::Transaction;
     use near_primitives::transaction::{
         CreateAccountAction, DeleteAccountAction, DeleteKeyAction, StakeAction, TransferAction,
     };
     use near_primitives::types::{AccountId, Balance, MerkleHash, StateChangeCause};
+    #[cfg(any(
+        feature = "protocol_feature_delegate_action",
+        feature = "protocol_feature_tx_validity_window"
+    ))]
+    use near_primitives::version::ProtocolFeature;
     use near_primitives::version::PROTOCOL_VERSION;
     use near_store::test_utils::create_tries;
     use testlib::runtime_utils::{alice_account, bob_account, eve_dot_alice_account};
@@ -507,6 +777,7 @@ mod tests {
                 gas_price,
                 signed_transaction,
                 true,
+                1,
                 None,
                 PROTOCOL_VERSION,
             )
@@ -540,6 +811,7 @@ mod tests {
             gas_price,
             &transaction,
             true,
+            1,
             None,
             PROTOCOL_VERSION,
         )
@@ -612,6 +884,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 false,
+                1,
                 None,
                 PROTOCOL_VERSION,
             )
@@ -679,6 +952,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                1,
                 None,
                 PROTOCOL_VERSION,
             )
@@ -712,6 +986,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                1,
                 None,
                 PROTOCOL_VERSION,
             )
@@ -761,6 +1036,7 @@ mod tests {
                 CryptoHash::default(),
             ),
             true,
+            1,
             None,
             PROTOCOL_VERSION,
         )
@@ -813,6 +1089,7 @@ mod tests {
                 CryptoHash::default(),
             ),
             true,
+            1,
             None,
             PROTOCOL_VERSION,
         )
@@ -830,6 +1107,129 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "protocol_feature_access_key_expiration")]
+    fn setup_expiring_key() -> (Arc<InMemorySigner>, TrieUpdate, Balance, SignedTransaction) {
+        let (signer, state_update, gas_price) = setup_common(
+            TESTING_INIT_BALANCE,
+            0,
+            Some(AccessKey {
+                nonce: 0,
+                permission: AccessKeyPermission::ExpiringFunctionCall(
+                    ExpiringFunctionCallPermission {
+                        function_call: FunctionCallPermission {
+                            allowance: Some(100),
+                            receiver_id: bob_account().into(),
+                            method_names: vec![],
+                        },
+                        expiration_block_height: Some(10),
+                        allowance_per_epoch: Some(NEAR_BASE),
+                        allowance_epoch_height: 1,
+                    },
+                ),
+            }),
+        );
+        let transaction = SignedTransaction::from_actions(
+            1,
+            alice_account(),
+            bob_account(),
+            &*signer,
+            vec![Action::FunctionCall(FunctionCallAction {
+                method_name: "hello".to_string(),
+                args: b"abc".to_vec(),
+                gas: 300,
+                deposit: 0,
+            })],
+            CryptoHash::default(),
+        );
+        (signer, state_update, gas_price, transaction)
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_access_key_expiration")]
+    fn test_validate_transaction_expired_access_key() {
+        let config = RuntimeConfig::test();
+        let (signer, mut state_update, gas_price, transaction) = setup_expiring_key();
+
+        let err = verify_and_charge_transaction(
+            &config,
+            &mut state_update,
+            gas_price,
+            &transaction,
+            true,
+            11,
+            Some(2),
+            PROTOCOL_VERSION,
+        )
+        .expect_err("expected an error");
+        assert_eq!(
+            err,
+            InvalidTxError::InvalidAccessKeyError(InvalidAccessKeyError::AccessKeyExpired {
+                account_id: alice_account(),
+                public_key: signer.public_key(),
+                expiration_block_height: 10,
+            })
+            .into()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_access_key_expiration")]
+    fn test_validate_transaction_refills_allowance() {
+        let config = RuntimeConfig::test();
+        let (signer, mut state_update, gas_price, transaction) = setup_expiring_key();
+
+        // The allowance is not refilled again within the same epoch.
+        let err = verify_and_charge_transaction(
+            &config,
+            &mut state_update,
+            gas_price,
+            &transaction,
+            true,
+            10,
+            Some(1),
+            PROTOCOL_VERSION,
+        )
+        .expect_err("expected an error");
+        assert!(matches!(
+            err,
+            RuntimeError::InvalidTxError(InvalidTxError::InvalidAccessKeyError(
+                InvalidAccessKeyError::NotEnoughAllowance { .. }
+            ))
+        ));
+
+        let verification_result = verify_and_charge_transaction(
+            &config,
+            &mut state_update,
+            gas_price,
+            &transaction,
+            true,
+            10,
+            Some(2),
+            PROTOCOL_VERSION,
+        )
+        .expect("valid transaction");
+
+        let access_key =
+            get_access_key(&state_update, &alice_account(), &signer.public_key()).unwrap().unwrap();
+        let expected_allowance = NEAR_BASE
+            - Balance::from(verification_result.gas_remaining)
+                * verification_result.receipt_gas_price
+            - verification_result.burnt_amount;
+        assert_eq!(
+            access_key.permission,
+            AccessKeyPermission::ExpiringFunctionCall(ExpiringFunctionCallPermission {
+                function_call: FunctionCallPermission {
+                    allowance: Some(expected_allowance),
+                    receiver_id: bob_account().into(),
+                    method_names: vec![],
+                },
+                expiration_block_height: Some(10),
+                allowance_per_epoch: Some(NEAR_BASE),
+                allowance_epoch_height: 2,
+            })
+        );
+    }
+
     /// Setup: account has 1B yoctoN and is 180 bytes. Storage requirement is 1M per byte.
     /// Test that such account can not send 950M yoctoN out as that will leave it under storage requirements.
     #[test]
@@ -855,6 +1255,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                1,
                 None,
                 PROTOCOL_VERSION,
             )
@@ -906,6 +1307,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                1,
                 None,
                 PROTOCOL_VERSION,
             )
@@ -929,6 +1331,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                1,
                 None,
                 PROTOCOL_VERSION,
             )
@@ -952,6 +1355,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                1,
                 None,
                 PROTOCOL_VERSION,
             )
@@ -997,6 +1401,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                1,
                 None,
                 PROTOCOL_VERSION,
             )
@@ -1045,6 +1450,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                1,
                 None,
                 PROTOCOL_VERSION,
             )
@@ -1090,6 +1496,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                1,
                 None,
                 PROTOCOL_VERSION,
             )
@@ -1126,6 +1533,7 @@ mod tests {
                 gas_price,
                 &transaction,
                 false,
+                1,
                 None,
                 PROTOCOL_VERSION,
             )
@@ -1143,12 +1551,94 @@ mod tests {
             gas_price,
             &transaction,
             false,
+            1,
             None,
             PROTOCOL_VERSION,
         )
         .expect("valid transaction");
     }
 
+    #[test]
+    #[cfg(feature = "protocol_feature_tx_validity_window")]
+    fn test_validate_transaction_validity_window() {
+        let config = RuntimeConfig::test();
+        let (signer, mut state_update, gas_price) =
+            setup_common(TESTING_INIT_BALANCE, 0, Some(AccessKey::full_access()));
+        let transaction = Transaction {
+            valid_from_height: Some(10),
+            valid_until_height: Some(20),
+            ..Transaction::new(
+                alice_account(),
+                signer.public_key(),
+                bob_account(),
+                1,
+                CryptoHash::default(),
+            )
+        }
+        .sign(&*signer);
+
+        let verify = |state_update: &mut TrieUpdate, height| {
+            verify_and_charge_transaction(
+                &config,
+                state_update,
+                gas_price,
+                &transaction,
+                true,
+                height,
+                None,
+                PROTOCOL_VERSION,
+            )
+        };
+        assert_eq!(
+            verify(&mut state_update, 9).expect_err("expected an error"),
+            RuntimeError::InvalidTxError(InvalidTxError::NotValidYet {
+                valid_from_height: 10,
+                height: 9
+            }),
+        );
+        assert_eq!(
+            verify(&mut state_update, 21).expect_err("expected an error"),
+            RuntimeError::InvalidTxError(InvalidTxError::ValidityWindowExpired {
+                valid_until_height: 20,
+                height: 21
+            }),
+        );
+        verify(&mut state_update, 20).expect("valid transaction");
+
+        let version = ProtocolFeature::TransactionValidityWindow.protocol_version() - 1;
+        assert_eq!(
+            validate_transaction(&config, gas_price, &transaction, true, version)
+                .expect_err("expected an error"),
+            RuntimeError::InvalidTxError(InvalidTxError::ActionsValidation(
+                ActionsValidationError::UnsupportedProtocolFeature {
+                    protocol_feature: "TransactionValidityWindow".to_string(),
+                    version,
+                }
+            )),
+        );
+
+        let transaction = Transaction {
+            valid_from_height: Some(20),
+            valid_until_height: Some(10),
+            ..Transaction::new(
+                alice_account(),
+                signer.public_key(),
+                bob_account(),
+                2,
+                CryptoHash::default(),
+            )
+        }
+        .sign(&*signer);
+        assert_eq!(
+            validate_transaction(&config, gas_price, &transaction, true, PROTOCOL_VERSION)
+                .expect_err("expected an error"),
+            RuntimeError::InvalidTxError(InvalidTxError::InvalidValidityWindow {
+                valid_from_height: 20,
+                valid_until_height: 10
+            }),
+        );
+    }
+
     // Receipts
 
     #[test]
@@ -1474,4 +1964,80 @@ mod tests {
         )
         .expect("valid action");
     }
+
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    fn create_signed_delegate_action(actions: Vec<Action>) -> Action {
+        let signer = InMemorySigner::from_seed(bob_account(), KeyType::ED25519, "bob");
+        let delegate_action = DelegateAction {
+            sender_id: bob_account(),
+            receiver_id: alice_account(),
+            actions: actions.into_iter().map(|action| action.try_into().unwrap()).collect(),
+            nonce: 1,
+            max_block_height: 100,
+            public_key: signer.public_key(),
+        };
+        let signature = signer.sign(delegate_action.get_hash().as_ref());
+        Action::Delegate(Box::new(SignedDelegateAction { delegate_action, signature }))
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    fn test_validate_delegate_action_must_be_only_one() {
+        let delegate_action =
+            create_signed_delegate_action(vec![Action::Transfer(TransferAction { deposit: 100 })]);
+        validate_actions(&VMLimitConfig::test(), &[delegate_action.clone()]).expect("valid action");
+        assert_eq!(
+            validate_actions(&VMLimitConfig::test(), &[delegate_action.clone(), delegate_action])
+                .expect_err("Expected an error"),
+            ActionsValidationError::DelegateActionMustBeOnlyOne,
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    fn test_validate_delegate_action_inner_actions() {
+        let delegate_action =
+            create_signed_delegate_action(vec![Action::FunctionCall(FunctionCallAction {
+                method_name: "hello".to_string(),
+                args: b"abc".to_vec(),
+                gas: 0,
+                deposit: 0,
+            })]);
+        assert_eq!(
+            validate_action(&VMLimitConfig::test(), &delegate_action)
+                .expect_err("Expected an error"),
+            ActionsValidationError::FunctionCallZeroAttachedGas,
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    fn test_validate_transaction_delegate_action_unsupported() {
+        let config = RuntimeConfig::test();
+        let (signer, _, gas_price) =
+            setup_common(TESTING_INIT_BALANCE, 0, Some(AccessKey::full_access()));
+        let transaction = SignedTransaction::from_actions(
+            1,
+            alice_account(),
+            bob_account(),
+            &*signer,
+            vec![create_signed_delegate_action(vec![Action::Transfer(TransferAction {
+                deposit: 100,
+            })])],
+            CryptoHash::default(),
+        );
+        let version = ProtocolFeature::DelegateAction.protocol_version() - 1;
+        assert_eq!(
+            validate_transaction(&config, gas_price, &transaction, true, version)
+                .expect_err("expected an error"),
+            RuntimeError::InvalidTxError(InvalidTxError::ActionsValidation(
+                ActionsValidationError::UnsupportedProtocolFeature {
+                    protocol_feature: "DelegateAction".to_string(),
+                    version,
+                }
+            )),
+        );
+        validate_transaction(&config, gas_price, &transaction, true, PROTOCOL_VERSION)
+            .expect("valid transaction");
+    }
 }
@@ -83,10 +83,12 @@ impl StandaloneRuntime {
 
         let apply_state = ApplyState {
             block_index: 1,
+            transaction_validity_height: 1,
             prev_block_hash: Default::default(),
             block_hash: Default::default(),
             epoch_id: Default::default(),
             epoch_height: 0,
+            shard_id: 0,
             gas_price: 100,
             block_timestamp: 0,
             gas_limit: None,
@@ -97,6 +99,7 @@ impl StandaloneRuntime {
             is_new_chunk: true,
             migration_data: Arc::new(MigrationData::default()),
             migration_flags: MigrationFlags::default(),
+            congestion_info: Default::default(),
         };
 
         Self {
@@ -133,6 +136,7 @@ impl StandaloneRuntime {
         self.root = root;
         store_update.commit().unwrap();
         self.apply_state.block_index += 1;
+        self.apply_state.transaction_validity_height += 1;
 
         (apply_result.outgoing_receipts, apply_result.outcomes)
     }
@@ -654,9 +654,9 @@ impl Account {
     pub fn function_call_keys(&self, receiver_id: &str) -> Vec<InMemorySigner> {
         let mut function_call_keys = vec![];
         for (_, key) in &self.keys {
-            match &key.access_key.permission {
-                AccessKeyPermission::FullAccess => function_call_keys.push(key.signer.clone()),
-                AccessKeyPermission::FunctionCall(function_call_permission) => {
+            match key.access_key.permission.function_call_permission() {
+                None => function_call_keys.push(key.signer.clone()),
+                Some(function_call_permission) => {
                     if function_call_permission.receiver_id == receiver_id {
                         function_call_keys.push(key.signer.clone())
                     }
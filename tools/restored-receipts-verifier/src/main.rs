@@ -100,6 +100,7 @@ fn main() -> Result<()> {
                 block.header().gas_price(),
                 chunk_extra.gas_limit(),
                 block.header().challenges_result(),
+                &Default::default(),
                 *block.header().random_value(),
                 false,
                 false, // because fix was not applied in for the blocks analyzed here
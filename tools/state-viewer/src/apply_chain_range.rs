@@ -5,12 +5,13 @@ use std::sync::Mutex;
 
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
-use near_chain::chain::collect_receipts_from_response;
+use near_chain::chain::{collect_receipts_from_response, get_block_congestion_info};
 use near_chain::migrations::check_if_block_is_first_with_chunk_of_version;
 use near_chain::types::ApplyTransactionResult;
 use near_chain::{ChainStore, ChainStoreAccess, ChainStoreUpdate, RuntimeAdapter};
 use near_chain_configs::Genesis;
 use near_primitives::borsh::maybestd::sync::Arc;
+use near_primitives::congestion_control::BlockCongestionInfo;
 use near_primitives::hash::CryptoHash;
 use near_primitives::receipt::DelayedReceiptIndices;
 use near_primitives::transaction::{
@@ -241,6 +242,7 @@ fn apply_block_from_range(
                 prev_block.header().gas_price(),
                 chunk_inner.gas_limit(),
                 block.header().challenges_result(),
+                &get_block_congestion_info(runtime_adapter.as_ref(), &prev_block).unwrap(),
                 *block.header().random_value(),
                 true,
                 is_first_block_with_chunk_of_version,
@@ -267,6 +269,7 @@ fn apply_block_from_range(
                 block.header().gas_price(),
                 chunk_extra.gas_limit(),
                 block.header().challenges_result(),
+                &BlockCongestionInfo::default(),
                 *block.header().random_value(),
                 false,
                 false,
@@ -283,6 +286,7 @@ fn apply_block_from_range(
         apply_result.total_gas_burnt,
         genesis.config.gas_limit,
         apply_result.total_balance_burnt,
+        apply_result.delayed_receipts_gas,
     );
 
     let state_update =
@@ -1,6 +1,6 @@
 use anyhow::{anyhow, Context};
 use borsh::BorshDeserialize;
-use near_chain::chain::collect_receipts_from_response;
+use near_chain::chain::{collect_receipts_from_response, get_block_congestion_info};
 use near_chain::migrations::check_if_block_is_first_with_chunk_of_version;
 use near_chain::types::ApplyTransactionResult;
 use near_chain::{ChainStore, ChainStoreAccess, RuntimeAdapter};
@@ -96,6 +96,7 @@ pub(crate) fn apply_chunk(
     };
     let prev_timestamp = prev_block.header().raw_timestamp();
     let gas_price = prev_block.header().gas_price();
+    let congestion_info = get_block_congestion_info(runtime, prev_block)?;
     let receipts = get_incoming_receipts(
         chain_store,
         &chunk_hash,
@@ -131,6 +132,7 @@ pub(crate) fn apply_chunk(
             gas_price,
             chunk_header.gas_limit(),
             &vec![],
+            &congestion_info,
             hash("random seed".as_ref()),
             true,
             is_first_block_with_chunk_of_version,
@@ -1,13 +1,15 @@
 use crate::commands::*;
 use crate::epoch_info;
+use crate::replay_receipt::ReceiptOverrides;
 use crate::rocksdb_stats::get_rocksdb_stats;
 use clap::{Args, Parser, Subcommand};
 use near_chain_configs::GenesisValidationMode;
 use near_logger_utils::init_integration_logger;
 use near_primitives::account::id::AccountId;
 use near_primitives::hash::CryptoHash;
+use near_primitives::serialize::from_base64;
 use near_primitives::sharding::ChunkHash;
-use near_primitives::types::{BlockHeight, ShardId};
+use near_primitives::types::{BlockHeight, Gas, ShardId};
 use near_primitives::version::{DB_VERSION, PROTOCOL_VERSION};
 use near_store::{create_store_with_config, Store, StoreConfig};
 use nearcore::{get_default_home, get_store_path, load_config, NearConfig};
@@ -94,6 +96,9 @@ pub enum StateViewerSubCommand {
     /// Dump contract data in storage of given account to binary file.
     #[clap(name = "dump_account_storage")]
     DumpAccountStorage(DumpAccountStorageCmd),
+    /// Print storage used by an account by category and compare it with its `storage_usage`.
+    #[clap(name = "storage_breakdown")]
+    StorageBreakdown(StorageBreakdownCmd),
     /// Print `EpochInfo` of an epoch given by `--epoch_id` or by `--epoch_height`.
     #[clap(name = "epoch_info")]
     EpochInfo(EpochInfoCmd),
@@ -140,6 +145,7 @@ impl StateViewerSubCommand {
             StateViewerSubCommand::CheckBlock => check_block_chunk_existence(store, near_config),
             StateViewerSubCommand::DumpCode(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::DumpAccountStorage(cmd) => cmd.run(home_dir, near_config, store),
+            StateViewerSubCommand::StorageBreakdown(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::EpochInfo(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::RocksDBStats(cmd) => cmd.run(home_dir),
             StateViewerSubCommand::Receipts(cmd) => cmd.run(near_config, store),
@@ -321,6 +327,28 @@ impl DumpAccountStorageCmd {
         );
     }
 }
+
+#[derive(Parser)]
+pub struct StorageBreakdownCmd {
+    #[clap(long)]
+    account_id: String,
+    /// Number of leading bytes of the contract data keys to group the data by.
+    #[clap(long, default_value = "1")]
+    data_prefix_len: u32,
+}
+
+impl StorageBreakdownCmd {
+    pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Store) {
+        storage_breakdown(
+            AccountId::from_str(&self.account_id).unwrap(),
+            self.data_prefix_len,
+            home_dir,
+            near_config,
+            store,
+        );
+    }
+}
+
 #[derive(Args)]
 pub struct EpochInfoCmd {
     #[clap(subcommand)]
@@ -425,11 +453,49 @@ impl ApplyTxCmd {
 pub struct ApplyReceiptCmd {
     #[clap(long)]
     hash: String,
+    /// Replays the receipt with the code of its receiver replaced by this Wasm file.
+    #[clap(long, parse(from_os_str))]
+    code: Option<PathBuf>,
+    /// Replays the receipt with this contract data of its receiver, as `key=value` encoded in
+    /// base64. Can be given multiple times.
+    #[clap(long)]
+    set_data: Vec<String>,
+    /// Replays the receipt with this contract data key of its receiver, encoded in base64,
+    /// removed. Can be given multiple times.
+    #[clap(long)]
+    remove_data: Vec<String>,
+    /// Replays the receipt with this gas attached to each of its function calls.
+    #[clap(long)]
+    gas: Option<Gas>,
+    /// Replays the receipt with the state records from this JSON file, in the format of genesis
+    /// records, written to the state of its shard.
+    #[clap(long, parse(from_os_str))]
+    state_records: Option<PathBuf>,
 }
 
 impl ApplyReceiptCmd {
     pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Store) {
         let hash = CryptoHash::from_str(&self.hash).unwrap();
-        apply_receipt(home_dir, near_config, store, hash).unwrap();
+        let mut overrides = ReceiptOverrides {
+            code: self.code.map(|path| std::fs::read(path).unwrap()),
+            data: vec![],
+            gas: self.gas,
+            records: self
+                .state_records
+                .map(|path| serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap())
+                .unwrap_or_default(),
+        };
+        for entry in self.set_data {
+            let (key, value) = entry.split_once('=').expect("--set-data should be key=value");
+            overrides.data.push((from_base64(key).unwrap(), Some(from_base64(value).unwrap())));
+        }
+        for key in self.remove_data {
+            overrides.data.push((from_base64(&key).unwrap(), None));
+        }
+        if overrides.is_empty() {
+            apply_receipt(home_dir, near_config, store, hash).unwrap();
+        } else {
+            replay_receipt(home_dir, near_config, store, hash, &overrides).unwrap();
+        }
     }
 }
@@ -1,9 +1,10 @@
 use crate::apply_chain_range::apply_chain_range;
+use crate::replay_receipt::ReceiptOverrides;
 use crate::state_dump::state_dump;
 use crate::state_dump::state_dump_redis;
-use crate::{apply_chunk, epoch_info};
+use crate::{apply_chunk, epoch_info, replay_receipt};
 use ansi_term::Color::Red;
-use near_chain::chain::collect_receipts_from_response;
+use near_chain::chain::{collect_receipts_from_response, get_block_congestion_info};
 use near_chain::migrations::check_if_block_is_first_with_chunk_of_version;
 use near_chain::types::{ApplyTransactionResult, BlockHeaderInfo};
 use near_chain::{ChainStore, ChainStoreAccess, ChainStoreUpdate, RuntimeAdapter};
@@ -11,6 +12,7 @@ use near_epoch_manager::EpochManager;
 use near_network::iter_peers_from_store;
 use near_primitives::account::id::AccountId;
 use near_primitives::block::{Block, BlockHeader};
+use near_primitives::congestion_control::BlockCongestionInfo;
 use near_primitives::hash::CryptoHash;
 use near_primitives::serialize::to_base;
 use near_primitives::shard_layout::ShardUId;
@@ -210,6 +212,62 @@ pub(crate) fn dump_account_storage(
     std::process::exit(1);
 }
 
+pub(crate) fn storage_breakdown(
+    account_id: AccountId,
+    data_prefix_len: u32,
+    home_dir: &Path,
+    near_config: NearConfig,
+    store: Store,
+) {
+    let (runtime, state_roots, header) = load_trie(store, home_dir, &near_config);
+    let epoch_id = &runtime.get_epoch_id(header.hash()).unwrap();
+    let shard_id = runtime.account_id_to_shard_id(&account_id, epoch_id).unwrap();
+    let shard_uid = runtime.shard_id_to_uid(shard_id, epoch_id).unwrap();
+    let storage_usage_config = runtime
+        .get_protocol_config(epoch_id)
+        .unwrap()
+        .runtime_config
+        .transaction_costs
+        .storage_usage_config;
+    let breakdown = match runtime.view_storage_breakdown(
+        &shard_uid,
+        state_roots[shard_id as usize],
+        &account_id,
+        Some(data_prefix_len),
+        &storage_usage_config,
+    ) {
+        Ok(breakdown) => breakdown,
+        Err(err) => {
+            println!("Failed to compute storage of account {}: {}", account_id, err);
+            std::process::exit(1);
+        }
+    };
+
+    println!("Storage of account {} at height {}:", account_id, header.height());
+    println!("{: >12}  account record", breakdown.account);
+    println!("{: >12}  contract code", breakdown.contract_code);
+    println!("{: >12}  access keys ({} keys)", breakdown.access_keys, breakdown.num_access_keys);
+    for data in &breakdown.contract_data {
+        let prefix: &[u8] = data.prefix.as_ref();
+        let prefix: String =
+            prefix.iter().copied().flat_map(std::ascii::escape_default).map(char::from).collect();
+        println!(
+            "{: >12}  contract data with prefix \"{}\" ({} records)",
+            data.storage_usage, prefix, data.num_records
+        );
+    }
+    println!("{: >12}  total", breakdown.total);
+    if breakdown.total == breakdown.stored_total {
+        println!("Matches storage_usage stored in the account");
+    } else {
+        println!(
+            "{}: storage_usage stored in the account is {}",
+            Red.bold().paint("MISMATCH"),
+            breakdown.stored_total
+        );
+    }
+}
+
 pub(crate) fn print_chain(
     start_height: BlockHeight,
     end_height: BlockHeight,
@@ -350,6 +408,7 @@ pub(crate) fn resulting_chunk_extra(result: &ApplyTransactionResult, gas_limit:
         result.total_gas_burnt,
         gas_limit,
         result.total_balance_burnt,
+        result.delayed_receipts_gas,
     )
 }
 
@@ -399,6 +458,7 @@ pub(crate) fn apply_block(
                 prev_block.header().gas_price(),
                 chunk_inner.gas_limit(),
                 block.header().challenges_result(),
+                &get_block_congestion_info(runtime_adapter, &prev_block).unwrap(),
                 *block.header().random_value(),
                 true,
                 is_first_block_with_chunk_of_version,
@@ -423,6 +483,7 @@ pub(crate) fn apply_block(
                 block.header().gas_price(),
                 chunk_extra.gas_limit(),
                 block.header().challenges_result(),
+                &BlockCongestionInfo::default(),
                 *block.header().random_value(),
                 false,
                 false,
@@ -797,3 +858,27 @@ pub(crate) fn apply_receipt(
     apply_chunk::apply_receipt(near_config.genesis.config.genesis_height, &runtime, store, hash)
         .map(|_| ())
 }
+
+pub(crate) fn replay_receipt(
+    home_dir: &Path,
+    near_config: NearConfig,
+    store: Store,
+    hash: CryptoHash,
+    overrides: &ReceiptOverrides,
+) -> anyhow::Result<()> {
+    let runtime = NightshadeRuntime::with_config(
+        home_dir,
+        store.clone(),
+        &near_config,
+        None,
+        near_config.client_config.max_gas_burnt_view,
+    );
+    replay_receipt::replay_receipt(
+        near_config.genesis.config.genesis_height,
+        &runtime,
+        store,
+        hash,
+        overrides,
+    )
+    .map(|_| ())
+}
@@ -0,0 +1,479 @@
+//! Replays a receipt with the contract code or state of its receiver, any other state of its
+//! shard, or its attached gas replaced, to check how a fixed contract would have handled real
+//! traffic.
+
+use anyhow::{anyhow, Context};
+use near_chain::chain::{collect_receipts_from_response, get_block_congestion_info};
+use near_chain::migrations::check_if_block_is_first_with_chunk_of_version;
+use near_chain::{ChainStore, ChainStoreAccess, ChainStoreUpdate, RuntimeAdapter};
+use near_primitives::contract::ContractCode;
+use near_primitives::hash::CryptoHash;
+use near_primitives::receipt::ReceivedData;
+use near_primitives::receipt::{DelayedReceiptIndices, Receipt, ReceiptEnum};
+use near_primitives::shard_layout::account_id_to_shard_id;
+use near_primitives::state_record::StateRecord;
+use near_primitives::transaction::{Action, ExecutionOutcome, ExecutionOutcomeWithId};
+use near_primitives::trie_key::TrieKey;
+use near_primitives::types::{BlockHeight, StateChangeCause};
+use near_primitives_core::types::Gas;
+use near_store::{
+    get, get_account, get_code_storage_usage, set, set_access_key, set_account, set_code,
+    set_postponed_receipt, set_received_data, Store, TrieUpdate,
+};
+use nearcore::NightshadeRuntime;
+use node_runtime::Runtime;
+use std::rc::Rc;
+
+/// Changes made to the state before the chunk and to the receipt before replaying it.
+#[derive(Default)]
+pub(crate) struct ReceiptOverrides {
+    /// Contract code deployed to the receiver instead of its current code.
+    pub code: Option<Vec<u8>>,
+    /// Contract data of the receiver to set, or to remove if the value is `None`.
+    pub data: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+    /// Gas attached to every function call of the receipt.
+    pub gas: Option<Gas>,
+    /// Records written to the state after the other overrides, in the format of genesis records.
+    /// Any account of the shard can be changed this way.
+    pub records: Vec<StateRecord>,
+}
+
+impl ReceiptOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.code.is_none() && self.data.is_empty() && self.gas.is_none() && self.records.is_empty()
+    }
+}
+
+/// Applies the chunk in which the receipt was executed again, with the overrides applied, and
+/// prints the new outcome of the receipt together with its differences from the original one.
+///
+/// The changed state is kept in memory, so the store can be opened read-only. The storage usage
+/// of the receiver is updated for the new code, but not for the changed contract data.
+pub(crate) fn replay_receipt(
+    genesis_height: BlockHeight,
+    runtime: &NightshadeRuntime,
+    store: Store,
+    id: CryptoHash,
+    overrides: &ReceiptOverrides,
+) -> anyhow::Result<ExecutionOutcomeWithId> {
+    let mut chain_store = ChainStore::new(store, genesis_height, false);
+    let original = chain_store.get_outcomes_by_id(&id)?.into_iter().next().ok_or_else(|| {
+        anyhow!("Receipt {} has no execution outcome. Only executed receipts can be replayed", id)
+    })?;
+    let receiver_id = original.outcome_with_id.outcome.executor_id.clone();
+    let block = chain_store.get_block(&original.block_hash)?.clone();
+    let prev_block = chain_store
+        .get_block(block.header().prev_hash())
+        .context("Failed getting the previous block")?
+        .clone();
+    let shard_layout = runtime.get_shard_layout_from_prev_block(prev_block.hash())?;
+    let shard_id = account_id_to_shard_id(&receiver_id, &shard_layout);
+    let chunk_header = block.chunks()[shard_id as usize].clone();
+    if chunk_header.height_included() != block.header().height() {
+        return Err(anyhow!("Block {} has no new chunk in shard {}", block.hash(), shard_id));
+    }
+    let chunk = chain_store.get_chunk(&chunk_header.chunk_hash())?.clone();
+    let chunk_inner = chunk.cloned_header().take_inner();
+    let mut receipts = collect_receipts_from_response(
+        &ChainStoreUpdate::new(&mut chain_store).get_incoming_receipts_for_shard(
+            shard_id,
+            *block.hash(),
+            prev_block.chunks()[shard_id as usize].height_included(),
+        )?,
+    );
+
+    let trie = runtime.get_trie_for_shard(shard_id, prev_block.hash())?;
+    let mut state_update = TrieUpdate::new(Rc::new(trie), chunk_inner.prev_state_root());
+    if let Some(gas) = overrides.gas {
+        if let Some(receipt) = receipts.iter_mut().find(|receipt| receipt.receipt_id == id) {
+            set_function_call_gas(receipt, gas)?;
+        } else {
+            // The gas of local receipts comes from their transactions.
+            let (index, mut receipt) = find_delayed_receipt(&state_update, &id)?
+                .ok_or_else(|| anyhow!("Receipt {} is not an incoming or delayed receipt", id))?;
+            set_function_call_gas(&mut receipt, gas)?;
+            set(&mut state_update, TrieKey::DelayedReceipt { index }, &receipt);
+        }
+    }
+    if let Some(code) = &overrides.code {
+        let mut account = get_account(&state_update, &receiver_id)?
+            .ok_or_else(|| anyhow!("Account {} does not exist before the chunk", receiver_id))?;
+        let prev_code_len = get_code_storage_usage(&state_update, &receiver_id)?;
+        let code = ContractCode::new(code.clone(), None);
+        account.set_code_hash(*code.hash());
+        account.set_storage_usage(
+            account.storage_usage().saturating_sub(prev_code_len) + code.code().len() as u64,
+        );
+        set_code(&mut state_update, receiver_id.clone(), &code);
+        set_account(&mut state_update, receiver_id.clone(), &account);
+    }
+    for (key, value) in &overrides.data {
+        let trie_key = TrieKey::ContractData { account_id: receiver_id.clone(), key: key.clone() };
+        match value {
+            Some(value) => state_update.set(trie_key, value.clone()),
+            None => state_update.remove(trie_key),
+        }
+    }
+    for record in &overrides.records {
+        set_state_record(&mut state_update, record.clone())?;
+    }
+    state_update.commit(StateChangeCause::InitialState);
+    let (trie_changes, _) = state_update.finalize()?;
+    let trie = runtime
+        .get_trie_for_shard(shard_id, prev_block.hash())?
+        .with_changes(&trie_changes)
+        .ok_or_else(|| anyhow!("The trie of shard {} can't read uncommitted changes", shard_id))?;
+
+    let is_first_block_with_chunk_of_version = check_if_block_is_first_with_chunk_of_version(
+        &mut chain_store,
+        runtime,
+        prev_block.hash(),
+        shard_id,
+    )?;
+    let apply_result = runtime.apply_transactions_on_trie(
+        trie,
+        shard_id,
+        &trie_changes.new_root,
+        block.header().height(),
+        block.header().raw_timestamp(),
+        prev_block.hash(),
+        block.hash(),
+        &receipts,
+        chunk.transactions(),
+        chunk_inner.validator_proposals(),
+        prev_block.header().gas_price(),
+        chunk_inner.gas_limit(),
+        block.header().challenges_result(),
+        &get_block_congestion_info(runtime, &prev_block)?,
+        *block.header().random_value(),
+        true,
+        is_first_block_with_chunk_of_version,
+    )?;
+
+    let replayed =
+        apply_result.outcomes.into_iter().find(|outcome| outcome.id == id).ok_or_else(|| {
+            anyhow!("Receipt {} wasn't executed in the replayed chunk, e.g. it was delayed", id)
+        })?;
+    println!(
+        "Replayed receipt {} in block {} shard {}:\n{:#?}\n",
+        id,
+        block.hash(),
+        shard_id,
+        replayed.outcome
+    );
+    print_outcome_diff(&original.outcome_with_id.outcome, &replayed.outcome);
+    Ok(replayed)
+}
+
+fn set_function_call_gas(receipt: &mut Receipt, gas: Gas) -> anyhow::Result<()> {
+    let mut found = false;
+    if let ReceiptEnum::Action(action_receipt) = &mut receipt.receipt {
+        for action in action_receipt.actions.iter_mut() {
+            if let Action::FunctionCall(function_call) = action {
+                function_call.gas = gas;
+                found = true;
+            }
+        }
+    }
+    if !found {
+        return Err(anyhow!("Receipt {} has no function calls", receipt.receipt_id));
+    }
+    Ok(())
+}
+
+/// Writes the record to the state like genesis does. The code hash of the account is updated for
+/// contract records, while the storage usage of accounts and the gas of the delayed receipts
+/// queue are left as is.
+fn set_state_record(state_update: &mut TrieUpdate, record: StateRecord) -> anyhow::Result<()> {
+    match record {
+        StateRecord::Account { account_id, account } => {
+            set_account(state_update, account_id, &account);
+        }
+        StateRecord::Data { account_id, data_key, value } => {
+            state_update.set(TrieKey::ContractData { key: data_key, account_id }, value);
+        }
+        StateRecord::Contract { account_id, code } => {
+            let mut account = get_account(state_update, &account_id)?.ok_or_else(|| {
+                anyhow!("Contract record of {} should follow its account", account_id)
+            })?;
+            let code = ContractCode::new(code, None);
+            account.set_code_hash(*code.hash());
+            set_code(state_update, account_id.clone(), &code);
+            set_account(state_update, account_id, &account);
+        }
+        StateRecord::AccessKey { account_id, public_key, access_key } => {
+            set_access_key(state_update, account_id, public_key, &access_key);
+        }
+        StateRecord::PostponedReceipt(receipt) => {
+            set_postponed_receipt(state_update, &receipt);
+        }
+        StateRecord::ReceivedData { account_id, data_id, data } => {
+            set_received_data(state_update, account_id, data_id, &ReceivedData { data });
+        }
+        StateRecord::DelayedReceipt(receipt) => {
+            let mut indices: DelayedReceiptIndices =
+                get(state_update, &TrieKey::DelayedReceiptIndices)?.unwrap_or_default();
+            Runtime::delay_receipt(state_update, &mut indices, &receipt)?;
+            set(state_update, TrieKey::DelayedReceiptIndices, &indices);
+        }
+    }
+    Ok(())
+}
+
+fn find_delayed_receipt(
+    state_update: &TrieUpdate,
+    id: &CryptoHash,
+) -> anyhow::Result<Option<(u64, Receipt)>> {
+    let indices: DelayedReceiptIndices =
+        get(state_update, &TrieKey::DelayedReceiptIndices)?.unwrap_or_default();
+    for index in indices.first_index..indices.next_available_index {
+        let receipt: Receipt = get(state_update, &TrieKey::DelayedReceipt { index })?
+            .ok_or_else(|| anyhow!("Delayed receipt #{} should be in the state", index))?;
+        if &receipt.receipt_id == id {
+            return Ok(Some((index, receipt)));
+        }
+    }
+    Ok(None)
+}
+
+fn print_outcome_diff(original: &ExecutionOutcome, replayed: &ExecutionOutcome) {
+    let fields = [
+        ("status", format!("{:?}", original.status), format!("{:?}", replayed.status)),
+        ("gas_burnt", original.gas_burnt.to_string(), replayed.gas_burnt.to_string()),
+        ("tokens_burnt", original.tokens_burnt.to_string(), replayed.tokens_burnt.to_string()),
+        ("logs", format!("{:?}", original.logs), format!("{:?}", replayed.logs)),
+        (
+            "receipt_ids",
+            format!("{:?}", original.receipt_ids),
+            format!("{:?}", replayed.receipt_ids),
+        ),
+    ];
+    let mut identical = true;
+    for (name, original, replayed) in fields.iter() {
+        if original != replayed {
+            identical = false;
+            println!("{}:\n  original: {}\n  replayed: {}", name, original, replayed);
+        }
+    }
+    if identical {
+        println!("Outcome is identical to the original one");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use near_chain::{ChainGenesis, ChainStore, ChainStoreAccess, Provenance};
+    use near_chain_configs::Genesis;
+    use near_client::test_utils::TestEnv;
+    use near_crypto::{InMemorySigner, KeyType};
+    use near_network::types::NetworkClientResponses;
+    use near_primitives::errors::{ActionErrorKind, TxExecutionError};
+    use near_primitives::runtime::config_store::RuntimeConfigStore;
+    use near_primitives::state_record::StateRecord;
+    use near_primitives::transaction::{
+        Action, DeployContractAction, ExecutionStatus, FunctionCallAction, SignedTransaction,
+    };
+    use near_primitives::types::AccountId;
+    use near_primitives::utils::get_num_seats_per_shard;
+    use near_store::test_utils::create_test_store;
+    use near_store::Store;
+    use nearcore::config::GenesisExt;
+    use nearcore::NightshadeRuntime;
+    use nearcore::TrackedConfig;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use super::{replay_receipt, ReceiptOverrides};
+
+    fn setup_env(accounts: Vec<AccountId>) -> (Genesis, Store, Arc<NightshadeRuntime>, TestEnv) {
+        let genesis = Genesis::test_sharded(accounts.clone(), 1, get_num_seats_per_shard(4, 1));
+        let store = create_test_store();
+        let runtime = Arc::new(NightshadeRuntime::test_with_runtime_config_store(
+            Path::new("."),
+            store.clone(),
+            &genesis,
+            TrackedConfig::AllShards,
+            RuntimeConfigStore::test(),
+        ));
+        let mut chain_genesis = ChainGenesis::test();
+        // receipts get delayed with the small ChainGenesis::test() limit
+        chain_genesis.gas_limit = genesis.config.gas_limit;
+        let env = TestEnv::builder(chain_genesis).runtime_adapters(vec![runtime.clone()]).build();
+        (genesis, store, runtime, env)
+    }
+
+    #[test]
+    fn test_replay_receipt() {
+        let accounts = (0..4).map(|i| format!("test{}", i).parse().unwrap()).collect::<Vec<_>>();
+        let (genesis, store, runtime, mut env) = setup_env(accounts.clone());
+        let mut chain_store = ChainStore::new(store.clone(), genesis.config.genesis_height, false);
+        let genesis_hash = *env.clients[0].chain.genesis().hash();
+
+        for height in 1..6 {
+            for (i, account_id) in accounts.iter().enumerate() {
+                let signer = InMemorySigner::from_seed(
+                    account_id.clone(),
+                    KeyType::ED25519,
+                    account_id.as_ref(),
+                );
+                let tx = SignedTransaction::send_money(
+                    height,
+                    account_id.clone(),
+                    accounts[(i + 1) % accounts.len()].clone(),
+                    &signer,
+                    100,
+                    genesis_hash,
+                );
+                let response = env.clients[0].process_tx(tx, false, false);
+                assert_eq!(response, NetworkClientResponses::ValidTx);
+            }
+            let block = env.clients[0].produce_block(height).unwrap().unwrap();
+            env.process_block(0, block, Provenance::PRODUCED);
+        }
+
+        let mut receipt_ids = vec![];
+        for height in 2..5 {
+            let block_hash = chain_store.get_block_hash_by_height(height).unwrap();
+            let block = chain_store.get_block(&block_hash).unwrap().clone();
+            for chunk_header in block.chunks().iter() {
+                let chunk = chain_store.get_chunk(&chunk_header.chunk_hash()).unwrap();
+                receipt_ids.extend(chunk.receipts().iter().map(|receipt| receipt.receipt_id));
+            }
+        }
+        assert!(!receipt_ids.is_empty());
+
+        for id in receipt_ids {
+            let original = chain_store.get_outcomes_by_id(&id).unwrap()[0].outcome_with_id.clone();
+            // Transfers don't depend on the contract data of the receiver.
+            let overrides = ReceiptOverrides {
+                data: vec![(b"key".to_vec(), Some(b"value".to_vec()))],
+                ..Default::default()
+            };
+            let replayed = replay_receipt(
+                genesis.config.genesis_height,
+                runtime.as_ref(),
+                store.clone(),
+                id,
+                &overrides,
+            )
+            .unwrap();
+            assert_eq!(replayed, original);
+
+            let overrides = ReceiptOverrides { gas: Some(1), ..Default::default() };
+            let err = replay_receipt(
+                genesis.config.genesis_height,
+                runtime.as_ref(),
+                store.clone(),
+                id,
+                &overrides,
+            )
+            .unwrap_err();
+            assert!(err.to_string().contains("has no function calls"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_replay_receipt_with_overrides() {
+        let accounts = (0..4).map(|i| format!("test{}", i).parse().unwrap()).collect::<Vec<_>>();
+        let (genesis, store, runtime, mut env) = setup_env(accounts.clone());
+        let mut chain_store = ChainStore::new(store.clone(), genesis.config.genesis_height, false);
+        let genesis_hash = *env.clients[0].chain.genesis().hash();
+        let (contract_id, caller_id) = (accounts[0].clone(), accounts[1].clone());
+
+        let signer =
+            InMemorySigner::from_seed(contract_id.clone(), KeyType::ED25519, contract_id.as_ref());
+        let deploy_tx = SignedTransaction::from_actions(
+            1,
+            contract_id.clone(),
+            contract_id.clone(),
+            &signer,
+            vec![Action::DeployContract(DeployContractAction {
+                code: near_test_contracts::rs_contract().to_vec(),
+            })],
+            genesis_hash,
+        );
+        assert_eq!(
+            env.clients[0].process_tx(deploy_tx, false, false),
+            NetworkClientResponses::ValidTx
+        );
+        for height in 1..4 {
+            let block = env.clients[0].produce_block(height).unwrap().unwrap();
+            env.process_block(0, block, Provenance::PRODUCED);
+        }
+        // Reads a value which isn't in the state of the contract.
+        let key = 7u64.to_le_bytes().to_vec();
+        let signer =
+            InMemorySigner::from_seed(caller_id.clone(), KeyType::ED25519, caller_id.as_ref());
+        let call_tx = SignedTransaction::from_actions(
+            1,
+            caller_id.clone(),
+            contract_id.clone(),
+            &signer,
+            vec![Action::FunctionCall(FunctionCallAction {
+                method_name: "read_value".to_string(),
+                args: key.clone(),
+                gas: 10u64.pow(13),
+                deposit: 0,
+            })],
+            genesis_hash,
+        );
+        let call_hash = call_tx.get_hash();
+        assert_eq!(
+            env.clients[0].process_tx(call_tx, false, false),
+            NetworkClientResponses::ValidTx
+        );
+        for height in 4..8 {
+            let block = env.clients[0].produce_block(height).unwrap().unwrap();
+            env.process_block(0, block, Provenance::PRODUCED);
+        }
+        let id = chain_store.get_outcomes_by_id(&call_hash).unwrap()[0]
+            .outcome_with_id
+            .outcome
+            .receipt_ids[0];
+        let original = chain_store.get_outcomes_by_id(&id).unwrap()[0].outcome_with_id.clone();
+        assert_eq!(original.outcome.status, ExecutionStatus::SuccessValue(vec![]));
+
+        // The replaced code doesn't have the method.
+        let overrides = ReceiptOverrides {
+            code: Some(near_test_contracts::trivial_contract().to_vec()),
+            ..Default::default()
+        };
+        let replayed = replay_receipt(
+            genesis.config.genesis_height,
+            runtime.as_ref(),
+            store.clone(),
+            id,
+            &overrides,
+        )
+        .unwrap();
+        assert!(
+            matches!(
+                &replayed.outcome.status,
+                ExecutionStatus::Failure(TxExecutionError::ActionError(error))
+                    if matches!(error.kind, ActionErrorKind::FunctionCallError(_))
+            ),
+            "{:?}",
+            replayed.outcome.status
+        );
+
+        // The value is found once it's written by a state record.
+        let value = 42u64.to_le_bytes().to_vec();
+        let overrides = ReceiptOverrides {
+            records: vec![StateRecord::Data {
+                account_id: contract_id.clone(),
+                data_key: key,
+                value: value.clone(),
+            }],
+            ..Default::default()
+        };
+        let replayed = replay_receipt(
+            genesis.config.genesis_height,
+            runtime.as_ref(),
+            store.clone(),
+            id,
+            &overrides,
+        )
+        .unwrap();
+        assert_eq!(replayed.outcome.status, ExecutionStatus::SuccessValue(value));
+    }
+}
@@ -0,0 +1,74 @@
+use std::time::Instant;
+
+/// Classic token bucket: holds up to `burst` tokens, refilled at `qps` tokens per second.
+/// Each accepted event consumes one token; events arriving when the bucket is empty are rejected.
+#[derive(Clone, Debug)]
+pub struct TokenBucket {
+    /// Maximum number of tokens stored in the bucket.
+    burst: u32,
+    /// Number of tokens added to the bucket per second.
+    qps: f64,
+    /// Number of tokens currently available.
+    tokens: f64,
+    /// Last time `tokens` was updated.
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Creates a full bucket.
+    pub fn new(burst: u32, qps: f64, now: Instant) -> Self {
+        Self { burst, qps, tokens: burst as f64, last_refill: now }
+    }
+
+    /// Tries to consume a single token. Returns `false` if the bucket is empty.
+    pub fn try_acquire(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.qps).min(self.burst as f64);
+        self.last_refill = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::token_bucket::TokenBucket;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_token_bucket_burst() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(3, 1.0, now);
+        assert!(bucket.try_acquire(now));
+        assert!(bucket.try_acquire(now));
+        assert!(bucket.try_acquire(now));
+        assert!(!bucket.try_acquire(now));
+    }
+
+    #[test]
+    fn test_token_bucket_refill() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(2, 10.0, now);
+        assert!(bucket.try_acquire(now));
+        assert!(bucket.try_acquire(now));
+        assert!(!bucket.try_acquire(now));
+
+        let now = now + Duration::from_millis(100);
+        assert!(bucket.try_acquire(now));
+        assert!(!bucket.try_acquire(now));
+
+        // Bucket never holds more than `burst` tokens.
+        let now = now + Duration::from_secs(60);
+        assert!(bucket.try_acquire(now));
+        assert!(bucket.try_acquire(now));
+        assert!(!bucket.try_acquire(now));
+    }
+}